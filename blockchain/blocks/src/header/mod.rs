@@ -325,6 +325,10 @@ impl BlockHeader {
     pub fn signature(&self) -> &Option<Signature> {
         &self.signature
     }
+    /// Length of the CBOR encoding of the header.
+    pub fn encoded_len(&self) -> usize {
+        self.cached_bytes().len()
+    }
     /// Key used for sorting headers and blocks.
     pub fn to_sort_key(&self) -> Option<([u8; 32], Vec<u8>)> {
         let ticket_hash = blake2b_256(self.ticket().as_ref()?.vrfproof.as_bytes());
@@ -0,0 +1,105 @@
+// Copyright 2019-2023 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use std::sync::Arc;
+
+use cid::Cid;
+use forest_blocks::Tipset;
+use forest_db::Store;
+use forest_ipld::{recurse_links_hash, CidHashSet};
+use fvm_ipld_blockstore::Blockstore;
+use fvm_shared::clock::ChainEpoch;
+use serde::Serialize;
+
+use super::{check::PLACEHOLDER, ChainStore, Error};
+
+/// Number and total size of the blocks of a kind.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct BlockCount {
+    pub count: u64,
+    pub bytes: u64,
+    /// Blocks linked to but absent from the store.
+    pub missing: u64,
+}
+
+impl BlockCount {
+    fn add(&mut self, data: &[u8]) {
+        self.count += 1;
+        self.bytes += data.len() as u64;
+    }
+}
+
+/// Blocks reachable from a tipset, by kind.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize)]
+pub struct BlockStats {
+    pub headers: BlockCount,
+    /// Messages and the `AMT`s listing them.
+    pub messages: BlockCount,
+    pub receipts: BlockCount,
+    /// State trees, including the actor states.
+    pub state: BlockCount,
+}
+
+impl<DB> ChainStore<DB>
+where
+    DB: Blockstore + Store + Send + Sync,
+{
+    /// Classifies the blocks reachable from the tipset. Messages, receipts
+    /// and state trees are only walked for the `recent_roots` last epochs,
+    /// like [`ChainStore::export`] does, and for the genesis state.
+    pub async fn block_stats(
+        &self,
+        tipset: Arc<Tipset>,
+        recent_roots: ChainEpoch,
+    ) -> Result<BlockStats, Error> {
+        let mut stats = BlockStats::default();
+        let mut seen = CidHashSet::default();
+        let incl_roots_epoch = tipset.epoch() - recent_roots;
+        let mut tipset = tipset;
+        loop {
+            for header in tipset.blocks() {
+                if !seen.insert(header.cid()) {
+                    continue;
+                }
+                if let Some(data) = self.blockstore().get(header.cid())? {
+                    stats.headers.add(&data);
+                }
+                let epoch = header.epoch();
+                if epoch > incl_roots_epoch {
+                    self.count_blocks(&mut seen, *header.messages(), &mut stats.messages)
+                        .await?;
+                    self.count_blocks(&mut seen, *header.message_receipts(), &mut stats.receipts)
+                        .await?;
+                }
+                if epoch == 0 || epoch > incl_roots_epoch {
+                    self.count_blocks(&mut seen, *header.state_root(), &mut stats.state)
+                        .await?;
+                }
+            }
+            if tipset.epoch() == 0 {
+                return Ok(stats);
+            }
+            tipset = self.tipset_from_keys(tipset.parents())?;
+        }
+    }
+
+    /// Counts the blocks linked from the root that weren't seen yet.
+    async fn count_blocks(
+        &self,
+        seen: &mut CidHashSet,
+        root: Cid,
+        count: &mut BlockCount,
+    ) -> Result<(), Error> {
+        recurse_links_hash(seen, root, &mut |cid| {
+            let data = self.blockstore().get(&cid);
+            match &data {
+                Ok(Some(data)) => count.add(data),
+                Ok(None) => count.missing += 1,
+                Err(_) => {}
+            }
+            async move { Ok(data?.unwrap_or_else(|| PLACEHOLDER.to_vec())) }
+        })
+        .await?;
+        Ok(())
+    }
+}
@@ -15,7 +15,7 @@ use digest::Digest;
 use forest_actor_interface::EPOCHS_IN_DAY;
 use forest_beacon::{BeaconEntry, IGNORE_DRAND_VAR};
 use forest_blocks::{Block, BlockHeader, FullTipset, Tipset, TipsetKeys, TxMeta};
-use forest_db::Store;
+use forest_db::{Store, BLOCK_VALIDATION_PREFIX};
 use forest_encoding::de::DeserializeOwned;
 use forest_interpreter::BlockMessages;
 use forest_ipld::{recurse_links_hash, CidHashSet};
@@ -60,7 +60,6 @@ use crate::Scale;
 
 const GENESIS_KEY: &str = "gen_block";
 const HEAD_KEY: &str = "head";
-const BLOCK_VAL_PREFIX: &[u8] = b"block_val/";
 
 // A cap on the size of the future_sink
 const SINK_CAP: usize = 200;
@@ -136,19 +135,8 @@ where
     where
         DB: Clone,
     {
-        let (publisher, _) = broadcast::channel(SINK_CAP);
-        let ts_cache = Arc::new(Mutex::new(LruCache::new(DEFAULT_TIPSET_CACHE_SIZE)));
         let genesis_ts = Arc::new(Tipset::from(genesis_block_header));
-        let cs = Self {
-            publisher,
-            // subscriptions: Default::default(),
-            // subscriptions_count: Default::default(),
-            chain_index: ChainIndex::new(ts_cache.clone(), db.clone()),
-            tipset_tracker: TipsetTracker::new(db.clone(), chain_config),
-            db,
-            ts_cache,
-            heaviest: Mutex::new(genesis_ts.clone()),
-        };
+        let cs = Self::with_heaviest(db, chain_config, genesis_ts.clone());
 
         // Result intentionally ignored, doesn't matter if heaviest doesn't exist in
         // store yet
@@ -163,6 +151,40 @@ where
         Ok(cs)
     }
 
+    /// Loads the chain of a database that [`ChainStore::new`] has set up,
+    /// without writing to it, so that the database can be opened for reading
+    /// only.
+    pub fn load(db: DB, chain_config: Arc<ChainConfig>) -> Result<Self>
+    where
+        DB: Clone,
+    {
+        let genesis = db
+            .read(GENESIS_KEY)?
+            .ok_or_else(|| anyhow::anyhow!("Genesis key not defined in database"))?;
+        let genesis = BlockHeader::unmarshal_cbor(&genesis)?;
+        let cs = Self::with_heaviest(db, chain_config, Arc::new(Tipset::from(&genesis)));
+        cs.load_heaviest_tipset()?;
+        Ok(cs)
+    }
+
+    fn with_heaviest(db: DB, chain_config: Arc<ChainConfig>, heaviest: Arc<Tipset>) -> Self
+    where
+        DB: Clone,
+    {
+        let (publisher, _) = broadcast::channel(SINK_CAP);
+        let ts_cache = Arc::new(Mutex::new(LruCache::new(DEFAULT_TIPSET_CACHE_SIZE)));
+        Self {
+            publisher,
+            // subscriptions: Default::default(),
+            // subscriptions_count: Default::default(),
+            chain_index: ChainIndex::new(ts_cache.clone(), db.clone()),
+            tipset_tracker: TipsetTracker::new(db.clone(), chain_config),
+            db,
+            ts_cache,
+            heaviest: Mutex::new(heaviest),
+        }
+    }
+
     /// Sets heaviest tipset within `ChainStore` and store its tipset keys under
     /// `HEAD_KEY`
     pub fn set_heaviest_tipset(&self, ts: Arc<Tipset>) -> Result<(), Error> {
@@ -700,7 +722,7 @@ where
 /// Helper to ensure consistent CID to db key translation.
 fn block_validation_key(cid: &Cid) -> Vec<u8> {
     let mut key = Vec::new();
-    key.extend_from_slice(BLOCK_VAL_PREFIX);
+    key.extend_from_slice(BLOCK_VALIDATION_PREFIX);
     key.extend(cid.to_bytes());
     key
 }
@@ -0,0 +1,243 @@
+// Copyright 2019-2023 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use cid::{
+    multihash::{Code, MultihashDigest},
+    Cid,
+};
+use forest_blocks::Tipset;
+use forest_db::Store;
+use futures::Future;
+use fvm_ipld_blockstore::Blockstore;
+use fvm_ipld_car::CarReader;
+use fvm_shared::clock::ChainEpoch;
+use log::{info, warn};
+use parking_lot::Mutex;
+use tokio::io::AsyncRead;
+use tokio_util::compat::TokioAsyncReadCompatExt;
+
+use super::{ChainStore, Error};
+
+/// `CBOR` null, loaded in place of the blocks that can't be checked so that
+/// the walk goes on without following their links.
+pub(super) const PLACEHOLDER: [u8; 1] = [0xf6];
+
+/// Outcome of [`ChainStore::check`].
+#[derive(Debug, Default)]
+pub struct CheckReport {
+    /// Number of blocks checked.
+    pub checked: u64,
+    /// Blocks linked to but absent from the store.
+    pub missing: Vec<Cid>,
+    /// Blocks whose data doesn't hash to their CID.
+    pub corrupted: Vec<Cid>,
+    /// Blocks that couldn't be read from the store, with the read error.
+    pub unreadable: Vec<(Cid, String)>,
+    /// Missing, corrupted and unreadable blocks that were written back.
+    pub repaired: Vec<Cid>,
+    /// Error which stopped the walk before all the reachable blocks were
+    /// checked, typically a missing block header.
+    pub aborted: Option<String>,
+}
+
+impl CheckReport {
+    /// Returns `true` if no bad block was found or all of them were repaired.
+    pub fn is_healthy(&self) -> bool {
+        self.aborted.is_none()
+            && self.missing.len() + self.corrupted.len() + self.unreadable.len()
+                == self.repaired.len()
+    }
+
+    /// Blocks found missing, corrupted or unreadable and not repaired yet.
+    pub fn unrepaired(&self) -> Vec<Cid> {
+        self.missing
+            .iter()
+            .chain(&self.corrupted)
+            .chain(self.unreadable.iter().map(|(cid, _)| cid))
+            .filter(|cid| !self.repaired.contains(cid))
+            .copied()
+            .collect()
+    }
+}
+
+/// Returns `true` if the data hashes to the CID. Blocks hashed with a function
+/// that isn't supported are assumed to be valid.
+pub fn verify_block(cid: &Cid, data: &[u8]) -> bool {
+    match Code::try_from(cid.hash().code()) {
+        Ok(code) => code.digest(data) == *cid.hash(),
+        Err(_) => true,
+    }
+}
+
+impl<DB> ChainStore<DB>
+where
+    DB: Blockstore + Store + Send + Sync,
+{
+    /// Walks the blocks reachable from the tipset like [`ChainStore::export`]
+    /// does, re-hashing every block against its CID, and counting them in
+    /// `progress`. The blocks found missing, corrupted or unreadable are asked
+    /// to `refetch`, and only overwritten in the store when it returns data
+    /// that hashes to their CID.
+    pub async fn check<F, T>(
+        &self,
+        tipset: &Tipset,
+        recent_roots: ChainEpoch,
+        progress: &AtomicU64,
+        mut refetch: F,
+    ) -> CheckReport
+    where
+        F: FnMut(Cid) -> T + Send,
+        T: Future<Output = Option<Vec<u8>>> + Send,
+    {
+        let report = Mutex::new(CheckReport::default());
+        let report_ref = &report;
+        let result = Self::walk_snapshot(tipset, recent_roots, |cid| {
+            let found = self.check_block(&cid, &mut report_ref.lock());
+            progress.fetch_add(1, Ordering::Relaxed);
+            let refetched = found.is_none().then(|| refetch(cid));
+            async move {
+                if let Some(data) = found {
+                    return Ok(data);
+                }
+                match refetched.expect("refetched when not found").await {
+                    Some(data) if verify_block(&cid, &data) => {
+                        self.blockstore().put_keyed(&cid, &data)?;
+                        report_ref.lock().repaired.push(cid);
+                        Ok(data)
+                    }
+                    _ => Ok(PLACEHOLDER.to_vec()),
+                }
+            }
+        })
+        .await;
+
+        let mut report = report.into_inner();
+        if let Err(e) = result {
+            report.aborted = Some(e.to_string());
+        }
+        info!(
+            "Checked {} blocks: {} missing, {} corrupted, {} unreadable, {} repaired",
+            report.checked,
+            report.missing.len(),
+            report.corrupted.len(),
+            report.unreadable.len(),
+            report.repaired.len()
+        );
+        report
+    }
+
+    /// Reads and verifies a block, returning its data if it is valid.
+    fn check_block(&self, cid: &Cid, report: &mut CheckReport) -> Option<Vec<u8>> {
+        report.checked += 1;
+        if cid.hash().code() == u64::from(Code::Identity) {
+            return Some(cid.hash().digest().to_vec());
+        }
+        match self.blockstore().get(cid) {
+            Ok(Some(data)) if verify_block(cid, &data) => Some(data),
+            Ok(Some(_)) => {
+                warn!("Block {cid} is corrupted");
+                report.corrupted.push(*cid);
+                None
+            }
+            Ok(None) => {
+                warn!("Block {cid} is missing");
+                report.missing.push(*cid);
+                None
+            }
+            Err(e) => {
+                warn!("Block {cid} is unreadable: {e}");
+                report.unreadable.push((*cid, e.to_string()));
+                None
+            }
+        }
+    }
+}
+
+/// Writes the blocks of the `CAR` file that are among the given ones and
+/// valid to the store. Returns the blocks written.
+pub async fn repair_from_car<DB, R>(db: &DB, reader: R, cids: &[Cid]) -> Result<Vec<Cid>, Error>
+where
+    DB: Blockstore,
+    R: AsyncRead + Send + Unpin,
+{
+    let mut wanted: ahash::HashSet<Cid> = cids.iter().copied().collect();
+    let mut repaired = vec![];
+    let mut car_reader = CarReader::new(reader.compat())
+        .await
+        .map_err(|e| Error::Other(format!("Failed to read snapshot: {e}")))?;
+    while !wanted.is_empty() {
+        let block = match car_reader
+            .next_block()
+            .await
+            .map_err(|e| Error::Other(format!("Failed to read snapshot: {e}")))?
+        {
+            Some(block) => block,
+            None => break,
+        };
+        if wanted.contains(&block.cid) && verify_block(&block.cid, &block.data) {
+            db.put_keyed(&block.cid, &block.data)
+                .map_err(|e| Error::Other(e.to_string()))?;
+            wanted.remove(&block.cid);
+            repaired.push(block.cid);
+        }
+    }
+    Ok(repaired)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use cid::multihash::Code::Blake2b256;
+    use forest_blocks::BlockHeader;
+    use forest_networks::ChainConfig;
+    use forest_shim::address::Address;
+    use fvm_ipld_encoding::DAG_CBOR;
+
+    use super::*;
+
+    #[test]
+    fn check_reports_and_repairs_bad_blocks() {
+        // `CBOR` empty list
+        let state = vec![0x80];
+        let state_root = Cid::new_v1(DAG_CBOR, Blake2b256.digest(&state));
+        let gen_block = BlockHeader::builder()
+            .state_root(state_root)
+            .miner_address(Address::new_id(0))
+            .build()
+            .unwrap();
+        let cs = ChainStore::new(
+            forest_db::MemoryDB::default(),
+            Arc::new(ChainConfig::default()),
+            &gen_block,
+        )
+        .unwrap();
+        let tipset = Tipset::from(&gen_block);
+        let progress = AtomicU64::default();
+
+        let report =
+            futures::executor::block_on(cs.check(&tipset, 0, &progress, |_| async { None }));
+        assert_eq!(report.checked, 2);
+        assert_eq!(progress.load(Ordering::Relaxed), 2);
+        assert_eq!(report.missing, vec![state_root]);
+        assert!(!report.is_healthy());
+
+        cs.blockstore()
+            .put_keyed(&state_root, &[0x81, 0x00])
+            .unwrap();
+        let report = futures::executor::block_on(cs.check(&tipset, 0, &progress, |_| {
+            let state = state.clone();
+            async move { Some(state) }
+        }));
+        assert_eq!(report.corrupted, vec![state_root]);
+        assert_eq!(report.repaired, vec![state_root]);
+        assert!(report.is_healthy());
+
+        let report =
+            futures::executor::block_on(cs.check(&tipset, 0, &progress, |_| async { None }));
+        assert!(report.unrepaired().is_empty());
+        assert!(report.is_healthy());
+    }
+}
@@ -2,9 +2,12 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 pub mod base_fee;
+mod block_stats;
 mod chain_store;
+mod check;
 mod errors;
 mod index;
+mod tiering;
 mod tipset_tracker;
 
-pub use self::{base_fee::*, chain_store::*, errors::*};
+pub use self::{base_fee::*, block_stats::*, chain_store::*, check::*, errors::*};
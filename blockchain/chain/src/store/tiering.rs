@@ -0,0 +1,254 @@
+// Copyright 2019-2023 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use std::sync::Arc;
+
+use ahash::HashSet;
+use cid::Cid;
+use forest_blocks::Tipset;
+use forest_db::Store;
+use forest_ipld::{recurse_links_hash, CidHashSet, Ipld};
+use fvm_ipld_blockstore::Blockstore;
+use fvm_ipld_encoding::{from_slice, to_vec, DAG_CBOR};
+use fvm_shared::{clock::ChainEpoch, IPLD_RAW};
+
+use super::{check::PLACEHOLDER, ChainStore, Error};
+
+/// Key of the first epoch kept in the hot store by the last migration to the
+/// cold store, whose older epochs were moved.
+const HOT_BOUNDARY_KEY: &str = "/tiering/hot_boundary";
+
+impl<DB> ChainStore<DB>
+where
+    DB: Blockstore + Store + Send + Sync,
+{
+    /// Moves the blocks of the epochs that fell out of the `hot_epochs` ones up
+    /// to the head with `move_blocks`: their headers, messages and receipts,
+    /// and the nodes of their state trees that the state tree of the next
+    /// epoch no longer links to. The state tree of the first hot epoch thus
+    /// stays whole in the hot store. Only the epochs between the first hot
+    /// epoch and the one of the previous migration are walked. Returns the
+    /// number of blocks moved.
+    pub async fn migrate_to_cold(
+        &self,
+        head: Arc<Tipset>,
+        hot_epochs: ChainEpoch,
+        mut move_blocks: impl FnMut(&[Cid]) -> anyhow::Result<u64>,
+    ) -> Result<u64, Error> {
+        let previous = match self.db.read(HOT_BOUNDARY_KEY)? {
+            Some(bytes) => from_slice(&bytes)?,
+            None => 0,
+        };
+        if head.epoch() - hot_epochs <= previous {
+            return Ok(0);
+        }
+        let boundary = self.tipset_by_height(head.epoch() - hot_epochs, head, true)?;
+        if boundary.epoch() <= previous {
+            return Ok(0);
+        }
+
+        let mut moved = 0;
+        let mut next = Arc::clone(&boundary);
+        while next.epoch() > previous {
+            let tipset = match self.tipset_from_keys(next.parents()) {
+                Ok(tipset) => tipset,
+                // The chain of a node started from a snapshot begins with a
+                // header whose parents are missing.
+                Err(Error::NotFound(_)) => break,
+                Err(e) => return Err(e),
+            };
+            moved += move_blocks(&self.cold_blocks(&tipset, &next).await?)?;
+            next = tipset;
+        }
+        self.db
+            .write(HOT_BOUNDARY_KEY, to_vec(&boundary.epoch())?)?;
+        Ok(moved)
+    }
+
+    /// Blocks of the tipset that the next one doesn't use: its headers,
+    /// messages and receipts, and the nodes of its parent state tree that the
+    /// parent state tree of `next` doesn't link to.
+    async fn cold_blocks(&self, tipset: &Tipset, next: &Tipset) -> Result<Vec<Cid>, Error> {
+        let mut cids = vec![];
+        let mut seen = CidHashSet::default();
+        for header in tipset.blocks() {
+            cids.push(*header.cid());
+            for root in [header.messages(), header.message_receipts()] {
+                recurse_links_hash(&mut seen, *root, &mut |cid| {
+                    cids.push(cid);
+                    let data = self.blockstore().get(&cid);
+                    async move { Ok(data?.unwrap_or_else(|| PLACEHOLDER.to_vec())) }
+                })
+                .await?;
+            }
+        }
+        cids.extend(self.tree_diff(*tipset.parent_state(), *next.parent_state())?);
+        Ok(cids)
+    }
+
+    /// Nodes of the tree at `old` that the tree at `new` doesn't link to at the
+    /// same depth. Both trees are walked down from their roots, one depth at a
+    /// time, only where they differ, so the walk is as long as the changes
+    /// between them.
+    fn tree_diff(&self, old: Cid, new: Cid) -> Result<Vec<Cid>, Error> {
+        let mut diff = vec![];
+        let mut old_nodes: HashSet<Cid> = [old].into_iter().collect();
+        let mut new_nodes: HashSet<Cid> = [new].into_iter().collect();
+        while !old_nodes.is_empty() {
+            let (mut old_links, mut new_links) = (vec![], vec![]);
+            for cid in old_nodes.difference(&new_nodes) {
+                diff.push(*cid);
+                self.push_links(*cid, &mut old_links)?;
+            }
+            for cid in new_nodes.difference(&old_nodes) {
+                self.push_links(*cid, &mut new_links)?;
+            }
+            old_nodes = old_links.into_iter().collect();
+            new_nodes = new_links.into_iter().collect();
+        }
+        Ok(diff)
+    }
+
+    /// Pushes the links of a `DAG-CBOR` block, if it is in the store.
+    fn push_links(&self, cid: Cid, links: &mut Vec<Cid>) -> Result<(), Error> {
+        if cid.codec() != DAG_CBOR {
+            return Ok(());
+        }
+        if let Some(data) = self.blockstore().get(&cid)? {
+            collect_links(&from_slice(&data)?, links);
+        }
+        Ok(())
+    }
+}
+
+/// Collects the links to `DAG-CBOR` and raw blocks, the ones the store holds.
+fn collect_links(ipld: &Ipld, links: &mut Vec<Cid>) {
+    match ipld {
+        Ipld::Map(map) => map.values().for_each(|v| collect_links(v, links)),
+        Ipld::List(list) => list.iter().for_each(|v| collect_links(v, links)),
+        Ipld::Link(cid) if cid.codec() == DAG_CBOR || cid.codec() == IPLD_RAW => links.push(*cid),
+        _ => (),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cid::multihash::Code::Blake2b256;
+    use forest_blocks::{BlockHeader, TipsetKeys};
+    use forest_db::{tiered::TieredDbConfig, MemoryDB};
+    use forest_networks::ChainConfig;
+    use forest_shim::address::Address;
+    use forest_utils::db::BlockstoreExt;
+
+    use super::*;
+
+    /// Blocks of an epoch of a test chain.
+    struct Epoch {
+        header: Cid,
+        messages: Cid,
+        receipts: Cid,
+        state_root: Cid,
+        state_leaf: Cid,
+    }
+
+    impl Epoch {
+        fn blocks(&self) -> [Cid; 5] {
+            [
+                self.header,
+                self.messages,
+                self.receipts,
+                self.state_root,
+                self.state_leaf,
+            ]
+        }
+    }
+
+    /// Chain of `len` epochs whose state trees share a leaf, each with a leaf
+    /// of its own.
+    fn chain(db: &MemoryDB, len: ChainEpoch) -> (ChainStore<MemoryDB>, Vec<Epoch>, Cid) {
+        let shared_leaf = db.put_obj(&"shared", Blake2b256).unwrap();
+        let mut epochs: Vec<Epoch> = vec![];
+        let mut headers = vec![];
+        for epoch in 0..len {
+            let state_leaf = db.put_obj(&("state", epoch), Blake2b256).unwrap();
+            let state_root = db.put_obj(&(shared_leaf, state_leaf), Blake2b256).unwrap();
+            let messages = db.put_obj(&("messages", epoch), Blake2b256).unwrap();
+            let receipts = db.put_obj(&("receipts", epoch), Blake2b256).unwrap();
+            let parents = match epochs.last() {
+                Some(parent) => TipsetKeys::new(vec![parent.header]),
+                None => TipsetKeys::default(),
+            };
+            let header = BlockHeader::builder()
+                .epoch(epoch)
+                .weight(epoch.into())
+                .parents(parents)
+                .messages(messages)
+                .message_receipts(receipts)
+                .state_root(state_root)
+                .miner_address(Address::new_id(0))
+                .build()
+                .unwrap();
+            db.put_obj(&header, Blake2b256).unwrap();
+            epochs.push(Epoch {
+                header: *header.cid(),
+                messages,
+                receipts,
+                state_root,
+                state_leaf,
+            });
+            headers.push(header);
+        }
+        let chain_store =
+            ChainStore::new(db.clone(), Arc::new(ChainConfig::default()), &headers[0]).unwrap();
+        chain_store
+            .set_heaviest_tipset(Arc::new(Tipset::from(headers.last().unwrap())))
+            .unwrap();
+        (chain_store, epochs, shared_leaf)
+    }
+
+    fn migrate(chain_store: &ChainStore<MemoryDB>, head: &Cid) -> HashSet<Cid> {
+        let head = chain_store
+            .tipset_from_keys(&TipsetKeys::new(vec![*head]))
+            .unwrap();
+        let mut moved = HashSet::default();
+        let count = futures::executor::block_on(chain_store.migrate_to_cold(
+            head,
+            TieredDbConfig::default().hot_epochs,
+            |cids| {
+                moved.extend(cids.iter().copied());
+                Ok(cids.len() as u64)
+            },
+        ))
+        .unwrap();
+        assert_eq!(count as usize, moved.len());
+        moved
+    }
+
+    #[test]
+    fn migrate_to_cold_moves_the_epochs_out_of_the_default_hot_epochs() {
+        let db = MemoryDB::default();
+        let hot_epochs = TieredDbConfig::default().hot_epochs;
+        let (chain_store, epochs, shared_leaf) = chain(&db, hot_epochs + 20);
+
+        // The head is at `hot_epochs + 19`, so the 19 first epochs are cold.
+        let moved = migrate(&chain_store, &epochs[hot_epochs as usize + 19].header);
+        let cold: HashSet<_> = epochs[..19].iter().flat_map(Epoch::blocks).collect();
+        assert_eq!(moved, cold);
+        assert!(!moved.contains(&shared_leaf));
+
+        // Only the epochs that fell out of the hot ones since are walked again.
+        assert!(migrate(&chain_store, &epochs[hot_epochs as usize + 19].header).is_empty());
+    }
+
+    #[test]
+    fn migrate_to_cold_resumes_from_the_previous_boundary() {
+        let db = MemoryDB::default();
+        let hot_epochs = TieredDbConfig::default().hot_epochs;
+        let (chain_store, epochs, _) = chain(&db, hot_epochs + 20);
+
+        migrate(&chain_store, &epochs[hot_epochs as usize + 9].header);
+        let moved = migrate(&chain_store, &epochs[hot_epochs as usize + 19].header);
+        let cold: HashSet<_> = epochs[9..19].iter().flat_map(Epoch::blocks).collect();
+        assert_eq!(moved, cold);
+    }
+}
@@ -482,6 +482,13 @@ where
             return Ok(None);
         }
 
+        // Remember where the headers come from, in case they turn out to be
+        // invalid once fully validated
+        network.peer_manager().record_block_providers(
+            source,
+            tipset.blocks().iter().map(|block| *block.header().cid()),
+        );
+
         // Validate tipset
         if let Err(why) = TipsetValidator(&tipset).validate(
             chain_store.clone(),
@@ -494,6 +501,16 @@ where
                 "Validating tipset received through GossipSub failed: {}",
                 why
             );
+            // Errors reading the local store are not the fault of the peer
+            if !matches!(
+                *why,
+                TipsetValidationError::Blockstore(_) | TipsetValidationError::Encoding(_)
+            ) {
+                network
+                    .peer_manager()
+                    .blame_peer(source, why.to_string())
+                    .await;
+            }
             return Err(why.into());
         }
 
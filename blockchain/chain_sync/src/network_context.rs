@@ -330,6 +330,13 @@ where
             Ok(Ok(Ok(bs_res))) => {
                 // Successful response
                 peer_manager.log_success(peer_id, res_duration).await;
+                peer_manager.record_block_providers(
+                    peer_id,
+                    bs_res
+                        .chain
+                        .iter()
+                        .flat_map(|bundle| bundle.blocks.iter().map(|header| *header.cid())),
+                );
                 log::debug!("Succeeded: ChainExchange Request to {peer_id}");
                 Ok(bs_res)
             }
@@ -19,7 +19,7 @@ use forest_blocks::{
 };
 use forest_chain::{persist_objects, ChainStore, Error as ChainStoreError};
 use forest_db::Store;
-use forest_libp2p::chain_exchange::TipsetBundle;
+use forest_libp2p::{chain_exchange::TipsetBundle, PeerManager};
 use forest_message::{message::valid_for_block_inclusion, Message as MessageTrait};
 use forest_networks::Height;
 use forest_shim::{
@@ -128,15 +128,38 @@ impl<C: Consensus> From<tokio::task::JoinError> for TipsetRangeSyncerError<C> {
 
 impl<C: Consensus> TipsetRangeSyncerError<C> {
     /// Concatenate all validation error messages into one comma separated
-    /// version.
+    /// version. It is a validation error if any of them is a fault of the
+    /// block, a processing error otherwise.
     fn concat(errs: NonEmpty<TipsetRangeSyncerError<C>>) -> Self {
+        let is_block_fault = errs.iter().any(Self::is_block_fault);
         let msg = errs
             .iter()
             .map(|e| e.to_string())
             .collect::<Vec<_>>()
             .join(", ");
 
-        TipsetRangeSyncerError::Validation(msg)
+        if is_block_fault {
+            TipsetRangeSyncerError::Validation(msg)
+        } else {
+            TipsetRangeSyncerError::Calculation(msg)
+        }
+    }
+
+    /// Whether the error is a fault of the block, found by its validation or
+    /// by the consensus, rather than one of the local node or of the network,
+    /// so that the peer providing the block is to blame.
+    fn is_block_fault(&self) -> bool {
+        matches!(
+            self,
+            TipsetRangeSyncerError::Validation(_)
+                | TipsetRangeSyncerError::BlockWithoutSignature
+                | TipsetRangeSyncerError::BlockWithoutBlsAggregate
+                | TipsetRangeSyncerError::BlockError(_)
+                | TipsetRangeSyncerError::BlsAggregateSignatureInvalid(_, _)
+                | TipsetRangeSyncerError::MessageSignatureInvalid(_)
+                | TipsetRangeSyncerError::BlockMessageRootInvalid(_, _)
+                | TipsetRangeSyncerError::ConsensusError(_)
+        )
     }
 }
 
@@ -1087,6 +1110,7 @@ async fn sync_messages_check_state<
     const REQUEST_WINDOW: usize = 4;
 
     let task_chainstore = chainstore.clone();
+    let peer_manager = network.peer_manager.clone();
 
     // Spawn a background task for the chain_exchange message requests
 
@@ -1128,6 +1152,7 @@ async fn sync_messages_check_state<
                 consensus.clone(),
                 state_manager.clone(),
                 &chainstore,
+                &peer_manager,
                 bad_block_cache,
                 full_tipset,
                 genesis,
@@ -1150,6 +1175,7 @@ async fn validate_tipset<DB: Blockstore + Store + Clone + Send + Sync + 'static,
     consensus: Arc<C>,
     state_manager: Arc<StateManager<DB>>,
     chainstore: &ChainStore<DB>,
+    peer_manager: &PeerManager,
     bad_block_cache: &BadBlockCache,
     full_tipset: FullTipset,
     genesis: &Tipset,
@@ -1201,6 +1227,13 @@ async fn validate_tipset<DB: Blockstore + Store + Clone + Send + Sync + 'static,
                         | TipsetRangeSyncerError::TipsetParentNotFound(_) => (),
                         why => {
                             bad_block_cache.put(cid, why.to_string());
+                            // Errors of the local node or of the network are
+                            // not the fault of the peer
+                            if why.is_block_fault() {
+                                peer_manager
+                                    .blame_block_provider(&cid, &why.to_string())
+                                    .await;
+                            }
                         }
                     }
                 }
@@ -47,7 +47,7 @@ use tokio::sync::{broadcast::error::RecvError, Mutex as TokioMutex, RwLock};
 use tracing::{debug, error, info, instrument, trace, warn};
 use vm_circ_supply::GenesisInfo;
 
-pub use self::errors::*;
+pub use self::{errors::*, vm_circ_supply::CirculatingSupply};
 
 const DEFAULT_TIPSET_CACHE_SIZE: NonZeroUsize =
     forest_utils::const_option!(NonZeroUsize::new(1024));
@@ -187,8 +187,10 @@ type StateCallResult = Result<InvocResult, Error>;
 #[derive(Default, Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct MarketBalance {
-    escrow: TokenAmount,
-    locked: TokenAmount,
+    #[serde(with = "forest_json::token_amount::json")]
+    pub escrow: TokenAmount,
+    #[serde(with = "forest_json::token_amount::json")]
+    pub locked: TokenAmount,
 }
 
 /// State manager handles all interactions with the internal Filecoin actors
@@ -250,6 +252,39 @@ where
         &self.chain_config
     }
 
+    /// Returns the circulating supply the VM sees at the epoch, on top of the
+    /// given state root.
+    pub fn get_circulating_supply(
+        &self,
+        epoch: ChainEpoch,
+        root: &Cid,
+    ) -> anyhow::Result<TokenAmount> {
+        self.genesis_info
+            .get_circulating_supply(epoch, self.blockstore(), root)
+    }
+
+    /// Returns the circulating supply the VM sees at the epoch along with the
+    /// amounts it is computed from.
+    pub fn get_circulating_supply_detail(
+        &self,
+        epoch: ChainEpoch,
+        root: &Cid,
+    ) -> anyhow::Result<CirculatingSupply> {
+        self.genesis_info
+            .get_circulating_supply_detail(epoch, self.blockstore(), root)
+    }
+
+    /// Returns the circulating supply at the epoch computed from the balances
+    /// of the actors in the given state root, as Lotus
+    /// `StateCirculatingSupply` does.
+    pub fn get_state_circulating_supply(
+        &self,
+        epoch: ChainEpoch,
+        root: &Cid,
+    ) -> anyhow::Result<TokenAmount> {
+        vm_circ_supply::get_state_circulating_supply(epoch, self.blockstore(), root)
+    }
+
     /// Gets actor from given [`Cid`], if it exists.
     pub fn get_actor(&self, addr: &Address, state_cid: Cid) -> anyhow::Result<Option<ActorState>> {
         let state = StateTree::new_from_root(self.blockstore().clone(), &state_cid)?;
@@ -899,6 +934,24 @@ where
     ) -> Result<Option<(Arc<Tipset>, Receipt)>, Error> {
         self.check_search(current, params)
     }
+    /// Looks the message up in the chain up to the head, without waiting for
+    /// it. Returns the receipt of the message and the tipset it was executed
+    /// in, if it is on chain.
+    pub fn search_for_message(
+        &self,
+        msg_cid: Cid,
+    ) -> Result<Option<(Arc<Tipset>, Receipt)>, Error> {
+        let message = forest_chain::get_chain_message(self.blockstore(), &msg_cid)
+            .map_err(|err| Error::Other(format!("failed to load message {err:}")))?;
+        let head = self.cs.heaviest_tipset();
+        if let Some(receipt) =
+            self.tipset_executed_message(&head, msg_cid, (&message.from(), &message.sequence()))?
+        {
+            return Ok(Some((head, receipt)));
+        }
+        self.search_back_for_message(head, (&message.from(), &msg_cid, &message.sequence()))
+    }
+
     /// Returns a message receipt from a given tipset and message CID.
     pub fn get_receipt(&self, tipset: Arc<Tipset>, msg: Cid) -> Result<Receipt, Error> {
         let m = forest_chain::get_chain_message(self.blockstore(), &msg)
@@ -4,7 +4,8 @@
 use anyhow::Context;
 use cid::Cid;
 use forest_actor_interface::{
-    market, power, reward, BURNT_FUNDS_ACTOR_ADDR, EPOCHS_IN_DAY, RESERVE_ADDRESS,
+    account, market, miner, multisig, paych, power, reward, system, BURNT_FUNDS_ACTOR_ADDR,
+    EPOCHS_IN_DAY, RESERVE_ADDRESS,
 };
 use forest_chain::*;
 use forest_db::Store;
@@ -16,6 +17,7 @@ use forest_shim::{
 use fvm_ipld_blockstore::Blockstore;
 use fvm_shared::{address::Address, clock::ChainEpoch};
 use num_traits::Zero;
+use serde::{Deserialize, Serialize};
 
 const EPOCHS_IN_YEAR: ChainEpoch = 365 * EPOCHS_IN_DAY;
 const PRE_CALICO_VESTING: [(ChainEpoch, usize); 5] = [
@@ -34,6 +36,20 @@ const CALICO_VESTING: [(ChainEpoch, usize); 6] = [
     (6 * EPOCHS_IN_YEAR, 100_000_000 + 300_000_000 + 9_805_053),
 ];
 
+/// IDs of the singleton actors whose balances never circulate: the system,
+/// init, reward, cron, power, verified registry, Ethereum address manager,
+/// reserve, burnt funds and SAFT actors.
+const NON_CIRCULATING_ACTOR_IDS: [u64; 10] = [0, 1, 2, 3, 4, 6, 10, 90, 99, 122];
+
+/// Manifest names of the actors whose whole balances circulate.
+const CIRCULATING_ACTOR_NAMES: [&str; 5] = [
+    account::ACTOR_NAME,
+    paych::ACTOR_NAME,
+    "ethaccount",
+    "evm",
+    "placeholder",
+];
+
 /// Genesis information used when calculating circulating supply.
 #[derive(Default, Clone)]
 pub(crate) struct GenesisInfo {
@@ -71,25 +87,136 @@ impl GenesisInfo {
         db: &DB,
         root: &Cid,
     ) -> Result<TokenAmount, anyhow::Error> {
+        Ok(self
+            .get_circulating_supply_detail(height, db, root)?
+            .fil_circulating)
+    }
+
+    /// Returns the circulating supply along with the amounts it is computed
+    /// from.
+    pub fn get_circulating_supply_detail<DB: Blockstore + Store + Clone>(
+        &self,
+        height: ChainEpoch,
+        db: &DB,
+        root: &Cid,
+    ) -> Result<CirculatingSupply, anyhow::Error> {
         let state_tree = StateTree::new_from_root(db, root)?;
         let fil_vested = get_fil_vested(self, height);
         let fil_mined = get_fil_mined(&state_tree)?;
         let fil_burnt = get_fil_burnt(&state_tree)?;
         let fil_locked = get_fil_locked(&state_tree)?;
-        let fil_reserve_distributed = if height > self.actors_v2_height {
+        let fil_reserve_disbursed = if height > self.actors_v2_height {
             get_fil_reserve_disbursed(&state_tree)?
         } else {
             TokenAmount::default()
         };
         let fil_circulating = TokenAmount::max(
-            &fil_vested + &fil_mined + &fil_reserve_distributed - &fil_burnt - &fil_locked,
+            &fil_vested + &fil_mined + &fil_reserve_disbursed - &fil_burnt - &fil_locked,
             TokenAmount::default(),
         );
 
-        Ok(fil_circulating)
+        Ok(CirculatingSupply {
+            fil_vested,
+            fil_mined,
+            fil_burnt,
+            fil_locked,
+            fil_circulating,
+            fil_reserve_disbursed,
+        })
     }
 }
 
+/// Returns the circulating supply the way Lotus `StateCirculatingSupply`
+/// computes it: the balances of all the actors, less the funds of the
+/// non-circulating singletons and the funds locked in the market, miner and
+/// multisig actors. Unlike [`GenesisInfo::get_circulating_supply`], it doesn't
+/// rely on the vesting schedules the VM uses.
+pub(crate) fn get_state_circulating_supply<DB: Blockstore + Store + Clone>(
+    height: ChainEpoch,
+    db: &DB,
+    root: &Cid,
+) -> Result<TokenAmount, anyhow::Error> {
+    let state_tree = StateTree::new_from_root(db, root)?;
+    let system_actor = state_tree
+        .get_actor(&system::ADDRESS)?
+        .context("System actor address could not be resolved")?;
+    let builtin_actors = system::State::load(db, &system_actor)?.builtin_actors(db)?;
+    let actor_name = |code: &Cid| {
+        builtin_actors
+            .iter()
+            .find(|(_, builtin)| builtin == code)
+            .map(|(name, _)| name.as_str())
+    };
+
+    let mut circulating = TokenAmount::zero();
+    let mut non_circulating = TokenAmount::zero();
+    state_tree.for_each(|address, actor| {
+        let balance = TokenAmount::from(&actor.balance);
+        if balance.is_zero() {
+            return Ok(());
+        }
+        let id = address.id().ok();
+        if id.map_or(false, |id| NON_CIRCULATING_ACTOR_IDS.contains(&id)) {
+            non_circulating += balance;
+        } else if address == market::ADDRESS {
+            let locked = market::State::load(db, actor)?.total_locked();
+            circulating += &balance - &locked;
+            non_circulating += locked;
+        } else {
+            match actor_name(&actor.code) {
+                Some(name) if CIRCULATING_ACTOR_NAMES.contains(&name) => circulating += balance,
+                Some(miner::ACTOR_NAME) => {
+                    let available = miner::State::load(db, actor)?.available_balance(&balance);
+                    if available < TokenAmount::zero() {
+                        // The miner is in debt, none of its balance circulates
+                        non_circulating += balance;
+                    } else {
+                        non_circulating += &balance - &available;
+                        circulating += available;
+                    }
+                }
+                Some(multisig::ACTOR_NAME) => {
+                    let locked = multisig::State::load(db, actor)?.locked_balance(height);
+                    circulating += TokenAmount::max(&balance - &locked, TokenAmount::zero());
+                    non_circulating += TokenAmount::min(balance, locked);
+                }
+                _ => anyhow::bail!("Unexpected actor {address} with code {}", actor.code),
+            }
+        }
+        Ok(())
+    })?;
+
+    let total = &circulating + &non_circulating;
+    let total_filecoin = TokenAmount::from(&*fvm_shared::TOTAL_FILECOIN);
+    anyhow::ensure!(
+        total == total_filecoin,
+        "Total token amount {total} doesn't match the supply of {total_filecoin}"
+    );
+    Ok(circulating)
+}
+
+/// Circulating supply as seen by the VM, and the amounts it is computed from.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct CirculatingSupply {
+    /// Genesis allocations vested so far.
+    #[serde(with = "forest_json::token_amount::json")]
+    pub fil_vested: TokenAmount,
+    /// Block rewards paid out so far.
+    #[serde(with = "forest_json::token_amount::json")]
+    pub fil_mined: TokenAmount,
+    #[serde(with = "forest_json::token_amount::json")]
+    pub fil_burnt: TokenAmount,
+    /// Collateral locked by the power and market actors.
+    #[serde(with = "forest_json::token_amount::json")]
+    pub fil_locked: TokenAmount,
+    #[serde(with = "forest_json::token_amount::json")]
+    pub fil_circulating: TokenAmount,
+    /// Funds disbursed from the mining reserve.
+    #[serde(with = "forest_json::token_amount::json")]
+    pub fil_reserve_disbursed: TokenAmount,
+}
+
 /// Vesting schedule info. These states are lazily filled, to avoid doing until
 /// needed to calculate circulating supply.
 #[derive(Default, Clone)]
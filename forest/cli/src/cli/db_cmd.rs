@@ -1,39 +1,171 @@
 // Copyright 2019-2023 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::Context;
 use clap::Subcommand;
-use forest_cli_shared::{chain_path, cli::Config};
-use forest_db::db_engine::db_path;
+use forest_blocks::tipset_keys_json::TipsetKeysJson;
+use forest_chain::ChainStore;
+use forest_cli_shared::{chain_path, cli::Config, DaemonLock};
+use forest_db::{
+    db_engine::{db_path, open_db, open_db_read_only, DbBackend, DbConfig},
+    migration::{progress_file, start_progress, Migration},
+    DBStatistics, DbStats,
+};
+use forest_genesis::read_genesis_header;
+use forest_json::cid::CidJson;
+use forest_key_management::{ENCRYPTED_KEYSTORE_NAME, KEYSTORE_NAME};
+use forest_rpc_api::{chain_api::ChainCheckStoreParams, data_types::RepairSource};
+use forest_rpc_client::{
+    chain_ops::{chain_check_store, chain_check_store_status, chain_head},
+    common_ops::create_backup,
+};
+use forest_utils::io::ProgressBar;
+use human_repr::HumanCount;
 use log::error;
+use serde::{Deserialize, Serialize};
 
-use crate::cli::prompt_confirm;
+use crate::cli::{handle_rpc_err, prompt_confirm};
 
 #[derive(Debug, Subcommand)]
 pub enum DBCommands {
-    /// Show DB stats
-    Stats,
+    /// Show DB stats. The database is opened for reading only, so the node
+    /// can be running
+    Stats {
+        /// Also classify the blocks reachable from the chain head into
+        /// headers, messages, receipts and state
+        #[arg(long)]
+        detailed: bool,
+        /// Number of recent state roots to classify with `--detailed`
+        #[arg(short, long, default_value = "2000")]
+        recent_stateroots: i64,
+    },
     /// DB Clean up
     Clean {
         /// Answer yes to all forest-cli yes/no questions without prompting
         #[arg(long)]
         force: bool,
     },
+    /// Check the blocks reachable from the chain head of the running node
+    /// against their CIDs, and report the missing, corrupted and unreadable
+    /// ones
+    Check {
+        /// Number of recent state roots to check, older state trees are skipped
+        #[arg(short, long, default_value = "2000")]
+        recent_stateroots: i64,
+        /// Fetch the bad blocks again from the peers of the node, over bitswap
+        #[arg(long, conflicts_with = "repair_from")]
+        repair: bool,
+        /// Fetch the bad blocks again from a snapshot file, on the host of the
+        /// node
+        #[arg(long)]
+        repair_from: Option<PathBuf>,
+    },
+    /// Copy all the entries of the database of a backend into a new database
+    /// of another backend. An interrupted migration resumes where it stopped
+    /// when run again
+    Migrate {
+        /// Backend of the database to copy, `rocksdb` or `paritydb`
+        #[arg(long)]
+        from: DbBackend,
+        /// Backend of the database to create, `rocksdb` or `paritydb`
+        #[arg(long)]
+        to: DbBackend,
+        /// Number of entries copied and verified at once
+        #[arg(long, default_value_t = 10_000)]
+        batch_size: usize,
+    },
+    /// Back the running node up: a consistent copy of its database, its
+    /// keystore and its configuration. The database is copied by the node, so
+    /// the directory has to be on its host
+    Backup {
+        /// Directory to create the backup in, which must not exist
+        dir: PathBuf,
+    },
+    /// Restore a backup made by `db backup`, with the node stopped, and check
+    /// that its chain head can be loaded
+    Restore {
+        /// Directory of the backup
+        dir: PathBuf,
+        /// Also write the configuration of the backup to this file
+        #[arg(long)]
+        restore_config: Option<PathBuf>,
+        /// Replace the database and keystore of the node, and restore the
+        /// backup of another network
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+/// Version of the layout of the backups.
+const BACKUP_FORMAT: u32 = 1;
+/// Manifest of a backup, written last so that incomplete backups are told
+/// apart.
+const BACKUP_MANIFEST: &str = "backup.json";
+
+#[derive(Debug, Serialize, Deserialize)]
+struct BackupManifest {
+    format: u32,
+    forest_version: String,
+    network: String,
+    db_backend: DbBackend,
+    /// Seconds since the Unix epoch.
+    created: u64,
 }
 
 impl DBCommands {
-    pub fn run(&self, config: &Config) -> anyhow::Result<()> {
+    pub async fn run(&self, config: &Config) -> anyhow::Result<()> {
         match self {
-            Self::Stats => {
-                use human_repr::HumanCount;
-
-                let dir = db_path(&chain_path(config));
+            Self::Stats {
+                detailed,
+                recent_stateroots,
+            } => {
+                let dir = db_path(&chain_path(config), config.client.db_backend);
                 println!("Database path: {}", dir.display());
-                let size = fs_extra::dir::get_size(dir).unwrap_or_default();
+                let size = fs_extra::dir::get_size(&dir).unwrap_or_default();
                 println!("Database size: {}", size.human_count_bytes());
+                if !dir.is_dir() {
+                    return Ok(());
+                }
+
+                let db = open_db_read_only(&dir, &config.db_config())
+                    .context("Failed to open the database")?;
+                match db.get_statistics() {
+                    Some(stats) => print_db_stats(&stats),
+                    None => println!(
+                        "Database statistics not enabled. Set enable_statistics to true in config"
+                    ),
+                }
+
+                if *detailed {
+                    let chain_store = ChainStore::load(db, config.chain.clone())?;
+                    let stats = chain_store
+                        .block_stats(chain_store.heaviest_tipset(), *recent_stateroots)
+                        .await?;
+                    for (kind, count) in [
+                        ("Headers", stats.headers),
+                        ("Messages", stats.messages),
+                        ("Receipts", stats.receipts),
+                        ("State", stats.state),
+                    ] {
+                        println!(
+                            "{kind}: {} blocks, {} ({} missing)",
+                            count.count,
+                            count.bytes.human_count_bytes(),
+                            count.missing
+                        );
+                    }
+                }
                 Ok(())
             }
             Self::Clean { force } => {
-                let dir = db_path(&chain_path(config));
+                let dir = db_path(&chain_path(config), config.client.db_backend);
                 if !dir.is_dir() {
                     println!(
                         "Aborted. Database path {} is not a valid directory",
@@ -57,6 +189,400 @@ impl DBCommands {
                     }
                 }
             }
+            Self::Check {
+                recent_stateroots,
+                repair,
+                repair_from,
+            } => {
+                let token = &config.client.rpc_token;
+                let head = chain_head(token).await.map_err(handle_rpc_err)?.0;
+                let repair = match repair_from {
+                    Some(path) => Some(RepairSource::Snapshot(path.clone())),
+                    None => repair.then_some(RepairSource::Bitswap),
+                };
+                chain_check_store(
+                    ChainCheckStoreParams {
+                        recent_roots: *recent_stateroots,
+                        tipset_keys: TipsetKeysJson(head.key().clone()),
+                        repair,
+                    },
+                    token,
+                )
+                .await
+                .map_err(handle_rpc_err)?;
+
+                let report = loop {
+                    tokio::time::sleep(Duration::from_secs(1)).await;
+                    let status = chain_check_store_status(token)
+                        .await
+                        .map_err(handle_rpc_err)?;
+                    match status.report {
+                        Some(report) => break report,
+                        None => {
+                            print!("\rChecked {} blocks", status.checked);
+                            std::io::stdout().flush()?;
+                        }
+                    }
+                };
+
+                println!("\rChecked {} blocks", report.checked);
+                for cid in &report.missing {
+                    println!("Missing: {cid}");
+                }
+                for cid in &report.corrupted {
+                    println!("Corrupted: {cid}");
+                }
+                for (CidJson(cid), e) in &report.unreadable {
+                    println!("Unreadable: {cid}: {e}");
+                }
+                for cid in &report.repaired {
+                    println!("Repaired: {cid}");
+                }
+                if let Some(e) = &report.aborted {
+                    println!("Check stopped before the end: {e}");
+                }
+                let bad = report.missing.len() + report.corrupted.len() + report.unreadable.len();
+                if report.aborted.is_some() || bad > report.repaired.len() {
+                    anyhow::bail!("The database is damaged");
+                }
+                if !report.repaired.is_empty() {
+                    println!(
+                        "Run the check again to verify the blocks linked by the repaired ones"
+                    );
+                }
+                Ok(())
+            }
+            Self::Migrate {
+                from,
+                to,
+                batch_size,
+            } => {
+                if from == to {
+                    anyhow::bail!("Source and destination backends are both {from}");
+                }
+                let chain_path = chain_path(config);
+                let from_path = db_path(&chain_path, *from);
+                let to_path = db_path(&chain_path, *to);
+                if !from_path.is_dir() {
+                    anyhow::bail!("No {from} database at {}", from_path.display());
+                }
+                let progress = progress_file(&to_path);
+                if to_path.exists() && !progress.exists() {
+                    anyhow::bail!(
+                        "A {to} database already exists at {}, remove it first",
+                        to_path.display()
+                    );
+                }
+
+                let mut db_config = config.db_config();
+                // Only the hot store of a tiered database is migrated.
+                db_config.tiered.enabled = false;
+                db_config.backend = *from;
+                let source = open_db(&from_path, &db_config)?;
+                // Entries written before the database had columns are only
+                // listed once moved to their column.
+                source.migrate_default_column()?;
+                // Recorded before the destination is created, so that a run
+                // interrupted before the first batch is resumed.
+                start_progress(&progress)?;
+                db_config.backend = *to;
+                let dest = open_db(&to_path, &db_config)?;
+
+                let migration = Migration::new(&source, &dest, &progress).batch_size(*batch_size);
+                if migration.is_resuming() {
+                    println!(
+                        "Resuming the interrupted migration into {}",
+                        to_path.display()
+                    );
+                }
+                let pb = ProgressBar::new(source.estimate_num_keys().unwrap_or_default());
+                pb.message("Migrating entries ");
+                let copied = migration.run(|copied| {
+                    pb.set(copied);
+                })?;
+                pb.finish();
+                println!(
+                    "Copied {copied} entries from {} to {}",
+                    from_path.display(),
+                    to_path.display()
+                );
+                println!("Set `db_backend = \"{to}\"` in the `[client]` section of the configuration to use the new database");
+                Ok(())
+            }
+            Self::Backup { dir } => backup(config, dir).await,
+            Self::Restore {
+                dir,
+                restore_config,
+                force,
+            } => restore(config, dir, restore_config.as_deref(), *force).await,
         }
     }
 }
+
+async fn backup(config: &Config, dir: &Path) -> anyhow::Result<()> {
+    // The node resolves relative paths against its own working directory
+    let dir = std::env::current_dir()?.join(dir);
+    if dir.exists() {
+        anyhow::bail!("Backup destination {} already exists", dir.display());
+    }
+
+    println!("Backing the node up to {}", dir.display());
+    let info = create_backup((dir.clone(),), &config.client.rpc_token)
+        .await
+        .map_err(handle_rpc_err)?;
+
+    let manifest = BackupManifest {
+        format: BACKUP_FORMAT,
+        forest_version: info.forest_version,
+        network: info.network,
+        db_backend: info.db_backend.parse()?,
+        created: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+    };
+    fs::write(
+        dir.join(BACKUP_MANIFEST),
+        serde_json::to_string_pretty(&manifest)?,
+    )?;
+    println!("Backup written to {}", dir.display());
+    Ok(())
+}
+
+/// Reads the manifest of a complete backup of the network of the node, or of
+/// any network with `force`.
+fn read_manifest(dir: &Path, network: &str, force: bool) -> anyhow::Result<BackupManifest> {
+    let manifest: BackupManifest = match fs::read(dir.join(BACKUP_MANIFEST)) {
+        Ok(manifest) => serde_json::from_slice(&manifest)
+            .with_context(|| format!("Invalid backup manifest in {}", dir.display()))?,
+        Err(_) => anyhow::bail!(
+            "No backup manifest in {}, the backup is incomplete or not a backup",
+            dir.display()
+        ),
+    };
+    if manifest.format != BACKUP_FORMAT {
+        anyhow::bail!("Unsupported backup format {}", manifest.format);
+    }
+    if manifest.network != network && !force {
+        anyhow::bail!(
+            "The backup is of network {}, the node is configured for {network}",
+            manifest.network,
+        );
+    }
+    Ok(manifest)
+}
+
+/// Fails if the daemon is running on the chain data directory, as it holds its
+/// lock. `action` is what the daemon has to be stopped before.
+pub(super) fn ensure_daemon_stopped(config: &Config, action: &str) -> anyhow::Result<()> {
+    anyhow::ensure!(
+        !DaemonLock::is_held(config)?,
+        "The daemon is running on {}, stop it before {action}",
+        chain_path(config).display()
+    );
+    Ok(())
+}
+
+/// Path next to `path` where it is copied before being moved into place.
+fn staging_path(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_owned();
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+fn remove_path(path: &Path) -> std::io::Result<()> {
+    if path.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// Moves the staged copy to `dest`, replacing what is there only once the
+/// rename succeeded.
+fn move_into_place(staged: &Path, dest: &Path) -> anyhow::Result<()> {
+    if !dest.exists() {
+        return Ok(fs::rename(staged, dest)?);
+    }
+    let replaced = staging_path(dest, ".replaced");
+    if replaced.exists() {
+        remove_path(&replaced)?;
+    }
+    fs::rename(dest, &replaced)?;
+    if let Err(e) = fs::rename(staged, dest) {
+        fs::rename(&replaced, dest)?;
+        return Err(e.into());
+    }
+    Ok(remove_path(&replaced)?)
+}
+
+async fn restore(
+    config: &Config,
+    dir: &Path,
+    restore_config: Option<&Path>,
+    force: bool,
+) -> anyhow::Result<()> {
+    let manifest = read_manifest(dir, &config.chain.name, force)?;
+
+    // Everything to restore and where, checked before anything is written
+    let chain_path = chain_path(config);
+    let db_dir = db_path(&chain_path, manifest.db_backend);
+    let mut files = vec![];
+    for entry in fs::read_dir(dir.join("db"))? {
+        let entry = entry?;
+        files.push((entry.path(), chain_path.join(entry.file_name())));
+    }
+    for name in [ENCRYPTED_KEYSTORE_NAME, KEYSTORE_NAME] {
+        if dir.join(name).is_file() {
+            files.push((dir.join(name), config.client.data_dir.join(name)));
+        }
+    }
+    for (_, dest) in &files {
+        if dest.exists() && !force {
+            anyhow::bail!(
+                "{} already exists, remove it or restore with --force",
+                dest.display()
+            );
+        }
+    }
+
+    let mut db_config = config.db_config();
+    db_config.backend = manifest.db_backend;
+    db_config.tiered.enabled = false;
+    ensure_daemon_stopped(config, "restoring")?;
+
+    // Copied next to their destination, and moved into place once the chain
+    // head of the copy is loaded
+    fs::create_dir_all(&chain_path)?;
+    let mut staged = vec![];
+    for (src, dest) in &files {
+        let copy = staging_path(dest, ".restore");
+        if copy.exists() {
+            // Left by an interrupted restore
+            remove_path(&copy)?;
+        }
+        if src.is_dir() {
+            let options = fs_extra::dir::CopyOptions {
+                copy_inside: true,
+                ..fs_extra::dir::CopyOptions::new()
+            };
+            fs_extra::dir::copy(src, &copy, &options)?;
+        } else {
+            fs::copy(src, &copy)?;
+        }
+        staged.push((copy, dest));
+    }
+
+    let db = open_db(&staging_path(&db_dir, ".restore"), &db_config)
+        .context("Failed to open the restored database")?;
+    let genesis = read_genesis_header(
+        config.client.genesis_file.as_ref(),
+        config.chain.genesis_bytes(),
+        &db,
+    )
+    .await?;
+    let chain_store = ChainStore::new(db, config.chain.clone(), &genesis)?;
+    let epoch = chain_store.heaviest_tipset().epoch();
+    drop(chain_store);
+
+    for (copy, dest) in &staged {
+        move_into_place(copy, dest)?;
+        println!("Restored {}", dest.display());
+    }
+    if let Some(path) = restore_config {
+        fs::copy(dir.join("config.toml"), path)?;
+        println!("Restored the configuration to {}", path.display());
+    }
+    println!(
+        "Restored the backup of {} taken by Forest {}, chain head at epoch {epoch}",
+        manifest.network, manifest.forest_version,
+    );
+    if manifest.db_backend != config.client.db_backend {
+        println!(
+            "Set `db_backend = \"{}\"` in the `[client]` section of the configuration to use the restored database",
+            manifest.db_backend
+        );
+    }
+    Ok(())
+}
+
+fn print_db_stats(stats: &DbStats) {
+    for column in &stats.columns {
+        println!(
+            "Column {}: {} keys, {}",
+            column.name,
+            column.keys,
+            column.bytes.human_count_bytes()
+        );
+    }
+    for file in &stats.files {
+        println!(
+            "Level {}: {} files, {}",
+            file.name,
+            file.files,
+            file.bytes.human_count_bytes()
+        );
+    }
+    if let Some(rate) = stats.cache_hit_rate() {
+        println!("Block cache hit rate: {:.1}%", rate * 100.);
+    }
+    if let Some(compactions) = stats.compactions {
+        println!("Compactions: {compactions}");
+    }
+    if let Some(stall) = stats.write_stall_micros {
+        println!("Write stalls: {}ms", stall / 1000);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write_manifest(dir: &Path, network: &str) {
+        let manifest = BackupManifest {
+            format: BACKUP_FORMAT,
+            forest_version: "0.0.0".into(),
+            network: network.into(),
+            db_backend: DbBackend::default(),
+            created: 0,
+        };
+        fs::write(
+            dir.join(BACKUP_MANIFEST),
+            serde_json::to_string(&manifest).unwrap(),
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn restore_rejects_backup_without_manifest() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir(dir.path().join("db")).unwrap();
+        assert!(read_manifest(dir.path(), "calibnet", true).is_err());
+    }
+
+    #[test]
+    fn restore_rejects_backup_of_another_network() {
+        let dir = tempfile::tempdir().unwrap();
+        write_manifest(dir.path(), "mainnet");
+        assert!(read_manifest(dir.path(), "calibnet", false).is_err());
+        assert!(read_manifest(dir.path(), "calibnet", true).is_ok());
+        assert_eq!(
+            read_manifest(dir.path(), "mainnet", false).unwrap().network,
+            "mainnet"
+        );
+    }
+
+    #[test]
+    fn move_into_place_replaces_destination() {
+        let dir = tempfile::tempdir().unwrap();
+        let dest = dir.path().join("db");
+        fs::create_dir(&dest).unwrap();
+        fs::write(dest.join("old"), "").unwrap();
+        let staged = staging_path(&dest, ".restore");
+        fs::create_dir(&staged).unwrap();
+        fs::write(staged.join("new"), "").unwrap();
+
+        move_into_place(&staged, &dest).unwrap();
+        assert!(dest.join("new").exists());
+        assert!(!dest.join("old").exists());
+        assert!(!staged.exists());
+        assert!(!staging_path(&dest, ".replaced").exists());
+    }
+}
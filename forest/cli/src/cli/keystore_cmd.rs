@@ -0,0 +1,127 @@
+// Copyright 2019-2023 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use std::{
+    fs::OpenOptions,
+    io::{self, Write},
+    path::PathBuf,
+};
+
+use clap::Subcommand;
+use forest_key_management::{
+    import_lotus_keystore, KeyStore, KeyStoreConfig, ENCRYPTED_KEYSTORE_NAME,
+    FOREST_KEYSTORE_PHRASE_ENV,
+};
+use rpassword::prompt_password;
+
+use super::{db_cmd::ensure_daemon_stopped, Config};
+
+/// Commands operating on the keystore files directly. The commands writing to
+/// the keystore refuse to run while the daemon is running, as it would
+/// overwrite the changes with its own copy of the keys.
+#[derive(Debug, Subcommand)]
+pub enum KeystoreCommands {
+    /// Re-encrypt the keystore with a new passphrase. The previous keystore is
+    /// kept as a backup next to it
+    ChangePassphrase,
+    /// Import the wallet keys of a Lotus repository
+    ImportLotus {
+        /// Path to the Lotus repository, or to its keystore directory
+        repo: PathBuf,
+    },
+    /// Export all the keys to an archive encrypted with a passphrase of its own
+    Export {
+        /// Path of the archive to create
+        output: PathBuf,
+    },
+    /// Import the keys of an archive created by `export`, skipping the ones
+    /// already in the keystore
+    Import {
+        /// Path of the archive to import
+        archive: PathBuf,
+    },
+}
+
+impl KeystoreCommands {
+    pub fn run(&self, config: &Config) -> anyhow::Result<()> {
+        match self {
+            Self::ChangePassphrase => {
+                anyhow::ensure!(
+                    config.client.encrypt_keystore,
+                    "Keystore encryption is disabled, there is no passphrase to change"
+                );
+                ensure_daemon_stopped(config, "changing its keystore")?;
+                let mut keystore = open_keystore(config)?;
+                let passphrase = prompt_new_passphrase("Enter the new keystore passphrase: ")?;
+                let backup_path = keystore.change_passphrase(&passphrase)?;
+                println!(
+                    "Keystore passphrase changed, previous keystore backed up to {}",
+                    backup_path.display()
+                );
+                Ok(())
+            }
+            Self::ImportLotus { repo } => {
+                ensure_daemon_stopped(config, "changing its keystore")?;
+                let mut keystore = open_keystore(config)?;
+                let imported = import_lotus_keystore(repo, &mut keystore)?;
+                for address in &imported {
+                    println!("{address}");
+                }
+                println!("Imported {} keys", imported.len());
+                Ok(())
+            }
+            Self::Export { output } => {
+                let keystore = open_keystore(config)?;
+                let passphrase = prompt_new_passphrase("Enter the export passphrase: ")?;
+                let archive = keystore.export(&passphrase)?;
+
+                let mut file = OpenOptions::new()
+                    .write(true)
+                    .create_new(true)
+                    .open(output)?;
+                forest_utils::io::set_user_perm(&file)?;
+                file.write_all(&archive)?;
+                println!("Keystore exported to {}", output.display());
+                Ok(())
+            }
+            Self::Import { archive } => {
+                ensure_daemon_stopped(config, "changing its keystore")?;
+                let data = std::fs::read(archive)?;
+                let mut keystore = open_keystore(config)?;
+                let passphrase = prompt_password("Enter the export passphrase: ")?;
+                let imported = keystore.import(&data, &passphrase)?;
+                for name in &imported {
+                    println!("{name}");
+                }
+                println!("Imported {} keys", imported.len());
+                Ok(())
+            }
+        }
+    }
+}
+
+pub(super) fn open_keystore(config: &Config) -> anyhow::Result<KeyStore> {
+    let data_dir = config.client.data_dir.clone();
+    if !config.client.encrypt_keystore {
+        return Ok(KeyStore::new(KeyStoreConfig::Persistent(data_dir))?);
+    }
+
+    let path = data_dir.join(ENCRYPTED_KEYSTORE_NAME);
+    anyhow::ensure!(path.exists(), "No keystore found at {}", path.display());
+    let passphrase = match std::env::var(FOREST_KEYSTORE_PHRASE_ENV) {
+        Ok(passphrase) => passphrase,
+        Err(_) => prompt_password("Enter the keystore passphrase: ")?,
+    };
+    KeyStore::new(KeyStoreConfig::Encrypted(data_dir, passphrase))
+        .map_err(|_| anyhow::anyhow!("Incorrect keystore passphrase"))
+}
+
+fn prompt_new_passphrase(prompt: &str) -> io::Result<String> {
+    loop {
+        let passphrase = prompt_password(prompt)?;
+        if passphrase == prompt_password("Confirm passphrase: ")? {
+            return Ok(passphrase);
+        }
+        eprintln!("Passphrases do not match. Please retry.");
+    }
+}
@@ -12,8 +12,12 @@ mod chain_cmd;
 mod config_cmd;
 mod db_cmd;
 mod fetch_params_cmd;
+mod keystore_cmd;
 mod mpool_cmd;
+mod msg_cmd;
+mod msig_cmd;
 mod net_cmd;
+mod paych_cmd;
 mod send_cmd;
 mod shutdown_cmd;
 mod snapshot_cmd;
@@ -21,7 +25,10 @@ mod state_cmd;
 mod sync_cmd;
 mod wallet_cmd;
 
-use std::io::{self, Write};
+use std::{
+    io::{self, Write},
+    path::Path,
+};
 
 use cid::Cid;
 use clap::Parser;
@@ -35,9 +42,10 @@ use serde::Serialize;
 pub(super) use self::{
     attach_cmd::AttachCommand, auth_cmd::AuthCommands, chain_cmd::ChainCommands,
     config_cmd::ConfigCommands, db_cmd::DBCommands, fetch_params_cmd::FetchCommands,
-    mpool_cmd::MpoolCommands, net_cmd::NetCommands, send_cmd::SendCommand,
-    shutdown_cmd::ShutdownCommand, snapshot_cmd::SnapshotCommands, state_cmd::StateCommands,
-    sync_cmd::SyncCommands, wallet_cmd::WalletCommands,
+    mpool_cmd::MpoolCommands, msg_cmd::MsgCommands, msig_cmd::MsigCommands, net_cmd::NetCommands,
+    paych_cmd::PaychCommands, send_cmd::SendCommand, shutdown_cmd::ShutdownCommand,
+    snapshot_cmd::SnapshotCommands, state_cmd::StateCommands, sync_cmd::SyncCommands,
+    wallet_cmd::WalletCommands,
 };
 
 /// CLI structure generated when interacting with Forest binary
@@ -82,6 +90,18 @@ pub enum Subcommand {
     #[command(subcommand)]
     Mpool(MpoolCommands),
 
+    /// Create messages to be signed offline
+    #[command(subcommand)]
+    Msg(MsgCommands),
+
+    /// Create and operate multisig wallets
+    #[command(subcommand)]
+    Msig(MsigCommands),
+
+    /// Manage payment channels
+    #[command(subcommand)]
+    Paych(PaychCommands),
+
     /// Interact with and query Filecoin chain state
     #[command(subcommand)]
     State(StateCommands),
@@ -144,6 +164,19 @@ pub(super) fn print_rpc_res_pretty<T: Serialize>(
     Ok(())
 }
 
+/// Writes a value as pretty JSON to the file, or to `stdout` if there is none
+pub(super) fn write_json_output<T: Serialize>(
+    value: &T,
+    output: Option<&Path>,
+) -> anyhow::Result<()> {
+    let json = serde_json::to_string_pretty(value)?;
+    match output {
+        Some(path) => std::fs::write(path, json)?,
+        None => println!("{json}"),
+    }
+    Ok(())
+}
+
 /// Prints a tipset from a HTTP JSON-RPC response result
 pub(super) fn print_rpc_res_cids(res: Result<TipsetJson, JsonRpcError>) -> anyhow::Result<()> {
     let tipset = res.map_err(handle_rpc_err)?;
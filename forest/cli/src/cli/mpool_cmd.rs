@@ -1,16 +1,37 @@
 // Copyright 2019-2023 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+use std::path::PathBuf;
+
 use clap::Subcommand;
+use forest_json::signed_message::json::SignedMessageJson;
+use forest_rpc_client::mpool_push;
+use forest_utils::io::read_file_to_string;
 
-use super::Config;
+use super::{handle_rpc_err, Config};
 
 #[derive(Debug, Subcommand)]
-pub enum MpoolCommands {}
+pub enum MpoolCommands {
+    /// Push a signed message to the message pool, such as one signed with
+    /// `forest-cli wallet sign-message`
+    Push {
+        /// File holding the signed message
+        #[arg(long)]
+        file: PathBuf,
+    },
+}
 
 impl MpoolCommands {
-    pub fn run(&self, _config: Config) -> anyhow::Result<()> {
-        // match self {}
-        Ok(())
+    pub async fn run(&self, config: Config) -> anyhow::Result<()> {
+        match self {
+            Self::Push { file } => {
+                let message: SignedMessageJson = serde_json::from_str(&read_file_to_string(file)?)?;
+                let cid = mpool_push((message,), &config.client.rpc_token)
+                    .await
+                    .map_err(handle_rpc_err)?;
+                println!("{}", cid.0);
+                Ok(())
+            }
+        }
     }
 }
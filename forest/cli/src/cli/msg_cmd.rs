@@ -0,0 +1,111 @@
+// Copyright 2019-2023 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use std::{path::PathBuf, str::FromStr};
+
+use anyhow::Context;
+use clap::Subcommand;
+use forest_blocks::tipset_keys_json::TipsetKeysJson;
+use forest_json::{address::json::AddressJson, message::json::MessageJson};
+use forest_rpc_client::{gas_estimate_message_gas, mpool_get_nonce, wallet_default_address};
+use fvm_ipld_encoding::RawBytes;
+use fvm_shared::{address::Address, econ::TokenAmount, message::Message, MethodNum, METHOD_SEND};
+use num::BigInt;
+
+use super::{handle_rpc_err, write_json_output, Config};
+
+#[derive(Debug, Subcommand)]
+pub enum MsgCommands {
+    /// Create an unsigned message, with its nonce and gas estimated by the
+    /// node, to be signed offline with `forest-cli wallet sign-message`
+    Create {
+        /// Account to send the message from, the default account of the wallet
+        /// if unset
+        #[arg(long)]
+        from: Option<Address>,
+        /// Recipient of the message
+        to: Address,
+        /// Token amount in attoFIL
+        amount: BigInt,
+        /// Method to invoke on the recipient
+        #[arg(long, default_value_t = METHOD_SEND)]
+        method: MethodNum,
+        /// Hex encoded parameters of the method
+        #[arg(long)]
+        params: Option<String>,
+        /// Gas fee cap in attoFIL, estimated if unset
+        #[arg(long)]
+        gas_feecap: Option<BigInt>,
+        /// Gas limit, estimated if unset
+        #[arg(long)]
+        gas_limit: Option<i64>,
+        /// Gas premium in attoFIL, estimated if unset
+        #[arg(long)]
+        gas_premium: Option<BigInt>,
+        /// File to write the message to, instead of the standard output
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+impl MsgCommands {
+    pub async fn run(&self, config: Config) -> anyhow::Result<()> {
+        match self {
+            Self::Create {
+                from,
+                to,
+                amount,
+                method,
+                params,
+                gas_feecap,
+                gas_limit,
+                gas_premium,
+                output,
+            } => {
+                let from = match from {
+                    Some(from) => *from,
+                    None => Address::from_str(
+                        &wallet_default_address((), &config.client.rpc_token)
+                            .await
+                            .map_err(handle_rpc_err)?,
+                    )?,
+                };
+                let params = match params {
+                    Some(params) => {
+                        RawBytes::new(hex::decode(params).context("Params have to be hex")?)
+                    }
+                    None => RawBytes::default(),
+                };
+                let sequence =
+                    mpool_get_nonce((AddressJson(from.into()),), &config.client.rpc_token)
+                        .await
+                        .map_err(handle_rpc_err)?;
+
+                let message = Message {
+                    from,
+                    to: *to,
+                    sequence,
+                    value: TokenAmount::from_atto(amount.clone()),
+                    method_num: *method,
+                    params,
+                    gas_limit: gas_limit.unwrap_or_default(),
+                    gas_fee_cap: TokenAmount::from_atto(gas_feecap.clone().unwrap_or_default()),
+                    gas_premium: TokenAmount::from_atto(gas_premium.clone().unwrap_or_default()),
+                    ..Default::default()
+                };
+                let message = gas_estimate_message_gas(
+                    (
+                        MessageJson(message.into()),
+                        None,
+                        TipsetKeysJson(Default::default()),
+                    ),
+                    &config.client.rpc_token,
+                )
+                .await
+                .map_err(handle_rpc_err)?;
+
+                write_json_output(&message, output.as_deref())
+            }
+        }
+    }
+}
@@ -0,0 +1,424 @@
+// Copyright 2019-2023 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use std::str::FromStr;
+
+use anyhow::Context;
+use clap::Subcommand;
+use forest_actor_interface::{init, multisig};
+use forest_blocks::tipset_keys_json::TipsetKeysJson;
+use forest_json::{address::json::AddressJson, cid::CidJson, message::json::MessageJson};
+use forest_rpc_client::*;
+use fvm_ipld_encoding::{Cbor, RawBytes};
+use fvm_shared::{
+    address::Address, clock::ChainEpoch, econ::TokenAmount, message::Message, MethodNum,
+    METHOD_SEND,
+};
+use num::BigInt;
+
+use super::{handle_rpc_err, Config};
+
+/// Number of epochs after which a message is considered final.
+const MESSAGE_CONFIDENCE: i64 = 5;
+
+#[derive(Debug, Subcommand)]
+pub enum MsigCommands {
+    /// Create a multisig wallet
+    Create {
+        /// Addresses of the signers
+        #[arg(required = true)]
+        signers: Vec<Address>,
+        /// Number of approvals required to execute a transaction, all the
+        /// signers if unset
+        #[arg(long)]
+        required: Option<u64>,
+        /// Initial balance of the multisig in attoFIL
+        #[arg(long, default_value = "0")]
+        value: BigInt,
+        /// Number of epochs over which the initial balance vests
+        #[arg(long, default_value_t = 0)]
+        duration: ChainEpoch,
+        /// Epoch at which the vesting starts, the current epoch if unset
+        #[arg(long)]
+        vesting_start: Option<ChainEpoch>,
+        /// Account to send the creation message from, the default account of
+        /// the wallet if unset
+        #[arg(long)]
+        from: Option<Address>,
+    },
+    /// Propose a transaction to a multisig
+    Propose {
+        /// Address of the multisig
+        multisig: Address,
+        /// Recipient of the transaction
+        to: Address,
+        /// Token amount in attoFIL
+        value: BigInt,
+        /// Method to invoke on the recipient
+        #[arg(long, default_value_t = METHOD_SEND)]
+        method: MethodNum,
+        /// Hex encoded parameters of the method
+        #[arg(long)]
+        params: Option<String>,
+        /// Signer proposing the transaction, the default account of the wallet
+        /// if unset
+        #[arg(long)]
+        from: Option<Address>,
+    },
+    /// Approve a pending transaction of a multisig
+    Approve {
+        /// Address of the multisig
+        multisig: Address,
+        /// ID of the transaction
+        txn_id: i64,
+        /// Signer approving the transaction, the default account of the wallet
+        /// if unset
+        #[arg(long)]
+        from: Option<Address>,
+    },
+    /// Cancel a pending transaction of a multisig. Only the signer who
+    /// proposed it can cancel it
+    Cancel {
+        /// Address of the multisig
+        multisig: Address,
+        /// ID of the transaction
+        txn_id: i64,
+        /// Signer cancelling the transaction, the default account of the
+        /// wallet if unset
+        #[arg(long)]
+        from: Option<Address>,
+    },
+    /// Show the signers, vesting schedule, balance and pending transactions
+    /// of a multisig
+    Inspect {
+        /// Address of the multisig
+        multisig: Address,
+    },
+    /// Propose to add a signer to a multisig
+    AddSigner {
+        /// Address of the multisig
+        multisig: Address,
+        /// Signer to add
+        signer: Address,
+        /// Also increase the number of required approvals by one
+        #[arg(long)]
+        increase_threshold: bool,
+        /// Signer proposing the change, the default account of the wallet if
+        /// unset
+        #[arg(long)]
+        from: Option<Address>,
+    },
+    /// Propose to replace a signer of a multisig
+    SwapSigner {
+        /// Address of the multisig
+        multisig: Address,
+        /// Signer to remove
+        old_signer: Address,
+        /// Signer to add in its place
+        new_signer: Address,
+        /// Signer proposing the change, the default account of the wallet if
+        /// unset
+        #[arg(long)]
+        from: Option<Address>,
+    },
+}
+
+impl MsigCommands {
+    pub async fn run(&self, config: Config) -> anyhow::Result<()> {
+        match self {
+            Self::Create {
+                signers,
+                required,
+                value,
+                duration,
+                vesting_start,
+                from,
+            } => {
+                let required = required.unwrap_or(signers.len() as u64);
+                anyhow::ensure!(
+                    required > 0 && required <= signers.len() as u64,
+                    "The number of required approvals must be between 1 and the number of signers"
+                );
+
+                let head = chain_head(&config.client.rpc_token)
+                    .await
+                    .map_err(handle_rpc_err)?
+                    .0;
+                let network_version = state_network_version(
+                    (TipsetKeysJson(head.key().clone()),),
+                    &config.client.rpc_token,
+                )
+                .await
+                .map_err(handle_rpc_err)?;
+                let code_cids = state_actor_code_cids((network_version,), &config.client.rpc_token)
+                    .await
+                    .map_err(handle_rpc_err)?;
+                let code_cid = code_cids
+                    .get("multisig")
+                    .context("No multisig actor in the current network")?
+                    .0;
+
+                let constructor_params = multisig::ConstructorParams {
+                    signers: signers.clone(),
+                    num_approvals_threshold: required,
+                    unlock_duration: *duration,
+                    start_epoch: vesting_start.unwrap_or(head.epoch()),
+                };
+                let params = init::ExecParams {
+                    code_cid,
+                    constructor_params: RawBytes::serialize(constructor_params)?,
+                };
+                let message = Message {
+                    from: sender(*from, &config).await?,
+                    to: init::ADDRESS,
+                    value: TokenAmount::from_atto(value.clone()),
+                    method_num: init::Method::Exec as MethodNum,
+                    params: RawBytes::serialize(params)?,
+                    ..Default::default()
+                };
+
+                let ret: init::ExecReturn = send_and_wait(message, &config).await?.deserialize()?;
+                println!("Created multisig {}", ret.robust_address);
+                println!("ID address: {}", ret.id_address);
+                Ok(())
+            }
+            Self::Propose {
+                multisig,
+                to,
+                value,
+                method,
+                params,
+                from,
+            } => {
+                let params = match params {
+                    Some(params) => {
+                        RawBytes::new(hex::decode(params).context("Params have to be hex")?)
+                    }
+                    None => RawBytes::default(),
+                };
+                propose(
+                    *multisig,
+                    multisig::ProposeParams {
+                        to: *to,
+                        value: TokenAmount::from_atto(value.clone()),
+                        method: *method,
+                        params,
+                    },
+                    *from,
+                    &config,
+                )
+                .await
+            }
+            Self::Approve {
+                multisig,
+                txn_id,
+                from,
+            } => {
+                let message = Message {
+                    from: sender(*from, &config).await?,
+                    to: *multisig,
+                    method_num: multisig::Method::Approve as MethodNum,
+                    params: RawBytes::serialize(multisig::TxnIDParams {
+                        id: multisig::TxnID(*txn_id),
+                        proposal_hash: vec![],
+                    })?,
+                    ..Default::default()
+                };
+                let ret: multisig::ApproveReturn =
+                    send_and_wait(message, &config).await?.deserialize()?;
+                if ret.applied {
+                    println!(
+                        "Transaction {txn_id} approved and executed with exit code {}",
+                        ret.code
+                    );
+                } else {
+                    println!("Transaction {txn_id} approved");
+                }
+                Ok(())
+            }
+            Self::Cancel {
+                multisig,
+                txn_id,
+                from,
+            } => {
+                let message = Message {
+                    from: sender(*from, &config).await?,
+                    to: *multisig,
+                    method_num: multisig::Method::Cancel as MethodNum,
+                    params: RawBytes::serialize(multisig::TxnIDParams {
+                        id: multisig::TxnID(*txn_id),
+                        proposal_hash: vec![],
+                    })?,
+                    ..Default::default()
+                };
+                send_and_wait(message, &config).await?;
+                println!("Transaction {txn_id} cancelled");
+                Ok(())
+            }
+            Self::Inspect { multisig } => {
+                let params = || {
+                    (
+                        AddressJson((*multisig).into()),
+                        TipsetKeysJson(Default::default()),
+                    )
+                };
+                let token = &config.client.rpc_token;
+                let balance = wallet_balance((multisig.to_string(),), token)
+                    .await
+                    .map_err(handle_rpc_err)?;
+                let available = msig_get_available_balance(params(), token)
+                    .await
+                    .map_err(handle_rpc_err)?;
+                let vesting = msig_get_vesting_schedule(params(), token)
+                    .await
+                    .map_err(handle_rpc_err)?;
+                let signers = msig_get_signers(params(), token)
+                    .await
+                    .map_err(handle_rpc_err)?;
+                let pending = msig_get_pending(params(), token)
+                    .await
+                    .map_err(handle_rpc_err)?;
+
+                println!("Balance: {balance} attoFIL");
+                println!("Spendable: {available} attoFIL");
+                if vesting.unlock_duration > 0 {
+                    println!(
+                        "Vesting: {} attoFIL from epoch {} over {} epochs",
+                        vesting.initial_balance.atto(),
+                        vesting.start_epoch,
+                        vesting.unlock_duration
+                    );
+                }
+                println!(
+                    "Threshold: {} / {}",
+                    signers.threshold,
+                    signers.signers.len()
+                );
+                println!("Signers:");
+                for signer in &signers.signers {
+                    println!("  {signer}");
+                }
+                println!("Pending transactions: {}", pending.len());
+                for txn in &pending {
+                    let approved: Vec<String> =
+                        txn.approved.iter().map(ToString::to_string).collect();
+                    println!(
+                        "  {}: {} attoFIL to {}, method {}, params {}, approved by [{}]",
+                        txn.id,
+                        txn.value.atto(),
+                        txn.to,
+                        txn.method,
+                        txn.params,
+                        approved.join(", ")
+                    );
+                }
+                Ok(())
+            }
+            Self::AddSigner {
+                multisig,
+                signer,
+                increase_threshold,
+                from,
+            } => {
+                let params = multisig::AddSignerParams {
+                    signer: *signer,
+                    increase: *increase_threshold,
+                };
+                propose(
+                    *multisig,
+                    multisig::ProposeParams {
+                        to: *multisig,
+                        value: TokenAmount::default(),
+                        method: multisig::Method::AddSigner as MethodNum,
+                        params: RawBytes::serialize(params)?,
+                    },
+                    *from,
+                    &config,
+                )
+                .await
+            }
+            Self::SwapSigner {
+                multisig,
+                old_signer,
+                new_signer,
+                from,
+            } => {
+                let params = multisig::SwapSignerParams {
+                    from: *old_signer,
+                    to: *new_signer,
+                };
+                propose(
+                    *multisig,
+                    multisig::ProposeParams {
+                        to: *multisig,
+                        value: TokenAmount::default(),
+                        method: multisig::Method::SwapSigner as MethodNum,
+                        params: RawBytes::serialize(params)?,
+                    },
+                    *from,
+                    &config,
+                )
+                .await
+            }
+        }
+    }
+}
+
+/// Returns the given sender, or the default address of the wallet.
+async fn sender(from: Option<Address>, config: &Config) -> anyhow::Result<Address> {
+    match from {
+        Some(from) => Ok(from),
+        None => Ok(Address::from_str(
+            &wallet_default_address((), &config.client.rpc_token)
+                .await
+                .map_err(handle_rpc_err)?,
+        )?),
+    }
+}
+
+async fn propose(
+    multisig: Address,
+    params: multisig::ProposeParams,
+    from: Option<Address>,
+    config: &Config,
+) -> anyhow::Result<()> {
+    let message = Message {
+        from: sender(from, config).await?,
+        to: multisig,
+        method_num: multisig::Method::Propose as MethodNum,
+        params: RawBytes::serialize(params)?,
+        ..Default::default()
+    };
+    let ret: multisig::ProposeReturn = send_and_wait(message, config).await?.deserialize()?;
+    if ret.applied {
+        println!(
+            "Transaction {} proposed and executed with exit code {}",
+            ret.txn_id.0, ret.code
+        );
+    } else {
+        println!("Transaction {} proposed", ret.txn_id.0);
+    }
+    Ok(())
+}
+
+/// Signs and sends the message with the wallet of the node, then waits for it
+/// to be executed. Returns the return value of the message.
+async fn send_and_wait(message: Message, config: &Config) -> anyhow::Result<RawBytes> {
+    let token = &config.client.rpc_token;
+    let signed = mpool_push_message((MessageJson(message.into()), None), token)
+        .await
+        .map_err(handle_rpc_err)?;
+    let cid = signed.0.cid()?;
+    println!("Sent message {cid}, waiting for it to be executed");
+
+    let lookup = state_wait_msg((CidJson(cid), MESSAGE_CONFIDENCE), token)
+        .await
+        .map_err(handle_rpc_err)?;
+    let receipt = lookup.receipt.0;
+    anyhow::ensure!(
+        receipt.exit_code().is_success(),
+        "Message {cid} failed with exit code {}",
+        receipt.exit_code()
+    );
+    Ok(RawBytes::new(receipt.return_data().bytes().to_vec()))
+}
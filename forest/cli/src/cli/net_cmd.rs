@@ -3,9 +3,10 @@
 
 use ahash::HashSet;
 use clap::Subcommand;
-use forest_libp2p::{Multiaddr, Protocol};
-use forest_rpc_api::data_types::AddrInfo;
+use forest_libp2p::{BandwidthStats, Multiaddr, Protocol};
+use forest_rpc_api::data_types::{AddrInfo, NetBlockList};
 use forest_rpc_client::net_ops::*;
+use human_repr::{HumanCount, HumanThroughput};
 
 use super::{handle_rpc_err, print_stdout, Config};
 use crate::cli::cli_error_and_die;
@@ -26,6 +27,98 @@ pub enum NetCommands {
         /// Peer ID to disconnect from
         id: String,
     },
+    /// Prints the agent, protocols and latency of a connected peer
+    PeerInfo {
+        /// Peer ID
+        id: String,
+    },
+    /// Prints the bandwidth usage of the node
+    Bandwidth {
+        /// Break down the bandwidth usage by peer
+        #[arg(long, conflicts_with = "by_protocol")]
+        by_peer: bool,
+        /// Break down the bandwidth usage by protocol
+        #[arg(long)]
+        by_protocol: bool,
+    },
+    /// Looks up the addresses of a peer through the DHT
+    FindPeer {
+        /// Peer ID to look up
+        id: String,
+    },
+    /// Protects peers from being banned
+    Protect {
+        /// Peer IDs to protect
+        #[arg(required = true)]
+        ids: Vec<String>,
+    },
+    /// Removes the protection of peers
+    Unprotect {
+        /// Peer IDs to unprotect
+        #[arg(required = true)]
+        ids: Vec<String>,
+    },
+    /// Lists protected peers
+    ListProtected,
+    /// Prints the reputation scores of known peers, best first
+    Scores,
+    /// Prints the blocks and bytes exchanged over bitswap with connected
+    /// peers, most bytes sent first
+    BitswapLedger,
+    /// Manages the list of peers, IP addresses and subnets the node refuses
+    /// to connect to
+    #[command(subcommand)]
+    Block(BlockCommands),
+}
+
+#[derive(Debug, Subcommand)]
+pub enum BlockCommands {
+    /// Adds entries to the block list
+    #[command(subcommand)]
+    Add(BlockTarget),
+    /// Removes entries from the block list
+    #[command(subcommand)]
+    Remove(BlockTarget),
+    /// Prints the block list
+    List,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum BlockTarget {
+    /// Peer IDs
+    Peer {
+        #[arg(required = true)]
+        ids: Vec<String>,
+    },
+    /// IP addresses
+    Ip {
+        #[arg(required = true)]
+        addrs: Vec<String>,
+    },
+    /// IP subnets, in CIDR notation
+    Subnet {
+        #[arg(required = true)]
+        subnets: Vec<String>,
+    },
+}
+
+impl BlockTarget {
+    fn to_block_list(&self) -> NetBlockList {
+        match self {
+            Self::Peer { ids } => NetBlockList {
+                peers: ids.clone(),
+                ..Default::default()
+            },
+            Self::Ip { addrs } => NetBlockList {
+                ip_addrs: addrs.clone(),
+                ..Default::default()
+            },
+            Self::Subnet { subnets } => NetBlockList {
+                ip_subnets: subnets.clone(),
+                ..Default::default()
+            },
+        }
+    }
 }
 
 impl NetCommands {
@@ -111,6 +204,181 @@ impl NetCommands {
                 println!("disconnect {id}: success");
                 Ok(())
             }
+            Self::PeerInfo { id } => {
+                let info = net_peer_info((id.to_owned(),), &config.client.rpc_token)
+                    .await
+                    .map_err(handle_rpc_err)?;
+                println!("Peer ID: {}", info.id);
+                println!("Agent: {}", info.agent);
+                if let Some(latency) = info.latency {
+                    println!("Latency: {latency:?}");
+                }
+                println!("Addresses:");
+                for addr in info.addrs {
+                    println!("  {addr}");
+                }
+                println!("Protocols:");
+                for protocol in info.protocols {
+                    println!("  {protocol}");
+                }
+                Ok(())
+            }
+            Self::Bandwidth {
+                by_peer,
+                by_protocol,
+            } => {
+                let mut rows: Vec<(String, BandwidthStats)> = if *by_peer {
+                    net_bandwidth_stats_by_peer((), &config.client.rpc_token)
+                        .await
+                        .map_err(handle_rpc_err)?
+                        .into_iter()
+                        .collect()
+                } else if *by_protocol {
+                    net_bandwidth_stats_by_protocol((), &config.client.rpc_token)
+                        .await
+                        .map_err(handle_rpc_err)?
+                        .into_iter()
+                        .collect()
+                } else {
+                    let stats = net_bandwidth_stats((), &config.client.rpc_token)
+                        .await
+                        .map_err(handle_rpc_err)?;
+                    vec![("Total".to_owned(), stats)]
+                };
+                rows.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+                let output: Vec<String> = rows
+                    .into_iter()
+                    .map(|(name, stats)| {
+                        format!(
+                            "{name}: in {} ({}), out {} ({})",
+                            stats.total_in.human_count_bytes(),
+                            stats.rate_in.human_throughput_bytes(),
+                            stats.total_out.human_count_bytes(),
+                            stats.rate_out.human_throughput_bytes(),
+                        )
+                    })
+                    .collect();
+                print_stdout(output.join("\n"));
+                Ok(())
+            }
+            Self::FindPeer { id } => {
+                let info = net_find_peer((id.to_owned(),), &config.client.rpc_token)
+                    .await
+                    .map_err(handle_rpc_err)?;
+                let addresses: Vec<String> =
+                    info.addrs.iter().map(|addr| addr.to_string()).collect();
+                print_stdout(addresses.join("\n"));
+                Ok(())
+            }
+            Self::Protect { ids } => {
+                net_protect_add((ids.clone(),), &config.client.rpc_token)
+                    .await
+                    .map_err(handle_rpc_err)?;
+                println!("protect {}: success", ids.join(", "));
+                Ok(())
+            }
+            Self::Unprotect { ids } => {
+                net_protect_remove((ids.clone(),), &config.client.rpc_token)
+                    .await
+                    .map_err(handle_rpc_err)?;
+                println!("unprotect {}: success", ids.join(", "));
+                Ok(())
+            }
+            Self::ListProtected => {
+                let peers = net_protect_list((), &config.client.rpc_token)
+                    .await
+                    .map_err(handle_rpc_err)?;
+                print_stdout(peers.join("\n"));
+                Ok(())
+            }
+            Self::Scores => {
+                let mut scores: Vec<_> = net_peer_scores((), &config.client.rpc_token)
+                    .await
+                    .map_err(handle_rpc_err)?
+                    .into_iter()
+                    .collect();
+                scores.sort_by(|(_, a), (_, b)| b.total().total_cmp(&a.total()));
+
+                let output: Vec<String> = scores
+                    .into_iter()
+                    .map(|(peer, score)| {
+                        format!(
+                            "{peer}: {:.2} (chain exchange {:.2}, bitswap {:.2}, gossipsub {:.2}, invalid data {:.2})",
+                            score.total(),
+                            score.chain_exchange,
+                            score.bitswap,
+                            score.gossipsub,
+                            score.invalid_data,
+                        )
+                    })
+                    .collect();
+                print_stdout(output.join("\n"));
+                Ok(())
+            }
+            Self::BitswapLedger => {
+                let mut ledgers: Vec<_> = net_bitswap_ledger((), &config.client.rpc_token)
+                    .await
+                    .map_err(handle_rpc_err)?
+                    .into_iter()
+                    .collect();
+                ledgers.sort_by(|(_, a), (_, b)| b.bytes_sent.cmp(&a.bytes_sent));
+
+                let output: Vec<String> = ledgers
+                    .into_iter()
+                    .map(|(peer, ledger)| {
+                        format!(
+                            "{peer}: sent {} blocks ({}), received {} blocks ({})",
+                            ledger.blocks_sent,
+                            ledger.bytes_sent.human_count_bytes(),
+                            ledger.blocks_received,
+                            ledger.bytes_received.human_count_bytes(),
+                        )
+                    })
+                    .collect();
+                print_stdout(output.join("\n"));
+                Ok(())
+            }
+            Self::Block(cmd) => cmd.run(config).await,
+        }
+    }
+}
+
+impl BlockCommands {
+    pub async fn run(&self, config: Config) -> anyhow::Result<()> {
+        match self {
+            Self::Add(target) => {
+                net_block_add((target.to_block_list(),), &config.client.rpc_token)
+                    .await
+                    .map_err(handle_rpc_err)?;
+                println!("block: success");
+                Ok(())
+            }
+            Self::Remove(target) => {
+                net_block_remove((target.to_block_list(),), &config.client.rpc_token)
+                    .await
+                    .map_err(handle_rpc_err)?;
+                println!("unblock: success");
+                Ok(())
+            }
+            Self::List => {
+                let list = net_block_list((), &config.client.rpc_token)
+                    .await
+                    .map_err(handle_rpc_err)?;
+                let output: Vec<String> = list
+                    .peers
+                    .iter()
+                    .map(|peer| format!("peer {peer}"))
+                    .chain(list.ip_addrs.iter().map(|ip| format!("ip {ip}")))
+                    .chain(
+                        list.ip_subnets
+                            .iter()
+                            .map(|subnet| format!("subnet {subnet}")),
+                    )
+                    .collect();
+                print_stdout(output.join("\n"));
+                Ok(())
+            }
         }
     }
 }
@@ -0,0 +1,205 @@
+// Copyright 2019-2023 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use anyhow::Context;
+use clap::Subcommand;
+use forest_json::address::json::AddressJson;
+use forest_rpc_client::*;
+use fvm_shared::address::Address;
+use num::BigInt;
+
+use super::{handle_rpc_err, Config};
+
+#[derive(Debug, Subcommand)]
+pub enum PaychCommands {
+    /// Get the payment channel from an address to another and add funds to
+    /// it. The channel is created if there is none yet, which waits for the
+    /// creation message to be executed
+    Get {
+        /// Owner of the channel, which must be in the wallet
+        from: Address,
+        /// Recipient of the channel
+        to: Address,
+        /// Amount to add to the channel in attoFIL
+        amount: BigInt,
+    },
+    /// Create and redeem vouchers
+    #[command(subcommand)]
+    Voucher(VoucherCommands),
+    /// Start settling a payment channel. It can be collected once the settling
+    /// delay is over
+    Settle {
+        /// Address of the channel
+        channel: Address,
+    },
+    /// Pay out the vouchers redeemed through a settled channel to its
+    /// recipient, and the rest of its balance to its owner
+    Collect {
+        /// Address of the channel
+        channel: Address,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+pub enum VoucherCommands {
+    /// Create a voucher on an outbound channel
+    Create {
+        /// Address of the channel
+        channel: Address,
+        /// Amount of the voucher in attoFIL, on top of the previous vouchers
+        /// of the lane
+        amount: BigInt,
+        /// Lane of the voucher
+        #[arg(long, default_value_t = 0)]
+        lane: u64,
+    },
+    /// Check that a voucher is valid for a channel
+    Check {
+        /// Address of the channel
+        channel: Address,
+        /// Encoded voucher
+        voucher: String,
+    },
+    /// Check and store a voucher received through an inbound channel
+    Add {
+        /// Address of the channel
+        channel: Address,
+        /// Encoded voucher
+        voucher: String,
+        /// Minimum amount in attoFIL the voucher must add to its lane
+        #[arg(long, default_value = "0")]
+        min_delta: BigInt,
+    },
+    /// Redeem a voucher on chain
+    Submit {
+        /// Address of the channel
+        channel: Address,
+        /// Encoded voucher
+        voucher: String,
+        /// Hex encoded secret, if the voucher requires one
+        #[arg(long)]
+        secret: Option<String>,
+    },
+}
+
+impl PaychCommands {
+    pub async fn run(&self, config: Config) -> anyhow::Result<()> {
+        let token = &config.client.rpc_token;
+        match self {
+            Self::Get { from, to, amount } => {
+                let info = paych_get(
+                    (
+                        AddressJson((*from).into()),
+                        AddressJson((*to).into()),
+                        amount.to_string(),
+                    ),
+                    token,
+                )
+                .await
+                .map_err(handle_rpc_err)?;
+                if let Some(sentinel) = &info.wait_sentinel {
+                    println!("Sent message {}", sentinel.0);
+                }
+                let channel = match (info.channel, info.wait_sentinel) {
+                    (Some(channel), _) => channel,
+                    (None, Some(sentinel)) => {
+                        println!("Waiting for the creation of the channel");
+                        paych_get_wait_ready((sentinel,), token)
+                            .await
+                            .map_err(handle_rpc_err)?
+                            .0
+                    }
+                    (None, None) => anyhow::bail!("No channel was returned"),
+                };
+                println!("{channel}");
+                Ok(())
+            }
+            Self::Voucher(cmd) => cmd.run(&config).await,
+            Self::Settle { channel } => {
+                let cid = paych_settle((AddressJson((*channel).into()),), token)
+                    .await
+                    .map_err(handle_rpc_err)?;
+                println!("Sent message {}", cid.0);
+                Ok(())
+            }
+            Self::Collect { channel } => {
+                let cid = paych_collect((AddressJson((*channel).into()),), token)
+                    .await
+                    .map_err(handle_rpc_err)?;
+                println!("Sent message {}", cid.0);
+                Ok(())
+            }
+        }
+    }
+}
+
+impl VoucherCommands {
+    pub async fn run(&self, config: &Config) -> anyhow::Result<()> {
+        let token = &config.client.rpc_token;
+        match self {
+            Self::Create {
+                channel,
+                amount,
+                lane,
+            } => {
+                let ret = paych_voucher_create(
+                    (AddressJson((*channel).into()), amount.to_string(), *lane),
+                    token,
+                )
+                .await
+                .map_err(handle_rpc_err)?;
+                match ret.voucher {
+                    Some(voucher) => println!("{voucher}"),
+                    None => anyhow::bail!(
+                        "The channel lacks {} attoFIL to cover the voucher",
+                        ret.shortfall.atto()
+                    ),
+                }
+                Ok(())
+            }
+            Self::Check { channel, voucher } => {
+                paych_voucher_check_valid((AddressJson((*channel).into()), voucher.clone()), token)
+                    .await
+                    .map_err(handle_rpc_err)?;
+                println!("Voucher is valid");
+                Ok(())
+            }
+            Self::Add {
+                channel,
+                voucher,
+                min_delta,
+            } => {
+                let delta = paych_voucher_add(
+                    (
+                        AddressJson((*channel).into()),
+                        voucher.clone(),
+                        min_delta.to_string(),
+                    ),
+                    token,
+                )
+                .await
+                .map_err(handle_rpc_err)?;
+                println!("Voucher added, worth {delta} attoFIL");
+                Ok(())
+            }
+            Self::Submit {
+                channel,
+                voucher,
+                secret,
+            } => {
+                let secret = match secret {
+                    Some(secret) => hex::decode(secret).context("Secret has to be hex")?,
+                    None => vec![],
+                };
+                let cid = paych_voucher_submit(
+                    (AddressJson((*channel).into()), voucher.clone(), secret),
+                    token,
+                )
+                .await
+                .map_err(handle_rpc_err)?;
+                println!("Sent message {}", cid.0);
+                Ok(())
+            }
+        }
+    }
+}
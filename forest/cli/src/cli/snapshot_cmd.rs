@@ -394,7 +394,7 @@ async fn validate(
     if confirm {
         let tmp_db_path = TempDir::new()?;
         let db_path = tmp_db_path.path().join(&config.chain.name);
-        let db = open_db(&db_path, config.db_config())?;
+        let db = open_db(&db_path, &config.db_config())?;
 
         let genesis = read_genesis_header(
             config.client.genesis_file.as_ref(),
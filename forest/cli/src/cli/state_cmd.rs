@@ -1,11 +1,23 @@
 // Copyright 2019-2023 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use clap::Subcommand;
+use std::ops::Range;
+
+use anyhow::Context;
+use clap::{Subcommand, ValueEnum};
+use forest_blocks::tipset_keys_json::TipsetKeysJson;
 use forest_encoding::tuple::*;
-use fvm_shared::{clock::ChainEpoch, econ::TokenAmount};
+use forest_json::address::json::AddressJson;
+use forest_rpc_api::data_types::{DealsFilter, MarketDeal};
+use forest_rpc_client::{
+    chain_get_tipset_by_height, chain_head, state_market_deals_page, state_miner_available_balance,
+    state_miner_vesting_funds, state_verified_client_status, state_vm_circulating_supply_internal,
+    wallet_balance,
+};
+use fvm_shared::{address::Address, clock::ChainEpoch, deal::DealID, econ::TokenAmount};
+use serde::Serialize;
 
-use super::Config;
+use super::{handle_rpc_err, Config};
 
 #[derive(Serialize_tuple, Deserialize_tuple, Clone, Debug)]
 struct VestingSchedule {
@@ -19,11 +31,239 @@ struct VestingScheduleEntry {
 }
 
 #[derive(Debug, Subcommand)]
-pub enum StateCommands {}
+pub enum StateCommands {
+    /// Show the data cap of a verified client
+    Datacap {
+        /// Address of the client
+        address: Address,
+    },
+    /// Show the balance of a miner, the part of it available for withdrawal
+    /// and its vesting schedule
+    MinerBalance {
+        /// Address of the miner
+        miner: Address,
+    },
+    /// List the storage market deals
+    Deals {
+        /// Only list the deals of this client
+        #[arg(long)]
+        client: Option<Address>,
+        /// Only list the deals of this provider
+        #[arg(long)]
+        provider: Option<Address>,
+        /// Only list verified deals if true, unverified deals if false
+        #[arg(long)]
+        verified: Option<bool>,
+        /// Only list the deals activated in a proven sector if true, the
+        /// others if false
+        #[arg(long)]
+        active: Option<bool>,
+        /// Output format
+        #[arg(long, value_enum, default_value = "csv")]
+        format: DealsFormat,
+        /// Number of deals to fetch from the node at once
+        #[arg(long, default_value_t = 1000)]
+        page_size: u64,
+    },
+    /// Show the circulating supply and the amounts it is computed from, in
+    /// attoFIL, as CSV
+    Supply {
+        /// Epochs to show the supply at, as `start..end` with the end excluded.
+        /// Defaults to the head of the chain
+        #[arg(long, value_parser = parse_epoch_range)]
+        range: Option<Range<ChainEpoch>>,
+        /// Number of epochs between two rows of the range
+        #[arg(long, default_value_t = 1, value_parser = clap::value_parser!(u64).range(1..))]
+        step: u64,
+    },
+}
+
+fn parse_epoch_range(range: &str) -> anyhow::Result<Range<ChainEpoch>> {
+    let (start, end) = range
+        .split_once("..")
+        .context("Range has to be formatted as start..end")?;
+    let range = start.parse()?..end.parse()?;
+    anyhow::ensure!(!range.is_empty(), "Range is empty");
+    Ok(range)
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum DealsFormat {
+    /// One row per deal, without the deal labels
+    Csv,
+    /// One JSON object per line
+    Json,
+}
+
+#[derive(Serialize)]
+struct DealLine<'a> {
+    #[serde(rename = "DealID")]
+    deal_id: DealID,
+    #[serde(flatten)]
+    deal: &'a MarketDeal,
+}
 
 impl StateCommands {
-    pub fn run(&self, _config: Config) -> anyhow::Result<()> {
-        // match self {}
-        Ok(())
+    pub async fn run(&self, config: Config) -> anyhow::Result<()> {
+        match self {
+            Self::Datacap { address } => {
+                let data_cap = state_verified_client_status(
+                    (
+                        AddressJson((*address).into()),
+                        TipsetKeysJson(Default::default()),
+                    ),
+                    &config.client.rpc_token,
+                )
+                .await
+                .map_err(handle_rpc_err)?;
+                match data_cap {
+                    Some(data_cap) => println!("{data_cap} bytes"),
+                    None => println!("{address} is not a verified client"),
+                }
+                Ok(())
+            }
+            Self::MinerBalance { miner } => {
+                let params = || {
+                    (
+                        AddressJson((*miner).into()),
+                        TipsetKeysJson(Default::default()),
+                    )
+                };
+                let token = &config.client.rpc_token;
+                let balance = wallet_balance((miner.to_string(),), token)
+                    .await
+                    .map_err(handle_rpc_err)?;
+                let available = state_miner_available_balance(params(), token)
+                    .await
+                    .map_err(handle_rpc_err)?;
+                let vesting = state_miner_vesting_funds(params(), token)
+                    .await
+                    .map_err(handle_rpc_err)?;
+
+                println!("Balance: {balance} attoFIL");
+                println!("Available: {available} attoFIL");
+                println!("Vesting:");
+                for fund in &vesting {
+                    println!("  {} attoFIL at epoch {}", fund.amount.atto(), fund.epoch);
+                }
+                Ok(())
+            }
+            Self::Deals {
+                client,
+                provider,
+                verified,
+                active,
+                format,
+                page_size,
+            } => {
+                let mut filter = DealsFilter {
+                    client: client.map(Into::into),
+                    provider: provider.map(Into::into),
+                    verified: *verified,
+                    active: *active,
+                    after: None,
+                    limit: *page_size,
+                };
+                if let DealsFormat::Csv = format {
+                    println!("DealID,Client,Provider,PieceCID,PieceSize,Verified,StartEpoch,EndEpoch,StoragePricePerEpoch,SectorStartEpoch,SlashEpoch");
+                }
+                // The deals are printed a page at a time, as the market can be
+                // too large to hold at once.
+                let head = chain_head(&config.client.rpc_token)
+                    .await
+                    .map_err(handle_rpc_err)?;
+                let tsk = TipsetKeysJson(head.0.key().clone());
+                loop {
+                    let page = state_market_deals_page(
+                        (filter.clone(), tsk.clone()),
+                        &config.client.rpc_token,
+                    )
+                    .await
+                    .map_err(handle_rpc_err)?;
+                    for (deal_id, deal) in &page.deals {
+                        match format {
+                            DealsFormat::Csv => {
+                                let proposal = &deal.proposal;
+                                println!(
+                                    "{deal_id},{},{},{},{},{},{},{},{},{},{}",
+                                    proposal.client,
+                                    proposal.provider,
+                                    proposal.piece_cid,
+                                    proposal.piece_size.0,
+                                    proposal.verified_deal,
+                                    proposal.start_epoch,
+                                    proposal.end_epoch,
+                                    proposal.storage_price_per_epoch.atto(),
+                                    deal.state.sector_start_epoch,
+                                    deal.state.slash_epoch,
+                                );
+                            }
+                            DealsFormat::Json => println!(
+                                "{}",
+                                serde_json::to_string(&DealLine {
+                                    deal_id: *deal_id,
+                                    deal
+                                })?
+                            ),
+                        }
+                    }
+                    match page.next {
+                        Some(next) => filter.after = Some(next),
+                        None => return Ok(()),
+                    }
+                }
+            }
+            Self::Supply { range, step } => {
+                let token = &config.client.rpc_token;
+                let head = chain_head(token).await.map_err(handle_rpc_err)?;
+                let tipsets = match range {
+                    Some(range) => {
+                        let mut tipsets = vec![];
+                        for epoch in range.clone().step_by(*step as usize) {
+                            let tipset =
+                                chain_get_tipset_by_height((epoch, head.0.key().clone()), token)
+                                    .await
+                                    .map_err(handle_rpc_err)?;
+                            tipsets.push(tipset);
+                        }
+                        tipsets
+                    }
+                    None => vec![head],
+                };
+                println!("Epoch,FilVested,FilMined,FilBurnt,FilLocked,FilCirculating,FilReserveDisbursed");
+                for tipset in tipsets {
+                    let supply = state_vm_circulating_supply_internal(
+                        (TipsetKeysJson(tipset.0.key().clone()),),
+                        token,
+                    )
+                    .await
+                    .map_err(handle_rpc_err)?;
+                    println!(
+                        "{},{},{},{},{},{},{}",
+                        tipset.0.epoch(),
+                        supply.fil_vested.atto(),
+                        supply.fil_mined.atto(),
+                        supply.fil_burnt.atto(),
+                        supply.fil_locked.atto(),
+                        supply.fil_circulating.atto(),
+                        supply.fil_reserve_disbursed.atto(),
+                    );
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn epoch_range_parsing() {
+        assert_eq!(parse_epoch_range("100..200").unwrap(), 100..200);
+        assert!(parse_epoch_range("200..100").is_err());
+        assert!(parse_epoch_range("100").is_err());
+        assert!(parse_epoch_range("a..200").is_err());
     }
 }
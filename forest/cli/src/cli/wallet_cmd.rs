@@ -11,22 +11,32 @@ use base64::{prelude::BASE64_STANDARD, Engine};
 use clap::{arg, Subcommand};
 use forest_json::{
     address::json::AddressJson,
+    message::json::MessageJson,
     signature::json::{signature_type::SignatureTypeJson, SignatureJson},
+    signed_message::json::SignedMessageJson,
 };
-use forest_key_management::json::KeyInfoJson;
+use forest_key_management::{json::KeyInfoJson, KeyStore};
+use forest_message::SignedMessage;
+use forest_rpc_api::wallet_api::WALLET_HD_NO_SEED_ERROR_CODE;
 use forest_rpc_client::wallet_ops::*;
 use forest_shim::{
     address::{Address, Protocol},
     crypto::{Signature, SignatureType},
+    message::Message,
 };
 use forest_utils::io::read_file_to_string;
+use fvm_ipld_encoding::Cbor;
 use fvm_shared::econ::TokenAmount;
 use num::BigInt;
 use rpassword::read_password;
 use rust_decimal::prelude::*;
 use rust_decimal_macros::dec;
 
-use super::{handle_rpc_err, Config};
+use super::{
+    handle_rpc_err,
+    keystore_cmd::{open_keystore, KeystoreCommands},
+    write_json_output, Config,
+};
 
 const NUM_SIGNIFICANT_DIGITS: u32 = 4;
 
@@ -54,10 +64,40 @@ enum FormattingMode {
 pub enum WalletCommands {
     /// Create a new wallet
     New {
+        /// The signature type to use. One of SECP256k1, BLS, or delegated for
+        /// `f4` addresses backed by SECP256k1 keys. The wallet can not sign the
+        /// messages of delegated addresses yet, only EIP-155 transactions
+        #[arg(default_value = "secp256k1")]
+        signature_type: String,
+        /// Derive the key from the mnemonic seed of the wallet. A new mnemonic
+        /// is generated and printed by the command if the wallet has none
+        #[arg(long)]
+        from_mnemonic: bool,
+    },
+    /// Restore the wallet from a mnemonic, deriving its first keys
+    Restore {
+        /// The signature type of the keys to derive. One of SECP256k1, or BLS
+        #[arg(default_value = "secp256k1")]
+        signature_type: String,
+        /// The number of keys to derive, at most 100
+        #[arg(long, default_value_t = 1)]
+        count: u32,
+        /// Prompt for the passphrase the mnemonic is protected with
+        #[arg(long)]
+        passphrase: bool,
+    },
+    /// Derive the key at the given index from the mnemonic seed of the wallet
+    Derive {
         /// The signature type to use. One of SECP256k1, or BLS
         #[arg(default_value = "secp256k1")]
         signature_type: String,
+        /// The derivation index of the key
+        #[arg(long)]
+        index: u32,
     },
+    /// Manage the keystore files of the node, which must be stopped
+    #[command(subcommand)]
+    Keystore(KeystoreCommands),
     /// Get account balance
     Balance {
         /// The address of the account to check
@@ -113,6 +153,16 @@ pub enum WalletCommands {
         #[arg(short)]
         address: String,
     },
+    /// Sign a message created with `forest-cli msg create` with a key of the
+    /// local keystore. The node does not need to be running. Messages from
+    /// delegated `f4` addresses are not supported yet
+    SignMessage {
+        /// File holding the unsigned message
+        file: PathBuf,
+        /// File to write the signed message to, instead of the standard output
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
     /// Verify the signature of a message. Returns true if the signature matches
     /// the message and address
     Verify {
@@ -131,13 +181,41 @@ pub enum WalletCommands {
 impl WalletCommands {
     pub async fn run(&self, config: Config) -> anyhow::Result<()> {
         match self {
-            Self::New { signature_type } => {
-                let signature_type = match signature_type.to_lowercase().as_str() {
-                    "secp256k1" => SignatureType::Secp256k1,
-                    _ => SignatureType::BLS,
-                };
-
-                let signature_type_json = SignatureTypeJson(signature_type);
+            Self::New {
+                signature_type,
+                from_mnemonic,
+            } => {
+                let signature_type_json = SignatureTypeJson(parse_signature_type(signature_type)?);
+
+                if *from_mnemonic {
+                    let token = &config.client.rpc_token;
+                    let key = match wallet_hd_new(
+                        (SignatureTypeJson(signature_type_json.0),),
+                        token,
+                    )
+                    .await
+                    {
+                        Err(jsonrpc_v2::Error::Full { code, .. })
+                            if code == WALLET_HD_NO_SEED_ERROR_CODE =>
+                        {
+                            // The mnemonic is generated here so that the node never sends it
+                            let mnemonic = forest_key_management::generate_mnemonic()?;
+                            let mut keys = wallet_hd_restore(
+                                (mnemonic.to_string(), String::new(), signature_type_json, 1),
+                                token,
+                            )
+                            .await
+                            .map_err(handle_rpc_err)?;
+                            println!("Write down the mnemonic of the wallet and keep it safe, it will not be shown again:");
+                            println!("{mnemonic}");
+                            println!();
+                            keys.pop().context("No key derived from the mnemonic")?
+                        }
+                        response => response.map_err(handle_rpc_err)?,
+                    };
+                    println!("{} ({})", key.address, key.path);
+                    return Ok(());
+                }
 
                 let response = wallet_new((signature_type_json,), &config.client.rpc_token)
                     .await
@@ -145,6 +223,53 @@ impl WalletCommands {
                 println!("{response}");
                 Ok(())
             }
+            Self::Keystore(cmd) => cmd.run(&config),
+            Self::Restore {
+                signature_type,
+                count,
+                passphrase,
+            } => {
+                println!("Enter the mnemonic: ");
+                let mnemonic = read_password()?;
+                let passphrase = if *passphrase {
+                    println!("Enter the passphrase: ");
+                    read_password()?
+                } else {
+                    String::new()
+                };
+
+                let response = wallet_hd_restore(
+                    (
+                        mnemonic.trim().to_owned(),
+                        passphrase,
+                        SignatureTypeJson(parse_signature_type(signature_type)?),
+                        *count,
+                    ),
+                    &config.client.rpc_token,
+                )
+                .await
+                .map_err(handle_rpc_err)?;
+                for key in response {
+                    println!("{} ({})", key.address, key.path);
+                }
+                Ok(())
+            }
+            Self::Derive {
+                signature_type,
+                index,
+            } => {
+                let response = wallet_hd_derive(
+                    (
+                        SignatureTypeJson(parse_signature_type(signature_type)?),
+                        *index,
+                    ),
+                    &config.client.rpc_token,
+                )
+                .await
+                .map_err(handle_rpc_err)?;
+                println!("{} ({})", response.address, response.path);
+                Ok(())
+            }
             Self::Balance { address } => {
                 let response = wallet_balance((address.to_string(),), &config.client.rpc_token)
                     .await
@@ -261,6 +386,12 @@ impl WalletCommands {
                 println!("{}", hex::encode(response.0.bytes()));
                 Ok(())
             }
+            Self::SignMessage { file, output } => {
+                let MessageJson(message) = serde_json::from_str(&read_file_to_string(file)?)?;
+                let mut keystore = open_keystore(&config)?;
+                let message = sign_message(message, &mut keystore)?;
+                write_json_output(&SignedMessageJson(message), output.as_deref())
+            }
             Self::Verify {
                 message,
                 address,
@@ -273,7 +404,8 @@ impl WalletCommands {
                 let signature = match address.protocol() {
                     Protocol::Secp256k1 => Signature::new_secp256k1(sig_bytes),
                     Protocol::BLS => Signature::new_bls(sig_bytes),
-                    _ => anyhow::bail!("Invalid signature (must be bls or secp256k1)"),
+                    Protocol::Delegated => Signature::new(SignatureType::Delegated, sig_bytes),
+                    _ => anyhow::bail!("Invalid signature (must be bls, secp256k1 or delegated)"),
                 };
                 let msg = hex::decode(message).context("Message has to be a hex string")?;
 
@@ -291,6 +423,36 @@ impl WalletCommands {
     }
 }
 
+/// Signs the message with the key of its sender in the keystore.
+fn sign_message(message: Message, keystore: &mut KeyStore) -> anyhow::Result<SignedMessage> {
+    let from = Address::from(message.from);
+    anyhow::ensure!(
+        from.protocol() != Protocol::ID,
+        "Messages signed offline must be sent from a key address, not {from}"
+    );
+    forest_key_management::ensure_message_signable(&from)?;
+
+    let key_info = forest_key_management::try_find(&from, keystore)
+        .with_context(|| format!("No key found for {from}"))?;
+    let signature = forest_key_management::sign(
+        *key_info.key_type(),
+        key_info.private_key(),
+        &message.cid()?.to_bytes(),
+    )?;
+    SignedMessage::new_from_parts(message, signature)
+}
+
+fn parse_signature_type(signature_type: &str) -> anyhow::Result<SignatureType> {
+    match signature_type.to_lowercase().as_str() {
+        "secp256k1" => Ok(SignatureType::Secp256k1),
+        "bls" => Ok(SignatureType::BLS),
+        "delegated" => Ok(SignatureType::Delegated),
+        _ => anyhow::bail!(
+            "Invalid signature type {signature_type}, use secp256k1, bls or delegated"
+        ),
+    }
+}
+
 /// Function to format `TokenAmount` accoding to `FormattingMode`:
 /// mode to show data in `FIL` units
 /// in full accuracy for `ExactFixed` mode,
@@ -390,10 +552,64 @@ fn bool_pair_to_mode(exact: bool, fixed: bool) -> FormattingMode {
 
 #[cfg(test)]
 mod test {
+    use forest_key_management::{generate_key, KeyStoreConfig};
     use fvm_shared::econ::TokenAmount;
 
     use super::*;
 
+    fn message_from(from: Address) -> Message {
+        fvm_shared::message::Message {
+            from: from.into(),
+            to: Address::new_id(1000).into(),
+            ..Default::default()
+        }
+        .into()
+    }
+
+    fn keystore_with_key(sig_type: SignatureType) -> (KeyStore, Address) {
+        let mut keystore = KeyStore::new(KeyStoreConfig::Memory).unwrap();
+        let key = generate_key(sig_type).unwrap();
+        keystore
+            .put(format!("wallet-{}", key.address), key.key_info)
+            .unwrap();
+        (keystore, key.address)
+    }
+
+    #[test]
+    fn sign_message_with_key_of_sender() {
+        for sig_type in [SignatureType::Secp256k1, SignatureType::BLS] {
+            let (mut keystore, address) = keystore_with_key(sig_type);
+            let signed = sign_message(message_from(address), &mut keystore).unwrap();
+            assert_eq!(signed.signature().signature_type(), sig_type);
+            signed.verify().unwrap();
+        }
+    }
+
+    #[test]
+    fn sign_message_rejects_unknown_and_unsupported_senders() {
+        let (mut keystore, _) = keystore_with_key(SignatureType::Secp256k1);
+        let other = generate_key(SignatureType::Secp256k1).unwrap().address;
+        assert!(sign_message(message_from(other), &mut keystore).is_err());
+        assert!(sign_message(message_from(Address::new_id(1)), &mut keystore).is_err());
+
+        let (mut keystore, address) = keystore_with_key(SignatureType::Delegated);
+        assert!(sign_message(message_from(address), &mut keystore).is_err());
+    }
+
+    #[test]
+    fn parse_signature_types() {
+        assert_eq!(
+            parse_signature_type("SECP256k1").unwrap(),
+            SignatureType::Secp256k1
+        );
+        assert_eq!(parse_signature_type("bls").unwrap(), SignatureType::BLS);
+        assert_eq!(
+            parse_signature_type("delegated").unwrap(),
+            SignatureType::Delegated
+        );
+        assert!(parse_signature_type("secp").is_err());
+    }
+
     #[test]
     fn exact_balance_fixed_unit() {
         let cases_vec = vec![
@@ -16,11 +16,14 @@ pub(super) async fn process(command: Subcommand, config: Config) -> anyhow::Resu
         Subcommand::Net(cmd) => cmd.run(config).await,
         Subcommand::Wallet(cmd) => cmd.run(config).await,
         Subcommand::Sync(cmd) => cmd.run(config).await,
-        Subcommand::Mpool(cmd) => cmd.run(config),
-        Subcommand::State(cmd) => cmd.run(config),
+        Subcommand::Mpool(cmd) => cmd.run(config).await,
+        Subcommand::Msg(cmd) => cmd.run(config).await,
+        Subcommand::Msig(cmd) => cmd.run(config).await,
+        Subcommand::Paych(cmd) => cmd.run(config).await,
+        Subcommand::State(cmd) => cmd.run(config).await,
         Subcommand::Config(cmd) => cmd.run(&config, &mut std::io::stdout()),
         Subcommand::Send(cmd) => cmd.run(config).await,
-        Subcommand::DB(cmd) => cmd.run(&config),
+        Subcommand::DB(cmd) => cmd.run(&config).await,
         Subcommand::Snapshot(cmd) => cmd.run(config).await,
         Subcommand::Attach(cmd) => cmd.run(config),
         Subcommand::Shutdown(cmd) => cmd.run(config).await,
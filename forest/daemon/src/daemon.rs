@@ -7,7 +7,7 @@ use anyhow::Context;
 use dialoguer::{theme::ColorfulTheme, Confirm};
 use forest_auth::{create_token, generate_priv_key, ADMIN, JWT_IDENTIFIER};
 use forest_blocks::Tipset;
-use forest_chain::ChainStore;
+use forest_chain::{ChainStore, HeadChange};
 use forest_chain_sync::{consensus::SyncGossipSubmitter, ChainMuxer};
 use forest_cli_shared::{
     chain_path,
@@ -15,20 +15,23 @@ use forest_cli_shared::{
         default_snapshot_dir, is_aria2_installed, snapshot_fetch, snapshot_fetch_size,
         to_size_string, CliOpts, Client, Config, FOREST_VERSION_STRING,
     },
+    DaemonLock,
 };
 use forest_db::{
     db_engine::{db_path, open_db, Db},
+    tiered::{TieredDb, TieredDbConfig},
     Store,
 };
 use forest_genesis::{get_network_name_from_genesis, import_chain, read_genesis_header};
 use forest_key_management::{
-    KeyStore, KeyStoreConfig, ENCRYPTED_KEYSTORE_NAME, FOREST_KEYSTORE_PHRASE_ENV,
+    KeyStore, KeyStoreConfig, KeyStoreSigner, RemoteSigner, Signer, ENCRYPTED_KEYSTORE_NAME,
+    FOREST_KEYSTORE_PHRASE_ENV,
 };
 use forest_libp2p::{
     ed25519, get_keypair, Keypair, Libp2pConfig, Libp2pService, PeerId, PeerManager,
 };
 use forest_message_pool::{MessagePool, MpoolConfig, MpoolRpcProvider};
-use forest_rpc::start_rpc;
+use forest_rpc::{bind_unix_socket, load_tls_config, start_rpc, RpcEndpoints, TlsEndpoint};
 use forest_rpc_api::data_types::RPCState;
 use forest_shim::version::NetworkVersion;
 use forest_state_manager::StateManager;
@@ -40,7 +43,7 @@ use raw_sync::events::{Event, EventInit, EventState};
 use rpassword::read_password;
 use tokio::{
     signal::unix::{signal, SignalKind},
-    sync::RwLock,
+    sync::{broadcast::error::RecvError, RwLock},
     task::JoinSet,
     time::sleep,
 };
@@ -73,6 +76,8 @@ pub(super) async fn start(opts: CliOpts, config: Config) -> anyhow::Result<Db> {
     if config.chain.name == "calibnet" {
         forest_shim::address::set_current_network(forest_shim::address::Network::Testnet);
     }
+    config.network.chain_exchange.validate()?;
+    config.network.bitswap.validate()?;
 
     set_sigint_handler();
     let (shutdown_send, mut shutdown_recv) = tokio::sync::mpsc::channel(1);
@@ -82,6 +87,9 @@ pub(super) async fn start(opts: CliOpts, config: Config) -> anyhow::Result<Db> {
         "Starting Forest daemon, version {}",
         FOREST_VERSION_STRING.as_str()
     );
+    // Held until the daemon stops, so that the commands changing its files
+    // refuse to run alongside it
+    let _daemon_lock = DaemonLock::acquire(&config)?;
 
     let path: PathBuf = config.client.data_dir.join("libp2p");
     let net_keypair = match get_keypair(&path.join("keypair")) {
@@ -112,7 +120,12 @@ pub(super) async fn start(opts: CliOpts, config: Config) -> anyhow::Result<Db> {
 
     let keystore = Arc::new(RwLock::new(keystore));
 
-    let db = open_db(&db_path(&chain_path(&config)), config.db_config())?;
+    let db_config = config.db_config();
+    let db = open_db(
+        &db_path(&chain_path(&config), db_config.backend),
+        &db_config,
+    )?;
+    db.spawn_default_column_migration();
 
     let mut services = JoinSet::new();
 
@@ -125,7 +138,7 @@ pub(super) async fn start(opts: CliOpts, config: Config) -> anyhow::Result<Db> {
             "Prometheus server started at {}",
             config.client.metrics_address
         );
-        let db_directory = forest_db::db_engine::db_path(&chain_path(&config));
+        let db_directory = db_path(&chain_path(&config), db_config.backend);
         let db = db.clone();
         services.spawn(async {
             forest_metrics::init_prometheus(prometheus_listener, db_directory, db)
@@ -211,6 +224,14 @@ pub(super) async fn start(opts: CliOpts, config: Config) -> anyhow::Result<Db> {
         false
     };
 
+    if let Db::Tiered(tiered) = &db {
+        services.spawn(migrate_to_cold_store(
+            Arc::clone(&chain_store),
+            Arc::clone(tiered),
+            config.tiered_db.clone(),
+        ));
+    }
+
     let peer_manager = Arc::new(PeerManager::default());
     services.spawn(peer_manager.clone().peer_operation_event_loop_task());
     let genesis_cid = *genesis_header.cid();
@@ -273,12 +294,34 @@ pub(super) async fn start(opts: CliOpts, config: Config) -> anyhow::Result<Db> {
     // Start services
     if config.client.enable_rpc {
         let keystore_rpc = Arc::clone(&keystore);
+        let signer = create_signer(&config, &keystore)?;
         let rpc_listen =
             std::net::TcpListener::bind(config.client.rpc_address).context(format!(
                 "could not bind to rpc address {}",
                 config.client.rpc_address
             ))?;
+        let mut rpc_endpoints = RpcEndpoints::new(rpc_listen);
+        if let Some(socket_path) = &config.client.rpc_unix_socket {
+            rpc_endpoints.unix = Some(bind_unix_socket(socket_path)?);
+            info!("JSON-RPC Unix socket started at {}", socket_path.display());
+        }
+        if let Some(tls_address) = config.client.rpc_tls_address {
+            let (Some(cert_path), Some(key_path)) = (
+                &config.client.rpc_tls_cert_path,
+                &config.client.rpc_tls_key_path,
+            ) else {
+                anyhow::bail!("TLS RPC endpoint requires both a certificate and a private key");
+            };
+            rpc_endpoints.tls = Some(TlsEndpoint {
+                listener: std::net::TcpListener::bind(tls_address)
+                    .context(format!("could not bind to rpc tls address {tls_address}"))?,
+                config: load_tls_config(cert_path, key_path)?,
+            });
+            info!("JSON-RPC TLS endpoint started at {tls_address}");
+        }
 
+        let rpc_slow_call_threshold = config.client.rpc_slow_call_threshold;
+        let backup_config = config.to_toml_without_secrets()?;
         let rpc_state_manager = Arc::clone(&state_manager);
         let rpc_chain_store = Arc::clone(&chain_store);
 
@@ -289,6 +332,7 @@ pub(super) async fn start(opts: CliOpts, config: Config) -> anyhow::Result<Db> {
                 Arc::new(RPCState {
                     state_manager: Arc::clone(&rpc_state_manager),
                     keystore: keystore_rpc,
+                    signer,
                     mpool,
                     bad_blocks,
                     sync_state,
@@ -299,9 +343,14 @@ pub(super) async fn start(opts: CliOpts, config: Config) -> anyhow::Result<Db> {
                                                                   * StateManager */
                     chain_store: rpc_chain_store,
                     new_mined_block_tx: tipset_sink,
+                    push_locks: Default::default(),
+                    paych_lock: Default::default(),
+                    store_check: Default::default(),
                 }),
-                rpc_listen,
+                rpc_endpoints,
+                rpc_slow_call_threshold,
                 FOREST_VERSION_STRING.as_str(),
+                backup_config,
                 shutdown_send,
             )
             .await
@@ -400,6 +449,47 @@ async fn propagate_error(services: &mut JoinSet<Result<(), anyhow::Error>>) -> a
     }
 }
 
+/// Moves the blocks that fell out of the hot epochs to the cold store, every
+/// `migration_interval` epochs applied to the head.
+async fn migrate_to_cold_store(
+    chain_store: Arc<ChainStore<Db>>,
+    tiered: Arc<TieredDb<Db, Db>>,
+    config: TieredDbConfig,
+) -> anyhow::Result<()> {
+    let mut head_changes = chain_store.publisher().subscribe();
+    let mut last_migration = chain_store.heaviest_tipset().epoch();
+    loop {
+        let head = match head_changes.recv().await {
+            Ok(HeadChange::Apply(head)) => head,
+            Ok(_) | Err(RecvError::Lagged(_)) => continue,
+            Err(RecvError::Closed) => return Ok(()),
+        };
+        if head.epoch() < last_migration + config.migration_interval {
+            continue;
+        }
+        last_migration = head.epoch();
+        let chain_store = Arc::clone(&chain_store);
+        let tiered = Arc::clone(&tiered);
+        let hot_epochs = config.hot_epochs;
+        // Walking the epochs that fell out of the hot ones reads the database
+        // a lot, keep it off the async runtime.
+        match tokio::task::spawn_blocking(move || {
+            futures::executor::block_on(
+                chain_store.migrate_to_cold(
+                    head,
+                    hot_epochs,
+                    |cids| Ok(tiered.move_to_cold(cids)?),
+                ),
+            )
+        })
+        .await?
+        {
+            Ok(moved) => info!("Moved {moved} blocks to the cold store"),
+            Err(e) => warn!("Failed to move blocks to the cold store: {e}"),
+        }
+    }
+}
+
 /// Optionally fetches the snapshot. Returns the configuration (modified
 /// accordingly if a snapshot was fetched).
 async fn maybe_fetch_snapshot(
@@ -501,6 +591,24 @@ fn get_actual_chain_name(internal_network_name: &str) -> &str {
     }
 }
 
+/// Signs with the remote signer if one is configured, with the keys of the
+/// keystore otherwise.
+fn create_signer(
+    config: &Config,
+    keystore: &Arc<RwLock<KeyStore>>,
+) -> anyhow::Result<Arc<dyn Signer>> {
+    match &config.client.remote_signer_url {
+        Some(url) => {
+            info!("Signing with the remote signer at {url}");
+            Ok(Arc::new(RemoteSigner::new(
+                url,
+                config.client.remote_signer_token.clone(),
+            )?))
+        }
+        None => Ok(Arc::new(KeyStoreSigner::new(Arc::clone(keystore)))),
+    }
+}
+
 fn create_keystore(config: &Config) -> anyhow::Result<KeyStore> {
     let passphrase = std::env::var(FOREST_KEYSTORE_PHRASE_ENV);
     let is_interactive = atty::is(atty::Stream::Stdin);
@@ -9,16 +9,21 @@ use std::{
 
 use chrono::Duration;
 use directories::ProjectDirs;
+use forest_db::db_engine::DbBackend;
 use forest_rpc_client::DEFAULT_PORT;
 use forest_utils::io::ProgressBarVisibility;
 use serde::{Deserialize, Serialize};
-use serde_with::{serde_as, DurationSeconds};
+use serde_with::{serde_as, DurationMilliseconds, DurationSeconds};
 
 #[serde_as]
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(default)]
 pub struct Client {
     pub data_dir: PathBuf,
+    /// Database backend, `rocksdb` or `paritydb`, among the ones Forest was
+    /// built with. The database of another backend can be converted with
+    /// `forest-cli db migrate`.
+    pub db_backend: DbBackend,
     pub genesis_file: Option<String>,
     pub enable_rpc: bool,
     pub rpc_port: u16,
@@ -36,6 +41,26 @@ pub struct Client {
     pub metrics_address: SocketAddr,
     /// RPC bind, e.g. 127.0.0.1:1234
     pub rpc_address: SocketAddr,
+    /// Optional Unix socket for local RPC access. Access is controlled by
+    /// file-system permissions and grants admin rights without a token.
+    pub rpc_unix_socket: Option<PathBuf>,
+    /// Optional TLS RPC bind, e.g. 0.0.0.0:1235. Requires both
+    /// `rpc_tls_cert_path` and `rpc_tls_key_path` to be set.
+    pub rpc_tls_address: Option<SocketAddr>,
+    /// PEM encoded certificate chain for the TLS RPC endpoint
+    pub rpc_tls_cert_path: Option<PathBuf>,
+    /// PEM encoded private key for the TLS RPC endpoint
+    pub rpc_tls_key_path: Option<PathBuf>,
+    /// RPC calls taking longer than this many milliseconds are logged along
+    /// with their parameters. Disabled when unset.
+    #[serde_as(as = "Option<DurationMilliseconds<u64>>")]
+    pub rpc_slow_call_threshold: Option<std::time::Duration>,
+    /// Optional remote signing service holding the keys of the wallet, e.g.
+    /// `http://127.0.0.1:1236/rpc/v0` or `unix:///run/signer.sock`. Keys of the
+    /// local keystore are used when unset.
+    pub remote_signer_url: Option<String>,
+    /// Token sent as the `Authorization` header to the remote signer
+    pub remote_signer_token: Option<String>,
     // Period of validity for JWT in seconds. Defaults to 60 days.
     #[serde_as(as = "DurationSeconds<i64>")]
     pub token_exp: Duration,
@@ -48,6 +73,7 @@ impl Default for Client {
         let dir = ProjectDirs::from("com", "ChainSafe", "Forest").expect("failed to find project directories, please set FOREST_CONFIG_PATH environment variable manually.");
         Self {
             data_dir: dir.data_dir().to_path_buf(),
+            db_backend: Default::default(),
             genesis_file: None,
             enable_rpc: true,
             rpc_port: DEFAULT_PORT,
@@ -59,6 +85,13 @@ impl Default for Client {
             encrypt_keystore: true,
             metrics_address: FromStr::from_str("0.0.0.0:6116").unwrap(),
             rpc_address: SocketAddr::new(IpAddr::V4(Ipv4Addr::LOCALHOST), DEFAULT_PORT),
+            rpc_unix_socket: None,
+            rpc_tls_address: None,
+            rpc_tls_cert_path: None,
+            rpc_tls_key_path: None,
+            rpc_slow_call_threshold: None,
+            remote_signer_url: None,
+            remote_signer_token: None,
             token_exp: Duration::seconds(5184000), // 60 Days = 5184000 Seconds
             show_progress_bars: Default::default(),
         }
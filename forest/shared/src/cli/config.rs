@@ -177,6 +177,7 @@ pub struct Config {
     pub client: Client,
     pub rocks_db: forest_db::rocks_config::RocksDbConfig,
     pub parity_db: forest_db::parity_db_config::ParityDbConfig,
+    pub tiered_db: forest_db::tiered::TieredDbConfig,
     pub network: Libp2pConfig,
     pub sync: SyncConfig,
     pub chain: Arc<ChainConfig>,
@@ -187,14 +188,24 @@ pub struct Config {
 }
 
 impl Config {
-    #[cfg(feature = "rocksdb")]
-    pub fn db_config(&self) -> &DbConfig {
-        &self.rocks_db
+    pub fn db_config(&self) -> DbConfig {
+        DbConfig {
+            backend: self.client.db_backend,
+            rocks_db: self.rocks_db.clone(),
+            parity_db: self.parity_db.clone(),
+            tiered: self.tiered_db.clone(),
+        }
     }
 
-    #[cfg(feature = "paritydb")]
-    pub fn db_config(&self) -> &DbConfig {
-        &self.parity_db
+    /// The configuration in `TOML` without the tokens of the client, to be
+    /// stored in backups.
+    pub fn to_toml_without_secrets(&self) -> anyhow::Result<String> {
+        let mut value = toml::Value::try_from(self)?;
+        if let Some(client) = value.get_mut("client").and_then(toml::Value::as_table_mut) {
+            client.remove("rpc_token");
+            client.remove("remote_signer_token");
+        }
+        Ok(toml::to_string(&value)?)
     }
 }
 
@@ -206,6 +217,7 @@ mod test {
     };
 
     use chrono::Duration;
+    use forest_db::db_engine::DbBackend;
     use forest_utils::io::ProgressBarVisibility;
     use quickcheck::Arbitrary;
     use quickcheck_macros::quickcheck;
@@ -230,6 +242,7 @@ mod test {
                 client: val.client,
                 rocks_db: val.rocks_db,
                 parity_db: val.parity_db,
+                tiered_db: Default::default(),
                 network: val.network,
                 sync: val.sync,
                 chain: Arc::new(ChainConfig::default()),
@@ -246,6 +259,9 @@ mod test {
             ConfigPartial {
                 client: Client {
                     data_dir: PathBuf::arbitrary(g),
+                    db_backend: *g
+                        .choose(&[DbBackend::RocksDb, DbBackend::ParityDb])
+                        .unwrap(),
                     genesis_file: Option::arbitrary(g),
                     enable_rpc: bool::arbitrary(g),
                     rpc_port: u16::arbitrary(g),
@@ -257,6 +273,14 @@ mod test {
                     encrypt_keystore: bool::arbitrary(g),
                     metrics_address: SocketAddr::arbitrary(g),
                     rpc_address: SocketAddr::arbitrary(g),
+                    rpc_unix_socket: Option::arbitrary(g),
+                    rpc_tls_address: Option::arbitrary(g),
+                    rpc_tls_cert_path: Option::arbitrary(g),
+                    rpc_tls_key_path: Option::arbitrary(g),
+                    rpc_slow_call_threshold: Option::<u32>::arbitrary(g)
+                        .map(|ms| std::time::Duration::from_millis(ms.into())),
+                    remote_signer_url: Option::arbitrary(g),
+                    remote_signer_token: Option::arbitrary(g),
                     token_exp: Duration::milliseconds(i64::arbitrary(g)),
                     show_progress_bars: ProgressBarVisibility::arbitrary(g),
                 },
@@ -273,10 +297,12 @@ mod test {
                     log_level: String::arbitrary(g),
                     optimize_filters_for_hits: bool::arbitrary(g),
                     optimize_for_point_lookup: i32::arbitrary(g),
+                    columns: Default::default(),
                 },
                 parity_db: forest_db::parity_db_config::ParityDbConfig {
                     enable_statistics: bool::arbitrary(g),
                     compression_type: String::arbitrary(g),
+                    columns: Default::default(),
                 },
                 network: Libp2pConfig {
                     listening_multiaddrs: vec![Ipv4Addr::arbitrary(g).into()],
@@ -284,6 +310,19 @@ mod test {
                     mdns: bool::arbitrary(g),
                     kademlia: bool::arbitrary(g),
                     target_peer_count: u32::arbitrary(g),
+                    chain_exchange: forest_libp2p::ChainExchangeConfig {
+                        max_window: u32::arbitrary(g) as _,
+                        max_concurrent_requests_per_peer: u16::arbitrary(g) as _,
+                        max_bytes_per_sec_per_peer: u32::arbitrary(g) as _,
+                        message_cache_size: u16::arbitrary(g) as _,
+                    },
+                    bitswap: forest_libp2p::BitswapConfig {
+                        max_message_size: u32::arbitrary(g) as _,
+                        max_want_list_size: u16::arbitrary(g) as _,
+                        max_bytes_per_sec_per_peer: u32::arbitrary(g) as _,
+                        serve_recent_state_only: bool::arbitrary(g),
+                        recent_state_depth: u32::arbitrary(g) as _,
+                    },
                 },
                 sync: SyncConfig {
                     req_window: i64::arbitrary(g),
@@ -308,6 +347,18 @@ mod test {
         )
     }
 
+    #[test]
+    fn test_config_without_secrets() {
+        let mut config = Config::default();
+        config.client.rpc_token = Some("rpc-secret".into());
+        config.client.remote_signer_token = Some("signer-secret".into());
+        let serialized = config.to_toml_without_secrets().unwrap();
+        assert!(!serialized.contains("secret"));
+        let restored: Config = toml::from_str(&serialized).unwrap();
+        assert_eq!(restored.client.rpc_token, None);
+        assert_eq!(restored.client.data_dir, config.client.data_dir);
+    }
+
     #[test]
     fn test_default_log_filters() {
         let config = LogConfig::default();
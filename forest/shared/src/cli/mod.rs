@@ -67,6 +67,20 @@ pub struct CliOpts {
     /// Address used for RPC. By defaults binds on localhost on port 1234.
     #[arg(long)]
     pub rpc_address: Option<SocketAddr>,
+    /// Path of a Unix socket to additionally serve RPC on. Callers with
+    /// access to the socket are granted admin permissions.
+    #[arg(long)]
+    pub rpc_unix_socket: Option<PathBuf>,
+    /// Address used for the TLS RPC endpoint. Requires `--rpc-tls-cert` and
+    /// `--rpc-tls-key`.
+    #[arg(long)]
+    pub rpc_tls_address: Option<SocketAddr>,
+    /// PEM encoded certificate chain for the TLS RPC endpoint
+    #[arg(long)]
+    pub rpc_tls_cert: Option<PathBuf>,
+    /// PEM encoded private key for the TLS RPC endpoint
+    #[arg(long)]
+    pub rpc_tls_key: Option<PathBuf>,
     /// Allow Kademlia (default: true)
     #[arg(short, long)]
     pub kademlia: Option<bool>,
@@ -166,6 +180,18 @@ impl CliOpts {
             if let Some(rpc_address) = self.rpc_address {
                 cfg.client.rpc_address = rpc_address;
             }
+            if let Some(rpc_unix_socket) = &self.rpc_unix_socket {
+                cfg.client.rpc_unix_socket = Some(rpc_unix_socket.to_owned());
+            }
+            if let Some(rpc_tls_address) = self.rpc_tls_address {
+                cfg.client.rpc_tls_address = Some(rpc_tls_address);
+            }
+            if let Some(rpc_tls_cert) = &self.rpc_tls_cert {
+                cfg.client.rpc_tls_cert_path = Some(rpc_tls_cert.to_owned());
+            }
+            if let Some(rpc_tls_key) = &self.rpc_tls_key {
+                cfg.client.rpc_tls_key_path = Some(rpc_tls_key.to_owned());
+            }
 
             if self.token.is_some() {
                 cfg.client.rpc_token = self.token.to_owned();
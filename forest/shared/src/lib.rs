@@ -4,8 +4,14 @@
 pub mod cli;
 pub mod logger;
 
-use std::path::PathBuf;
+use std::{
+    fs::{self, File, OpenOptions},
+    io,
+    path::PathBuf,
+};
 
+use anyhow::Context;
+use fs2::FileExt;
 #[cfg(feature = "mimalloc")]
 pub use mimalloc;
 #[cfg(feature = "jemalloc")]
@@ -15,3 +21,68 @@ pub use tikv_jemallocator;
 pub fn chain_path(config: &crate::cli::Config) -> PathBuf {
     PathBuf::from(&config.client.data_dir).join(&config.chain.name)
 }
+
+/// File of the chain data directory that the daemon holds a lock on while it
+/// runs.
+const DAEMON_LOCK_FILE: &str = "daemon.lock";
+
+/// Lock of the chain data directory held by the daemon while it runs, released
+/// when dropped or when the daemon exits. Commands changing the files of the
+/// daemon check it to refuse to run alongside it.
+pub struct DaemonLock(File);
+
+impl DaemonLock {
+    /// Takes the lock, failing if another daemon holds it.
+    pub fn acquire(config: &crate::cli::Config) -> anyhow::Result<Self> {
+        let dir = chain_path(config);
+        fs::create_dir_all(&dir)?;
+        let path = dir.join(DAEMON_LOCK_FILE);
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open {}", path.display()))?;
+        file.try_lock_exclusive().with_context(|| {
+            format!(
+                "Failed to lock {}, is another daemon running on {}?",
+                path.display(),
+                dir.display()
+            )
+        })?;
+        Ok(Self(file))
+    }
+
+    /// Whether a daemon holds the lock.
+    pub fn is_held(config: &crate::cli::Config) -> anyhow::Result<bool> {
+        let path = chain_path(config).join(DAEMON_LOCK_FILE);
+        let file = match File::open(&path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(false),
+            Err(e) => return Err(e.into()),
+        };
+        match file.try_lock_shared() {
+            Ok(()) => Ok(false),
+            Err(e) if e.kind() == fs2::lock_contended_error().kind() => Ok(true),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn daemon_lock_is_held_until_dropped() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut config = crate::cli::Config::default();
+        config.client.data_dir = dir.path().to_owned();
+
+        assert!(!DaemonLock::is_held(&config).unwrap());
+        let lock = DaemonLock::acquire(&config).unwrap();
+        assert!(DaemonLock::is_held(&config).unwrap());
+        assert!(DaemonLock::acquire(&config).is_err());
+        drop(lock);
+        assert!(!DaemonLock::is_held(&config).unwrap());
+    }
+}
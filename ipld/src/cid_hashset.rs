@@ -12,4 +12,9 @@ impl CidHashSet {
         let hash = self.0.hasher().hash_one(cid);
         self.0.insert(hash)
     }
+
+    pub fn contains(&self, cid: &Cid) -> bool {
+        let hash = self.0.hasher().hash_one(cid);
+        self.0.contains(&hash)
+    }
 }
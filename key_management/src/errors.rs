@@ -23,6 +23,10 @@ pub enum Error {
     Other(String),
     #[error("Could not convert from KeyInfo to Key")]
     KeyInfoConversion,
+    /// Keys can not be derived because the wallet was not created from a
+    /// mnemonic
+    #[error("Wallet has no mnemonic seed")]
+    NoSeed,
 }
 
 impl From<io::Error> for Error {
@@ -0,0 +1,159 @@
+// Copyright 2019-2023 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Signing of legacy Ethereum transactions with replay protection, as specified
+//! in `EIP-155`, for submission to the FEVM through the Ethereum JSON-RPC API.
+
+use forest_shim::{address::EthAddress, crypto::SignatureType};
+use num_bigint::BigUint;
+use sha3::{Digest, Keccak256};
+
+use super::{errors::Error, wallet_helpers::sign_secp256k1_hash, KeyInfo};
+
+/// Legacy Ethereum transaction, signed with the chain ID to prevent replays on
+/// other chains.
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Eip155Transaction {
+    pub chain_id: u64,
+    pub nonce: u64,
+    pub gas_price: BigUint,
+    pub gas_limit: u64,
+    /// Recipient of the transaction, `None` for contract creations
+    pub to: Option<EthAddress>,
+    pub value: BigUint,
+    pub input: Vec<u8>,
+}
+
+impl Eip155Transaction {
+    /// Hash signed by the sender: the `Keccak-256` hash of the `RLP` encoded
+    /// transaction, followed by the chain ID and two empty values.
+    pub fn signing_hash(&self) -> [u8; 32] {
+        let mut fields = self.fields();
+        fields.extend([
+            rlp_uint(&self.chain_id.into()),
+            rlp_bytes(&[]),
+            rlp_bytes(&[]),
+        ]);
+        Keccak256::digest(rlp_list(&fields)).into()
+    }
+
+    /// Signs the transaction with a delegated or `secp256k1` key. Returns the
+    /// `RLP` encoded signed transaction, ready to be sent with
+    /// `eth_sendRawTransaction`.
+    pub fn sign(&self, key_info: &KeyInfo) -> Result<Vec<u8>, Error> {
+        if !matches!(
+            *key_info.key_type(),
+            SignatureType::Delegated | SignatureType::Secp256k1
+        ) {
+            return Err(Error::Other(
+                "Ethereum transactions can only be signed with secp256k1 keys".to_string(),
+            ));
+        }
+        let sig = sign_secp256k1_hash(key_info.private_key(), &self.signing_hash())?;
+        let v = u64::from(sig[64]) + self.chain_id * 2 + 35;
+
+        let mut fields = self.fields();
+        fields.extend([
+            rlp_uint(&v.into()),
+            rlp_uint(&BigUint::from_bytes_be(&sig[..32])),
+            rlp_uint(&BigUint::from_bytes_be(&sig[32..64])),
+        ]);
+        Ok(rlp_list(&fields))
+    }
+
+    fn fields(&self) -> Vec<Vec<u8>> {
+        vec![
+            rlp_uint(&self.nonce.into()),
+            rlp_uint(&self.gas_price),
+            rlp_uint(&self.gas_limit.into()),
+            rlp_bytes(self.to.as_ref().map_or(&[][..], |to| &to.0[..])),
+            rlp_uint(&self.value),
+            rlp_bytes(&self.input),
+        ]
+    }
+}
+
+/// `RLP` encoding of an integer: its big-endian bytes without leading zeros.
+fn rlp_uint(value: &BigUint) -> Vec<u8> {
+    if value == &BigUint::default() {
+        rlp_bytes(&[])
+    } else {
+        rlp_bytes(&value.to_bytes_be())
+    }
+}
+
+fn rlp_bytes(bytes: &[u8]) -> Vec<u8> {
+    match bytes {
+        [byte] if *byte < 0x80 => vec![*byte],
+        _ => {
+            let mut encoded = rlp_length_prefix(bytes.len(), 0x80);
+            encoded.extend(bytes);
+            encoded
+        }
+    }
+}
+
+/// Encodes a list of already encoded items.
+fn rlp_list(items: &[Vec<u8>]) -> Vec<u8> {
+    let len = items.iter().map(Vec::len).sum();
+    let mut encoded = rlp_length_prefix(len, 0xc0);
+    for item in items {
+        encoded.extend(item);
+    }
+    encoded
+}
+
+fn rlp_length_prefix(len: usize, offset: u8) -> Vec<u8> {
+    if len < 56 {
+        vec![offset + len as u8]
+    } else {
+        let len_bytes: Vec<u8> = len
+            .to_be_bytes()
+            .into_iter()
+            .skip_while(|byte| *byte == 0)
+            .collect();
+        let mut prefix = vec![offset + 55 + len_bytes.len() as u8];
+        prefix.extend(len_bytes);
+        prefix
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{eth_address, to_public};
+
+    #[test]
+    fn eip155_example() {
+        // Example of the `EIP-155` specification
+        let key_info = KeyInfo::new(SignatureType::Delegated, vec![0x46; 32]);
+        let tx = Eip155Transaction {
+            chain_id: 1,
+            nonce: 9,
+            gas_price: 20_000_000_000u64.into(),
+            gas_limit: 21000,
+            to: Some(
+                "0x3535353535353535353535353535353535353535"
+                    .parse()
+                    .unwrap(),
+            ),
+            value: 1_000_000_000_000_000_000u64.into(),
+            input: vec![],
+        };
+
+        assert_eq!(
+            hex::encode(tx.signing_hash()),
+            "daf5a779ae972f972197303d7b574746c7ef83eadac0f2791ad23db92e4c8e53"
+        );
+        assert_eq!(
+            hex::encode(tx.sign(&key_info).unwrap()),
+            "f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83"
+        );
+
+        let public_key = to_public(SignatureType::Delegated, key_info.private_key()).unwrap();
+        assert_eq!(
+            eth_address(&public_key).unwrap().to_string(),
+            "0x9d8a62f656a8d1615c1294fd71e9cfb3e4855a4f"
+        );
+    }
+}
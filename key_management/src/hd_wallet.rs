@@ -0,0 +1,404 @@
+// Copyright 2019-2023 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Hierarchical deterministic keys, derived from a `BIP-39` mnemonic along
+//! `BIP-44` paths.
+//!
+//! `secp256k1` keys follow the `BIP-32` path used by other Filecoin wallets,
+//! `m/44'/461'/0'/0/i`. BLS keys are derived with the `EIP-2333` tree of
+//! BLS12-381 keys, along the `EIP-2334` style path `m/12381/461/0/0/i`.
+
+use bip39::Mnemonic;
+use forest_shim::crypto::SignatureType;
+use hmac::{Hmac, Mac};
+use libsecp256k1::{PublicKey as SecpPublic, SecretKey as SecpPrivate};
+use num_bigint::BigUint;
+use rand::{rngs::OsRng, RngCore};
+use sha2::{Digest, Sha256, Sha512};
+
+use super::{errors::Error, Key, KeyInfo, KeyStore};
+
+/// `KeyStore` entry holding the seed derived from the mnemonic.
+pub const HD_SEED_KEY: &str = "hd-seed";
+
+/// Prefix of the `KeyStore` entries holding the next derivation index of each
+/// key type.
+const HD_INDEX_KEY_PREFIX: &str = "hd-index-";
+
+/// Coin type of Filecoin in `SLIP-44`.
+pub const FILECOIN_COIN_TYPE: u32 = 461;
+
+/// Number of words of generated mnemonics.
+const MNEMONIC_WORD_COUNT: usize = 24;
+
+const HARDENED: u32 = 1 << 31;
+
+/// Order of the BLS12-381 scalar field, big-endian.
+const BLS12_381_R: [u8; 32] = [
+    0x73, 0xed, 0xa7, 0x53, 0x29, 0x9d, 0x7d, 0x48, 0x33, 0x39, 0xd8, 0x08, 0x09, 0xa1, 0xd8, 0x05,
+    0x53, 0xbd, 0xa4, 0x02, 0xff, 0xfe, 0x5b, 0xfe, 0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x01,
+];
+
+/// A key derived from the wallet seed.
+#[derive(Clone, PartialEq, Debug, Eq)]
+pub struct DerivedKey {
+    pub key: Key,
+    pub index: u32,
+    pub path: String,
+}
+
+/// Generates a new random 24 words mnemonic.
+pub fn generate_mnemonic() -> Result<Mnemonic, Error> {
+    let mut entropy = [0; MNEMONIC_WORD_COUNT / 3 * 4];
+    OsRng.fill_bytes(&mut entropy);
+    Mnemonic::from_entropy(&entropy).map_err(|err| Error::Other(err.to_string()))
+}
+
+/// Parses a mnemonic phrase, validating its checksum.
+pub fn parse_mnemonic(phrase: &str) -> Result<Mnemonic, Error> {
+    Mnemonic::parse(phrase).map_err(|err| Error::Other(format!("Invalid mnemonic: {err}")))
+}
+
+/// Returns whether the `KeyStore` holds a wallet seed.
+pub fn has_hd_seed(keystore: &KeyStore) -> bool {
+    keystore.get(HD_SEED_KEY).is_ok()
+}
+
+/// Stores the seed of the mnemonic in the `KeyStore`. Fails with
+/// [`Error::KeyExists`] if the `KeyStore` already holds a seed.
+pub fn init_hd_seed(
+    keystore: &mut KeyStore,
+    mnemonic: &Mnemonic,
+    passphrase: &str,
+) -> Result<(), Error> {
+    let seed = mnemonic.to_seed(passphrase);
+    // The key type is irrelevant for the seed, it is never used as a key
+    keystore.put(
+        HD_SEED_KEY.to_string(),
+        KeyInfo::new(SignatureType::Secp256k1, seed.to_vec()),
+    )
+}
+
+/// Derives the key at the next unused index for the key type, and adds it to
+/// the `KeyStore`.
+pub fn derive_next_key(keystore: &mut KeyStore, typ: SignatureType) -> Result<DerivedKey, Error> {
+    let index = next_index(keystore, typ)?;
+    derive_key(keystore, typ, index)
+}
+
+/// Derives the key at the given index for the key type, and adds it to the
+/// `KeyStore` if it is not there yet.
+pub fn derive_key(
+    keystore: &mut KeyStore,
+    typ: SignatureType,
+    index: u32,
+) -> Result<DerivedKey, Error> {
+    let seed = keystore.get(HD_SEED_KEY).map_err(|_| Error::NoSeed)?;
+    let key = derive_key_from_seed(seed.private_key(), typ, index)?;
+
+    match keystore.put(format!("wallet-{}", key.address), key.key_info.clone()) {
+        Ok(()) | Err(Error::KeyExists) => {}
+        Err(err) => return Err(err),
+    }
+    if index >= next_index(keystore, typ)? {
+        set_next_index(keystore, typ, index + 1)?;
+    }
+
+    Ok(DerivedKey {
+        key,
+        index,
+        path: derivation_path(typ, index)?,
+    })
+}
+
+/// Derives the key at the given index for the key type from a seed.
+pub fn derive_key_from_seed(seed: &[u8], typ: SignatureType, index: u32) -> Result<Key, Error> {
+    let path = path_indexes(typ, index)?;
+    let private_key = match typ {
+        SignatureType::Secp256k1 => ExtendedKey::derive(seed, &path)?.key.serialize().to_vec(),
+        SignatureType::BLS => {
+            // Filecoin BLS private keys are little-endian scalars
+            let mut secret = eip2333::derive(seed, &path)?.to_bytes_le();
+            secret.resize(32, 0);
+            secret
+        }
+        SignatureType::Delegated => unreachable!("rejected by path_indexes"),
+    };
+    Key::try_from(KeyInfo::new(typ, private_key))
+}
+
+/// Returns the derivation path of the key at the given index for the key type.
+pub fn derivation_path(typ: SignatureType, index: u32) -> Result<String, Error> {
+    let path: Vec<_> = path_indexes(typ, index)?
+        .into_iter()
+        .map(|i| {
+            if i >= HARDENED {
+                format!("{}'", i - HARDENED)
+            } else {
+                i.to_string()
+            }
+        })
+        .collect();
+    Ok(format!("m/{}", path.join("/")))
+}
+
+fn path_indexes(typ: SignatureType, index: u32) -> Result<Vec<u32>, Error> {
+    if index >= HARDENED {
+        return Err(Error::Other(format!(
+            "Derivation index must be lower than {HARDENED}"
+        )));
+    }
+    match typ {
+        SignatureType::Secp256k1 => Ok(vec![
+            44 | HARDENED,
+            FILECOIN_COIN_TYPE | HARDENED,
+            HARDENED,
+            0,
+            index,
+        ]),
+        // All EIP-2333 derivations are hardened
+        SignatureType::BLS => Ok(vec![12381, FILECOIN_COIN_TYPE, 0, 0, index]),
+        SignatureType::Delegated => Err(Error::Other(
+            "Delegated keys can not be derived".to_string(),
+        )),
+    }
+}
+
+fn index_key(typ: SignatureType) -> String {
+    let name = match typ {
+        SignatureType::Secp256k1 => "secp256k1",
+        SignatureType::BLS => "bls",
+        SignatureType::Delegated => "delegated",
+    };
+    format!("{HD_INDEX_KEY_PREFIX}{name}")
+}
+
+fn next_index(keystore: &KeyStore, typ: SignatureType) -> Result<u32, Error> {
+    match keystore.get(&index_key(typ)) {
+        Ok(key_info) => Ok(u32::from_be_bytes(
+            key_info
+                .private_key()
+                .as_slice()
+                .try_into()
+                .map_err(|_| Error::Other("Invalid derivation index".to_string()))?,
+        )),
+        Err(_) => Ok(0),
+    }
+}
+
+fn set_next_index(keystore: &mut KeyStore, typ: SignatureType, index: u32) -> Result<(), Error> {
+    let key = index_key(typ);
+    if keystore.get(&key).is_ok() {
+        keystore
+            .remove(key.clone())
+            .map_err(|err| Error::Other(err.to_string()))?;
+    }
+    keystore.put(key, KeyInfo::new(typ, index.to_be_bytes().to_vec()))
+}
+
+/// `BIP-32` extended private key on `secp256k1`.
+struct ExtendedKey {
+    key: SecpPrivate,
+    chain_code: [u8; 32],
+}
+
+impl ExtendedKey {
+    fn derive(seed: &[u8], path: &[u32]) -> Result<Self, Error> {
+        let mut key = Self::from_hmac(hmac_sha512(b"Bitcoin seed", &[seed])?)?;
+        for &index in path {
+            key = key.child(index)?;
+        }
+        Ok(key)
+    }
+
+    fn child(&self, index: u32) -> Result<Self, Error> {
+        let index_bytes = index.to_be_bytes();
+        let hash = if index >= HARDENED {
+            hmac_sha512(
+                &self.chain_code,
+                &[&[0], &self.key.serialize(), &index_bytes],
+            )?
+        } else {
+            let public_key = SecpPublic::from_secret_key(&self.key).serialize_compressed();
+            hmac_sha512(&self.chain_code, &[&public_key, &index_bytes])?
+        };
+        let mut child = Self::from_hmac(hash)?;
+        child
+            .key
+            .tweak_add_assign(&self.key)
+            .map_err(|err| Error::Other(err.to_string()))?;
+        Ok(child)
+    }
+
+    fn from_hmac(hash: [u8; 64]) -> Result<Self, Error> {
+        let (key, chain_code) = hash.split_at(32);
+        Ok(Self {
+            key: SecpPrivate::parse_slice(key).map_err(|err| Error::Other(err.to_string()))?,
+            chain_code: chain_code.try_into().expect("Infallible"),
+        })
+    }
+}
+
+/// `EIP-2333` derivation of BLS12-381 keys.
+mod eip2333 {
+    use super::*;
+
+    /// Derives the secret key at the path from the seed.
+    pub(super) fn derive(seed: &[u8], path: &[u32]) -> Result<BigUint, Error> {
+        if seed.len() < 32 {
+            return Err(Error::Other("Seed must be at least 32 bytes".to_string()));
+        }
+        let mut secret = hkdf_mod_r(seed)?;
+        for &index in path {
+            secret = derive_child(&secret, index)?;
+        }
+        Ok(secret)
+    }
+
+    fn derive_child(parent: &BigUint, index: u32) -> Result<BigUint, Error> {
+        hkdf_mod_r(&parent_to_lamport_public_key(parent, index)?)
+    }
+
+    fn parent_to_lamport_public_key(parent: &BigUint, index: u32) -> Result<[u8; 32], Error> {
+        let salt = index.to_be_bytes();
+        let secret = parent.to_bytes_be();
+        let mut ikm = vec![0; 32 - secret.len()];
+        ikm.extend(secret);
+        let not_ikm: Vec<u8> = ikm.iter().map(|b| !b).collect();
+
+        let mut public_key = Sha256::new();
+        for ikm in [&ikm, &not_ikm] {
+            let prk = hmac_sha256(&salt, &[ikm.as_slice()])?;
+            for chunk in hkdf_expand(&prk, &[], 255 * 32)?.chunks(32) {
+                public_key.update(Sha256::digest(chunk));
+            }
+        }
+        Ok(public_key.finalize().into())
+    }
+
+    /// `KeyGen` of the BLS signature draft 4, with an empty `key_info`.
+    fn hkdf_mod_r(ikm: &[u8]) -> Result<BigUint, Error> {
+        const L: usize = 48;
+        let r = BigUint::from_bytes_be(&BLS12_381_R);
+        let mut salt = Sha256::digest(b"BLS-SIG-KEYGEN-SALT-");
+        loop {
+            let prk = hmac_sha256(&salt, &[ikm, &[0]])?;
+            let okm = hkdf_expand(&prk, &(L as u16).to_be_bytes(), L)?;
+            let secret = BigUint::from_bytes_be(&okm) % &r;
+            if secret != BigUint::default() {
+                return Ok(secret);
+            }
+            salt = Sha256::digest(salt);
+        }
+    }
+
+    fn hkdf_expand(prk: &[u8], info: &[u8], len: usize) -> Result<Vec<u8>, Error> {
+        let mut okm = Vec::with_capacity(len);
+        let mut block = vec![];
+        for i in 1..=(len + 31) / 32 {
+            block = hmac_sha256(prk, &[&block, info, &[i as u8]])?.to_vec();
+            okm.extend(&block);
+        }
+        okm.truncate(len);
+        Ok(okm)
+    }
+
+    fn hmac_sha256(key: &[u8], data: &[&[u8]]) -> Result<[u8; 32], Error> {
+        let mut mac =
+            Hmac::<Sha256>::new_from_slice(key).map_err(|err| Error::Other(err.to_string()))?;
+        for chunk in data {
+            mac.update(chunk);
+        }
+        Ok(mac.finalize().into_bytes().into())
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn eip2333_test_vector() {
+            // Test case 0 of EIP-2333
+            let seed = hex::decode("c55257c360c07c72029aebc1b53c05ed0362ada38ead3e3e9efa3708e53495531f09a6987599d18264c1e1c92f2cf141630c7a3c4ab7c81b2f001698e7463b04").unwrap();
+            let master = derive(&seed, &[]).unwrap();
+            assert_eq!(
+                master.to_string(),
+                "6083874454709270928345386274498605044986640685124978867557563392430687146096"
+            );
+            assert_eq!(
+                derive_child(&master, 0).unwrap().to_string(),
+                "20397789859736650942317412262472558107875392172444076792671091975210932703118"
+            );
+        }
+    }
+}
+
+fn hmac_sha512(key: &[u8], data: &[&[u8]]) -> Result<[u8; 64], Error> {
+    let mut mac =
+        Hmac::<Sha512>::new_from_slice(key).map_err(|err| Error::Other(err.to_string()))?;
+    for chunk in data {
+        mac.update(chunk);
+    }
+    let mut hash = [0; 64];
+    hash.copy_from_slice(&mac.finalize().into_bytes());
+    Ok(hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::KeyStoreConfig;
+
+    #[test]
+    fn bip32_test_vector() {
+        // Test vector 1 of BIP-32, chain m/0'/1
+        let seed = hex::decode("000102030405060708090a0b0c0d0e0f").unwrap();
+        let key = ExtendedKey::derive(&seed, &[HARDENED, 1]).unwrap();
+        assert_eq!(
+            hex::encode(key.key.serialize()),
+            "3c6cb8d0f6a264c91ea8b5030fadaa8e538b020f0a387421a12de9319dc93368"
+        );
+        assert_eq!(
+            hex::encode(key.chain_code),
+            "2a7857631386ba23dacac34180dd1983734e444fdbf774041578e9b6adb37c19"
+        );
+    }
+
+    #[test]
+    fn restore_derives_same_keys() {
+        let mnemonic = generate_mnemonic().unwrap();
+        assert_eq!(mnemonic.word_count(), MNEMONIC_WORD_COUNT);
+
+        let mut keystore = KeyStore::new(KeyStoreConfig::Memory).unwrap();
+        init_hd_seed(&mut keystore, &mnemonic, "").unwrap();
+        let secp = derive_next_key(&mut keystore, SignatureType::Secp256k1).unwrap();
+        let bls = derive_next_key(&mut keystore, SignatureType::BLS).unwrap();
+        let second = derive_next_key(&mut keystore, SignatureType::Secp256k1).unwrap();
+        assert_eq!((secp.index, bls.index, second.index), (0, 0, 1));
+        assert_eq!(secp.path, "m/44'/461'/0'/0/0");
+        assert_eq!(bls.path, "m/12381/461/0/0/0");
+
+        let mut restored = KeyStore::new(KeyStoreConfig::Memory).unwrap();
+        let phrase = mnemonic.to_string();
+        init_hd_seed(&mut restored, &parse_mnemonic(&phrase).unwrap(), "").unwrap();
+        assert_eq!(
+            derive_key(&mut restored, SignatureType::Secp256k1, 1)
+                .unwrap()
+                .key,
+            second.key
+        );
+        assert_eq!(
+            derive_key(&mut restored, SignatureType::BLS, 0)
+                .unwrap()
+                .key,
+            bls.key
+        );
+        // Keys derived at explicit indexes are not derived again
+        assert_eq!(
+            derive_next_key(&mut restored, SignatureType::Secp256k1)
+                .unwrap()
+                .index,
+            2
+        );
+    }
+}
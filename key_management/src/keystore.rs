@@ -6,6 +6,7 @@ use std::{
     fs::{self, create_dir, File},
     io::{BufReader, BufWriter, ErrorKind, Read, Write},
     path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
 };
 
 use ahash::{HashMap, HashMapExt};
@@ -28,6 +29,12 @@ use super::errors::Error;
 pub const KEYSTORE_NAME: &str = "keystore.json";
 pub const ENCRYPTED_KEYSTORE_NAME: &str = "keystore";
 
+/// Extension of the copy of the keystore kept when changing its passphrase.
+pub const KEYSTORE_BACKUP_EXTENSION: &str = "bak";
+
+/// Header of keystore exports, followed by the salt and the encrypted keys.
+const EXPORT_MAGIC: &[u8] = b"forest-keystore-export-v1";
+
 /// Environmental variable which holds the keystore encryption phrase.
 pub const FOREST_KEYSTORE_PHRASE_ENV: &str = "FOREST_KEYSTORE_PHRASE";
 
@@ -329,6 +336,8 @@ impl KeyStore {
         }
     }
 
+    /// Writes the keys to the keystore file. The file is replaced atomically,
+    /// so that it is never left half written.
     pub fn flush(&self) -> anyhow::Result<()> {
         match &self.persistence {
             Some(persistent_keystore) => {
@@ -337,7 +346,8 @@ impl KeyStore {
                     .parent()
                     .ok_or_else(|| Error::Other("Invalid Path".to_string()))?;
                 fs::create_dir_all(dir)?;
-                let file = File::create(&persistent_keystore.file_path)?;
+                let tmp_path = persistent_keystore.file_path.with_extension("tmp");
+                let file = File::create(&tmp_path)?;
 
                 // Restrict permissions on files containing private keys
                 #[cfg(unix)]
@@ -357,8 +367,7 @@ impl KeyStore {
                         let mut salt_vec = encrypted_keystore.salt.to_vec();
                         salt_vec.extend(encrypted_data);
                         writer.write_all(&salt_vec)?;
-
-                        Ok(())
+                        writer.into_inner()?.sync_all()?;
                     }
                     None => {
                         let mut key_info: HashMap<String, PersistentKeyInfo> = HashMap::new();
@@ -373,13 +382,15 @@ impl KeyStore {
                         }
 
                         // Flush for PersistentKeyStore
-                        serde_json::to_writer_pretty(writer, &key_info).map_err(|e| {
+                        serde_json::to_writer_pretty(&mut writer, &key_info).map_err(|e| {
                             Error::Other(format!("failed to serialize and write key info: {e}"))
                         })?;
-
-                        Ok(())
+                        writer.into_inner()?.sync_all()?;
                     }
                 }
+                fs::rename(&tmp_path, &persistent_keystore.file_path)?;
+
+                Ok(())
             }
             None => {
                 // NoOp for MemKeyStore
@@ -388,6 +399,11 @@ impl KeyStore {
         }
     }
 
+    /// Path of the keystore file, `None` for a keystore held in memory.
+    pub fn file_path(&self) -> Option<&Path> {
+        self.persistence.as_ref().map(|p| p.file_path.as_path())
+    }
+
     /// Return all of the keys that are stored in the `KeyStore`
     pub fn list(&self) -> Vec<String> {
         self.key_info.keys().cloned().collect()
@@ -422,6 +438,100 @@ impl KeyStore {
 
         Ok(key_out)
     }
+
+    /// Re-encrypts the keystore with a new passphrase. The keystore file is
+    /// first copied next to itself, suffixed with the current time and the
+    /// [`KEYSTORE_BACKUP_EXTENSION`], and the path of this backup is returned.
+    /// Existing backups are never overwritten.
+    pub fn change_passphrase(&mut self, passphrase: &str) -> anyhow::Result<PathBuf> {
+        let (Some(persistence), Some(_)) = (&self.persistence, &self.encryption) else {
+            anyhow::bail!(EncryptedKeyStoreError::ConfigurationError);
+        };
+        let timestamp = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs();
+        let backup_path = persistence
+            .file_path
+            .with_extension(format!("{timestamp}.{KEYSTORE_BACKUP_EXTENSION}"));
+        anyhow::ensure!(
+            !backup_path.exists(),
+            "Keystore backup {} already exists",
+            backup_path.display()
+        );
+        fs::copy(&persistence.file_path, &backup_path)?;
+
+        let (salt, encryption_key) = EncryptedKeyStore::derive_key(passphrase, None)?;
+        let previous = self.encryption.replace(EncryptedKeyStore {
+            salt,
+            encryption_key,
+        });
+        if let Err(e) = self.flush() {
+            self.encryption = previous;
+            return Err(e);
+        }
+        Ok(backup_path)
+    }
+
+    /// Exports all the keys to an archive encrypted with the passphrase. The
+    /// keys are stored in the `JSON` format of Lotus, so that they can be read
+    /// back regardless of how the keystore is persisted.
+    pub fn export(&self, passphrase: &str) -> anyhow::Result<Vec<u8>> {
+        let keys: HashMap<&String, json::KeyInfoJsonRef> = self
+            .key_info
+            .iter()
+            .map(|(name, key_info)| (name, json::KeyInfoJsonRef(key_info)))
+            .collect();
+        let (salt, encryption_key) = EncryptedKeyStore::derive_key(passphrase, None)?;
+        let encrypted_data =
+            EncryptedKeyStore::encrypt(&encryption_key, &serde_json::to_vec(&keys)?)?;
+
+        let mut archive = EXPORT_MAGIC.to_vec();
+        archive.extend(salt);
+        archive.extend(encrypted_data);
+        Ok(archive)
+    }
+
+    /// Reads the keys of an archive created by [`KeyStore::export`].
+    pub fn read_export(
+        archive: &[u8],
+        passphrase: &str,
+    ) -> anyhow::Result<HashMap<String, KeyInfo>> {
+        let data = archive
+            .strip_prefix(EXPORT_MAGIC)
+            .ok_or_else(|| anyhow::anyhow!("Not a keystore export"))?;
+        anyhow::ensure!(
+            data.len() > RECOMMENDED_SALT_LEN + NONCE_SIZE,
+            "Truncated keystore export"
+        );
+        let (salt, encrypted_data) = data.split_at(RECOMMENDED_SALT_LEN);
+        let (_, encryption_key) =
+            EncryptedKeyStore::derive_key(passphrase, Some(salt.try_into()?))?;
+        let data = EncryptedKeyStore::decrypt(&encryption_key, encrypted_data)
+            .map_err(|_| EncryptedKeyStoreError::DecryptionError)?;
+        let keys: HashMap<String, json::KeyInfoJson> = serde_json::from_slice(&data)?;
+        Ok(keys
+            .into_iter()
+            .map(|(name, key_info)| (name, key_info.0))
+            .collect())
+    }
+
+    /// Imports the keys of an archive created by [`KeyStore::export`], skipping
+    /// the ones already in the keystore. Returns the names of the imported
+    /// keys.
+    pub fn import(&mut self, archive: &[u8], passphrase: &str) -> anyhow::Result<Vec<String>> {
+        let mut imported = vec![];
+        for (name, key_info) in Self::read_export(archive, passphrase)? {
+            if self.key_info.contains_key(&name) {
+                warn!("Key {name} already in the keystore, skipping it");
+                continue;
+            }
+            self.key_info.insert(name.clone(), key_info);
+            imported.push(name);
+        }
+        if !imported.is_empty() {
+            self.flush()?;
+        }
+        imported.sort();
+        Ok(imported)
+    }
 }
 
 impl EncryptedKeyStore {
@@ -606,6 +716,60 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_change_passphrase() -> Result<()> {
+        let keystore_location = tempfile::tempdir()?.into_path();
+        let config = |passphrase: &str| {
+            KeyStoreConfig::Encrypted(keystore_location.clone(), passphrase.to_string())
+        };
+        let mut ks = KeyStore::new(config(PASSPHRASE))?;
+        let key = wallet::generate_key(SignatureType::Secp256k1)?;
+        ks.put(format!("wallet-{}", key.address), key.key_info)?;
+
+        let backup_path = ks.change_passphrase("new passphrase")?;
+        ensure!(KeyStore::new(config(PASSPHRASE)).is_err());
+        ensure!(KeyStore::new(config("new passphrase"))? == ks);
+
+        // The backup can still be opened with the previous passphrase
+        let backup_location = tempfile::tempdir()?.into_path();
+        fs::copy(backup_path, backup_location.join(ENCRYPTED_KEYSTORE_NAME))?;
+        let backup = KeyStore::new(KeyStoreConfig::Encrypted(
+            backup_location,
+            PASSPHRASE.to_string(),
+        ))?;
+        ensure!(backup.key_info == ks.key_info);
+        Ok(())
+    }
+
+    #[test]
+    fn test_export_roundtrip() -> Result<()> {
+        let mut ks = KeyStore::new(KeyStoreConfig::Memory)?;
+        let key = wallet::generate_key(SignatureType::BLS)?;
+        ks.put(format!("wallet-{}", key.address), key.key_info)?;
+
+        let archive = ks.export(PASSPHRASE)?;
+        ensure!(KeyStore::read_export(&archive, "wrong passphrase").is_err());
+        ensure!(KeyStore::read_export(&archive, PASSPHRASE)? == ks.key_info);
+        Ok(())
+    }
+
+    #[test]
+    fn test_import() -> Result<()> {
+        let mut ks = KeyStore::new(KeyStoreConfig::Memory)?;
+        let key = wallet::generate_key(SignatureType::Secp256k1)?;
+        let name = format!("wallet-{}", key.address);
+        ks.put(name.clone(), key.key_info)?;
+        let archive = ks.export(PASSPHRASE)?;
+
+        let mut other = KeyStore::new(KeyStoreConfig::Memory)?;
+        ensure!(other.import(&archive, "wrong passphrase").is_err());
+        ensure!(other.import(&archive, PASSPHRASE)? == vec![name]);
+        ensure!(other.key_info == ks.key_info);
+        // Importing again is a no-op
+        ensure!(other.import(&archive, PASSPHRASE)?.is_empty());
+        Ok(())
+    }
+
     impl quickcheck::Arbitrary for KeyInfo {
         fn arbitrary(g: &mut quickcheck::Gen) -> Self {
             let sigtype = g
@@ -2,11 +2,19 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 mod errors;
+mod eth_tx;
+mod hd_wallet;
 mod keystore;
+mod lotus_keystore;
+mod signer;
 mod wallet;
 mod wallet_helpers;
 
 pub use errors::*;
+pub use eth_tx::*;
+pub use hd_wallet::*;
 pub use keystore::*;
+pub use lotus_keystore::*;
+pub use signer::*;
 pub use wallet::*;
 pub use wallet_helpers::*;
@@ -0,0 +1,115 @@
+// Copyright 2019-2023 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Import of wallet keys from a Lotus keystore directory. Each key is stored in
+//! a file of its own, named after the unpadded base32 encoding of the key name,
+//! and holding the `JSON` encoded `KeyInfo`.
+
+use std::{fs, path::Path};
+
+use forest_shim::address::Address;
+use log::{debug, warn};
+use multibase::Base;
+
+use super::{json::KeyInfoJson, Error, Key, KeyStore};
+
+/// Name of the keystore directory in a Lotus repository.
+pub const LOTUS_KEYSTORE_DIR: &str = "keystore";
+
+/// Prefix of the names of wallet keys, in both Lotus and Forest keystores.
+const WALLET_KEY_PREFIX: &str = "wallet-";
+
+/// Imports the wallet keys of a Lotus keystore, given either the Lotus
+/// repository or its keystore directory. Other keys, such as the `libp2p`
+/// identity or the `JWT` secret, are skipped. The default key is imported
+/// only if the keystore has none yet. Returns the addresses of the imported
+/// keys, skipping the ones already in the keystore.
+pub fn import_lotus_keystore(path: &Path, keystore: &mut KeyStore) -> anyhow::Result<Vec<Address>> {
+    let dir = if path.join(LOTUS_KEYSTORE_DIR).is_dir() {
+        path.join(LOTUS_KEYSTORE_DIR)
+    } else {
+        path.to_path_buf()
+    };
+
+    let mut imported = vec![];
+    let mut default = None;
+    for entry in fs::read_dir(&dir)? {
+        let entry = entry?;
+        let file_name = entry.file_name();
+        let Some(name) = file_name
+            .to_str()
+            .and_then(|name| Base::Base32Upper.decode(name).ok())
+            .and_then(|name| String::from_utf8(name).ok())
+        else {
+            debug!("Skipping {}, not a Lotus key file", entry.path().display());
+            continue;
+        };
+        if !name.starts_with(WALLET_KEY_PREFIX) && name != "default" {
+            continue;
+        }
+
+        let KeyInfoJson(key_info) = serde_json::from_slice(&fs::read(entry.path())?)?;
+        let key = match Key::try_from(key_info) {
+            Ok(key) => key,
+            Err(e) => {
+                warn!("Skipping invalid Lotus key {name}: {e}");
+                continue;
+            }
+        };
+        if name == "default" {
+            default = Some(key);
+            continue;
+        }
+        match keystore.put(format!("{WALLET_KEY_PREFIX}{}", key.address), key.key_info) {
+            Ok(()) => imported.push(key.address),
+            Err(Error::KeyExists) => debug!("Key {} already in the keystore", key.address),
+            Err(e) => return Err(e.into()),
+        }
+    }
+
+    if let Some(key) = default {
+        if keystore.get("default").is_err() {
+            keystore.put("default".to_string(), key.key_info)?;
+        }
+    }
+    Ok(imported)
+}
+
+#[cfg(test)]
+mod tests {
+    use forest_shim::crypto::SignatureType;
+
+    use super::*;
+    use crate::{generate_key, get_default, json::KeyInfoJsonRef, KeyStoreConfig};
+
+    fn write_lotus_key(dir: &Path, name: &str, contents: &[u8]) {
+        fs::write(dir.join(Base::Base32Upper.encode(name)), contents).unwrap();
+    }
+
+    #[test]
+    fn import_wallet_keys() {
+        let repo = tempfile::tempdir().unwrap();
+        let dir = repo.path().join(LOTUS_KEYSTORE_DIR);
+        fs::create_dir(&dir).unwrap();
+
+        let key = generate_key(SignatureType::BLS).unwrap();
+        let key_json = serde_json::to_vec(&KeyInfoJsonRef(&key.key_info)).unwrap();
+        write_lotus_key(&dir, &format!("wallet-{}", key.address), &key_json);
+        write_lotus_key(&dir, "default", &key_json);
+        write_lotus_key(
+            &dir,
+            "libp2p-host",
+            br#"{"Type":"libp2p-host","PrivateKey":""}"#,
+        );
+
+        let mut keystore = KeyStore::new(KeyStoreConfig::Memory).unwrap();
+        let imported = import_lotus_keystore(repo.path(), &mut keystore).unwrap();
+        assert_eq!(imported, vec![key.address]);
+        assert_eq!(get_default(&keystore).unwrap(), key.address);
+
+        // Importing again is a no-op
+        assert!(import_lotus_keystore(&dir, &mut keystore)
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -0,0 +1,237 @@
+// Copyright 2019-2023 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Signing of messages on behalf of the wallet, either with the keys of the
+//! local [`KeyStore`] or by a remote signing service.
+//!
+//! The remote service speaks `JSON-RPC` 2.0 over HTTP or a Unix socket, and
+//! implements three methods:
+//! - `WalletSign`: `[address, base64 message]` to a signature
+//! - `WalletList`: `[]` to the list of addresses it holds keys for
+//! - `WalletHas`: `[address]` to whether it holds the key of the address
+
+use std::{path::PathBuf, sync::Arc};
+
+use anyhow::Context;
+use async_trait::async_trait;
+use base64::{prelude::BASE64_STANDARD, Engine};
+use forest_json::signature::json::SignatureJson;
+use forest_shim::{address::Address, crypto::Signature};
+use forest_utils::net::{
+    https_client,
+    hyper::{self, http::HeaderValue},
+    HyperBodyExt,
+};
+use log::debug;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tokio::sync::RwLock;
+
+use super::{find_key, list_addrs, try_find, wallet_helpers, Key, KeyStore};
+
+pub const REMOTE_WALLET_SIGN: &str = "WalletSign";
+pub const REMOTE_WALLET_LIST: &str = "WalletList";
+pub const REMOTE_WALLET_HAS: &str = "WalletHas";
+
+/// Prefix of remote signer URLs pointing at a Unix socket.
+const UNIX_SCHEME: &str = "unix://";
+
+/// Holder of the keys of the wallet, signing messages on its behalf.
+#[async_trait]
+pub trait Signer: Send + Sync {
+    /// Signs the message with the key of the address.
+    async fn sign(&self, addr: &Address, msg: &[u8]) -> anyhow::Result<Signature>;
+
+    /// Lists the addresses the signer holds keys for.
+    async fn list(&self) -> anyhow::Result<Vec<Address>>;
+
+    /// Returns whether the signer holds the key of the address.
+    async fn has(&self, addr: &Address) -> anyhow::Result<bool>;
+}
+
+/// Signs with the keys of the local [`KeyStore`].
+pub struct KeyStoreSigner {
+    keystore: Arc<RwLock<KeyStore>>,
+}
+
+impl KeyStoreSigner {
+    pub fn new(keystore: Arc<RwLock<KeyStore>>) -> Self {
+        Self { keystore }
+    }
+}
+
+#[async_trait]
+impl Signer for KeyStoreSigner {
+    async fn sign(&self, addr: &Address, msg: &[u8]) -> anyhow::Result<Signature> {
+        let keystore = &mut *self.keystore.write().await;
+        let key = match find_key(addr, keystore) {
+            Ok(key) => key,
+            Err(_) => Key::try_from(try_find(addr, keystore)?)?,
+        };
+        Ok(wallet_helpers::sign(
+            *key.key_info.key_type(),
+            key.key_info.private_key(),
+            msg,
+        )?)
+    }
+
+    async fn list(&self) -> anyhow::Result<Vec<Address>> {
+        Ok(list_addrs(&*self.keystore.read().await)?)
+    }
+
+    async fn has(&self, addr: &Address) -> anyhow::Result<bool> {
+        Ok(find_key(addr, &*self.keystore.read().await).is_ok())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum RemoteEndpoint {
+    Http(String),
+    Unix(PathBuf),
+}
+
+/// Signs with the keys held by a remote signing service.
+pub struct RemoteSigner {
+    endpoint: RemoteEndpoint,
+    token: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct RemoteError {
+    code: i64,
+    message: String,
+}
+
+#[derive(Deserialize)]
+struct RemoteResponse<R> {
+    result: Option<R>,
+    error: Option<RemoteError>,
+}
+
+impl RemoteSigner {
+    /// Creates a signer for the service at the given URL, either
+    /// `http(s)://host:port/path` or `unix:///path/to/socket`. The token, if
+    /// any, is sent as the `Authorization` header of each request.
+    pub fn new(url: &str, token: Option<String>) -> anyhow::Result<Self> {
+        let endpoint = if let Some(path) = url.strip_prefix(UNIX_SCHEME) {
+            RemoteEndpoint::Unix(PathBuf::from(path))
+        } else if url.starts_with("http://") || url.starts_with("https://") {
+            RemoteEndpoint::Http(url.to_owned())
+        } else {
+            anyhow::bail!("Unsupported remote signer URL: {url}");
+        };
+        Ok(Self { endpoint, token })
+    }
+
+    async fn call<P, R>(&self, method: &str, params: P) -> anyhow::Result<R>
+    where
+        P: Serialize,
+        R: DeserializeOwned,
+    {
+        let body = serde_json::json!({
+            "jsonrpc": "2.0",
+            "method": method,
+            "params": params,
+            "id": 1,
+        });
+        let uri = match &self.endpoint {
+            RemoteEndpoint::Http(url) => url.as_str(),
+            RemoteEndpoint::Unix(_) => "/",
+        };
+        let mut request = hyper::Request::post(uri).body(serde_json::to_string(&body)?.into())?;
+        let headers = request.headers_mut();
+        headers.insert("content-type", HeaderValue::from_static("application/json"));
+        if let Some(token) = &self.token {
+            headers.insert("Authorization", HeaderValue::from_str(token)?);
+        }
+
+        let response = match &self.endpoint {
+            RemoteEndpoint::Http(_) => https_client().request(request).await?,
+            RemoteEndpoint::Unix(path) => {
+                headers.insert("host", HeaderValue::from_static("localhost"));
+                let stream = tokio::net::UnixStream::connect(path)
+                    .await
+                    .with_context(|| format!("Failed to connect to {}", path.display()))?;
+                let (mut sender, connection) = hyper::client::conn::handshake(stream).await?;
+                tokio::spawn(async move {
+                    if let Err(e) = connection.await {
+                        debug!("Remote signer connection closed with error: {e}");
+                    }
+                });
+                sender.send_request(request).await?
+            }
+        };
+        let status = response.status();
+        if !status.is_success() {
+            anyhow::bail!("Remote signer responded to {method} with HTTP status {status}");
+        }
+
+        let response: RemoteResponse<R> = response.into_body().json().await?;
+        match (response.result, response.error) {
+            (_, Some(RemoteError { code, message })) => {
+                anyhow::bail!("Remote signer failed {method} ({code}): {message}")
+            }
+            (Some(result), None) => Ok(result),
+            (None, None) => anyhow::bail!("Remote signer returned no result for {method}"),
+        }
+    }
+}
+
+#[async_trait]
+impl Signer for RemoteSigner {
+    async fn sign(&self, addr: &Address, msg: &[u8]) -> anyhow::Result<Signature> {
+        let SignatureJson(signature) = self
+            .call(
+                REMOTE_WALLET_SIGN,
+                (addr.to_string(), BASE64_STANDARD.encode(msg)),
+            )
+            .await?;
+        Ok(signature)
+    }
+
+    async fn list(&self) -> anyhow::Result<Vec<Address>> {
+        let addrs: Vec<String> = self.call(REMOTE_WALLET_LIST, Vec::<()>::new()).await?;
+        addrs.iter().map(|addr| Ok(addr.parse()?)).collect()
+    }
+
+    async fn has(&self, addr: &Address) -> anyhow::Result<bool> {
+        self.call(REMOTE_WALLET_HAS, (addr.to_string(),)).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use forest_shim::crypto::SignatureType;
+
+    use super::*;
+    use crate::{generate_key, KeyStoreConfig};
+
+    #[tokio::test]
+    async fn keystore_signer() {
+        let key = generate_key(SignatureType::Secp256k1).unwrap();
+        let mut keystore = KeyStore::new(KeyStoreConfig::Memory).unwrap();
+        keystore
+            .put(format!("wallet-{}", key.address), key.key_info.clone())
+            .unwrap();
+        let signer = KeyStoreSigner::new(Arc::new(RwLock::new(keystore)));
+
+        assert_eq!(signer.list().await.unwrap(), vec![key.address]);
+        assert!(signer.has(&key.address).await.unwrap());
+        let signature = signer.sign(&key.address, b"message").await.unwrap();
+        assert!(signature.verify(b"message", &key.address.into()).is_ok());
+    }
+
+    #[test]
+    fn remote_signer_urls() {
+        let signer = RemoteSigner::new("unix:///run/signer.sock", None).unwrap();
+        assert_eq!(
+            signer.endpoint,
+            RemoteEndpoint::Unix(PathBuf::from("/run/signer.sock"))
+        );
+        let signer = RemoteSigner::new("http://127.0.0.1:1235/rpc/v0", None).unwrap();
+        assert_eq!(
+            signer.endpoint,
+            RemoteEndpoint::Http("http://127.0.0.1:1235/rpc/v0".to_owned())
+        );
+        assert!(RemoteSigner::new("127.0.0.1:1235", None).is_err());
+    }
+}
@@ -4,11 +4,12 @@
 use bls_signatures::{PrivateKey as BlsPrivate, Serialize};
 use forest_encoding::blake2b_256;
 use forest_shim::{
-    address::Address,
+    address::{Address, EthAddress, Protocol, EAM_NAMESPACE},
     crypto::{Signature, SignatureType},
 };
 use libsecp256k1::{Message as SecpMessage, PublicKey as SecpPublic, SecretKey as SecpPrivate};
 use rand::rngs::OsRng;
+use sha3::{Digest, Keccak256};
 
 use super::errors::Error;
 
@@ -19,15 +20,13 @@ pub fn to_public(sig_type: SignatureType, private_key: &[u8]) -> Result<Vec<u8>,
             .map_err(|err| Error::Other(err.to_string()))?
             .public_key()
             .as_bytes()),
-        SignatureType::Secp256k1 => {
+        // Delegated keys are `secp256k1` keys
+        SignatureType::Secp256k1 | SignatureType::Delegated => {
             let private_key = SecpPrivate::parse_slice(private_key)
                 .map_err(|err| Error::Other(err.to_string()))?;
             let public_key = SecpPublic::from_secret_key(&private_key);
             Ok(public_key.serialize().to_vec())
         }
-        SignatureType::Delegated => {
-            unimplemented!()
-        }
     }
 }
 
@@ -45,11 +44,25 @@ pub fn new_address(sig_type: SignatureType, public_key: &[u8]) -> Result<Address
             Ok(addr)
         }
         SignatureType::Delegated => {
-            unimplemented!()
+            let addr = Address::new_delegated(EAM_NAMESPACE, &eth_address(public_key)?.0)
+                .map_err(|err| Error::Other(err.to_string()))?;
+            Ok(addr)
         }
     }
 }
 
+/// Return the Ethereum address of an uncompressed `secp256k1` public key: the
+/// last 20 bytes of the `Keccak-256` hash of the key, without its prefix byte.
+pub fn eth_address(public_key: &[u8]) -> Result<EthAddress, Error> {
+    let public_key = SecpPublic::parse_slice(public_key, None)
+        .map_err(|err| Error::Other(err.to_string()))?
+        .serialize();
+    let hash = Keccak256::digest(&public_key[1..]);
+    let mut addr = [0; 20];
+    addr.copy_from_slice(&hash[12..]);
+    Ok(EthAddress(addr))
+}
+
 /// Sign takes in `SignatureType`, private key and message. Returns a Signature
 /// for that message
 pub fn sign(sig_type: SignatureType, private_key: &[u8], msg: &[u8]) -> Result<Signature, Error> {
@@ -64,23 +77,44 @@ pub fn sign(sig_type: SignatureType, private_key: &[u8], msg: &[u8]) -> Result<S
             Ok(crypto_sig)
         }
         SignatureType::Secp256k1 => {
-            let priv_key = SecpPrivate::parse_slice(private_key)
-                .map_err(|err| Error::Other(err.to_string()))?;
-            let msg_hash = blake2b_256(msg);
-            let message = SecpMessage::parse(&msg_hash);
-            let (sig, recovery_id) = libsecp256k1::sign(&message, &priv_key);
-            let mut new_bytes = [0; 65];
-            new_bytes[..64].copy_from_slice(&sig.serialize());
-            new_bytes[64] = recovery_id.serialize();
-            let crypto_sig = Signature::new_secp256k1(new_bytes.to_vec());
-            Ok(crypto_sig)
+            let sig = sign_secp256k1_hash(private_key, &blake2b_256(msg))?;
+            Ok(Signature::new_secp256k1(sig.to_vec()))
         }
+        // Delegated keys sign like Ethereum accounts, over the `Keccak-256` hash.
+        // Messages are not signed over their `CID` though, see
+        // `ensure_message_signable`.
         SignatureType::Delegated => {
-            unimplemented!()
+            let sig = sign_secp256k1_hash(private_key, &Keccak256::digest(msg).into())?;
+            Ok(Signature::new(SignatureType::Delegated, sig.to_vec()))
         }
     }
 }
 
+/// Fails for the messages of delegated addresses, which are signed over the
+/// `RLP` encoding of their `EIP-1559` Ethereum transaction rather than over
+/// their `CID`. That encoding is not supported.
+pub fn ensure_message_signable(from: &Address) -> Result<(), Error> {
+    if from.protocol() == Protocol::Delegated {
+        return Err(Error::Other(format!(
+            "Signing the messages of the delegated address {from} is not supported"
+        )));
+    }
+    Ok(())
+}
+
+/// Signs a 32 bytes hash with a `secp256k1` private key. Returns the `r` and
+/// `s` values of the signature followed by the recovery ID.
+pub fn sign_secp256k1_hash(private_key: &[u8], hash: &[u8; 32]) -> Result<[u8; 65], Error> {
+    let priv_key =
+        SecpPrivate::parse_slice(private_key).map_err(|err| Error::Other(err.to_string()))?;
+    let message = SecpMessage::parse(hash);
+    let (sig, recovery_id) = libsecp256k1::sign(&message, &priv_key);
+    let mut new_bytes = [0; 65];
+    new_bytes[..64].copy_from_slice(&sig.serialize());
+    new_bytes[64] = recovery_id.serialize();
+    Ok(new_bytes)
+}
+
 /// Generate a new private key
 pub fn generate(sig_type: SignatureType) -> Result<Vec<u8>, Error> {
     let rng = &mut OsRng::default();
@@ -89,12 +123,9 @@ pub fn generate(sig_type: SignatureType) -> Result<Vec<u8>, Error> {
             let key = BlsPrivate::generate(rng);
             Ok(key.as_bytes())
         }
-        SignatureType::Secp256k1 => {
+        SignatureType::Secp256k1 | SignatureType::Delegated => {
             let key = SecpPrivate::random(rng);
             Ok(key.serialize().to_vec())
         }
-        SignatureType::Delegated => {
-            unimplemented!()
-        }
     }
 }
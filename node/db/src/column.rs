@@ -0,0 +1,89 @@
+// Copyright 2019-2023 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use cid::Cid;
+
+/// Prefix of the keys flagging the blocks that passed validation.
+pub const BLOCK_VALIDATION_PREFIX: &[u8] = b"block_val/";
+
+/// Class of chain data, stored in a column family of its own by `RocksDB` and
+/// in a column of its own by `ParityDb`. All IPLD blocks share a column, as a
+/// block is looked up by CID without knowing what it holds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DbColumn {
+    /// IPLD blocks: headers, messages, state trees, receipts and `AMT`s.
+    Blocks,
+    /// Settings and pointers such as the head or the message pool
+    /// configuration.
+    Metadata,
+    /// Flags and lookup entries derived from the chain.
+    Indices,
+}
+
+impl DbColumn {
+    pub const ALL: [DbColumn; 3] = [DbColumn::Blocks, DbColumn::Metadata, DbColumn::Indices];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            DbColumn::Blocks => "blocks",
+            DbColumn::Metadata => "metadata",
+            DbColumn::Indices => "indices",
+        }
+    }
+
+    /// Index of the column, stable across versions.
+    pub fn index(self) -> u8 {
+        self as u8
+    }
+
+    pub fn from_index(index: u8) -> Option<Self> {
+        Self::ALL.get(index as usize).copied()
+    }
+
+    pub fn is_block(self) -> bool {
+        self == DbColumn::Blocks
+    }
+
+    /// Column of an entry: CID keys are blocks, keys
+    /// under [`BLOCK_VALIDATION_PREFIX`] indices and all the other ones
+    /// metadata.
+    pub fn of_key(key: &[u8]) -> Self {
+        if key.starts_with(BLOCK_VALIDATION_PREFIX) {
+            return DbColumn::Indices;
+        }
+        let mut rest = key;
+        match Cid::read_bytes(&mut rest) {
+            Ok(_) if rest.is_empty() => DbColumn::Blocks,
+            _ => DbColumn::Metadata,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use cid::multihash::{Code::Blake2b256, MultihashDigest};
+
+    use super::*;
+
+    #[test]
+    fn column_of_key() {
+        let cid = Cid::new_v1(0x71, Blake2b256.digest(b"block"));
+        assert_eq!(DbColumn::of_key(&cid.to_bytes()), DbColumn::Blocks);
+        assert_eq!(DbColumn::of_key(b"head"), DbColumn::Metadata);
+        assert_eq!(DbColumn::of_key(b"/mpool/config"), DbColumn::Metadata);
+        let mut key = BLOCK_VALIDATION_PREFIX.to_vec();
+        key.extend(cid.to_bytes());
+        assert_eq!(DbColumn::of_key(&key), DbColumn::Indices);
+        let mut key = cid.to_bytes();
+        key.push(0);
+        assert_eq!(DbColumn::of_key(&key), DbColumn::Metadata);
+    }
+
+    #[test]
+    fn column_index_roundtrip() {
+        for column in DbColumn::ALL {
+            assert_eq!(DbColumn::from_index(column.index()), Some(column));
+        }
+        assert_eq!(DbColumn::from_index(3), None);
+    }
+}
@@ -0,0 +1,420 @@
+// Copyright 2019-2023 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Database backend of the node, selected at runtime through the `db_backend`
+//! setting among the backends compiled in.
+
+#[cfg(any(feature = "rocksdb", feature = "paritydb"))]
+use std::sync::Arc;
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+    str::FromStr,
+};
+
+#[cfg(any(feature = "rocksdb", feature = "paritydb"))]
+use cid::Cid;
+#[cfg(any(feature = "rocksdb", feature = "paritydb"))]
+use forest_libp2p_bitswap::{BitswapStoreRead, BitswapStoreReadWrite};
+#[cfg(any(feature = "rocksdb", feature = "paritydb"))]
+use fvm_ipld_blockstore::Blockstore;
+use serde::{Deserialize, Serialize};
+
+#[cfg(any(feature = "rocksdb", feature = "paritydb"))]
+use crate::tiered::TieredDb;
+use crate::{
+    parity_db_config::ParityDbConfig, rocks_config::RocksDbConfig, tiered::TieredDbConfig,
+};
+#[cfg(any(feature = "rocksdb", feature = "paritydb"))]
+use crate::{DBStatistics, DbCheckpoint, DbColumn, DbStats, Error, Store};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DbBackend {
+    RocksDb,
+    ParityDb,
+}
+
+impl Default for DbBackend {
+    fn default() -> Self {
+        if cfg!(feature = "rocksdb") || !cfg!(feature = "paritydb") {
+            DbBackend::RocksDb
+        } else {
+            DbBackend::ParityDb
+        }
+    }
+}
+
+impl DbBackend {
+    /// Name of the backend, also the name of its directory in the chain data
+    /// directory.
+    pub fn name(&self) -> &'static str {
+        match self {
+            DbBackend::RocksDb => "rocksdb",
+            DbBackend::ParityDb => "paritydb",
+        }
+    }
+}
+
+impl fmt::Display for DbBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for DbBackend {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "rocksdb" => Ok(DbBackend::RocksDb),
+            "paritydb" => Ok(DbBackend::ParityDb),
+            _ => Err(anyhow::anyhow!(
+                "Invalid database backend {s}. Must be one of [rocksdb, paritydb]"
+            )),
+        }
+    }
+}
+
+/// Configuration of the database: the backend to use, the settings of each
+/// backend and the tiering in hot and cold stores.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DbConfig {
+    pub backend: DbBackend,
+    pub rocks_db: RocksDbConfig,
+    pub parity_db: ParityDbConfig,
+    pub tiered: TieredDbConfig,
+}
+
+pub fn db_path(path: &Path, backend: DbBackend) -> PathBuf {
+    path.join(backend.name())
+}
+
+/// Default directory of the cold store, next to the hot store at `path`.
+pub fn cold_db_path(path: &Path, backend: DbBackend) -> PathBuf {
+    path.with_file_name(format!("cold_{backend}"))
+}
+
+/// Database of the node, of any of the backends compiled in.
+#[cfg(any(feature = "rocksdb", feature = "paritydb"))]
+#[derive(Clone)]
+pub enum Db {
+    #[cfg(feature = "rocksdb")]
+    RocksDb(crate::rocks::RocksDb),
+    #[cfg(feature = "paritydb")]
+    ParityDb(crate::parity_db::ParityDb),
+    /// Hot and cold stores, see [`crate::tiered`].
+    Tiered(Arc<TieredDb<Db, Db>>),
+}
+
+/// Opens the database of the configured backend, which has to be compiled in.
+/// With tiering enabled, the database at `path` is the hot store and the cold
+/// store is opened alongside.
+#[cfg(any(feature = "rocksdb", feature = "paritydb"))]
+pub fn open_db(path: &Path, config: &DbConfig) -> anyhow::Result<Db> {
+    open_db_with(path, config, false)
+}
+
+/// Opens the database like [`open_db`], for reading only. It can be opened
+/// while the node runs, and sees the entries written until then.
+#[cfg(any(feature = "rocksdb", feature = "paritydb"))]
+pub fn open_db_read_only(path: &Path, config: &DbConfig) -> anyhow::Result<Db> {
+    open_db_with(path, config, true)
+}
+
+#[cfg(any(feature = "rocksdb", feature = "paritydb"))]
+fn open_db_with(path: &Path, config: &DbConfig, read_only: bool) -> anyhow::Result<Db> {
+    let hot = open_backend(path, config.backend, config, read_only)?;
+    if !config.tiered.enabled {
+        return Ok(hot);
+    }
+    if config.backend != DbBackend::RocksDb {
+        anyhow::bail!(
+            "The hot store of a tiered database must be rocksdb, whose entries can be listed"
+        );
+    }
+    let cold_backend = config.tiered.cold_backend;
+    let cold_path = config
+        .tiered
+        .cold_path
+        .clone()
+        .unwrap_or_else(|| cold_db_path(path, cold_backend));
+    let cold = open_backend(&cold_path, cold_backend, config, read_only)?;
+    Ok(Db::Tiered(Arc::new(TieredDb::new(hot, cold))))
+}
+
+#[cfg(any(feature = "rocksdb", feature = "paritydb"))]
+fn open_backend(
+    path: &Path,
+    backend: DbBackend,
+    config: &DbConfig,
+    read_only: bool,
+) -> anyhow::Result<Db> {
+    match backend {
+        #[cfg(feature = "rocksdb")]
+        DbBackend::RocksDb => {
+            let db = if read_only {
+                crate::rocks::RocksDb::open_read_only(path, &config.rocks_db)?
+            } else {
+                crate::rocks::RocksDb::open(path, &config.rocks_db)?
+            };
+            Ok(Db::RocksDb(db))
+        }
+        #[cfg(feature = "paritydb")]
+        DbBackend::ParityDb => {
+            let db = if read_only {
+                crate::parity_db::ParityDb::open_read_only(path.to_owned(), &config.parity_db)?
+            } else {
+                crate::parity_db::ParityDb::open(path.to_owned(), &config.parity_db)?
+            };
+            Ok(Db::ParityDb(db))
+        }
+        #[cfg(not(feature = "rocksdb"))]
+        DbBackend::RocksDb => anyhow::bail!("Forest was built without the rocksdb backend"),
+        #[cfg(not(feature = "paritydb"))]
+        DbBackend::ParityDb => anyhow::bail!("Forest was built without the paritydb backend"),
+    }
+}
+
+/// Calls the expression on the database of the backend.
+#[cfg(any(feature = "rocksdb", feature = "paritydb"))]
+macro_rules! dispatch {
+    ($self:ident, $db:ident => $expr:expr) => {
+        match $self {
+            #[cfg(feature = "rocksdb")]
+            Db::RocksDb($db) => $expr,
+            #[cfg(feature = "paritydb")]
+            Db::ParityDb($db) => $expr,
+            Db::Tiered(tiered) => {
+                let $db = tiered.as_ref();
+                $expr
+            }
+        }
+    };
+}
+
+#[cfg(any(feature = "rocksdb", feature = "paritydb"))]
+impl Db {
+    pub fn backend(&self) -> DbBackend {
+        match self {
+            #[cfg(feature = "rocksdb")]
+            Db::RocksDb(_) => DbBackend::RocksDb,
+            #[cfg(feature = "paritydb")]
+            Db::ParityDb(_) => DbBackend::ParityDb,
+            Db::Tiered(tiered) => tiered.hot().backend(),
+        }
+    }
+
+    /// Estimated number of keys in the database, if the backend tracks it.
+    pub fn estimate_num_keys(&self) -> Option<u64> {
+        match self {
+            #[cfg(feature = "rocksdb")]
+            Db::RocksDb(db) => db.estimate_num_keys(),
+            #[cfg(feature = "paritydb")]
+            Db::ParityDb(_) => None,
+            Db::Tiered(tiered) => tiered.hot().estimate_num_keys(),
+        }
+    }
+
+    /// Moves the entries written before the database had columns to their
+    /// column, see [`crate::rocks::RocksDb::migrate_default_column`].
+    pub fn migrate_default_column(&self) -> Result<(), Error> {
+        match self {
+            #[cfg(feature = "rocksdb")]
+            Db::RocksDb(db) => db.migrate_default_column(),
+            #[cfg(feature = "paritydb")]
+            Db::ParityDb(_) => Ok(()),
+            Db::Tiered(tiered) => {
+                tiered.hot().migrate_default_column()?;
+                tiered.cold().migrate_default_column()
+            }
+        }
+    }
+
+    /// Moves the entries written before the database had columns to their
+    /// column in the background, see
+    /// [`crate::rocks::RocksDb::spawn_default_column_migration`].
+    pub fn spawn_default_column_migration(&self) {
+        match self {
+            #[cfg(feature = "rocksdb")]
+            Db::RocksDb(db) => db.spawn_default_column_migration(),
+            #[cfg(feature = "paritydb")]
+            Db::ParityDb(_) => (),
+            Db::Tiered(tiered) => {
+                tiered.hot().spawn_default_column_migration();
+                tiered.cold().spawn_default_column_migration();
+            }
+        }
+    }
+
+    /// Iterates over the key-value pairs of a column, from the given key
+    /// included. `RocksDB` lists them in key order, `ParityDb` in an order of
+    /// its own, see [`crate::parity_db::ParityDb::iter_column`]. A tiered
+    /// database can't be iterated over, its stores don't share an order.
+    #[allow(clippy::type_complexity)]
+    pub fn iter_column<'a>(
+        &'a self,
+        column: DbColumn,
+        start: Option<&[u8]>,
+    ) -> anyhow::Result<Box<dyn Iterator<Item = Result<(Vec<u8>, Vec<u8>), Error>> + 'a>> {
+        match self {
+            #[cfg(feature = "rocksdb")]
+            Db::RocksDb(db) => Ok(Box::new(db.iter_column(column, start)?)),
+            #[cfg(feature = "paritydb")]
+            Db::ParityDb(db) => Ok(db.iter_column(column, start)?),
+            Db::Tiered(_) => anyhow::bail!("The entries of a tiered database can't be listed"),
+        }
+    }
+}
+
+#[cfg(any(feature = "rocksdb", feature = "paritydb"))]
+impl Store for Db {
+    fn read<K>(&self, key: K) -> Result<Option<Vec<u8>>, Error>
+    where
+        K: AsRef<[u8]>,
+    {
+        dispatch!(self, db => db.read(key))
+    }
+
+    fn write<K, V>(&self, key: K, value: V) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        dispatch!(self, db => db.write(key, value))
+    }
+
+    fn delete<K>(&self, key: K) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+    {
+        dispatch!(self, db => db.delete(key))
+    }
+
+    fn exists<K>(&self, key: K) -> Result<bool, Error>
+    where
+        K: AsRef<[u8]>,
+    {
+        dispatch!(self, db => db.exists(key))
+    }
+
+    fn bulk_read<K>(&self, keys: &[K]) -> Result<Vec<Option<Vec<u8>>>, Error>
+    where
+        K: AsRef<[u8]>,
+    {
+        dispatch!(self, db => db.bulk_read(keys))
+    }
+
+    fn bulk_write(
+        &self,
+        values: impl IntoIterator<Item = (impl Into<Vec<u8>>, impl Into<Vec<u8>>)>,
+    ) -> Result<(), Error> {
+        dispatch!(self, db => db.bulk_write(values))
+    }
+
+    fn bulk_write_column(
+        &self,
+        column: DbColumn,
+        values: impl IntoIterator<Item = (impl Into<Vec<u8>>, impl Into<Vec<u8>>)>,
+    ) -> Result<(), Error> {
+        dispatch!(self, db => db.bulk_write_column(column, values))
+    }
+
+    fn bulk_delete<K>(&self, keys: &[K]) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+    {
+        dispatch!(self, db => db.bulk_delete(keys))
+    }
+
+    fn flush(&self) -> Result<(), Error> {
+        dispatch!(self, db => db.flush())
+    }
+}
+
+#[cfg(any(feature = "rocksdb", feature = "paritydb"))]
+impl Blockstore for Db {
+    fn get(&self, k: &Cid) -> anyhow::Result<Option<Vec<u8>>> {
+        dispatch!(self, db => Blockstore::get(db, k))
+    }
+
+    fn put_keyed(&self, k: &Cid, block: &[u8]) -> anyhow::Result<()> {
+        dispatch!(self, db => db.put_keyed(k, block))
+    }
+
+    fn put_many_keyed<D, I>(&self, blocks: I) -> anyhow::Result<()>
+    where
+        Self: Sized,
+        D: AsRef<[u8]>,
+        I: IntoIterator<Item = (Cid, D)>,
+    {
+        dispatch!(self, db => db.put_many_keyed(blocks))
+    }
+}
+
+#[cfg(any(feature = "rocksdb", feature = "paritydb"))]
+impl BitswapStoreRead for Db {
+    fn contains(&self, cid: &Cid) -> anyhow::Result<bool> {
+        dispatch!(self, db => db.contains(cid))
+    }
+
+    fn get(&self, cid: &Cid) -> anyhow::Result<Option<Vec<u8>>> {
+        dispatch!(self, db => BitswapStoreRead::get(db, cid))
+    }
+}
+
+#[cfg(any(feature = "rocksdb", feature = "paritydb"))]
+impl BitswapStoreReadWrite for Db {
+    /// `fvm_ipld_encoding::DAG_CBOR(0x71)` is covered by
+    /// [`libipld::DefaultParams`] under feature `dag-cbor`
+    type Params = libipld::DefaultParams;
+
+    fn insert(&self, block: &libipld::Block<Self::Params>) -> anyhow::Result<()> {
+        self.put_keyed(block.cid(), block.data())
+    }
+}
+
+#[cfg(any(feature = "rocksdb", feature = "paritydb"))]
+impl DbCheckpoint for Db {
+    fn backend_name(&self) -> &'static str {
+        self.backend().name()
+    }
+
+    /// The cold store of a tiered database is copied next to the hot one, where
+    /// [`open_db`] looks for it by default.
+    fn checkpoint(&self, dir: &Path) -> anyhow::Result<()> {
+        match self {
+            #[cfg(feature = "rocksdb")]
+            Db::RocksDb(db) => db.checkpoint(dir),
+            #[cfg(feature = "paritydb")]
+            Db::ParityDb(db) => db.checkpoint(dir),
+            Db::Tiered(tiered) => {
+                tiered.hot().checkpoint(dir)?;
+                let cold = tiered.cold();
+                cold.checkpoint(&cold_db_path(dir, cold.backend()))
+            }
+        }
+    }
+}
+
+#[cfg(any(feature = "rocksdb", feature = "paritydb"))]
+impl DBStatistics for Db {
+    fn get_statistics(&self) -> Option<DbStats> {
+        dispatch!(self, db => DBStatistics::get_statistics(db))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backend_from_str() {
+        assert_eq!("rocksdb".parse::<DbBackend>().unwrap(), DbBackend::RocksDb);
+        assert_eq!(
+            "ParityDB".parse::<DbBackend>().unwrap(),
+            DbBackend::ParityDb
+        );
+        assert!("sled".parse::<DbBackend>().is_err());
+    }
+}
@@ -12,10 +12,10 @@ pub enum Error {
     Unopened,
     #[cfg(feature = "rocksdb")]
     #[error(transparent)]
-    Database(#[from] rocksdb::Error),
+    RocksDb(#[from] rocksdb::Error),
     #[cfg(feature = "paritydb")]
     #[error(transparent)]
-    Database(#[from] parity_db::Error),
+    ParityDb(#[from] parity_db::Error),
     #[error("{0}")]
     Other(String),
 }
@@ -28,9 +28,9 @@ impl PartialEq for Error {
             (&InvalidBulkLen, &InvalidBulkLen) => true,
             (&Unopened, &Unopened) => true,
             #[cfg(feature = "rocksdb")]
-            (&Database(_), &Database(_)) => true,
+            (&RocksDb(_), &RocksDb(_)) => true,
             #[cfg(feature = "paritydb")]
-            (&Database(_), &Database(_)) => true,
+            (&ParityDb(_), &ParityDb(_)) => true,
             (Other(a), Other(b)) => a == b,
             _ => false,
         }
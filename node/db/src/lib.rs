@@ -1,6 +1,7 @@
 // Copyright 2019-2023 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+mod column;
 mod errors;
 mod memory;
 mod metrics;
@@ -11,11 +12,18 @@ pub mod rocks;
 #[cfg(feature = "paritydb")]
 pub mod parity_db;
 
+pub mod db_engine;
+#[cfg(any(feature = "rocksdb", feature = "paritydb"))]
+pub mod migration;
 pub mod parity_db_config;
 pub mod rocks_config;
+mod stats;
+pub mod tiered;
 
+pub use column::{DbColumn, BLOCK_VALIDATION_PREFIX};
 pub use errors::Error;
 pub use memory::MemoryDB;
+pub use stats::{ColumnStats, DbStats, FileStats};
 
 /// Store interface used as a KV store implementation
 pub trait Store {
@@ -59,6 +67,16 @@ pub trait Store {
             .try_for_each(|(key, value)| self.write(key.into(), value.into()))
     }
 
+    /// Write slice of KV pairs to the given column. Stores without columns
+    /// write them like [`Store::bulk_write`] does.
+    fn bulk_write_column(
+        &self,
+        _column: DbColumn,
+        values: impl IntoIterator<Item = (impl Into<Vec<u8>>, impl Into<Vec<u8>>)>,
+    ) -> Result<(), Error> {
+        self.bulk_write(values)
+    }
+
     /// Bulk delete keys from the data store.
     fn bulk_delete<K>(&self, keys: &[K]) -> Result<(), Error>
     where
@@ -117,6 +135,14 @@ impl<BS: Store> Store for &BS {
         (*self).bulk_write(values)
     }
 
+    fn bulk_write_column(
+        &self,
+        column: DbColumn,
+        values: impl IntoIterator<Item = (impl Into<Vec<u8>>, impl Into<Vec<u8>>)>,
+    ) -> Result<(), Error> {
+        (*self).bulk_write_column(column, values)
+    }
+
     fn bulk_delete<K>(&self, keys: &[K]) -> Result<(), Error>
     where
         K: AsRef<[u8]>,
@@ -125,42 +151,18 @@ impl<BS: Store> Store for &BS {
     }
 }
 
-/// Traits for collecting DB stats
-pub trait DBStatistics {
-    fn get_statistics(&self) -> Option<String> {
-        None
-    }
-}
+/// Consistent copy of a database, taken while it is in use.
+pub trait DbCheckpoint {
+    /// Name of the backend of the database, see [`db_engine::DbBackend`].
+    fn backend_name(&self) -> &'static str;
 
-#[cfg(feature = "rocksdb")]
-pub mod db_engine {
-    use std::path::{Path, PathBuf};
-
-    pub type Db = crate::rocks::RocksDb;
-    pub type DbConfig = crate::rocks_config::RocksDbConfig;
-
-    pub fn db_path(path: &Path) -> PathBuf {
-        path.join("rocksdb")
-    }
-
-    pub fn open_db(path: &std::path::Path, config: &DbConfig) -> anyhow::Result<Db> {
-        crate::rocks::RocksDb::open(path, config).map_err(Into::into)
-    }
+    /// Writes the copy to the directory, which must not exist yet.
+    fn checkpoint(&self, dir: &std::path::Path) -> anyhow::Result<()>;
 }
 
-#[cfg(feature = "paritydb")]
-pub mod db_engine {
-    use std::path::{Path, PathBuf};
-
-    pub type Db = crate::parity_db::ParityDb;
-    pub type DbConfig = crate::parity_db_config::ParityDbConfig;
-
-    pub fn db_path(path: &Path) -> PathBuf {
-        path.join("paritydb")
-    }
-
-    pub fn open_db(path: &std::path::Path, config: &DbConfig) -> anyhow::Result<Db> {
-        use crate::parity_db::ParityDb;
-        ParityDb::open(path.to_owned(), config)
+/// Traits for collecting DB stats
+pub trait DBStatistics {
+    fn get_statistics(&self) -> Option<DbStats> {
+        None
     }
 }
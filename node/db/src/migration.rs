@@ -0,0 +1,161 @@
+// Copyright 2019-2023 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Copy of all the entries of a database into a database of another backend.
+//! Entries are copied column by column, in key order and in batches, each
+//! batch is read back from the destination to verify it. The column and last
+//! copied key are recorded in a progress file after every batch, so that an
+//! interrupted migration resumes from there. The progress file is created
+//! empty by [`start_progress`] before the destination database, so that a
+//! destination database without a progress file is always a complete one.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context;
+
+use crate::{db_engine::Db, DbColumn, Store};
+
+/// Migration of the entries of a database into another.
+pub struct Migration<'a> {
+    from: &'a Db,
+    to: &'a Db,
+    progress_file: PathBuf,
+    batch_size: usize,
+}
+
+impl<'a> Migration<'a> {
+    /// Migration from a database to another, recording its progress in the
+    /// given file.
+    pub fn new(from: &'a Db, to: &'a Db, progress_file: impl Into<PathBuf>) -> Self {
+        Self {
+            from,
+            to,
+            progress_file: progress_file.into(),
+            batch_size: 10_000,
+        }
+    }
+
+    /// Sets the number of entries copied and verified at once.
+    pub fn batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size.max(1);
+        self
+    }
+
+    /// Returns `true` if a previous migration was interrupted after copying
+    /// some entries.
+    pub fn is_resuming(&self) -> bool {
+        matches!(read_progress(&self.progress_file), Ok(Some(_)))
+    }
+
+    /// Runs the migration, calling `on_batch` with the number of entries
+    /// copied after each batch. Returns the number of entries copied by this
+    /// run. The progress file is removed once all the entries are copied.
+    pub fn run(&self, mut on_batch: impl FnMut(u64)) -> anyhow::Result<u64> {
+        let resume = read_progress(&self.progress_file)?;
+        let mut copied = 0;
+        for column in DbColumn::ALL {
+            let start = match &resume {
+                Some((resume_column, _)) if column.index() < resume_column.index() => continue,
+                Some((resume_column, key)) if column == *resume_column => Some(key.as_slice()),
+                _ => None,
+            };
+            let mut batch = Vec::with_capacity(self.batch_size);
+            for entry in self.from.iter_column(column, start)? {
+                batch.push(entry?);
+                if batch.len() == self.batch_size {
+                    copied += self.copy_batch(column, &mut batch)?;
+                    on_batch(copied);
+                }
+            }
+            if !batch.is_empty() {
+                copied += self.copy_batch(column, &mut batch)?;
+                on_batch(copied);
+            }
+        }
+        if self.progress_file.exists() {
+            fs::remove_file(&self.progress_file).with_context(|| {
+                format!(
+                    "Failed to remove migration progress file {}",
+                    self.progress_file.display()
+                )
+            })?;
+        }
+        Ok(copied)
+    }
+
+    /// Writes the batch to the same column of the destination, verifies it
+    /// and records its last key as the progress of the migration.
+    fn copy_batch(
+        &self,
+        column: DbColumn,
+        batch: &mut Vec<(Vec<u8>, Vec<u8>)>,
+    ) -> anyhow::Result<u64> {
+        self.to.bulk_write_column(column, batch.iter().cloned())?;
+        let keys: Vec<_> = batch.iter().map(|(k, _)| k).collect();
+        for ((key, value), written) in batch.iter().zip(self.to.bulk_read(&keys)?) {
+            if written.as_ref() != Some(value) {
+                anyhow::bail!(
+                    "Entry of key {} differs in the destination database",
+                    hex_key(key)
+                );
+            }
+        }
+        self.to.flush()?;
+        let (last_key, _) = batch.last().expect("batch is never empty");
+        write_progress(&self.progress_file, column, last_key)?;
+        let len = batch.len() as u64;
+        batch.clear();
+        Ok(len)
+    }
+}
+
+/// Path of the progress file of a migration into the given database
+/// directory.
+pub fn progress_file(dest: &Path) -> PathBuf {
+    dest.with_extension("migration")
+}
+
+/// Creates the progress file of a migration that hasn't copied anything yet,
+/// unless it exists. To be called before the destination database is created.
+pub fn start_progress(path: &Path) -> anyhow::Result<()> {
+    if !path.exists() {
+        fs::write(path, [])
+            .with_context(|| format!("Failed to write migration progress {}", path.display()))?;
+    }
+    Ok(())
+}
+
+/// Reads the column and last key copied, stored as the index of the column
+/// followed by the key. The file is empty until the first batch is copied.
+fn read_progress(path: &Path) -> anyhow::Result<Option<(DbColumn, Vec<u8>)>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    let progress = fs::read(path)
+        .with_context(|| format!("Failed to read migration progress {}", path.display()))?;
+    match progress.split_first() {
+        Some((&index, key)) => match DbColumn::from_index(index) {
+            Some(column) => Ok(Some((column, key.to_vec()))),
+            None => anyhow::bail!("Invalid migration progress {}", path.display()),
+        },
+        None => Ok(None),
+    }
+}
+
+fn write_progress(path: &Path, column: DbColumn, last_key: &[u8]) -> anyhow::Result<()> {
+    // Written to a temporary file first so that a crash never leaves a
+    // truncated key behind.
+    let tmp = path.with_extension("migration.tmp");
+    let mut progress = vec![column.index()];
+    progress.extend_from_slice(last_key);
+    fs::write(&tmp, progress)?;
+    fs::rename(&tmp, path)
+        .with_context(|| format!("Failed to write migration progress {}", path.display()))
+}
+
+fn hex_key(key: &[u8]) -> String {
+    key.iter().map(|b| format!("{b:02x}")).collect()
+}
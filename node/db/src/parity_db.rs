@@ -1,22 +1,99 @@
 // Copyright 2019-2023 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use std::{path::PathBuf, sync::Arc};
+use std::{
+    collections::VecDeque,
+    iter,
+    path::{Path, PathBuf},
+    sync::{mpsc, Arc},
+    thread,
+};
 
 use anyhow::anyhow;
-use cid::Cid;
+use cid::{
+    multihash::{Code, MultihashDigest},
+    Cid,
+};
 use forest_libp2p_bitswap::{BitswapStoreRead, BitswapStoreReadWrite};
 use fvm_ipld_blockstore::Blockstore;
-use log::warn;
+use log::{info, warn};
 use parity_db::{CompressionType, Db, Operation, Options};
+use parking_lot::{Mutex, RwLock};
 
 use super::errors::Error;
-use crate::{parity_db_config::ParityDbConfig, DBStatistics, Store};
+use crate::{
+    parity_db_config::ParityDbConfig, ColumnStats, DBStatistics, DbCheckpoint, DbColumn, DbStats,
+    Store,
+};
+
+/// Codecs and hash functions of the CIDs of the blocks, from which the keys of
+/// the blocks column are recomputed when they are listed.
+const BLOCK_CID_KINDS: [(u64, Code); 4] = [
+    (DAG_CBOR, Code::Blake2b256),
+    (IPLD_RAW, Code::Blake2b256),
+    (DAG_CBOR, Code::Sha2_256),
+    (IPLD_RAW, Code::Sha2_256),
+];
+const DAG_CBOR: u64 = 0x71;
+const IPLD_RAW: u64 = 0x55;
+
+/// Size of the latest writes whose keys are kept, twice the size of the
+/// commit queue of `ParityDb`.
+const RECENT_WRITES_BYTES: usize = 32 * 1024 * 1024;
+
+/// Number of entries written at once into a checkpoint.
+const CHECKPOINT_BATCH_SIZE: usize = 10_000;
+
+/// Keys of the metadata of databases created before the columns existed, in
+/// which all the entries were written to the blocks column. The block
+/// validation flags are left behind, the blocks are validated again.
+const LEGACY_METADATA_KEYS: [&[u8]; 3] = [b"head", b"gen_block", b"/mpool/config"];
 
 #[derive(Clone)]
 pub struct ParityDb {
     pub db: Arc<parity_db::Db>,
+    config: ParityDbConfig,
     statistics_enabled: bool,
+    /// Taken shared by the writes and exclusively by the checkpoints, which
+    /// pause the writes while they copy the last entries written.
+    writes: Arc<RwLock<()>>,
+    recent_writes: Arc<Mutex<RecentWrites>>,
+}
+
+/// Keys of the latest commits. `ParityDb` only lists the values of its hash
+/// indexed columns once the commits are processed by its background threads,
+/// so a listing may miss them.
+#[derive(Default)]
+struct RecentWrites {
+    commits: VecDeque<(Vec<(u8, Vec<u8>)>, usize)>,
+    bytes: usize,
+    /// Keys written since a checkpoint started, copied into it once the
+    /// database is listed.
+    checkpoint: Option<Vec<(u8, Vec<u8>)>>,
+}
+
+impl RecentWrites {
+    /// Records the keys of a commit of the given size, dropping the oldest
+    /// commits beyond [`RECENT_WRITES_BYTES`].
+    fn record(&mut self, keys: Vec<(u8, Vec<u8>)>, bytes: usize) {
+        if let Some(checkpoint) = &mut self.checkpoint {
+            checkpoint.extend(keys.iter().cloned());
+        }
+        self.commits.push_back((keys, bytes));
+        self.bytes += bytes;
+        while self.bytes > RECENT_WRITES_BYTES && self.commits.len() > 1 {
+            if let Some((_, bytes)) = self.commits.pop_front() {
+                self.bytes -= bytes;
+            }
+        }
+    }
+
+    fn keys(&self) -> Vec<(u8, Vec<u8>)> {
+        self.commits
+            .iter()
+            .flat_map(|(keys, _)| keys.iter().cloned())
+            .collect()
+    }
 }
 
 /// Converts string to a compression `ParityDb` variant.
@@ -31,32 +108,213 @@ fn compression_type_from_str(s: &str) -> anyhow::Result<CompressionType> {
 
 impl ParityDb {
     fn to_options(path: PathBuf, config: &ParityDbConfig) -> anyhow::Result<Options> {
-        const COLUMNS: usize = 1;
-        let compression = compression_type_from_str(&config.compression_type)?;
         Ok(Options {
             path,
             sync_wal: true,
             sync_data: true,
             stats: config.enable_statistics,
             salt: None,
-            columns: (0..COLUMNS)
-                .map(|_| parity_db::ColumnOptions {
-                    compression,
-                    // btree_index: true,
-                    ..Default::default()
-                })
-                .collect(),
+            columns: DbColumn::ALL
+                .into_iter()
+                .map(|column| Self::column_options(config, column))
+                .collect::<anyhow::Result<_>>()?,
             compression_threshold: Default::default(),
         })
     }
 
+    /// Options of a column. Metadata and indices are kept in a B-tree, so that
+    /// they can be listed.
+    fn column_options(
+        config: &ParityDbConfig,
+        column: DbColumn,
+    ) -> anyhow::Result<parity_db::ColumnOptions> {
+        let compression = config
+            .columns
+            .get(column.name())
+            .and_then(|column| column.compression_type.as_deref())
+            .unwrap_or(&config.compression_type);
+        Ok(parity_db::ColumnOptions {
+            compression: compression_type_from_str(compression)?,
+            btree_index: !column.is_block(),
+            ..Default::default()
+        })
+    }
+
     pub fn open(path: PathBuf, config: &ParityDbConfig) -> anyhow::Result<Self> {
+        for name in config.columns.keys() {
+            if !DbColumn::ALL.iter().any(|column| column.name() == name) {
+                warn!("Ignoring the settings of unknown column {name}");
+            }
+        }
+        let mut opts = Self::to_options(path, config)?;
+        // Databases created before the columns existed only have the blocks
+        // column, whose index hasn't changed. The other columns are added.
+        if let Some(metadata) = opts.load_metadata()? {
+            let missing = opts
+                .columns
+                .split_off(metadata.columns.len().min(opts.columns.len()));
+            for column in missing {
+                Db::add_column(&mut opts, column)?;
+            }
+        }
+        let db = Self {
+            db: Arc::new(Db::open_or_create(&opts)?),
+            config: config.clone(),
+            statistics_enabled: opts.stats,
+            writes: Default::default(),
+            recent_writes: Default::default(),
+        };
+        db.migrate_legacy_metadata()?;
+        Ok(db)
+    }
+
+    /// Opens the database for reading only, so that it can be inspected while
+    /// the node runs. Nothing is written to it, so it has to have been opened
+    /// by [`ParityDb::open`] since its columns were added.
+    pub fn open_read_only(path: PathBuf, config: &ParityDbConfig) -> anyhow::Result<Self> {
         let opts = Self::to_options(path, config)?;
         Ok(Self {
-            db: Arc::new(Db::open_or_create(&opts)?),
+            db: Arc::new(Db::open_read_only(&opts)?),
+            config: config.clone(),
             statistics_enabled: opts.stats,
+            writes: Default::default(),
+            recent_writes: Default::default(),
         })
     }
+
+    /// Moves the metadata of a database created before the columns existed
+    /// from the blocks column to the metadata column.
+    fn migrate_legacy_metadata(&self) -> Result<(), Error> {
+        let (blocks, metadata) = (DbColumn::Blocks.index(), DbColumn::Metadata.index());
+        let mut changes = vec![];
+        for key in LEGACY_METADATA_KEYS {
+            if let Some(value) = self.db.get(blocks, key)? {
+                changes.push((metadata, Operation::Set(key.to_vec(), value)));
+                changes.push((blocks, Operation::Dereference(key.to_vec())));
+            }
+        }
+        if changes.is_empty() {
+            return Ok(());
+        }
+        info!("Moving the database metadata to its column");
+        self.commit(changes)
+    }
+
+    /// Commits the changes, unless a checkpoint is being taken, in which case
+    /// it waits for the checkpoint to be done.
+    fn commit(&self, changes: Vec<(u8, Operation<Vec<u8>, Vec<u8>>)>) -> Result<(), Error> {
+        let _writes = self.writes.read();
+        let mut bytes = 0;
+        let keys = changes
+            .iter()
+            .map(|(column, change)| {
+                let key = match change {
+                    Operation::Set(key, value) => {
+                        bytes += key.len() + value.len();
+                        key
+                    }
+                    Operation::Dereference(key) | Operation::Reference(key) => {
+                        bytes += key.len();
+                        key
+                    }
+                };
+                (*column, key.clone())
+            })
+            .collect();
+        self.db.commit_changes(changes)?;
+        self.recent_writes.lock().record(keys, bytes);
+        Ok(())
+    }
+
+    /// Iterates over the key-value pairs of a column, from the given key
+    /// included. Metadata and indices are listed in key order. The blocks
+    /// column only keeps the hashes of its keys, so its blocks are listed in
+    /// storage order, with their CIDs recomputed from their data, and the
+    /// listing ends with an error if some of them can't be. That order doesn't
+    /// change as long as the database isn't written to.
+    #[allow(clippy::type_complexity)]
+    pub fn iter_column(
+        &self,
+        column: DbColumn,
+        start: Option<&[u8]>,
+    ) -> Result<Box<dyn Iterator<Item = Result<(Vec<u8>, Vec<u8>), Error>> + '_>, Error> {
+        if !column.is_block() {
+            let mut entries = self.db.iter(column.index())?;
+            match start {
+                Some(key) => entries.seek(key)?,
+                None => entries.seek_to_first()?,
+            }
+            return Ok(Box::new(iter::from_fn(move || {
+                entries.next().map_err(Error::from).transpose()
+            })));
+        }
+
+        // Values can only be listed through a callback, which is run on a
+        // thread of its own to be turned into an iterator.
+        let db = Arc::clone(&self.db);
+        let (tx, rx) = mpsc::sync_channel(1024);
+        thread::spawn(move || {
+            let mut unlisted = 0u64;
+            let mut failure = None;
+            let listed = db.iter_column_while(column.index(), |entry| {
+                match block_keys(&db, column, &entry.value) {
+                    Ok(keys) if keys.is_empty() => {
+                        unlisted += 1;
+                        true
+                    }
+                    Ok(keys) => keys
+                        .into_iter()
+                        .all(|key| tx.send(Ok((key, entry.value.clone()))).is_ok()),
+                    Err(e) => {
+                        failure = Some(e);
+                        false
+                    }
+                }
+            });
+            // The listing fails rather than leaving entries out, as a copy of
+            // the column would then be incomplete.
+            let unlisted = (unlisted > 0).then(|| {
+                Error::Other(format!(
+                    "{unlisted} entries of the {} column have a key that can't be recomputed from their data",
+                    column.name()
+                ))
+            });
+            if let Some(e) = failure.or(listed.err().map(Error::from)).or(unlisted) {
+                let _ = tx.send(Err(e));
+            }
+        });
+
+        let mut start = start.map(<[u8]>::to_vec);
+        Ok(Box::new(iter::from_fn(move || loop {
+            match rx.recv() {
+                Ok(Ok((key, value))) => {
+                    if start.as_ref().map_or(true, |start| *start == key) {
+                        start = None;
+                        return Some(Ok((key, value)));
+                    }
+                }
+                Ok(Err(e)) => return Some(Err(e)),
+                Err(_) => {
+                    return start
+                        .take()
+                        .map(|_| Err(Error::Other("Start key not found in the column".into())))
+                }
+            }
+        })))
+    }
+}
+
+/// Keys of the block of the blocks column whose data is `value`, among the CIDs
+/// of the [`BLOCK_CID_KINDS`].
+fn block_keys(db: &Db, column: DbColumn, value: &[u8]) -> Result<Vec<Vec<u8>>, Error> {
+    let mut keys = vec![];
+    for (codec, code) in BLOCK_CID_KINDS {
+        let key = Cid::new_v1(codec, code.digest(value)).to_bytes();
+        if db.get_size(column.index(), &key)?.is_some() {
+            keys.push(key);
+        }
+    }
+    Ok(keys)
 }
 
 impl Store for ParityDb {
@@ -64,7 +322,8 @@ impl Store for ParityDb {
     where
         K: AsRef<[u8]>,
     {
-        self.db.get(0, key.as_ref()).map_err(Error::from)
+        let key = key.as_ref();
+        Ok(self.db.get(DbColumn::of_key(key).index(), key)?)
     }
 
     fn write<K, V>(&self, key: K, value: V) -> Result<(), Error>
@@ -72,66 +331,66 @@ impl Store for ParityDb {
         K: AsRef<[u8]>,
         V: AsRef<[u8]>,
     {
-        let tx = [(0, key.as_ref(), Some(value.as_ref().to_vec()))];
-        self.db.commit(tx).map_err(Error::from)
+        let column = DbColumn::of_key(key.as_ref()).index();
+        let change = Operation::Set(key.as_ref().to_vec(), value.as_ref().to_vec());
+        self.commit(vec![(column, change)])
     }
 
-    /// [ParityDB::commit] API is doing extra allocations on keys,
-    /// See <https://docs.rs/crate/parity-db/0.4.3/source/src/db.rs>
     fn bulk_write(
         &self,
         values: impl IntoIterator<Item = (impl Into<Vec<u8>>, impl Into<Vec<u8>>)>,
     ) -> Result<(), Error> {
-        let tx = values
+        let changes = values
+            .into_iter()
+            .map(|(k, v)| {
+                let k = k.into();
+                (DbColumn::of_key(&k).index(), Operation::Set(k, v.into()))
+            })
+            .collect();
+        self.commit(changes)
+    }
+
+    fn bulk_write_column(
+        &self,
+        column: DbColumn,
+        values: impl IntoIterator<Item = (impl Into<Vec<u8>>, impl Into<Vec<u8>>)>,
+    ) -> Result<(), Error> {
+        let changes = values
             .into_iter()
-            .map(|(k, v)| (0, Operation::Set(k.into(), v.into())));
-        self.db.commit_changes(tx).map_err(Error::from)
-        // <https://docs.rs/crate/parity-db/0.4.3/source/src/db.rs>
-        // ```
-        // fn commit<I, K>(&self, tx: I) -> Result<()>
-        // where
-        //     I: IntoIterator<Item = (ColId, K, Option<Value>)>,
-        //     K: AsRef<[u8]>,
-        // {
-        //     self.commit_changes(tx.into_iter().map(|(c, k, v)| {
-        //         (
-        //             c,
-        //             match v {
-        //                 Some(v) => Operation::Set(k.as_ref().to_vec(), v),
-        //                 None => Operation::Dereference(k.as_ref().to_vec()),
-        //             },
-        //         )
-        //     }))
-        // }
-        // ```
+            .map(|(k, v)| (column.index(), Operation::Set(k.into(), v.into())))
+            .collect();
+        self.commit(changes)
     }
 
     fn delete<K>(&self, key: K) -> Result<(), Error>
     where
         K: AsRef<[u8]>,
     {
-        let tx = [(0, key.as_ref(), None)];
-        self.db.commit(tx).map_err(Error::from)
+        let key = key.as_ref();
+        let change = Operation::Dereference(key.to_vec());
+        self.commit(vec![(DbColumn::of_key(key).index(), change)])
     }
 
     fn exists<K>(&self, key: K) -> Result<bool, Error>
     where
         K: AsRef<[u8]>,
     {
-        self.db
-            .get_size(0, key.as_ref())
-            .map(|size| size.is_some())
-            .map_err(Error::from)
+        let key = key.as_ref();
+        Ok(self
+            .db
+            .get_size(DbColumn::of_key(key).index(), key)?
+            .is_some())
     }
 }
 
 impl Blockstore for ParityDb {
     fn get(&self, k: &Cid) -> anyhow::Result<Option<Vec<u8>>> {
-        self.read(k.to_bytes()).map_err(|e| e.into())
+        Ok(self.db.get(DbColumn::Blocks.index(), &k.to_bytes())?)
     }
 
     fn put_keyed(&self, k: &Cid, block: &[u8]) -> anyhow::Result<()> {
-        self.write(k.to_bytes(), block).map_err(|e| e.into())
+        let change = Operation::Set(k.to_bytes(), block.to_vec());
+        Ok(self.commit(vec![(DbColumn::Blocks.index(), change)])?)
     }
 
     fn put_many_keyed<D, I>(&self, blocks: I) -> anyhow::Result<()>
@@ -144,7 +403,8 @@ impl Blockstore for ParityDb {
             .into_iter()
             .map(|(k, v)| (k.to_bytes(), v.as_ref().to_vec()))
             .collect::<Vec<_>>();
-        self.bulk_write(values).map_err(|e| e.into())
+        self.bulk_write_column(DbColumn::Blocks, values)
+            .map_err(|e| e.into())
     }
 }
 
@@ -168,25 +428,114 @@ impl BitswapStoreReadWrite for ParityDb {
     }
 }
 
+impl DbCheckpoint for ParityDb {
+    fn backend_name(&self) -> &'static str {
+        crate::db_engine::DbBackend::ParityDb.name()
+    }
+
+    /// `ParityDb` has no snapshots, so all the entries are listed and copied
+    /// into a new database while the writes go on. The entries written in the
+    /// meantime are then copied again, and the ones written during that copy
+    /// once more with the writes paused, which only lasts as long as copying
+    /// them.
+    fn checkpoint(&self, dir: &Path) -> anyhow::Result<()> {
+        anyhow::ensure!(!dir.exists(), "{} already exists", dir.display());
+        {
+            let mut recent = self.recent_writes.lock();
+            anyhow::ensure!(
+                recent.checkpoint.is_none(),
+                "A checkpoint of the database is already being taken"
+            );
+            // The latest writes may be missing from the listing.
+            recent.checkpoint = Some(recent.keys());
+        }
+        let copied = self.copy_into(dir);
+        self.recent_writes.lock().checkpoint = None;
+        copied
+    }
+}
+
+impl ParityDb {
+    fn copy_into(&self, dir: &Path) -> anyhow::Result<()> {
+        let copy = ParityDb::open(dir.to_owned(), &self.config)?;
+        for column in DbColumn::ALL {
+            let mut batch = Vec::with_capacity(CHECKPOINT_BATCH_SIZE);
+            for entry in self.iter_column(column, None)? {
+                batch.push(entry?);
+                if batch.len() == CHECKPOINT_BATCH_SIZE {
+                    copy.bulk_write_column(column, batch.drain(..))?;
+                }
+            }
+            copy.bulk_write_column(column, batch)?;
+        }
+        let written = self.recent_writes.lock().checkpoint.replace(vec![]);
+        self.copy_entries(&copy, written.unwrap_or_default())?;
+        let _writes = self.writes.write();
+        let written = self.recent_writes.lock().checkpoint.take();
+        self.copy_entries(&copy, written.unwrap_or_default())?;
+        Ok(())
+    }
+
+    /// Copies the current value of the entries into `copy`, or deletes them
+    /// from it when they were deleted.
+    fn copy_entries(&self, copy: &ParityDb, mut keys: Vec<(u8, Vec<u8>)>) -> Result<(), Error> {
+        keys.sort_unstable();
+        keys.dedup();
+        for keys in keys.chunks(CHECKPOINT_BATCH_SIZE) {
+            let changes = keys
+                .iter()
+                .map(|(column, key)| {
+                    let change = match self.db.get(*column, key)? {
+                        Some(value) => Operation::Set(key.clone(), value),
+                        None => Operation::Dereference(key.clone()),
+                    };
+                    Ok((*column, change))
+                })
+                .collect::<Result<_, Error>>()?;
+            copy.commit(changes)?;
+        }
+        Ok(())
+    }
+}
+
 impl DBStatistics for ParityDb {
-    fn get_statistics(&self) -> Option<String> {
+    fn get_statistics(&self) -> Option<DbStats> {
         if !self.statistics_enabled {
             return None;
         }
 
-        let mut buf = Vec::new();
-        if let Err(err) = self.db.write_stats_text(&mut buf, None) {
-            warn!("Unable to write database statistics: {err}");
-            return None;
-        }
+        let columns = self
+            .db
+            .stats()
+            .columns
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, column)| {
+                column.map(|column| ColumnStats {
+                    name: DbColumn::from_index(i as u8)
+                        .map_or_else(|| format!("col{i}"), |column| column.name().into()),
+                    keys: column.total_values,
+                    bytes: column.total_bytes,
+                })
+            })
+            .collect();
 
-        match String::from_utf8(buf) {
-            Ok(stats) => Some(stats),
-            Err(e) => {
-                warn!("Malformed statistics: {e}");
+        let mut buf = Vec::new();
+        let raw = match self.db.write_stats_text(&mut buf, None) {
+            Ok(()) => String::from_utf8(buf)
+                .map_err(|e| warn!("Malformed statistics: {e}"))
+                .ok(),
+            Err(err) => {
+                warn!("Unable to write database statistics: {err}");
                 None
             }
-        }
+        };
+
+        Some(DbStats {
+            columns,
+            raw,
+            ..Default::default()
+        })
     }
 }
 
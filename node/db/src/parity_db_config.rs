@@ -1,6 +1,8 @@
 // Copyright 2019-2023 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+use std::collections::BTreeMap;
+
 use serde::{Deserialize, Serialize};
 
 /// `ParityDb` configuration exposed in Forest.
@@ -9,6 +11,17 @@ use serde::{Deserialize, Serialize};
 pub struct ParityDbConfig {
     pub enable_statistics: bool,
     pub compression_type: String,
+    /// Settings of the columns overriding the ones above, by column name
+    /// (`blocks`, `metadata` or `indices`).
+    pub columns: BTreeMap<String, ParityDbColumnConfig>,
+}
+
+/// Settings of a `ParityDb` column, unset ones falling back to the database
+/// wide ones.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct ParityDbColumnConfig {
+    pub compression_type: Option<String>,
 }
 
 impl Default for ParityDbConfig {
@@ -16,6 +29,7 @@ impl Default for ParityDbConfig {
         Self {
             enable_statistics: false,
             compression_type: "lz4".into(),
+            columns: BTreeMap::new(),
         }
     }
 }
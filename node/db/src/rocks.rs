@@ -1,19 +1,32 @@
 // Copyright 2019-2023 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use std::{path::Path, sync::Arc};
+use std::{
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+};
 
 use anyhow::anyhow;
 use cid::Cid;
 use forest_libp2p_bitswap::{BitswapStoreRead, BitswapStoreReadWrite};
 use fvm_ipld_blockstore::Blockstore;
+use log::{info, warn};
+use parking_lot::Mutex;
 use rocksdb::{
-    BlockBasedOptions, Cache, DBCompactionStyle, DBCompressionType, DataBlockIndexType, LogLevel,
+    checkpoint::Checkpoint, BlockBasedOptions, Cache, ColumnFamily, ColumnFamilyDescriptor,
+    DBCompactionStyle, DBCompressionType, DataBlockIndexType, Direction, IteratorMode, LogLevel,
     Options, WriteBatch, WriteOptions, DB,
 };
 
 use super::{errors::Error, Store};
-use crate::{metrics, rocks_config::RocksDbConfig, DBStatistics};
+use crate::{
+    metrics, rocks_config::RocksDbConfig, ColumnStats, DBStatistics, DbCheckpoint, DbColumn,
+    DbStats, FileStats,
+};
 
 lazy_static::lazy_static! {
     static ref WRITE_OPT_NO_WAL: WriteOptions = {
@@ -109,6 +122,40 @@ mod test {
         }
     }
 
+    #[test]
+    fn statistics_counter_test() {
+        let raw = "rocksdb.block.cache.miss COUNT : 7\n\
+                   rocksdb.block.cache.hit COUNT : 42\n\
+                   rocksdb.compaction.times.micros P50 : 1.0 P95 : 2.0 P99 : 3.0 P100 : 4.0 COUNT : 3 SUM : 10\n";
+        assert_eq!(statistics_counter(raw, "rocksdb.block.cache.hit"), Some(42));
+        assert_eq!(statistics_counter(raw, "rocksdb.block.cache.miss"), Some(7));
+        assert_eq!(
+            statistics_counter(raw, "rocksdb.compaction.times.micros"),
+            Some(3)
+        );
+        assert_eq!(statistics_counter(raw, "rocksdb.stall.micros"), None);
+    }
+
+    #[test]
+    fn level_stats_test() {
+        let table = "Level Files Size(MB)\n--------------------\n  0        2        1\n  1        4       12\n";
+        assert_eq!(
+            level_stats(table),
+            vec![
+                FileStats {
+                    name: "L0".into(),
+                    files: 2,
+                    bytes: 1024 * 1024,
+                },
+                FileStats {
+                    name: "L1".into(),
+                    files: 4,
+                    bytes: 12 * 1024 * 1024,
+                },
+            ]
+        );
+    }
+
     #[test]
     fn compression_style_from_str_test() {
         let test_cases = vec![
@@ -148,6 +195,45 @@ mod test {
 pub struct RocksDb {
     pub db: Arc<DB>,
     options: Options,
+    migration: Arc<DefaultColumnMigration>,
+    migration_thread: Arc<MigrationThread>,
+}
+
+/// State of the move of the entries of the default column family, where all
+/// of them were written before the database had columns, to their column.
+/// Until the move is done, the keys are looked up in the default column family
+/// first, and the keys written or deleted are removed from it under `lock`, so
+/// that the move never brings back an older value.
+#[derive(Default)]
+struct DefaultColumnMigration {
+    running: AtomicBool,
+    stop: AtomicBool,
+    lock: Mutex<()>,
+}
+
+impl DefaultColumnMigration {
+    fn is_running(&self) -> bool {
+        self.running.load(Ordering::Acquire)
+    }
+}
+
+/// Thread moving the default column family in the background, stopped and
+/// waited for when the last handle to the database is dropped, so that the
+/// database is closed then.
+struct MigrationThread {
+    migration: Arc<DefaultColumnMigration>,
+    handle: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl Drop for MigrationThread {
+    fn drop(&mut self) {
+        self.migration.stop.store(true, Ordering::Release);
+        if let Some(handle) = self.handle.get_mut().take() {
+            if handle.join().is_err() {
+                warn!("The move of the database entries to their column family panicked");
+            }
+        }
+    }
 }
 
 /// `RocksDb` is used as the KV store for Forest
@@ -202,19 +288,260 @@ impl RocksDb {
         db_opts
     }
 
+    /// Options of a column family: the database wide settings, overridden by
+    /// the ones of the column.
+    fn column_options(config: &RocksDbConfig, column: DbColumn) -> Options {
+        let Some(overrides) = config.columns.get(column.name()) else {
+            return Self::to_options(config);
+        };
+        Self::to_options(&RocksDbConfig {
+            write_buffer_size: overrides
+                .write_buffer_size
+                .unwrap_or(config.write_buffer_size),
+            compaction_style: overrides
+                .compaction_style
+                .clone()
+                .unwrap_or_else(|| config.compaction_style.clone()),
+            compression_type: overrides
+                .compression_type
+                .clone()
+                .unwrap_or_else(|| config.compression_type.clone()),
+            optimize_filters_for_hits: overrides
+                .optimize_filters_for_hits
+                .unwrap_or(config.optimize_filters_for_hits),
+            optimize_for_point_lookup: overrides
+                .optimize_for_point_lookup
+                .unwrap_or(config.optimize_for_point_lookup),
+            ..config.clone()
+        })
+    }
+
     pub fn open<P>(path: P, config: &RocksDbConfig) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+    {
+        for name in config.columns.keys() {
+            if !DbColumn::ALL.iter().any(|column| column.name() == name) {
+                warn!("Ignoring the settings of unknown column {name}");
+            }
+        }
+        let mut db_opts = Self::to_options(config);
+        db_opts.create_missing_column_families(true);
+        let columns = DbColumn::ALL.map(|column| {
+            ColumnFamilyDescriptor::new(column.name(), Self::column_options(config, column))
+        });
+        Ok(Self::new(
+            DB::open_cf_descriptors(&db_opts, path, columns)?,
+            db_opts,
+        ))
+    }
+
+    /// Opens the database for reading only, without taking its lock, so that
+    /// it can be inspected while the node runs. Nothing is written to it.
+    pub fn open_read_only<P>(path: P, config: &RocksDbConfig) -> Result<Self, Error>
     where
         P: AsRef<Path>,
     {
         let db_opts = Self::to_options(config);
-        Ok(Self {
-            db: Arc::new(DB::open(&db_opts, path)?),
-            options: db_opts,
-        })
+        let columns = DbColumn::ALL.map(|column| {
+            ColumnFamilyDescriptor::new(column.name(), Self::column_options(config, column))
+        });
+        Ok(Self::new(
+            DB::open_cf_descriptors_read_only(&db_opts, path, columns, false)?,
+            db_opts,
+        ))
     }
 
-    pub fn get_statistics(&self) -> Option<String> {
-        self.options.get_statistics()
+    fn new(db: DB, options: Options) -> Self {
+        let migration = Arc::new(DefaultColumnMigration::default());
+        if db.iterator(IteratorMode::Start).next().is_some() {
+            migration.running.store(true, Ordering::Release);
+        }
+        Self {
+            db: Arc::new(db),
+            options,
+            migration_thread: Arc::new(MigrationThread {
+                migration: migration.clone(),
+                handle: Default::default(),
+            }),
+            migration,
+        }
+    }
+
+    /// Moves the entries of the database written before it had columns to
+    /// their column family, and returns once they are all moved. Meant for the
+    /// tools that list the entries of the database, which only sees the moved
+    /// ones.
+    pub fn migrate_default_column(&self) -> Result<(), Error> {
+        if self.migration.is_running() {
+            migrate_default_column(&self.db, &self.migration)?;
+        }
+        Ok(())
+    }
+
+    /// Moves the entries of the database written before it had columns to
+    /// their column family in the background, where they stay readable in the
+    /// meantime. The move stops when the database is dropped, and goes on the
+    /// next time it is started.
+    pub fn spawn_default_column_migration(&self) {
+        let mut handle = self.migration_thread.handle.lock();
+        if !self.migration.is_running() || handle.is_some() {
+            return;
+        }
+        let (db, migration) = (self.db.clone(), self.migration.clone());
+        *handle = Some(thread::spawn(move || {
+            if let Err(e) = migrate_default_column(&db, &migration) {
+                warn!("Failed to move the database entries to their column family: {e}");
+            }
+        }));
+    }
+
+    /// Whether the entries of the database written before it had columns are
+    /// still being moved to their column family.
+    pub fn is_migrating_default_column(&self) -> bool {
+        self.migration.is_running()
+    }
+
+    fn cf(&self, column: DbColumn) -> Result<&ColumnFamily, Error> {
+        column_family(&self.db, column)
+    }
+
+    /// Estimated number of keys in the database.
+    pub fn estimate_num_keys(&self) -> Option<u64> {
+        DbColumn::ALL
+            .into_iter()
+            .map(|column| {
+                self.db
+                    .property_int_value_cf(self.cf(column).ok()?, "rocksdb.estimate-num-keys")
+                    .ok()
+                    .flatten()
+            })
+            .sum()
+    }
+
+    /// Iterates over the key-value pairs of a column in key order, from the
+    /// given key included or from the first key. Fails while entries written
+    /// before the database had columns are left to move, see
+    /// [`RocksDb::migrate_default_column`], as they would be missed.
+    pub fn iter_column(
+        &self,
+        column: DbColumn,
+        start: Option<&[u8]>,
+    ) -> Result<impl Iterator<Item = Result<(Vec<u8>, Vec<u8>), Error>> + '_, Error> {
+        if self.migration.is_running() {
+            return Err(Error::Other(
+                "The database entries written before it had columns are still being moved".into(),
+            ));
+        }
+        let mode = match start {
+            Some(key) => IteratorMode::From(key, Direction::Forward),
+            None => IteratorMode::Start,
+        };
+        Ok(self.db.iterator_cf(self.cf(column)?, mode).map(|item| {
+            item.map(|(k, v)| (k.into_vec(), v.into_vec()))
+                .map_err(Error::from)
+        }))
+    }
+
+    /// Looks a key up in its column.
+    fn read_key(&self, key: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+        if self.migration.is_running() {
+            if let Some(value) = self.db.get(key)? {
+                return Ok(Some(value));
+            }
+        }
+        Ok(self.db.get_cf(self.cf(DbColumn::of_key(key))?, key)?)
+    }
+
+    /// Writes the batch of changes to `keys`. While the default column family
+    /// is migrated, the keys are also deleted from it, see
+    /// [`DefaultColumnMigration`].
+    fn commit<'a>(
+        &self,
+        mut batch: WriteBatch,
+        keys: impl IntoIterator<Item = &'a [u8]>,
+        write_opts: &WriteOptions,
+    ) -> Result<(), Error> {
+        let _guard = self.migration.is_running().then(|| {
+            let guard = self.migration.lock.lock();
+            for key in keys {
+                batch.delete(key);
+            }
+            guard
+        });
+        Ok(self.db.write_opt(batch, write_opts)?)
+    }
+
+    fn write_column(
+        &self,
+        column: DbColumn,
+        values: impl IntoIterator<Item = (Vec<u8>, Vec<u8>)>,
+    ) -> Result<(), Error> {
+        let cf = self.cf(column)?;
+        let values: Vec<_> = values.into_iter().collect();
+        let mut batch = WriteBatch::default();
+        for (k, v) in &values {
+            batch.put_cf(cf, k, v);
+        }
+        self.commit(
+            batch,
+            values.iter().map(|(k, _)| k.as_slice()),
+            &WRITE_OPT_NO_WAL,
+        )
+    }
+}
+
+fn column_family(db: &DB, column: DbColumn) -> Result<&ColumnFamily, Error> {
+    db.cf_handle(column.name())
+        .ok_or_else(|| Error::Other(format!("Column family {} not found", column.name())))
+}
+
+/// Moves the entries of the default column family to their column, in batches
+/// that each write and delete their entries at once, so that an interrupted
+/// move goes on where it stopped. Returns early, with the move unfinished, when
+/// asked to stop.
+fn migrate_default_column(db: &DB, migration: &DefaultColumnMigration) -> Result<(), Error> {
+    const BATCH_SIZE: usize = 10_000;
+    const PROGRESS_INTERVAL: u64 = 1_000_000;
+    let total = db
+        .property_int_value("rocksdb.estimate-num-keys")
+        .ok()
+        .flatten()
+        .unwrap_or_default();
+    info!("Moving about {total} database entries to their column family, this is only done once");
+    let mut entries = db.iterator(IteratorMode::Start);
+    let mut keys = Vec::with_capacity(BATCH_SIZE);
+    let mut moved = 0;
+    loop {
+        if migration.stop.load(Ordering::Acquire) {
+            info!("Stopped moving the database entries to their column family after {moved}, the move goes on the next time");
+            return Ok(());
+        }
+        keys.clear();
+        for entry in entries.by_ref().take(BATCH_SIZE) {
+            keys.push(entry?.0);
+        }
+        if keys.is_empty() {
+            migration.running.store(false, Ordering::Release);
+            info!("Moved {moved} database entries to their column family");
+            return Ok(());
+        }
+        let _guard = migration.lock.lock();
+        let mut batch = WriteBatch::default();
+        for key in &keys {
+            // The entry may have been written or deleted since it was listed.
+            let Some(value) = db.get(key)? else {
+                continue;
+            };
+            batch.put_cf(column_family(db, DbColumn::of_key(key))?, key, value);
+            batch.delete(key);
+        }
+        db.write(batch)?;
+        let previous = moved;
+        moved += keys.len() as u64;
+        if moved / PROGRESS_INTERVAL > previous / PROGRESS_INTERVAL {
+            info!("Moved {moved} of about {total} database entries to their column family");
+        }
     }
 }
 
@@ -223,7 +550,7 @@ impl Store for RocksDb {
     where
         K: AsRef<[u8]>,
     {
-        self.db.get(key).map_err(Error::from)
+        self.read_key(key.as_ref())
     }
 
     fn write<K, V>(&self, key: K, value: V) -> Result<(), Error>
@@ -231,50 +558,89 @@ impl Store for RocksDb {
         K: AsRef<[u8]>,
         V: AsRef<[u8]>,
     {
-        Ok(self.db.put_opt(key, value, &WRITE_OPT_NO_WAL)?)
+        let key = key.as_ref();
+        let mut batch = WriteBatch::default();
+        batch.put_cf(self.cf(DbColumn::of_key(key))?, key, value);
+        self.commit(batch, [key], &WRITE_OPT_NO_WAL)
     }
 
     fn delete<K>(&self, key: K) -> Result<(), Error>
     where
         K: AsRef<[u8]>,
     {
-        Ok(self.db.delete(key)?)
+        let key = key.as_ref();
+        let mut batch = WriteBatch::default();
+        batch.delete_cf(self.cf(DbColumn::of_key(key))?, key);
+        self.commit(batch, [key], &WriteOptions::default())
     }
 
     fn exists<K>(&self, key: K) -> Result<bool, Error>
     where
         K: AsRef<[u8]>,
     {
-        self.db
-            .get_pinned(key)
-            .map(|v| v.is_some())
-            .map_err(Error::from)
+        let key = key.as_ref();
+        if self.migration.is_running() && self.db.get_pinned(key)?.is_some() {
+            return Ok(true);
+        }
+        Ok(self
+            .db
+            .get_pinned_cf(self.cf(DbColumn::of_key(key))?, key)?
+            .is_some())
     }
 
     fn bulk_write(
         &self,
         values: impl IntoIterator<Item = (impl Into<Vec<u8>>, impl Into<Vec<u8>>)>,
     ) -> Result<(), Error> {
+        let values: Vec<(Vec<u8>, Vec<u8>)> = values
+            .into_iter()
+            .map(|(k, v)| (k.into(), v.into()))
+            .collect();
         let mut batch = WriteBatch::default();
-        for (k, v) in values {
-            batch.put(k.into(), v.into());
+        for (k, v) in &values {
+            batch.put_cf(self.cf(DbColumn::of_key(k))?, k, v);
         }
-        Ok(self.db.write_without_wal(batch)?)
+        self.commit(
+            batch,
+            values.iter().map(|(k, _)| k.as_slice()),
+            &WRITE_OPT_NO_WAL,
+        )
+    }
+
+    fn bulk_write_column(
+        &self,
+        column: DbColumn,
+        values: impl IntoIterator<Item = (impl Into<Vec<u8>>, impl Into<Vec<u8>>)>,
+    ) -> Result<(), Error> {
+        self.write_column(
+            column,
+            values.into_iter().map(|(k, v)| (k.into(), v.into())),
+        )
     }
 
     fn flush(&self) -> Result<(), Error> {
-        self.db.flush().map_err(|e| Error::Other(e.to_string()))
+        for column in DbColumn::ALL {
+            self.db
+                .flush_cf(self.cf(column)?)
+                .map_err(|e| Error::Other(e.to_string()))?;
+        }
+        Ok(())
     }
 }
 
 impl Blockstore for RocksDb {
     fn get(&self, k: &Cid) -> anyhow::Result<Option<Vec<u8>>> {
-        self.read(k.to_bytes()).map_err(Into::into)
+        self.read_key(&k.to_bytes()).map_err(Into::into)
     }
 
+    /// Blocks never change, so they are written without deleting an older
+    /// copy from the default column family.
     fn put_keyed(&self, k: &Cid, block: &[u8]) -> anyhow::Result<()> {
         metrics::BLOCK_SIZE_BYTES.observe(block.len() as f64);
-        self.write(k.to_bytes(), block).map_err(Into::into)
+        let cf = self.cf(DbColumn::Blocks)?;
+        Ok(self
+            .db
+            .put_cf_opt(cf, k.to_bytes(), block, &WRITE_OPT_NO_WAL)?)
     }
 
     fn put_many_keyed<D, I>(&self, blocks: I) -> anyhow::Result<()>
@@ -283,12 +649,13 @@ impl Blockstore for RocksDb {
         D: AsRef<[u8]>,
         I: IntoIterator<Item = (Cid, D)>,
     {
+        let cf = self.cf(DbColumn::Blocks)?;
         let mut batch = WriteBatch::default();
         for (cid, v) in blocks.into_iter() {
             let k = cid.to_bytes();
             let v = v.as_ref();
             metrics::BLOCK_SIZE_BYTES.observe(v.len() as f64);
-            batch.put(k, v);
+            batch.put_cf(cf, k, v);
         }
         // This function is used in `fvm_ipld_car::load_car`
         // It reduces time cost of loading mainnet snapshot
@@ -317,8 +684,92 @@ impl BitswapStoreReadWrite for RocksDb {
     }
 }
 
+impl DbCheckpoint for RocksDb {
+    fn backend_name(&self) -> &'static str {
+        crate::db_engine::DbBackend::RocksDb.name()
+    }
+
+    /// The `SST` files are hard-linked into the checkpoint when it is on the
+    /// same file system, and the memtables flushed first since the writes skip
+    /// the write-ahead log.
+    fn checkpoint(&self, dir: &Path) -> anyhow::Result<()> {
+        Checkpoint::new(&self.db)?.create_checkpoint(dir)?;
+        Ok(())
+    }
+}
+
 impl DBStatistics for RocksDb {
-    fn get_statistics(&self) -> Option<String> {
-        self.options.get_statistics()
+    /// Returns `None` unless the statistics are enabled in the config.
+    fn get_statistics(&self) -> Option<DbStats> {
+        let raw = self.options.get_statistics()?;
+        let counter = |name| statistics_counter(&raw, name);
+        let mut columns = vec![];
+        let mut files: Vec<FileStats> = vec![];
+        for column in DbColumn::ALL {
+            let Ok(cf) = self.cf(column) else {
+                continue;
+            };
+            let property = |name| self.db.property_int_value_cf(cf, name).ok().flatten();
+            columns.push(ColumnStats {
+                name: column.name().into(),
+                keys: property("rocksdb.estimate-num-keys").unwrap_or_default(),
+                bytes: property("rocksdb.estimate-live-data-size").unwrap_or_default(),
+            });
+            let levels = self
+                .db
+                .property_value_cf(cf, "rocksdb.levelstats")
+                .ok()
+                .flatten()
+                .map(|levels| level_stats(&levels))
+                .unwrap_or_default();
+            // The files of a level are summed over the column families.
+            for level in levels {
+                match files.iter_mut().find(|file| file.name == level.name) {
+                    Some(file) => {
+                        file.files += level.files;
+                        file.bytes += level.bytes;
+                    }
+                    None => files.push(level),
+                }
+            }
+        }
+        Some(DbStats {
+            columns,
+            files,
+            cache_hits: counter("rocksdb.block.cache.hit"),
+            cache_misses: counter("rocksdb.block.cache.miss"),
+            compactions: counter("rocksdb.compaction.times.micros"),
+            write_stall_micros: counter("rocksdb.stall.micros"),
+            raw: Some(raw),
+        })
     }
 }
+
+/// Reads the count of a ticker or histogram of a statistics dump, in lines
+/// like `rocksdb.block.cache.hit COUNT : 42`.
+fn statistics_counter(raw: &str, name: &str) -> Option<u64> {
+    raw.lines().find_map(|line| {
+        let rest = line.strip_prefix(name)?.strip_prefix(' ')?;
+        let (_, count) = rest.split_once("COUNT : ")?;
+        count.split_whitespace().next()?.parse().ok()
+    })
+}
+
+/// Parses the `rocksdb.levelstats` table of the number of files and size in
+/// megabytes of each level.
+fn level_stats(table: &str) -> Vec<FileStats> {
+    table
+        .lines()
+        .filter_map(|line| {
+            let mut columns = line.split_whitespace();
+            let level: u64 = columns.next()?.parse().ok()?;
+            let files = columns.next()?.parse().ok()?;
+            let megabytes: f64 = columns.next()?.parse().ok()?;
+            Some(FileStats {
+                name: format!("L{level}"),
+                files,
+                bytes: (megabytes * 1024. * 1024.) as u64,
+            })
+        })
+        .collect()
+}
@@ -1,5 +1,7 @@
 // Copyright 2019-2023 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
+use std::collections::BTreeMap;
+
 use num_cpus;
 use serde::{Deserialize, Serialize};
 
@@ -23,6 +25,21 @@ pub struct RocksDbConfig {
     pub log_level: String,
     pub optimize_filters_for_hits: bool,
     pub optimize_for_point_lookup: i32,
+    /// Settings of the column families overriding the ones above, by column
+    /// name (`blocks`, `metadata` or `indices`).
+    pub columns: BTreeMap<String, RocksDbColumnConfig>,
+}
+
+/// Settings of a `RocksDB` column family, unset ones falling back to the
+/// database wide ones.
+#[derive(Clone, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct RocksDbColumnConfig {
+    pub write_buffer_size: Option<usize>,
+    pub compaction_style: Option<String>,
+    pub compression_type: Option<String>,
+    pub optimize_filters_for_hits: Option<bool>,
+    pub optimize_for_point_lookup: Option<i32>,
 }
 
 impl Default for RocksDbConfig {
@@ -40,6 +57,7 @@ impl Default for RocksDbConfig {
             log_level: "warn".into(),
             optimize_filters_for_hits: true,
             optimize_for_point_lookup: 8,
+            columns: BTreeMap::new(),
         }
     }
 }
@@ -0,0 +1,48 @@
+// Copyright 2019-2023 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use serde::{Deserialize, Serialize};
+
+/// Statistics of a database. The counters a backend doesn't track are left
+/// unset.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct DbStats {
+    /// Keys and size of each column, or column family for `RocksDB`.
+    pub columns: Vec<ColumnStats>,
+    /// Data files, the `SST` files of each level for `RocksDB`.
+    pub files: Vec<FileStats>,
+    pub cache_hits: Option<u64>,
+    pub cache_misses: Option<u64>,
+    /// Number of compactions run since the database was opened.
+    pub compactions: Option<u64>,
+    /// Time writes were stalled for since the database was opened, in
+    /// microseconds.
+    pub write_stall_micros: Option<u64>,
+    /// Statistics dump of the backend, when its statistics are enabled.
+    pub raw: Option<String>,
+}
+
+impl DbStats {
+    /// Share of the reads served from the block cache.
+    pub fn cache_hit_rate(&self) -> Option<f64> {
+        let hits = self.cache_hits?;
+        let total = hits + self.cache_misses?;
+        (total > 0).then(|| hits as f64 / total as f64)
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ColumnStats {
+    pub name: String,
+    /// Number of keys, estimated by `RocksDB`.
+    pub keys: u64,
+    /// Size of the values in bytes.
+    pub bytes: u64,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FileStats {
+    pub name: String,
+    pub files: u64,
+    pub bytes: u64,
+}
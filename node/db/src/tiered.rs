@@ -0,0 +1,236 @@
+// Copyright 2019-2023 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Database split in a hot store, holding the recent chain data and all the
+//! writes, and a cold store the older blocks are moved to. Reads go through
+//! the hot store first and fall back to the cold one, so the split is
+//! invisible to the users of the database.
+
+use std::path::PathBuf;
+
+use cid::Cid;
+use forest_libp2p_bitswap::{BitswapStoreRead, BitswapStoreReadWrite};
+use fvm_ipld_blockstore::Blockstore;
+use serde::{Deserialize, Serialize};
+
+use crate::{db_engine::DbBackend, ColumnStats, DBStatistics, DbColumn, DbStats, Error, Store};
+
+/// Configuration of the tiering of the database.
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct TieredDbConfig {
+    pub enabled: bool,
+    pub cold_backend: DbBackend,
+    /// Directory of the cold store, next to the hot one by default.
+    pub cold_path: Option<PathBuf>,
+    /// Number of epochs whose headers, messages, receipts and state trees
+    /// are kept in the hot store.
+    pub hot_epochs: i64,
+    /// Number of epochs between two migrations to the cold store.
+    pub migration_interval: i64,
+}
+
+impl Default for TieredDbConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            cold_backend: DbBackend::default(),
+            cold_path: None,
+            hot_epochs: 2880, // one day
+            migration_interval: 120,
+        }
+    }
+}
+
+/// Hot store backed by a cold store.
+pub struct TieredDb<H, C> {
+    hot: H,
+    cold: C,
+}
+
+impl<H, C> TieredDb<H, C> {
+    pub fn new(hot: H, cold: C) -> Self {
+        Self { hot, cold }
+    }
+
+    pub fn hot(&self) -> &H {
+        &self.hot
+    }
+
+    pub fn cold(&self) -> &C {
+        &self.cold
+    }
+}
+
+impl<H: Store, C: Store> TieredDb<H, C> {
+    /// Moves the blocks still in the hot store to the cold store: they are
+    /// written to the cold store, which is flushed so that a crash never loses
+    /// a block, then removed from the hot one. Returns the number of blocks
+    /// moved.
+    pub fn move_to_cold(&self, cids: &[Cid]) -> Result<u64, Error> {
+        let mut blocks = vec![];
+        for cid in cids {
+            let key = cid.to_bytes();
+            if let Some(block) = self.hot.read(&key)? {
+                blocks.push((key, block));
+            }
+        }
+        if blocks.is_empty() {
+            return Ok(0);
+        }
+        self.cold
+            .bulk_write_column(DbColumn::Blocks, blocks.iter().cloned())?;
+        self.cold.flush()?;
+        let keys: Vec<_> = blocks.into_iter().map(|(key, _)| key).collect();
+        self.hot.bulk_delete(&keys)?;
+        Ok(keys.len() as u64)
+    }
+}
+
+impl<H: Store, C: Store> Store for TieredDb<H, C> {
+    fn read<K>(&self, key: K) -> Result<Option<Vec<u8>>, Error>
+    where
+        K: AsRef<[u8]>,
+    {
+        match self.hot.read(key.as_ref())? {
+            Some(value) => Ok(Some(value)),
+            None => self.cold.read(key),
+        }
+    }
+
+    fn write<K, V>(&self, key: K, value: V) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+        V: AsRef<[u8]>,
+    {
+        self.hot.write(key, value)
+    }
+
+    fn delete<K>(&self, key: K) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+    {
+        self.hot.delete(key.as_ref())?;
+        self.cold.delete(key)
+    }
+
+    fn exists<K>(&self, key: K) -> Result<bool, Error>
+    where
+        K: AsRef<[u8]>,
+    {
+        Ok(self.hot.exists(key.as_ref())? || self.cold.exists(key)?)
+    }
+
+    fn bulk_write(
+        &self,
+        values: impl IntoIterator<Item = (impl Into<Vec<u8>>, impl Into<Vec<u8>>)>,
+    ) -> Result<(), Error> {
+        self.hot.bulk_write(values)
+    }
+
+    fn bulk_write_column(
+        &self,
+        column: DbColumn,
+        values: impl IntoIterator<Item = (impl Into<Vec<u8>>, impl Into<Vec<u8>>)>,
+    ) -> Result<(), Error> {
+        self.hot.bulk_write_column(column, values)
+    }
+
+    fn bulk_delete<K>(&self, keys: &[K]) -> Result<(), Error>
+    where
+        K: AsRef<[u8]>,
+    {
+        self.hot.bulk_delete(keys)?;
+        self.cold.bulk_delete(keys)
+    }
+
+    fn flush(&self) -> Result<(), Error> {
+        self.hot.flush()?;
+        self.cold.flush()
+    }
+}
+
+impl<H: Blockstore, C: Blockstore> Blockstore for TieredDb<H, C> {
+    fn get(&self, k: &Cid) -> anyhow::Result<Option<Vec<u8>>> {
+        match self.hot.get(k)? {
+            Some(block) => Ok(Some(block)),
+            None => self.cold.get(k),
+        }
+    }
+
+    fn put_keyed(&self, k: &Cid, block: &[u8]) -> anyhow::Result<()> {
+        self.hot.put_keyed(k, block)
+    }
+
+    fn put_many_keyed<D, I>(&self, blocks: I) -> anyhow::Result<()>
+    where
+        Self: Sized,
+        D: AsRef<[u8]>,
+        I: IntoIterator<Item = (Cid, D)>,
+    {
+        self.hot.put_many_keyed(blocks)
+    }
+}
+
+impl<H: BitswapStoreRead, C: BitswapStoreRead> BitswapStoreRead for TieredDb<H, C> {
+    fn contains(&self, cid: &Cid) -> anyhow::Result<bool> {
+        Ok(self.hot.contains(cid)? || self.cold.contains(cid)?)
+    }
+
+    fn get(&self, cid: &Cid) -> anyhow::Result<Option<Vec<u8>>> {
+        match self.hot.get(cid)? {
+            Some(block) => Ok(Some(block)),
+            None => self.cold.get(cid),
+        }
+    }
+}
+
+impl<H, C> BitswapStoreReadWrite for TieredDb<H, C>
+where
+    H: BitswapStoreReadWrite<Params = libipld::DefaultParams>,
+    C: BitswapStoreRead,
+{
+    type Params = libipld::DefaultParams;
+
+    fn insert(&self, block: &libipld::Block<Self::Params>) -> anyhow::Result<()> {
+        self.hot.insert(block)
+    }
+}
+
+impl<H: DBStatistics, C: DBStatistics> DBStatistics for TieredDb<H, C> {
+    /// Statistics of both stores, the columns and files of the cold one
+    /// prefixed with `cold_`.
+    fn get_statistics(&self) -> Option<DbStats> {
+        let hot = self.hot.get_statistics();
+        let Some(cold) = self.cold.get_statistics() else {
+            return hot;
+        };
+        let mut stats = hot.unwrap_or_default();
+        stats
+            .columns
+            .extend(cold.columns.into_iter().map(|column| ColumnStats {
+                name: format!("cold_{}", column.name),
+                ..column
+            }));
+        stats.files.extend(cold.files.into_iter().map(|mut file| {
+            file.name = format!("cold_{}", file.name);
+            file
+        }));
+        stats.cache_hits = add(stats.cache_hits, cold.cache_hits);
+        stats.cache_misses = add(stats.cache_misses, cold.cache_misses);
+        stats.compactions = add(stats.compactions, cold.compactions);
+        stats.write_stall_micros = add(stats.write_stall_micros, cold.write_stall_micros);
+        stats.raw = match (stats.raw, cold.raw) {
+            (Some(hot), Some(cold)) => Some(format!("{hot}\n** Cold store **\n{cold}")),
+            (hot, cold) => hot.or(cold),
+        };
+        Some(stats)
+    }
+}
+
+fn add(a: Option<u64>, b: Option<u64>) -> Option<u64> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a + b),
+        (a, b) => a.or(b),
+    }
+}
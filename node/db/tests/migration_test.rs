@@ -0,0 +1,141 @@
+// Copyright 2019-2023 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+#[cfg(all(feature = "rocksdb", feature = "paritydb"))]
+mod migration_tests {
+    use std::path::Path;
+
+    use cid::{
+        multihash::{Code::Blake2b256, MultihashDigest},
+        Cid,
+    };
+    use forest_db::{
+        db_engine::{db_path, open_db, Db, DbBackend, DbConfig},
+        migration::{progress_file, start_progress, Migration},
+        DbColumn, Store,
+    };
+    use fvm_ipld_blockstore::Blockstore;
+    use fvm_ipld_encoding::DAG_CBOR;
+
+    const IPLD_RAW: u64 = 0x55;
+
+    fn open(dir: &Path, backend: DbBackend) -> Db {
+        let config = DbConfig {
+            backend,
+            ..Default::default()
+        };
+        open_db(&db_path(dir, backend), &config).unwrap()
+    }
+
+    #[test]
+    fn migrate_rocksdb_to_paritydb() {
+        let dir = tempfile::tempdir().unwrap();
+        let from = open(dir.path(), DbBackend::RocksDb);
+        let to = open(dir.path(), DbBackend::ParityDb);
+        let entries: Vec<_> = (0..25u32)
+            .map(|i| (i.to_be_bytes().to_vec(), vec![i as u8; 3]))
+            .collect();
+        from.bulk_write(entries.clone()).unwrap();
+
+        let progress = progress_file(&db_path(dir.path(), DbBackend::ParityDb));
+        let mut batches = 0;
+        let copied = Migration::new(&from, &to, &progress)
+            .batch_size(10)
+            .run(|_| batches += 1)
+            .unwrap();
+
+        assert_eq!(copied, 25);
+        assert_eq!(batches, 3);
+        assert!(!progress.exists());
+        for (key, value) in entries {
+            assert_eq!(to.read(key).unwrap(), Some(value));
+        }
+    }
+
+    #[test]
+    fn migrate_paritydb_to_rocksdb() {
+        let dir = tempfile::tempdir().unwrap();
+        let from = open(dir.path(), DbBackend::ParityDb);
+        let to = open(dir.path(), DbBackend::RocksDb);
+        let blocks: Vec<_> = (0..10u8)
+            .map(|i| {
+                let codec = if i % 2 == 0 { DAG_CBOR } else { IPLD_RAW };
+                (Cid::new_v1(codec, Blake2b256.digest(&[i])), vec![i])
+            })
+            .collect();
+        for (cid, data) in &blocks {
+            from.put_keyed(cid, data).unwrap();
+        }
+        from.write("head", [1]).unwrap();
+
+        let progress = progress_file(&db_path(dir.path(), DbBackend::RocksDb));
+        let copied = Migration::new(&from, &to, &progress)
+            .batch_size(4)
+            .run(|_| ())
+            .unwrap();
+
+        assert_eq!(copied, 11);
+        for (cid, data) in &blocks {
+            assert_eq!(to.get(cid).unwrap().as_ref(), Some(data));
+        }
+        assert_eq!(to.read("head").unwrap(), Some(vec![1]));
+    }
+
+    #[test]
+    fn migration_resumes_after_the_last_batch() {
+        let dir = tempfile::tempdir().unwrap();
+        let from = open(dir.path(), DbBackend::RocksDb);
+        let entries: Vec<_> = (0..25u32)
+            .map(|i| (i.to_be_bytes().to_vec(), vec![i as u8; 3]))
+            .collect();
+        from.bulk_write(entries.clone()).unwrap();
+
+        // Interrupted after copying the entries up to the 10th one.
+        let progress = progress_file(&db_path(dir.path(), DbBackend::ParityDb));
+        let mut recorded = vec![DbColumn::Metadata.index()];
+        recorded.extend_from_slice(&entries[9].0);
+        std::fs::write(&progress, recorded).unwrap();
+
+        let to = open(dir.path(), DbBackend::ParityDb);
+        let migration = Migration::new(&from, &to, &progress).batch_size(10);
+        assert!(migration.is_resuming());
+        assert_eq!(migration.run(|_| ()).unwrap(), 16);
+        assert!(!progress.exists());
+        for (i, (key, value)) in entries.into_iter().enumerate() {
+            let expected = (i >= 9).then_some(value);
+            assert_eq!(to.read(key).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn migration_interrupted_before_the_first_batch_starts_over() {
+        let dir = tempfile::tempdir().unwrap();
+        let from = open(dir.path(), DbBackend::RocksDb);
+        from.write("head", [1]).unwrap();
+
+        let progress = progress_file(&db_path(dir.path(), DbBackend::ParityDb));
+        start_progress(&progress).unwrap();
+        let to = open(dir.path(), DbBackend::ParityDb);
+        let migration = Migration::new(&from, &to, &progress);
+        assert!(!migration.is_resuming());
+        assert_eq!(migration.run(|_| ()).unwrap(), 1);
+        assert_eq!(to.read("head").unwrap(), Some(vec![1]));
+    }
+
+    #[test]
+    fn migration_fails_on_blocks_that_cant_be_listed() {
+        const DAG_JSON: u64 = 0x0129;
+        let dir = tempfile::tempdir().unwrap();
+        let from = open(dir.path(), DbBackend::ParityDb);
+        let to = open(dir.path(), DbBackend::RocksDb);
+        from.put_keyed(&Cid::new_v1(DAG_CBOR, Blake2b256.digest(&[0])), &[0])
+            .unwrap();
+        from.put_keyed(&Cid::new_v1(DAG_JSON, Blake2b256.digest(&[1])), &[1])
+            .unwrap();
+
+        let progress = progress_file(&db_path(dir.path(), DbBackend::RocksDb));
+        start_progress(&progress).unwrap();
+        assert!(Migration::new(&from, &to, &progress).run(|_| ()).is_err());
+        assert!(progress.exists());
+    }
+}
@@ -58,4 +58,12 @@ mod paritydb_tests {
         let db = TempParityDB::new();
         subtests::bulk_delete(&*db);
     }
+
+    #[test]
+    fn db_checkpoint() {
+        let db = TempParityDB::new();
+        subtests::checkpoint(&*db, |path| {
+            forest_db::parity_db::ParityDb::open(path.to_owned(), &Default::default()).unwrap()
+        });
+    }
 }
@@ -59,4 +59,98 @@ mod rocksdb_tests {
         let db = TempRocksDB::new();
         subtests::bulk_delete(&*db);
     }
+
+    #[test]
+    fn db_checkpoint() {
+        let db = TempRocksDB::new();
+        subtests::checkpoint(&*db, |path| {
+            forest_db::rocks::RocksDb::open(path, &Default::default()).unwrap()
+        });
+    }
+
+    #[test]
+    fn db_moves_default_column_entries() {
+        use cid::{
+            multihash::{Code::Blake2b256, MultihashDigest},
+            Cid,
+        };
+        use forest_db::{rocks::RocksDb, rocks_config::RocksDbConfig, DbColumn, Store};
+
+        let dir = tempfile::tempdir().unwrap();
+        let cid = Cid::new_v1(fvm_ipld_encoding::DAG_CBOR, Blake2b256.digest(b"block"));
+        {
+            let db = rocksdb::DB::open_default(dir.path()).unwrap();
+            db.put(cid.to_bytes(), b"block").unwrap();
+            db.put(b"head", b"tipset").unwrap();
+        }
+
+        let db = RocksDb::open(dir.path(), &RocksDbConfig::default()).unwrap();
+        // The entries are readable before they are moved, but not listed.
+        assert!(db.is_migrating_default_column());
+        assert_eq!(db.read("head").unwrap(), Some(b"tipset".to_vec()));
+        assert!(db.iter_column(DbColumn::Metadata, None).is_err());
+
+        db.migrate_default_column().unwrap();
+        assert!(!db.is_migrating_default_column());
+        assert!(db
+            .db
+            .iterator(rocksdb::IteratorMode::Start)
+            .next()
+            .is_none());
+        let entries = |column| {
+            db.iter_column(column, None)
+                .unwrap()
+                .map(|entry| entry.unwrap().0)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(entries(DbColumn::Blocks), vec![cid.to_bytes()]);
+        assert_eq!(entries(DbColumn::Metadata), vec![b"head".to_vec()]);
+        assert_eq!(db.read(cid.to_bytes()).unwrap(), Some(b"block".to_vec()));
+        assert_eq!(db.read("head").unwrap(), Some(b"tipset".to_vec()));
+    }
+
+    #[test]
+    fn db_stops_moving_default_column_entries_when_dropped() {
+        use forest_db::{rocks::RocksDb, rocks_config::RocksDbConfig, Store};
+
+        let dir = tempfile::tempdir().unwrap();
+        {
+            let db = rocksdb::DB::open_default(dir.path()).unwrap();
+            for i in 0..50_000u32 {
+                db.put(i.to_be_bytes(), b"value").unwrap();
+            }
+        }
+
+        let db = RocksDb::open(dir.path(), &RocksDbConfig::default()).unwrap();
+        db.spawn_default_column_migration();
+        drop(db);
+        // The database is closed, with the move stopped or done.
+        let db = RocksDb::open(dir.path(), &RocksDbConfig::default()).unwrap();
+        db.spawn_default_column_migration();
+        for _ in 0..500 {
+            if !db.is_migrating_default_column() {
+                break;
+            }
+            std::thread::sleep(std::time::Duration::from_millis(10));
+        }
+        assert!(!db.is_migrating_default_column());
+        for i in 0..50_000u32 {
+            assert_eq!(db.read(i.to_be_bytes()).unwrap(), Some(b"value".to_vec()));
+        }
+    }
+
+    #[test]
+    fn db_opens_read_only_while_open() {
+        use forest_db::{rocks::RocksDb, rocks_config::RocksDbConfig, Store};
+
+        let dir = tempfile::tempdir().unwrap();
+        let db = RocksDb::open(dir.path(), &RocksDbConfig::default()).unwrap();
+        db.write("head", "tipset").unwrap();
+        db.flush().unwrap();
+
+        let read_only = RocksDb::open_read_only(dir.path(), &RocksDbConfig::default()).unwrap();
+        assert_eq!(read_only.read("head").unwrap(), Some(b"tipset".to_vec()));
+        assert!(read_only.write("head", "other").is_err());
+        assert_eq!(db.read("head").unwrap(), Some(b"tipset".to_vec()));
+    }
 }
@@ -108,3 +108,39 @@ where
         assert!(!res);
     }
 }
+
+// The in-memory database has no checkpoints.
+#[allow(dead_code)]
+pub fn checkpoint<DB>(db: &DB, open: impl Fn(&std::path::Path) -> DB)
+where
+    DB: Store + fvm_ipld_blockstore::Blockstore + forest_db::DbCheckpoint,
+{
+    use cid::{
+        multihash::{Code::Blake2b256, MultihashDigest},
+        Cid,
+    };
+
+    let blocks: Vec<_> = (0..10u8)
+        .map(|i| {
+            let cid = Cid::new_v1(fvm_ipld_encoding::DAG_CBOR, Blake2b256.digest(&[i]));
+            (cid, vec![i])
+        })
+        .collect();
+    for (cid, data) in &blocks {
+        db.put_keyed(cid, data).unwrap();
+    }
+    db.write("head", [1]).unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("checkpoint");
+    db.checkpoint(&path).unwrap();
+    assert!(db.checkpoint(&path).is_err());
+    // Written after the checkpoint, so not in it.
+    db.write("head", [2]).unwrap();
+
+    let copy = open(&path);
+    for (cid, data) in &blocks {
+        assert_eq!(copy.get(cid).unwrap().as_ref(), Some(data));
+    }
+    assert_eq!(copy.read("head").unwrap(), Some(vec![1]));
+}
@@ -0,0 +1,80 @@
+// Copyright 2019-2023 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use forest_db::{tiered::TieredDb, MemoryDB, Store};
+
+#[test]
+fn tiered_db_reads_through_cold_store() {
+    let db = TieredDb::new(MemoryDB::default(), MemoryDB::default());
+    db.cold().write([1], [10]).unwrap();
+    db.write([2], [20]).unwrap();
+
+    assert_eq!(db.read([1]).unwrap(), Some(vec![10]));
+    assert_eq!(db.read([2]).unwrap(), Some(vec![20]));
+    assert!(db.exists([1]).unwrap());
+    assert!(!db.cold().exists([2]).unwrap());
+
+    db.delete([1]).unwrap();
+    assert!(!db.exists([1]).unwrap());
+}
+
+#[cfg(all(feature = "rocksdb", feature = "paritydb"))]
+mod tiering_tests {
+    use cid::{
+        multihash::{Code::Blake2b256, MultihashDigest},
+        Cid,
+    };
+    use forest_db::{
+        db_engine::{db_path, open_db, Db, DbBackend, DbConfig},
+        tiered::TieredDbConfig,
+        Store,
+    };
+    use fvm_ipld_blockstore::Blockstore;
+    use fvm_ipld_encoding::DAG_CBOR;
+
+    #[test]
+    fn move_to_cold_moves_blocks() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = DbConfig {
+            tiered: TieredDbConfig {
+                enabled: true,
+                cold_backend: DbBackend::ParityDb,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let db = open_db(&db_path(dir.path(), DbBackend::RocksDb), &config).unwrap();
+        let Db::Tiered(tiered) = &db else {
+            panic!("tiering is enabled");
+        };
+
+        let blocks: Vec<_> = (0..10u8)
+            .map(|i| (Cid::new_v1(DAG_CBOR, Blake2b256.digest(&[i])), vec![i]))
+            .collect();
+        for (cid, data) in &blocks {
+            db.put_keyed(cid, data).unwrap();
+        }
+        db.write("head", [0]).unwrap();
+
+        let cold: Vec<_> = blocks[3..].iter().map(|(cid, _)| *cid).collect();
+        let missing = Cid::new_v1(DAG_CBOR, Blake2b256.digest(b"missing"));
+        let mut cids = cold.clone();
+        cids.push(missing);
+        assert_eq!(tiered.move_to_cold(&cids).unwrap(), 7);
+        // Already moved.
+        assert_eq!(tiered.move_to_cold(&cold).unwrap(), 0);
+
+        for (cid, data) in &blocks {
+            assert_eq!(db.get(cid).unwrap().as_ref(), Some(data));
+            assert_eq!(
+                tiered.hot().get(cid).unwrap().is_some(),
+                !cold.contains(cid)
+            );
+            assert_eq!(
+                tiered.cold().get(cid).unwrap().is_some(),
+                cold.contains(cid)
+            );
+        }
+        assert!(tiered.hot().exists("head").unwrap());
+    }
+}
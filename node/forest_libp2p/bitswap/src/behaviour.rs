@@ -21,6 +21,7 @@ pub type BitswapBehaviourEvent = RequestResponseEvent<Vec<BitswapMessage>, ()>;
 pub struct BitswapBehaviour {
     inner: RequestResponse<BitswapRequestResponseCodec>,
     request_manager: Arc<BitswapRequestManager>,
+    server: BitswapServer,
 }
 
 impl BitswapBehaviour {
@@ -35,9 +36,16 @@ impl BitswapBehaviour {
         BitswapBehaviour {
             inner: RequestResponse::new(BitswapRequestResponseCodec, protocols, cfg),
             request_manager: Default::default(),
+            server: Default::default(),
         }
     }
 
+    /// Applies the given limits when serving the want lists of peers
+    pub fn with_server_config(mut self, config: BitswapServerConfig) -> Self {
+        self.server = BitswapServer::new(config);
+        self
+    }
+
     /// Gets mutable borrow of the inner [RequestResponse]
     pub fn inner_mut(&mut self) -> &mut RequestResponse<BitswapRequestResponseCodec> {
         &mut self.inner
@@ -59,12 +67,64 @@ impl BitswapBehaviour {
 
     /// Sends a [BitswapResponse] to a peer
     pub fn send_response(&mut self, peer: &PeerId, response: (Cid, BitswapResponse)) -> RequestId {
-        match response.1 {
-            BitswapResponse::Have(..) => metrics::message_counter_outbound_response_have().inc(),
-            BitswapResponse::Block(..) => metrics::message_counter_outbound_response_block().inc(),
+        self.send_responses(peer, vec![response])
+    }
+
+    /// Sends a batch of [BitswapResponse]s to a peer in a single message
+    pub fn send_responses(
+        &mut self,
+        peer: &PeerId,
+        responses: Vec<(Cid, BitswapResponse)>,
+    ) -> RequestId {
+        let messages = responses
+            .into_iter()
+            .map(|(cid, response)| {
+                match response {
+                    BitswapResponse::Have(..) => {
+                        metrics::message_counter_outbound_response_have().inc()
+                    }
+                    BitswapResponse::Block(..) => {
+                        metrics::message_counter_outbound_response_block().inc()
+                    }
+                }
+                BitswapMessage::Response(cid, response)
+            })
+            .collect();
+        self.inner.send_request(peer, messages)
+    }
+}
+
+// Server related API(s)
+impl BitswapBehaviour {
+    /// Gets the associated [BitswapServer]
+    pub fn server(&self) -> &BitswapServer {
+        &self.server
+    }
+
+    /// Only serves the blocks for which the filter returns `true`, see
+    /// [BitswapServer::set_serve_filter]
+    pub fn set_serve_filter(&mut self, filter: ServeFilter) {
+        self.server.set_serve_filter(filter);
+    }
+
+    /// Sends the responses to the want list of a peer, in as many messages as
+    /// its rate limit allows. Remaining entries are served by later calls.
+    pub(crate) fn serve_peer<S: BitswapStoreRead>(&mut self, store: &S, peer: &PeerId) {
+        loop {
+            let batch = self.server.next_batch(store, peer);
+            if batch.is_empty() {
+                break;
+            }
+            self.send_responses(peer, batch);
+        }
+    }
+
+    /// Serves the entries left in the want lists of peers, typically those of
+    /// peers that were over their rate limits. Should be called periodically.
+    pub fn serve_pending<S: BitswapStoreRead>(&mut self, store: &S) {
+        for peer in self.server.pending_peers() {
+            self.serve_peer(store, &peer);
         }
-        self.inner
-            .send_request(peer, vec![BitswapMessage::Response(response.0, response.1)])
     }
 }
 
@@ -137,6 +197,9 @@ impl NetworkBehaviour for BitswapBehaviour {
             }
             libp2p::swarm::derive_prelude::FromSwarm::ConnectionClosed(e) => {
                 self.request_manager.on_peer_disconnected(&e.peer_id);
+                if e.remaining_established == 0 {
+                    self.server.on_peer_disconnected(&e.peer_id);
+                }
             }
             _ => {}
         };
@@ -45,6 +45,7 @@ impl RequestResponseCodec for BitswapRequestResponseCodec {
                 cid,
                 send_dont_have: entry.sendDontHave,
                 cancel: entry.cancel,
+                priority: entry.priority,
             }));
         }
         for payload in pb_msg.payload {
@@ -90,14 +91,7 @@ impl RequestResponseCodec for BitswapRequestResponseCodec {
     where
         T: AsyncWrite + Send + Unpin,
     {
-        // TODO: Low priority, batch sending is not supported in `libp2p-bitswap` either
-        // panic here means bug in public API of this crate
-        assert!(
-            messages.len() == 1,
-            "It's only supported to send a single message"
-        );
-
-        let bytes = messages[0].to_bytes()?;
+        let bytes = BitswapMessage::batch_to_bytes(&messages)?;
 
         metrics::outbound_stream_count().inc();
         metrics::outbound_bytes().inc_by(bytes.len() as _);
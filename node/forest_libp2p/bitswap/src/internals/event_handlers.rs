@@ -30,12 +30,13 @@ pub fn handle_event_impl<S: BitswapStoreRead>(
                 // Close inbound stream immediately since `go-bitswap` does not read this
                 // stream. responses will be sent over a new outbound request
                 _ = bitswap.inner_mut().send_response(channel, ());
+                let mut has_requests = false;
                 for message in request {
                     match message {
                         BitswapMessage::Request(request) => {
-                            if let Some(response) = handle_inbound_request(store, &request) {
-                                bitswap.send_response(&peer, (request.cid, response));
-                            }
+                            // Cancels in the same message apply before anything is served
+                            bitswap.server().on_inbound_request(peer, &request);
+                            has_requests = true;
                         }
                         BitswapMessage::Response(cid, response) => {
                             if let Some(event) = match response {
@@ -50,6 +51,7 @@ pub fn handle_event_impl<S: BitswapStoreRead>(
                                 }
                                 BitswapResponse::Block(data) => {
                                     metrics::message_counter_inbound_response_block().inc();
+                                    bitswap.server().on_inbound_block(peer, data.len());
                                     Some(BitswapInboundResponseEvent::DataBlock(peer, cid, data))
                                 }
                             } {
@@ -58,6 +60,9 @@ pub fn handle_event_impl<S: BitswapStoreRead>(
                         }
                     }
                 }
+                if has_requests {
+                    bitswap.serve_peer(store, &peer);
+                }
             }
             RequestResponseMessage::Response { .. } => {
                 // Left empty by design
@@ -67,35 +72,3 @@ pub fn handle_event_impl<S: BitswapStoreRead>(
 
     Ok(())
 }
-
-fn handle_inbound_request<S: BitswapStoreRead>(
-    store: &S,
-    request: &BitswapRequest,
-) -> Option<BitswapResponse> {
-    if request.cancel {
-        return None;
-    }
-
-    match request.ty {
-        RequestType::Have => {
-            metrics::message_counter_inbound_request_have().inc();
-            let have = store.contains(&request.cid).ok().unwrap_or_default();
-            if have || request.send_dont_have {
-                Some(BitswapResponse::Have(have))
-            } else {
-                None
-            }
-        }
-        RequestType::Block => {
-            metrics::message_counter_inbound_request_block().inc();
-            let block = store.get(&request.cid).ok().unwrap_or_default();
-            if let Some(data) = block {
-                Some(BitswapResponse::Block(data))
-            } else if request.send_dont_have {
-                Some(BitswapResponse::Have(false))
-            } else {
-                None
-            }
-        }
-    }
-}
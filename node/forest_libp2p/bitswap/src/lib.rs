@@ -23,6 +23,9 @@ pub use metrics::register_metrics;
 
 pub mod request_manager;
 
+mod server;
+pub use server::*;
+
 mod store;
 pub use store::*;
 
@@ -56,6 +56,8 @@ pub struct BitswapRequest {
     pub cid: Cid,
     pub send_dont_have: bool,
     pub cancel: bool,
+    /// Relative priority of the entry in the want list, higher first
+    pub priority: i32,
 }
 
 impl BitswapRequest {
@@ -65,6 +67,7 @@ impl BitswapRequest {
             cid,
             send_dont_have: false,
             cancel: false,
+            priority: 1,
         }
     }
 
@@ -74,6 +77,7 @@ impl BitswapRequest {
             cid,
             send_dont_have: false,
             cancel: false,
+            priority: 1,
         }
     }
 
@@ -82,6 +86,11 @@ impl BitswapRequest {
         self
     }
 
+    pub fn priority(mut self, priority: i32) -> Self {
+        self.priority = priority;
+        self
+    }
+
     pub fn new_cancel(cid: Cid) -> Self {
         // Matches `https://github.com/ipfs/go-libipfs/blob/v0.6.0/bitswap/message/message.go#L309`
         Self {
@@ -89,6 +98,7 @@ impl BitswapRequest {
             cid,
             send_dont_have: false,
             cancel: true,
+            priority: 1,
         }
     }
 }
@@ -110,50 +120,58 @@ pub enum BitswapMessage {
 
 impl BitswapMessage {
     pub fn to_bytes(&self) -> IOResult<Vec<u8>> {
+        Self::batch_to_bytes(std::slice::from_ref(self))
+    }
+
+    /// Encodes a batch of messages into a single `bitswap` message
+    pub fn batch_to_bytes(messages: &[BitswapMessage]) -> IOResult<Vec<u8>> {
         let mut msg = bitswap_pb::Message::new();
-        match self {
-            Self::Request(BitswapRequest {
-                ty,
-                cid,
-                send_dont_have,
-                cancel,
-            }) => {
-                let mut wantlist = bitswap_pb::message::Wantlist::new();
-
-                wantlist.entries.push({
-                    let mut entry = bitswap_pb::message::wantlist::Entry::new();
-                    entry.block = cid.to_bytes();
-                    entry.wantType = (*ty).into();
-                    entry.sendDontHave = *send_dont_have;
-                    entry.cancel = *cancel;
-                    entry.priority = 1;
-                    entry
-                });
-
-                msg.wantlist = Some(wantlist).into();
-            }
-            Self::Response(cid, BitswapResponse::Have(have)) => {
-                let mut block_presence = bitswap_pb::message::BlockPresence::new();
-
-                block_presence.cid = cid.to_bytes();
-                block_presence.type_ = if *have {
-                    bitswap_pb::message::BlockPresenceType::Have
-                } else {
-                    bitswap_pb::message::BlockPresenceType::DontHave
+        let mut wantlist = bitswap_pb::message::Wantlist::new();
+        for message in messages {
+            match message {
+                Self::Request(BitswapRequest {
+                    ty,
+                    cid,
+                    send_dont_have,
+                    cancel,
+                    priority,
+                }) => {
+                    wantlist.entries.push({
+                        let mut entry = bitswap_pb::message::wantlist::Entry::new();
+                        entry.block = cid.to_bytes();
+                        entry.wantType = (*ty).into();
+                        entry.sendDontHave = *send_dont_have;
+                        entry.cancel = *cancel;
+                        entry.priority = *priority;
+                        entry
+                    });
                 }
-                .into();
-
-                msg.blockPresences.push(block_presence);
-            }
-            Self::Response(cid, BitswapResponse::Block(bytes)) => {
-                let mut payload = bitswap_pb::message::Block::new();
+                Self::Response(cid, BitswapResponse::Have(have)) => {
+                    let mut block_presence = bitswap_pb::message::BlockPresence::new();
+
+                    block_presence.cid = cid.to_bytes();
+                    block_presence.type_ = if *have {
+                        bitswap_pb::message::BlockPresenceType::Have
+                    } else {
+                        bitswap_pb::message::BlockPresenceType::DontHave
+                    }
+                    .into();
+
+                    msg.blockPresences.push(block_presence);
+                }
+                Self::Response(cid, BitswapResponse::Block(bytes)) => {
+                    let mut payload = bitswap_pb::message::Block::new();
 
-                payload.prefix = Prefix::from(cid).to_bytes();
-                payload.data = bytes.to_vec();
+                    payload.prefix = Prefix::from(cid).to_bytes();
+                    payload.data = bytes.to_vec();
 
-                msg.payload.push(payload);
+                    msg.payload.push(payload);
+                }
             }
         }
+        if !wantlist.entries.is_empty() {
+            msg.wantlist = Some(wantlist).into();
+        }
         msg.write_to_bytes().map_err(map_io_err)
     }
 }
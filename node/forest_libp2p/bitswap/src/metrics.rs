@@ -72,6 +72,14 @@ pub(crate) fn message_counter_inbound_request_block() -> GenericCounter<AtomicU6
     MESSAGE_COUNTER.with_label_values(&["inbound_request_block"])
 }
 
+pub(crate) fn message_counter_inbound_request_cancel() -> GenericCounter<AtomicU64> {
+    MESSAGE_COUNTER.with_label_values(&["inbound_request_cancel"])
+}
+
+pub(crate) fn message_counter_inbound_request_dropped() -> GenericCounter<AtomicU64> {
+    MESSAGE_COUNTER.with_label_values(&["inbound_request_dropped"])
+}
+
 pub(crate) fn message_counter_outbound_request_cancel() -> GenericCounter<AtomicU64> {
     MESSAGE_COUNTER.with_label_values(&["outbound_request_cancel"])
 }
@@ -122,6 +130,10 @@ pub(crate) fn message_counter_outbound_response_block() -> GenericCounter<Atomic
     MESSAGE_COUNTER.with_label_values(&["outbound_response_block"])
 }
 
+pub(crate) fn message_counter_outbound_response_rate_limited() -> GenericCounter<AtomicU64> {
+    MESSAGE_COUNTER.with_label_values(&["outbound_response_rate_limited"])
+}
+
 pub(crate) fn peer_container_capacity() -> GenericGauge<AtomicU64> {
     CONTAINER_CAPACITIES.with_label_values(&["peer_container_capacity"])
 }
@@ -13,7 +13,7 @@ use ahash::{HashMap, HashMapExt, HashSet, HashSetExt};
 use flume::TryRecvError;
 use libipld::{Block, Cid};
 use libp2p::PeerId;
-use parking_lot::RwLock;
+use parking_lot::{Mutex, RwLock};
 
 use crate::{event_handlers::*, *};
 
@@ -23,6 +23,32 @@ const BITSWAP_BLOCK_REQUEST_INTERVAL: Duration = Duration::from_millis(500);
 struct ResponseChannels {
     block_have: flume::Sender<PeerId>,
     block_received: flume::Sender<Option<Vec<u8>>>,
+    /// Whether the block is wanted even if the store has it.
+    refetch: bool,
+}
+
+/// Store of a block fetched again by
+/// [BitswapRequestManager::refetch_block], which never has the block.
+#[derive(Default)]
+struct RefetchStore(Mutex<Option<Vec<u8>>>);
+
+impl BitswapStoreRead for RefetchStore {
+    fn contains(&self, _cid: &Cid) -> anyhow::Result<bool> {
+        Ok(false)
+    }
+
+    fn get(&self, _cid: &Cid) -> anyhow::Result<Option<Vec<u8>>> {
+        Ok(self.0.lock().clone())
+    }
+}
+
+impl BitswapStoreReadWrite for RefetchStore {
+    type Params = libipld::DefaultParams;
+
+    fn insert(&self, block: &Block<Self::Params>) -> anyhow::Result<()> {
+        *self.0.lock() = Some(block.data().to_vec());
+        Ok(())
+    }
 }
 
 /// Request manager implementation that is optimized for `filecoin` network
@@ -86,10 +112,11 @@ impl BitswapRequestManager {
             let mut success = store.contains(&cid).unwrap_or_default();
             if !success {
                 let deadline = start.checked_add(timeout).expect("Infallible");
-                success =
-                    task::spawn_blocking(move || self.get_block_sync(store_cloned, cid, deadline))
-                        .await
-                        .unwrap_or_default();
+                success = task::spawn_blocking(move || {
+                    self.get_block_sync(store_cloned, cid, deadline, false)
+                })
+                .await
+                .unwrap_or_default();
                 // Spin check db when `get_block_sync` fails fast,
                 // which means there is other task actually processing the same `cid`
                 while !success && Instant::now() < deadline {
@@ -114,11 +141,40 @@ impl BitswapRequestManager {
         });
     }
 
+    /// Fetches a block from the peers even if the store of the node has it, to
+    /// replace a corrupted copy. The block, verified against its CID, is sent
+    /// to the channel without being written to any store, `None` if it could
+    /// not be fetched. Note: this method is a non-blocking, it is intended to
+    /// return immediately.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn refetch_block(
+        self: Arc<Self>,
+        cid: Cid,
+        timeout: Duration,
+        responder: flume::Sender<Option<Vec<u8>>>,
+    ) {
+        let deadline = Instant::now().checked_add(timeout).expect("Infallible");
+        task::spawn(async move {
+            let store = Arc::new(RefetchStore::default());
+            let store_cloned = store.clone();
+            let success = task::spawn_blocking(move || {
+                self.get_block_sync(store_cloned, cid, deadline, true)
+            })
+            .await
+            .unwrap_or_default();
+            let data = if success { store.0.lock().take() } else { None };
+            if let Err(e) = responder.send_async(data).await {
+                warn!("{e}");
+            }
+        });
+    }
+
     fn get_block_sync(
         &self,
         store: Arc<impl BitswapStoreReadWrite>,
         cid: Cid,
         deadline: Instant,
+        refetch: bool,
     ) -> bool {
         // Fail fast here when the given `cid` is being processed by other tasks
         if self.response_channels.read().contains_key(&cid) {
@@ -130,6 +186,7 @@ impl BitswapRequestManager {
         let channels = ResponseChannels {
             block_have: block_have_tx,
             block_received: block_saved_tx,
+            refetch,
         };
         {
             self.response_channels.write().insert(cid, channels);
@@ -202,6 +259,12 @@ impl BitswapRequestManager {
         success
     }
 
+    /// Returns true if a block is being fetched, in which case receiving it
+    /// from a peer is a response to one of our requests.
+    pub fn is_block_requested(&self, cid: &Cid) -> bool {
+        self.response_channels.read().contains_key(cid)
+    }
+
     pub(crate) fn on_inbound_response_event<S: BitswapStoreRead>(
         &self,
         store: &S,
@@ -217,7 +280,7 @@ impl BitswapRequestManager {
             }
             DataBlock(_peer, cid, data) => {
                 if let Some(chans) = self.response_channels.read().get(&cid) {
-                    if let Ok(true) = store.contains(&cid) {
+                    if !chans.refetch && matches!(store.contains(&cid), Ok(true)) {
                         // Avoid duplicate writes, still notify the receiver
                         metrics::message_counter_inbound_response_block_already_exists_in_db()
                             .inc();
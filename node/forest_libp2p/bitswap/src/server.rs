@@ -0,0 +1,364 @@
+// Copyright 2019-2023 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Server side of `bitswap`, serving the want lists of peers from a
+//! [BitswapStoreRead]
+
+use std::{num::NonZeroU64, sync::Arc, time::Instant};
+
+use ahash::HashMap;
+use libp2p::PeerId;
+use parking_lot::Mutex;
+use serde::{Deserialize, Serialize};
+
+use crate::*;
+
+/// Limits applied when serving the want lists of peers
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BitswapServerConfig {
+    /// Responses to a peer are batched into messages of up to this many bytes.
+    /// A block larger than this is sent in a message of its own.
+    pub max_message_size: usize,
+    /// Maximum number of entries kept in the want list of a peer, further
+    /// entries are dropped.
+    pub max_want_list_size: usize,
+    /// Maximum number of block bytes per second served to a single peer,
+    /// unlimited when `None`. Entries of a peer over its limit stay in its want
+    /// list until they can be served.
+    pub max_bytes_per_sec_per_peer: Option<NonZeroU64>,
+}
+
+impl Default for BitswapServerConfig {
+    fn default() -> Self {
+        Self {
+            max_message_size: 1024 * 1024,
+            max_want_list_size: 1024,
+            max_bytes_per_sec_per_peer: None,
+        }
+    }
+}
+
+/// What has been exchanged with a peer over `bitswap`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct BitswapLedger {
+    /// Number of block bytes sent to the peer
+    pub bytes_sent: u64,
+    /// Number of block bytes received from the peer
+    pub bytes_received: u64,
+    /// Number of blocks sent to the peer
+    pub blocks_sent: u64,
+    /// Number of blocks received from the peer
+    pub blocks_received: u64,
+}
+
+/// Predicate deciding whether a block may be served to peers
+pub type ServeFilter = Arc<dyn Fn(&Cid) -> bool + Send + Sync>;
+
+#[derive(Debug, Clone, Copy)]
+struct WantEntry {
+    ty: RequestType,
+    priority: i32,
+    send_dont_have: bool,
+    // Breaks ties between entries of equal priority, oldest first
+    seq: u64,
+}
+
+/// Token bucket limiting the block bytes served to a peer. Responses are
+/// charged once sent, so the budget can go negative, in which case the peer
+/// is not served until it is refilled.
+#[derive(Debug)]
+struct ByteBudget {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl ByteBudget {
+    fn new(rate: u64) -> Self {
+        Self {
+            tokens: rate as f64,
+            last_refill: Instant::now(),
+        }
+    }
+
+    fn refill(&mut self, rate: u64) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rate as f64).min(rate as f64);
+        self.last_refill = now;
+    }
+}
+
+#[derive(Debug, Default)]
+struct PeerState {
+    want_list: HashMap<Cid, WantEntry>,
+    next_seq: u64,
+    budget: Option<ByteBudget>,
+    ledger: BitswapLedger,
+}
+
+/// Serves the want lists of connected peers, highest priority entries first
+#[derive(Default)]
+pub struct BitswapServer {
+    config: BitswapServerConfig,
+    peers: Mutex<HashMap<PeerId, PeerState>>,
+    serve_filter: Option<ServeFilter>,
+}
+
+impl BitswapServer {
+    /// Creates a [BitswapServer] instance
+    pub fn new(config: BitswapServerConfig) -> Self {
+        Self {
+            config,
+            peers: Default::default(),
+            serve_filter: None,
+        }
+    }
+
+    /// Only serves the blocks for which the filter returns `true`, other
+    /// blocks are reported as missing.
+    pub fn set_serve_filter(&mut self, filter: ServeFilter) {
+        self.serve_filter = Some(filter);
+    }
+
+    /// Gets the ledgers of the connected peers
+    pub fn ledgers(&self) -> HashMap<PeerId, BitswapLedger> {
+        self.peers
+            .lock()
+            .iter()
+            .map(|(peer, state)| (*peer, state.ledger))
+            .collect()
+    }
+
+    /// Adds the entry of a request to the want list of the peer, or removes it
+    /// if the request is a cancel.
+    pub(crate) fn on_inbound_request(&self, peer: PeerId, request: &BitswapRequest) {
+        let mut peers = self.peers.lock();
+        let state = peers.entry(peer).or_default();
+        if request.cancel {
+            metrics::message_counter_inbound_request_cancel().inc();
+            state.want_list.remove(&request.cid);
+            return;
+        }
+
+        match request.ty {
+            RequestType::Have => metrics::message_counter_inbound_request_have().inc(),
+            RequestType::Block => metrics::message_counter_inbound_request_block().inc(),
+        }
+        if !state.want_list.contains_key(&request.cid)
+            && state.want_list.len() >= self.config.max_want_list_size
+        {
+            metrics::message_counter_inbound_request_dropped().inc();
+            return;
+        }
+        let seq = state.next_seq;
+        state.next_seq += 1;
+        // A new entry for the same block replaces the previous one
+        state.want_list.insert(
+            request.cid,
+            WantEntry {
+                ty: request.ty,
+                priority: request.priority,
+                send_dont_have: request.send_dont_have,
+                seq,
+            },
+        );
+    }
+
+    pub(crate) fn on_inbound_block(&self, peer: PeerId, len: usize) {
+        let mut peers = self.peers.lock();
+        let ledger = &mut peers.entry(peer).or_default().ledger;
+        ledger.bytes_received += len as u64;
+        ledger.blocks_received += 1;
+    }
+
+    pub(crate) fn on_peer_disconnected(&self, peer: &PeerId) {
+        self.peers.lock().remove(peer);
+    }
+
+    /// Peers with entries left in their want lists
+    pub(crate) fn pending_peers(&self) -> Vec<PeerId> {
+        self.peers
+            .lock()
+            .iter()
+            .filter(|(_, state)| !state.want_list.is_empty())
+            .map(|(peer, _)| *peer)
+            .collect()
+    }
+
+    /// Takes the next batch of responses to the want list of the peer, highest
+    /// priority first. Returns an empty batch when the want list is empty or
+    /// the peer is over its rate limit.
+    pub(crate) fn next_batch<S: BitswapStoreRead>(
+        &self,
+        store: &S,
+        peer: &PeerId,
+    ) -> Vec<(Cid, BitswapResponse)> {
+        let mut peers = self.peers.lock();
+        let state = match peers.get_mut(peer) {
+            Some(state) if !state.want_list.is_empty() => state,
+            _ => return vec![],
+        };
+
+        if let Some(rate) = self.config.max_bytes_per_sec_per_peer.map(NonZeroU64::get) {
+            let budget = state.budget.get_or_insert_with(|| ByteBudget::new(rate));
+            budget.refill(rate);
+            if budget.tokens < 0. {
+                metrics::message_counter_outbound_response_rate_limited().inc();
+                return vec![];
+            }
+        }
+
+        let mut entries: Vec<_> = state
+            .want_list
+            .iter()
+            .map(|(cid, entry)| (*cid, *entry))
+            .collect();
+        entries
+            .sort_unstable_by(|(_, a), (_, b)| b.priority.cmp(&a.priority).then(a.seq.cmp(&b.seq)));
+
+        let mut batch = vec![];
+        let mut batch_size = 0;
+        let mut block_bytes = 0;
+        for (cid, entry) in entries {
+            let response = self.response_to(store, &cid, &entry);
+            let size = match &response {
+                Some(BitswapResponse::Block(data)) => data.len(),
+                Some(BitswapResponse::Have(_)) => cid.encoded_len(),
+                None => 0,
+            };
+            if !batch.is_empty() && batch_size + size > self.config.max_message_size {
+                break;
+            }
+            state.want_list.remove(&cid);
+            if let Some(response) = response {
+                if let BitswapResponse::Block(data) = &response {
+                    block_bytes += data.len();
+                    state.ledger.bytes_sent += data.len() as u64;
+                    state.ledger.blocks_sent += 1;
+                }
+                batch_size += size;
+                batch.push((cid, response));
+            }
+        }
+
+        if let Some(budget) = state.budget.as_mut() {
+            budget.tokens -= block_bytes as f64;
+        }
+        batch
+    }
+
+    fn response_to<S: BitswapStoreRead>(
+        &self,
+        store: &S,
+        cid: &Cid,
+        entry: &WantEntry,
+    ) -> Option<BitswapResponse> {
+        let allowed = self
+            .serve_filter
+            .as_ref()
+            .map_or(true, |filter| filter(cid));
+        match entry.ty {
+            RequestType::Have => {
+                let have = allowed && store.contains(cid).ok().unwrap_or_default();
+                if have || entry.send_dont_have {
+                    Some(BitswapResponse::Have(have))
+                } else {
+                    None
+                }
+            }
+            RequestType::Block => {
+                let block = if allowed {
+                    store.get(cid).ok().unwrap_or_default()
+                } else {
+                    None
+                };
+                if let Some(data) = block {
+                    Some(BitswapResponse::Block(data))
+                } else if entry.send_dont_have {
+                    Some(BitswapResponse::Have(false))
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use libipld::multihash::{self, MultihashDigest};
+
+    use super::*;
+
+    #[derive(Default)]
+    struct TestStore(HashMap<Cid, Vec<u8>>);
+
+    impl TestStore {
+        fn put(&mut self, data: Vec<u8>) -> Cid {
+            let cid = Cid::new_v0(multihash::Code::Sha2_256.digest(&data)).unwrap();
+            self.0.insert(cid, data);
+            cid
+        }
+    }
+
+    impl BitswapStoreRead for TestStore {
+        fn contains(&self, cid: &Cid) -> anyhow::Result<bool> {
+            Ok(self.0.contains_key(cid))
+        }
+
+        fn get(&self, cid: &Cid) -> anyhow::Result<Option<Vec<u8>>> {
+            Ok(self.0.get(cid).cloned())
+        }
+    }
+
+    #[test]
+    fn serves_by_priority_within_message_size() {
+        let mut store = TestStore::default();
+        let low = store.put(vec![1; 600]);
+        let high = store.put(vec![2; 600]);
+        let cancelled = store.put(vec![3; 600]);
+        let server = BitswapServer::new(BitswapServerConfig {
+            max_message_size: 1000,
+            ..Default::default()
+        });
+        let peer = PeerId::random();
+
+        server.on_inbound_request(peer, &BitswapRequest::new_block(low).priority(1));
+        server.on_inbound_request(peer, &BitswapRequest::new_block(high).priority(10));
+        server.on_inbound_request(peer, &BitswapRequest::new_block(cancelled).priority(5));
+        server.on_inbound_request(peer, &BitswapRequest::new_cancel(cancelled));
+
+        let batch = server.next_batch(&store, &peer);
+        assert_eq!(batch.len(), 1);
+        assert_eq!(batch[0].0, high);
+        let batch = server.next_batch(&store, &peer);
+        assert_eq!(batch.len(), 1);
+        assert_eq!(batch[0].0, low);
+        assert!(server.next_batch(&store, &peer).is_empty());
+
+        let ledger = server.ledgers()[&peer];
+        assert_eq!(ledger.blocks_sent, 2);
+        assert_eq!(ledger.bytes_sent, 1200);
+    }
+
+    #[test]
+    fn rate_limited_entries_stay_queued() {
+        let mut store = TestStore::default();
+        let first = store.put(vec![1; 2000]);
+        let second = store.put(vec![2; 2000]);
+        let server = BitswapServer::new(BitswapServerConfig {
+            max_message_size: 1000,
+            max_bytes_per_sec_per_peer: NonZeroU64::new(1000),
+            ..Default::default()
+        });
+        let peer = PeerId::random();
+
+        server.on_inbound_request(peer, &BitswapRequest::new_block(first));
+        server.on_inbound_request(peer, &BitswapRequest::new_block(second));
+
+        assert_eq!(server.next_batch(&store, &peer).len(), 1);
+        assert!(server.next_batch(&store, &peer).is_empty());
+        assert_eq!(server.pending_peers(), vec![peer]);
+    }
+}
@@ -0,0 +1,282 @@
+// Copyright 2019-2023 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use std::{
+    io,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    time::Instant,
+};
+
+use ahash::HashMap;
+use futures::{AsyncRead, AsyncWrite};
+use libp2p::{
+    core::upgrade::{InboundUpgrade, OutboundUpgrade, UpgradeInfo},
+    PeerId,
+};
+use parking_lot::{Mutex, RwLock};
+use pin_project_lite::pin_project;
+use serde::{Deserialize, Serialize};
+
+/// Bytes exchanged with the network, along with the transfer rates measured
+/// over the last update period.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct BandwidthStats {
+    pub total_in: u64,
+    pub total_out: u64,
+    /// Inbound rate in bytes per second.
+    pub rate_in: f64,
+    /// Outbound rate in bytes per second.
+    pub rate_out: f64,
+}
+
+#[derive(Default)]
+struct Rates {
+    last_in: u64,
+    last_out: u64,
+    rate_in: f64,
+    rate_out: f64,
+}
+
+/// Traffic counter of a single peer, protocol, or of the whole node.
+#[derive(Default)]
+pub(crate) struct Meter {
+    total_in: AtomicU64,
+    total_out: AtomicU64,
+    rates: Mutex<Rates>,
+}
+
+impl Meter {
+    fn record_in(&self, bytes: usize) {
+        self.total_in.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    fn record_out(&self, bytes: usize) {
+        self.total_out.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    fn update_rates(&self, elapsed_secs: f64) {
+        let total_in = self.total_in.load(Ordering::Relaxed);
+        let total_out = self.total_out.load(Ordering::Relaxed);
+        let mut rates = self.rates.lock();
+        rates.rate_in = total_in.saturating_sub(rates.last_in) as f64 / elapsed_secs;
+        rates.rate_out = total_out.saturating_sub(rates.last_out) as f64 / elapsed_secs;
+        rates.last_in = total_in;
+        rates.last_out = total_out;
+    }
+
+    fn stats(&self) -> BandwidthStats {
+        let rates = self.rates.lock();
+        BandwidthStats {
+            total_in: self.total_in.load(Ordering::Relaxed),
+            total_out: self.total_out.load(Ordering::Relaxed),
+            rate_in: rates.rate_in,
+            rate_out: rates.rate_out,
+        }
+    }
+}
+
+/// Keeps track of the traffic of the node in total, by peer and by protocol.
+///
+/// Totals and per-peer counters are fed by the transport, per-protocol
+/// counters by the protocol codecs, so the latter only account for the
+/// application payload.
+#[derive(Default)]
+pub struct BandwidthCounter {
+    total: Arc<Meter>,
+    by_peer: RwLock<HashMap<PeerId, Arc<Meter>>>,
+    by_protocol: RwLock<HashMap<String, Arc<Meter>>>,
+    last_update: Mutex<Option<Instant>>,
+}
+
+impl BandwidthCounter {
+    fn peer_meter(&self, peer: PeerId) -> Arc<Meter> {
+        if let Some(meter) = self.by_peer.read().get(&peer) {
+            return meter.clone();
+        }
+        self.by_peer.write().entry(peer).or_default().clone()
+    }
+
+    pub(crate) fn protocol_meter(&self, protocol: &[u8]) -> Arc<Meter> {
+        let protocol = String::from_utf8_lossy(protocol);
+        if let Some(meter) = self.by_protocol.read().get(protocol.as_ref()) {
+            return meter.clone();
+        }
+        self.by_protocol
+            .write()
+            .entry(protocol.into_owned())
+            .or_default()
+            .clone()
+    }
+
+    /// Records payload received over the given protocol.
+    pub fn record_protocol_in(&self, protocol: &[u8], bytes: usize) {
+        self.protocol_meter(protocol).record_in(bytes);
+    }
+
+    /// Records payload sent over the given protocol.
+    pub fn record_protocol_out(&self, protocol: &[u8], bytes: usize) {
+        self.protocol_meter(protocol).record_out(bytes);
+    }
+
+    /// Recomputes the transfer rates since the previous call. Counters of
+    /// peers we are no longer connected to are dropped.
+    pub fn update_rates(&self) {
+        let now = Instant::now();
+        let elapsed = match self.last_update.lock().replace(now) {
+            Some(last) => now.duration_since(last).as_secs_f64(),
+            None => return,
+        };
+        if elapsed <= 0.0 {
+            return;
+        }
+
+        self.total.update_rates(elapsed);
+        // Open connections hold a reference to the meter of their peer.
+        self.by_peer
+            .write()
+            .retain(|_, meter| Arc::strong_count(meter) > 1);
+        for meter in self.by_peer.read().values() {
+            meter.update_rates(elapsed);
+        }
+        for meter in self.by_protocol.read().values() {
+            meter.update_rates(elapsed);
+        }
+    }
+
+    /// Returns the traffic of the node.
+    pub fn totals(&self) -> BandwidthStats {
+        self.total.stats()
+    }
+
+    /// Returns the traffic of each connected peer.
+    pub fn by_peer(&self) -> HashMap<PeerId, BandwidthStats> {
+        self.by_peer
+            .read()
+            .iter()
+            .map(|(peer, meter)| (*peer, meter.stats()))
+            .collect()
+    }
+
+    /// Returns the traffic of each protocol.
+    pub fn by_protocol(&self) -> HashMap<String, BandwidthStats> {
+        self.by_protocol
+            .read()
+            .iter()
+            .map(|(protocol, meter)| (protocol.clone(), meter.stats()))
+            .collect()
+    }
+}
+
+pin_project! {
+    /// Stream wrapper recording the bytes read and written into a set of
+    /// meters.
+    pub(crate) struct CountingStream<S> {
+        #[pin]
+        inner: S,
+        meters: Vec<Arc<Meter>>,
+    }
+}
+
+impl<S> CountingStream<S> {
+    pub(crate) fn new(inner: S, meters: Vec<Arc<Meter>>) -> Self {
+        Self { inner, meters }
+    }
+}
+
+impl<S: AsyncRead> AsyncRead for CountingStream<S> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut [u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.project();
+        let poll = this.inner.poll_read(cx, buf);
+        if let Poll::Ready(Ok(n)) = poll {
+            this.meters.iter().for_each(|meter| meter.record_in(n));
+        }
+        poll
+    }
+}
+
+impl<S: AsyncWrite> AsyncWrite for CountingStream<S> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.project();
+        let poll = this.inner.poll_write(cx, buf);
+        if let Poll::Ready(Ok(n)) = poll {
+            this.meters.iter().for_each(|meter| meter.record_out(n));
+        }
+        poll
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().inner.poll_flush(cx)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.project().inner.poll_close(cx)
+    }
+}
+
+/// Upgrade wrapping the authenticated connection of a peer into a
+/// [`CountingStream`] before handing it to the inner (multiplexer) upgrade.
+#[derive(Clone)]
+pub(crate) struct CountingUpgrade<U> {
+    inner: U,
+    meters: Vec<Arc<Meter>>,
+}
+
+impl<U> CountingUpgrade<U> {
+    pub(crate) fn new(inner: U, peer: PeerId, counter: &BandwidthCounter) -> Self {
+        Self {
+            inner,
+            meters: vec![counter.total.clone(), counter.peer_meter(peer)],
+        }
+    }
+}
+
+impl<U: UpgradeInfo> UpgradeInfo for CountingUpgrade<U> {
+    type Info = U::Info;
+    type InfoIter = U::InfoIter;
+
+    fn protocol_info(&self) -> Self::InfoIter {
+        self.inner.protocol_info()
+    }
+}
+
+impl<C, U> InboundUpgrade<C> for CountingUpgrade<U>
+where
+    U: InboundUpgrade<CountingStream<C>>,
+{
+    type Output = U::Output;
+    type Error = U::Error;
+    type Future = U::Future;
+
+    fn upgrade_inbound(self, socket: C, info: Self::Info) -> Self::Future {
+        self.inner
+            .upgrade_inbound(CountingStream::new(socket, self.meters), info)
+    }
+}
+
+impl<C, U> OutboundUpgrade<C> for CountingUpgrade<U>
+where
+    U: OutboundUpgrade<CountingStream<C>>,
+{
+    type Output = U::Output;
+    type Error = U::Error;
+    type Future = U::Future;
+
+    fn upgrade_outbound(self, socket: C, info: Self::Info) -> Self::Future {
+        self.inner
+            .upgrade_outbound(CountingStream::new(socket, self.meters), info)
+    }
+}
@@ -1,9 +1,12 @@
 // Copyright 2019-2023 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+use std::sync::Arc;
+
 use ahash::{HashMap, HashSet};
 use forest_encoding::blake2b_256;
 use forest_libp2p_bitswap::BitswapBehaviour;
+use futures::channel::oneshot::Sender as OneShotSender;
 use libp2p::{
     core::{identity::Keypair, PeerId},
     gossipsub::{
@@ -21,6 +24,7 @@ use libp2p::{
 use log::warn;
 
 use crate::{
+    bandwidth::BandwidthCounter,
     chain_exchange::ChainExchangeBehaviour,
     config::Libp2pConfig,
     discovery::{DiscoveryBehaviour, DiscoveryConfig},
@@ -53,7 +57,12 @@ impl Recorder<ForestBehaviourEvent> for Metrics {
 }
 
 impl ForestBehaviour {
-    pub fn new(local_key: &Keypair, config: &Libp2pConfig, network_name: &str) -> Self {
+    pub fn new(
+        local_key: &Keypair,
+        config: &Libp2pConfig,
+        network_name: &str,
+        bandwidth: &Arc<BandwidthCounter>,
+    ) -> Self {
         let mut gs_config_builder = GossipsubConfigBuilder::default();
         gs_config_builder.max_transmit_size(1 << 20);
         gs_config_builder.validation_mode(ValidationMode::Strict);
@@ -84,7 +93,8 @@ impl ForestBehaviour {
                 b"/chain/ipfs/bitswap",
             ],
             Default::default(),
-        );
+        )
+        .with_server_config(config.bitswap.server_config());
         if let Err(err) = forest_libp2p_bitswap::register_metrics(prometheus::default_registry()) {
             warn!("Fail to register prometheus metrics for libp2p_bitswap: {err}");
         }
@@ -106,8 +116,8 @@ impl ForestBehaviour {
                 local_key.public(),
             )),
             bitswap,
-            hello: HelloBehaviour::default(),
-            chain_exchange: ChainExchangeBehaviour::default(),
+            hello: HelloBehaviour::new(bandwidth.clone()),
+            chain_exchange: ChainExchangeBehaviour::new(bandwidth.clone()),
         }
     }
 
@@ -130,6 +140,14 @@ impl ForestBehaviour {
         self.gossipsub.subscribe(topic)
     }
 
+    /// Returns the gossipsub scores of the peers we are connected to.
+    pub fn gossipsub_scores(&self) -> Vec<(PeerId, f64)> {
+        self.gossipsub
+            .all_peers()
+            .filter_map(|(peer, _)| Some((*peer, self.gossipsub.peer_score(peer)?)))
+            .collect()
+    }
+
     /// Returns a set of peer ids
     pub fn peers(&self) -> &HashSet<PeerId> {
         self.discovery.peers()
@@ -139,4 +157,14 @@ impl ForestBehaviour {
     pub fn peer_addresses(&mut self) -> &HashMap<PeerId, Vec<Multiaddr>> {
         self.discovery.peer_addresses()
     }
+
+    /// Adds known addresses of a peer to the discovery.
+    pub fn add_peer_addresses(&mut self, peer_id: PeerId, addresses: &[Multiaddr]) {
+        self.discovery.add_peer_addresses(peer_id, addresses)
+    }
+
+    /// Looks up the addresses of a peer through Kademlia.
+    pub fn find_peer(&mut self, peer_id: PeerId, response_channel: OneShotSender<Vec<Multiaddr>>) {
+        self.discovery.find_peer(peer_id, response_channel)
+    }
 }
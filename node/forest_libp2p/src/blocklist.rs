@@ -0,0 +1,133 @@
+// Copyright 2019-2023 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use std::net::IpAddr;
+
+use ahash::HashSet;
+use ipnet::IpNet;
+use libp2p::{multiaddr::Protocol, Multiaddr, PeerId};
+use parking_lot::RwLock;
+
+/// Peers, IP addresses and IP subnets to add to or remove from the
+/// [`BlockList`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NetBlockList {
+    pub peers: Vec<PeerId>,
+    pub ip_addrs: Vec<IpAddr>,
+    pub ip_subnets: Vec<IpNet>,
+}
+
+#[derive(Default)]
+struct BlockListInner {
+    peers: HashSet<PeerId>,
+    ip_addrs: HashSet<IpAddr>,
+    ip_subnets: HashSet<IpNet>,
+}
+
+/// Set of peers and addresses the node refuses to communicate with. Blocked
+/// addresses are checked by the transport before any handshake takes place,
+/// blocked peers are banned from the swarm.
+#[derive(Default)]
+pub struct BlockList {
+    inner: RwLock<BlockListInner>,
+}
+
+impl BlockList {
+    pub fn add(&self, list: &NetBlockList) {
+        let mut inner = self.inner.write();
+        inner.peers.extend(list.peers.iter().copied());
+        inner.ip_addrs.extend(list.ip_addrs.iter().copied());
+        inner
+            .ip_subnets
+            .extend(list.ip_subnets.iter().map(IpNet::trunc));
+    }
+
+    pub fn remove(&self, list: &NetBlockList) {
+        let mut inner = self.inner.write();
+        for peer in &list.peers {
+            inner.peers.remove(peer);
+        }
+        for ip in &list.ip_addrs {
+            inner.ip_addrs.remove(ip);
+        }
+        for subnet in &list.ip_subnets {
+            inner.ip_subnets.remove(&subnet.trunc());
+        }
+    }
+
+    pub fn list(&self) -> NetBlockList {
+        let inner = self.inner.read();
+        NetBlockList {
+            peers: inner.peers.iter().copied().collect(),
+            ip_addrs: inner.ip_addrs.iter().copied().collect(),
+            ip_subnets: inner.ip_subnets.iter().copied().collect(),
+        }
+    }
+
+    pub fn is_peer_blocked(&self, peer: &PeerId) -> bool {
+        self.inner.read().peers.contains(peer)
+    }
+
+    pub fn is_ip_blocked(&self, ip: &IpAddr) -> bool {
+        let inner = self.inner.read();
+        inner.ip_addrs.contains(ip) || inner.ip_subnets.iter().any(|net| net.contains(ip))
+    }
+
+    /// Returns true if the address points to a blocked IP address or to a
+    /// blocked peer.
+    pub fn is_addr_blocked(&self, addr: &Multiaddr) -> bool {
+        addr.iter().any(|protocol| match protocol {
+            Protocol::Ip4(ip) => self.is_ip_blocked(&ip.into()),
+            Protocol::Ip6(ip) => self.is_ip_blocked(&ip.into()),
+            Protocol::P2p(mh) => {
+                PeerId::from_multihash(mh).map_or(false, |peer| self.is_peer_blocked(&peer))
+            }
+            _ => false,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn block_subnet() {
+        let block_list = BlockList::default();
+        block_list.add(&NetBlockList {
+            ip_subnets: vec!["10.1.2.3/16".parse().unwrap()],
+            ..Default::default()
+        });
+
+        assert!(block_list.is_addr_blocked(&"/ip4/10.1.200.1/tcp/1234".parse().unwrap()));
+        assert!(!block_list.is_addr_blocked(&"/ip4/10.2.0.1/tcp/1234".parse().unwrap()));
+
+        // Subnets are normalized, so they can be removed using any address
+        // within them.
+        block_list.remove(&NetBlockList {
+            ip_subnets: vec!["10.1.0.0/16".parse().unwrap()],
+            ..Default::default()
+        });
+        assert!(!block_list.is_addr_blocked(&"/ip4/10.1.200.1/tcp/1234".parse().unwrap()));
+    }
+
+    #[test]
+    fn block_peer_and_ip() {
+        let block_list = BlockList::default();
+        let peer = PeerId::random();
+        block_list.add(&NetBlockList {
+            peers: vec![peer],
+            ip_addrs: vec!["::1".parse().unwrap()],
+            ..Default::default()
+        });
+
+        assert!(block_list.is_peer_blocked(&peer));
+        assert!(block_list.is_addr_blocked(
+            &format!("/dns4/example.com/tcp/1/p2p/{peer}")
+                .parse()
+                .unwrap()
+        ));
+        assert!(block_list.is_addr_blocked(&"/ip6/::1/tcp/1234".parse().unwrap()));
+        assert!(!block_list.is_addr_blocked(&"/ip6/::2/tcp/1234".parse().unwrap()));
+    }
+}
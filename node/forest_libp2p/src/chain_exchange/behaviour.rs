@@ -1,6 +1,8 @@
 // Copyright 2019-2023 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+use std::sync::Arc;
+
 use ahash::HashMap;
 use libp2p::{
     request_response::{
@@ -12,7 +14,7 @@ use libp2p::{
 use log::warn;
 
 use super::*;
-use crate::{rpc::RequestResponseError, service::metrics};
+use crate::{bandwidth::BandwidthCounter, rpc::RequestResponseError, service::metrics};
 
 type InnerBehaviour = RequestResponse<ChainExchangeCodec>;
 
@@ -76,11 +78,17 @@ impl ChainExchangeBehaviour {
     }
 }
 
-impl Default for ChainExchangeBehaviour {
-    fn default() -> Self {
+impl ChainExchangeBehaviour {
+    /// Creates the behaviour, recording the protocol traffic into the given
+    /// bandwidth counter.
+    pub fn new(bandwidth: Arc<BandwidthCounter>) -> Self {
+        Self::with_codec(ChainExchangeCodec::with_bandwidth_counter(bandwidth))
+    }
+
+    fn with_codec(codec: ChainExchangeCodec) -> Self {
         Self {
             inner: RequestResponse::new(
-                ChainExchangeCodec::default(),
+                codec,
                 [(ChainExchangeProtocolName, ProtocolSupport::Full)],
                 Default::default(),
             ),
@@ -89,6 +97,12 @@ impl Default for ChainExchangeBehaviour {
     }
 }
 
+impl Default for ChainExchangeBehaviour {
+    fn default() -> Self {
+        Self::with_codec(ChainExchangeCodec::default())
+    }
+}
+
 impl NetworkBehaviour for ChainExchangeBehaviour {
     type ConnectionHandler = <InnerBehaviour as NetworkBehaviour>::ConnectionHandler;
 
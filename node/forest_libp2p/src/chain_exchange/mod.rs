@@ -4,10 +4,11 @@
 mod behaviour;
 mod message;
 mod provider;
+mod responder;
 pub use behaviour::*;
 use libp2p::core::ProtocolName;
 
-pub use self::{message::*, provider::*};
+pub use self::{message::*, provider::*, responder::*};
 use super::rpc::CborRequestResponse;
 
 /// Libp2p protocol ID for `ChainExchange`.
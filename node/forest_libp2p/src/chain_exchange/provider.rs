@@ -8,21 +8,30 @@ use forest_chain::{ChainStore, Error as ChainError};
 use forest_db::Store;
 use fvm_ipld_blockstore::Blockstore;
 use log::debug;
+use serde::de::DeserializeOwned;
 
 use super::{
     ChainExchangeRequest, ChainExchangeResponse, ChainExchangeResponseStatus, CompactedMessages,
     TipsetBundle,
 };
 
-/// Builds chain exchange response out of chain data.
-pub fn make_chain_exchange_response<DB>(
+/// Builds chain exchange response of at most `max_len` tipsets, getting the
+/// messages of each tipset, along with their size, from `get_messages`.
+///
+/// Returns the response and the size of the encoded headers and messages it
+/// carries, which leaves out only the few bytes framing them.
+pub(super) fn build_chain_exchange_response<DB>(
     cs: &ChainStore<DB>,
     request: &ChainExchangeRequest,
-) -> ChainExchangeResponse
+    max_len: u64,
+    get_messages: impl Fn(&Tipset) -> Result<(CompactedMessages, usize), ChainError>,
+) -> (ChainExchangeResponse, usize)
 where
     DB: Blockstore + Store + Clone + Send + Sync + 'static,
 {
-    let mut response_chain: Vec<TipsetBundle> = Vec::with_capacity(request.request_len as usize);
+    let request_len = request.request_len.min(max_len);
+    let mut response_chain: Vec<TipsetBundle> = Vec::with_capacity(request_len as usize);
+    let mut size = 0;
 
     let mut curr_tipset_cids = request.start.clone();
 
@@ -33,25 +42,34 @@ where
             Err(err) => {
                 debug!("Cannot get tipset from keys: {}", err);
 
-                return ChainExchangeResponse {
-                    chain: vec![],
-                    status: ChainExchangeResponseStatus::InternalError,
-                    message: "Tipset was not found in the database".to_owned(),
-                };
+                return (
+                    ChainExchangeResponse {
+                        chain: vec![],
+                        status: ChainExchangeResponseStatus::InternalError,
+                        message: "Tipset was not found in the database".to_owned(),
+                    },
+                    0,
+                );
             }
         };
 
         if request.include_messages() {
-            match compact_messages(cs.blockstore(), &tipset) {
-                Ok(compacted_messages) => tipset_bundle.messages = Some(compacted_messages),
+            match get_messages(&tipset) {
+                Ok((compacted_messages, messages_size)) => {
+                    tipset_bundle.messages = Some(compacted_messages);
+                    size += messages_size;
+                }
                 Err(err) => {
                     debug!("Cannot compact messages for tipset: {}", err);
 
-                    return ChainExchangeResponse {
-                        chain: vec![],
-                        status: ChainExchangeResponseStatus::InternalError,
-                        message: "Can not fulfil the request".to_owned(),
-                    };
+                    return (
+                        ChainExchangeResponse {
+                            chain: vec![],
+                            status: ChainExchangeResponseStatus::InternalError,
+                            message: "Can not fulfil the request".to_owned(),
+                        },
+                        0,
+                    );
                 }
             }
         }
@@ -64,30 +82,42 @@ where
             // data in the function. This may not be possible without overriding rpc in
             // libp2p
             tipset_bundle.blocks = tipset.blocks().to_vec();
+            size += tipset_bundle
+                .blocks
+                .iter()
+                .map(|header| header.encoded_len())
+                .sum::<usize>();
         }
 
         response_chain.push(tipset_bundle);
 
-        if response_chain.len() as u64 >= request.request_len || tipset_epoch == 0 {
+        if response_chain.len() as u64 >= request_len || tipset_epoch == 0 {
             break;
         }
     }
 
     let result_chain_length = response_chain.len() as u64;
 
-    ChainExchangeResponse {
-        chain: response_chain,
-        status: if result_chain_length < request.request_len {
-            ChainExchangeResponseStatus::PartialResponse
-        } else {
-            ChainExchangeResponseStatus::Success
+    (
+        ChainExchangeResponse {
+            chain: response_chain,
+            status: if result_chain_length < request_len {
+                ChainExchangeResponseStatus::PartialResponse
+            } else {
+                ChainExchangeResponseStatus::Success
+            },
+            message: "Success".to_owned(),
         },
-        message: "Success".to_owned(),
-    }
+        size,
+    )
 }
 
-// Builds CompactedMessages for given Tipset.
-fn compact_messages<DB>(db: &DB, tipset: &Tipset) -> Result<CompactedMessages, ChainError>
+// Builds CompactedMessages for given Tipset, along with the size of the encoded
+// messages.
+pub(super) fn compact_messages<DB>(
+    db: &DB,
+    tipset: &Tipset,
+) -> Result<(CompactedMessages, usize), ChainError>
 where
     DB: Blockstore + Store + Clone,
 {
@@ -135,15 +165,40 @@ where
         secp_msg_includes.push(secp_include);
     }
 
-    let (bls_msgs, secp_msgs) =
-        forest_chain::block_messages_from_cids(db, &bls_cids_combined, &secp_cids_combined)?;
+    let (bls_msgs, bls_size) = messages_from_cids(db, &bls_cids_combined)?;
+    let (secp_msgs, secp_size) = messages_from_cids(db, &secp_cids_combined)?;
 
-    Ok(CompactedMessages {
-        bls_msgs,
-        bls_msg_includes,
-        secp_msgs,
-        secp_msg_includes,
-    })
+    Ok((
+        CompactedMessages {
+            bls_msgs,
+            bls_msg_includes,
+            secp_msgs,
+            secp_msg_includes,
+        },
+        bls_size + secp_size,
+    ))
+}
+
+/// Loads the messages, along with the size of their encoding, which is the one
+/// they are sent with.
+fn messages_from_cids<DB, T>(db: &DB, keys: &[Cid]) -> Result<(Vec<T>, usize), ChainError>
+where
+    DB: Blockstore,
+    T: DeserializeOwned,
+{
+    let mut size = 0;
+    let messages = keys
+        .iter()
+        .map(|k| {
+            let bytes = db
+                .get(k)
+                .map_err(|e| ChainError::Other(e.to_string()))?
+                .ok_or_else(|| ChainError::UndefinedKey(k.to_string()))?;
+            size += bytes.len();
+            fvm_ipld_encoding::from_slice(&bytes).map_err(|e| ChainError::Other(e.to_string()))
+        })
+        .collect::<Result<_, _>>()?;
+    Ok((messages, size))
 }
 
 #[cfg(test)]
@@ -156,11 +211,12 @@ mod tests {
     use forest_networks::ChainConfig;
     use forest_shim::address::Address;
     use fvm_ipld_car::load_car;
+    use libp2p::PeerId;
     use tokio::io::BufReader;
     use tokio_util::compat::TokioAsyncReadCompatExt;
 
     use super::{
-        super::{HEADERS, MESSAGES},
+        super::{ChainExchangeResponder, HEADERS, MESSAGES},
         *,
     };
 
@@ -181,8 +237,10 @@ mod tests {
             .build()
             .unwrap();
 
-        let response = make_chain_exchange_response(
-            &ChainStore::new(db, Arc::new(ChainConfig::default()), &gen_block).unwrap(),
+        let cs = ChainStore::new(db, Arc::new(ChainConfig::default()), &gen_block).unwrap();
+        let responder = Arc::new(ChainExchangeResponder::new(Default::default()));
+        let response = responder.try_acquire(PeerId::random()).unwrap().respond(
+            &cs,
             &ChainExchangeRequest {
                 start: cids,
                 request_len: 2,
@@ -239,4 +297,31 @@ mod tests {
         assert_eq!(ts_38_msgs.secp_msg_includes[1].len(), 1);
         assert_eq!(ts_38_msgs.bls_msg_includes[1].len(), 11);
     }
+
+    #[tokio::test]
+    async fn response_size_test() {
+        let (cids, db) = populate_db().await;
+
+        let gen_block = BlockHeader::builder()
+            .miner_address(Address::new_id(0))
+            .build()
+            .unwrap();
+        let cs = ChainStore::new(db, Arc::new(ChainConfig::default()), &gen_block).unwrap();
+
+        let (response, size) = build_chain_exchange_response(
+            &cs,
+            &ChainExchangeRequest {
+                start: cids,
+                request_len: 2,
+                options: HEADERS | MESSAGES,
+            },
+            2,
+            |tipset| compact_messages(cs.blockstore(), tipset),
+        );
+
+        // Only the framing of the headers and messages is left out.
+        let encoded_len = fvm_ipld_encoding::to_vec(&response).unwrap().len();
+        assert!(size <= encoded_len);
+        assert!(encoded_len - size < 256);
+    }
 }
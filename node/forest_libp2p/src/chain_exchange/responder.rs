@@ -0,0 +1,282 @@
+// Copyright 2019-2023 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use std::{num::NonZeroUsize, sync::Arc, time::Instant};
+
+use ahash::HashMap;
+use forest_blocks::TipsetKeys;
+use forest_chain::ChainStore;
+use forest_db::Store;
+use fvm_ipld_blockstore::Blockstore;
+use fvm_shared::clock::ChainEpoch;
+use libp2p::PeerId;
+use lru::LruCache;
+use parking_lot::Mutex;
+
+use super::{
+    build_chain_exchange_response, compact_messages, ChainExchangeRequest, ChainExchangeResponse,
+    ChainExchangeResponseStatus, CompactedMessages,
+};
+use crate::{metrics, ChainExchangeConfig};
+
+/// Only the messages of tipsets this close to the head are cached, as these
+/// are the ones requested by most syncing peers.
+const MESSAGE_CACHE_MAX_DEPTH: ChainEpoch = 900;
+
+/// Byte budgets are dropped once refilled, past this number of peers.
+const MAX_IDLE_BYTE_BUDGETS: usize = 1024;
+
+/// Token bucket limiting the bytes served to a peer. Responses are charged
+/// after being built, so the budget can go negative, in which case further
+/// requests are turned away until it is refilled.
+struct ByteBudget {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl ByteBudget {
+    fn refill(&mut self, rate: f64) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * rate).min(rate);
+        self.last_refill = now;
+    }
+}
+
+/// Serves chain exchange requests within the limits of
+/// [`ChainExchangeConfig`], caching the compacted messages of recent tipsets.
+pub struct ChainExchangeResponder {
+    config: ChainExchangeConfig,
+    in_flight: Mutex<HashMap<PeerId, usize>>,
+    byte_budgets: Mutex<HashMap<PeerId, ByteBudget>>,
+    /// Compacted messages of tipsets, along with their encoded size.
+    message_cache: Option<Mutex<LruCache<TipsetKeys, (CompactedMessages, usize)>>>,
+}
+
+/// A request of a peer being processed. Its slot is released once dropped.
+pub struct RequestSlot {
+    responder: Arc<ChainExchangeResponder>,
+    peer: PeerId,
+}
+
+impl RequestSlot {
+    /// Builds the response to the request this slot was acquired for.
+    pub fn respond<DB>(
+        &self,
+        cs: &ChainStore<DB>,
+        request: &ChainExchangeRequest,
+    ) -> ChainExchangeResponse
+    where
+        DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    {
+        self.responder.respond(cs, self.peer, request)
+    }
+}
+
+impl Drop for RequestSlot {
+    fn drop(&mut self) {
+        let mut in_flight = self.responder.in_flight.lock();
+        if let Some(count) = in_flight.get_mut(&self.peer) {
+            *count -= 1;
+            if *count == 0 {
+                in_flight.remove(&self.peer);
+            }
+        }
+    }
+}
+
+impl ChainExchangeResponder {
+    pub fn new(config: ChainExchangeConfig) -> Self {
+        let message_cache =
+            NonZeroUsize::new(config.message_cache_size).map(|cap| Mutex::new(LruCache::new(cap)));
+        Self {
+            config,
+            in_flight: Default::default(),
+            byte_budgets: Default::default(),
+            message_cache,
+        }
+    }
+
+    /// Reserves a slot to process a request of the peer. If the peer is over
+    /// its limits, the response turning the request away is returned instead.
+    pub fn try_acquire(
+        self: &Arc<Self>,
+        peer: PeerId,
+    ) -> Result<RequestSlot, ChainExchangeResponse> {
+        if !self.has_byte_budget(peer) {
+            return Err(self.reject(
+                ChainExchangeResponseStatus::GoAway,
+                "Bandwidth limit exceeded",
+            ));
+        }
+
+        let mut in_flight = self.in_flight.lock();
+        let count = in_flight.entry(peer).or_default();
+        if *count >= self.config.max_concurrent_requests_per_peer {
+            return Err(self.reject(
+                ChainExchangeResponseStatus::GoAway,
+                "Too many concurrent requests",
+            ));
+        }
+        *count += 1;
+
+        Ok(RequestSlot {
+            responder: self.clone(),
+            peer,
+        })
+    }
+
+    fn respond<DB>(
+        &self,
+        cs: &ChainStore<DB>,
+        peer: PeerId,
+        request: &ChainExchangeRequest,
+    ) -> ChainExchangeResponse
+    where
+        DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    {
+        if request.request_len == 0 {
+            return self.reject(
+                ChainExchangeResponseStatus::BadRequest,
+                "Invalid request length",
+            );
+        }
+
+        let head_epoch = cs.heaviest_tipset().epoch();
+        let (response, size) =
+            build_chain_exchange_response(cs, request, self.config.max_window, |tipset| {
+                let cache = match &self.message_cache {
+                    Some(cache) if tipset.epoch() + MESSAGE_CACHE_MAX_DEPTH >= head_epoch => cache,
+                    _ => return compact_messages(cs.blockstore(), tipset),
+                };
+                if let Some(messages) = cache.lock().get(tipset.key()) {
+                    metrics::CHAIN_EXCHANGE_MESSAGE_CACHE
+                        .with_label_values(&[metrics::values::HIT])
+                        .inc();
+                    return Ok(messages.clone());
+                }
+                metrics::CHAIN_EXCHANGE_MESSAGE_CACHE
+                    .with_label_values(&[metrics::values::MISS])
+                    .inc();
+                let messages = compact_messages(cs.blockstore(), tipset)?;
+                cache.lock().put(tipset.key().clone(), messages.clone());
+                Ok(messages)
+            });
+
+        metrics::CHAIN_EXCHANGE_RESPONSE_BYTES.observe(size as f64);
+        self.charge(peer, size);
+        record_status(response.status);
+        response
+    }
+
+    fn reject(&self, status: ChainExchangeResponseStatus, message: &str) -> ChainExchangeResponse {
+        record_status(status);
+        ChainExchangeResponse {
+            status,
+            message: message.to_owned(),
+            chain: vec![],
+        }
+    }
+
+    /// Returns true if the peer has not used up its byte budget.
+    fn has_byte_budget(&self, peer: PeerId) -> bool {
+        if self.config.max_bytes_per_sec_per_peer == 0 {
+            return true;
+        }
+        let rate = self.config.max_bytes_per_sec_per_peer as f64;
+        let mut budgets = self.byte_budgets.lock();
+        if budgets.len() > MAX_IDLE_BYTE_BUDGETS {
+            budgets.retain(|_, budget| {
+                budget.refill(rate);
+                budget.tokens < rate
+            });
+        }
+        match budgets.get_mut(&peer) {
+            Some(budget) => {
+                budget.refill(rate);
+                budget.tokens >= 0.
+            }
+            None => true,
+        }
+    }
+
+    fn charge(&self, peer: PeerId, bytes: usize) {
+        if self.config.max_bytes_per_sec_per_peer == 0 {
+            return;
+        }
+        let rate = self.config.max_bytes_per_sec_per_peer as f64;
+        let mut budgets = self.byte_budgets.lock();
+        let budget = budgets.entry(peer).or_insert_with(|| ByteBudget {
+            tokens: rate,
+            last_refill: Instant::now(),
+        });
+        budget.refill(rate);
+        budget.tokens -= bytes as f64;
+    }
+}
+
+fn record_status(status: ChainExchangeResponseStatus) {
+    use ChainExchangeResponseStatus::*;
+    let label = match status {
+        Success => "success",
+        PartialResponse => "partial_response",
+        BlockNotFound => "block_not_found",
+        GoAway => "go_away",
+        InternalError => "internal_error",
+        BadRequest => "bad_request",
+        Other(_) => "other",
+    };
+    metrics::CHAIN_EXCHANGE_RESPONSES_TOTAL
+        .with_label_values(&[label])
+        .inc();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn concurrent_requests_are_limited() {
+        let responder = Arc::new(ChainExchangeResponder::new(ChainExchangeConfig {
+            max_concurrent_requests_per_peer: 1,
+            ..Default::default()
+        }));
+        let peer = PeerId::random();
+
+        let slot = responder.try_acquire(peer).unwrap();
+        let rejected = responder.try_acquire(peer).err().unwrap();
+        assert_eq!(rejected.status, ChainExchangeResponseStatus::GoAway);
+        // Other peers are not affected.
+        assert!(responder.try_acquire(PeerId::random()).is_ok());
+
+        drop(slot);
+        assert!(responder.try_acquire(peer).is_ok());
+    }
+
+    #[test]
+    fn byte_rate_is_limited() {
+        let responder = Arc::new(ChainExchangeResponder::new(ChainExchangeConfig {
+            max_bytes_per_sec_per_peer: 1000,
+            ..Default::default()
+        }));
+        let peer = PeerId::random();
+
+        responder.charge(peer, 500);
+        assert!(responder.try_acquire(peer).is_ok());
+        responder.charge(peer, 10_000);
+        let rejected = responder.try_acquire(peer).err().unwrap();
+        assert_eq!(rejected.status, ChainExchangeResponseStatus::GoAway);
+    }
+
+    #[test]
+    fn zero_byte_rate_is_unlimited() {
+        let responder = Arc::new(ChainExchangeResponder::new(ChainExchangeConfig {
+            max_bytes_per_sec_per_peer: 0,
+            ..Default::default()
+        }));
+        let peer = PeerId::random();
+
+        responder.charge(peer, 10_000);
+        assert!(responder.try_acquire(peer).is_ok());
+    }
+}
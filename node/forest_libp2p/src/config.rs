@@ -1,6 +1,9 @@
 // Copyright 2019-2023 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+use std::num::NonZeroU64;
+
+use forest_libp2p_bitswap::BitswapServerConfig;
 use libp2p::Multiaddr;
 use serde::{Deserialize, Serialize};
 
@@ -20,6 +23,10 @@ pub struct Libp2pConfig {
     pub kademlia: bool,
     /// Target peer count.
     pub target_peer_count: u32,
+    /// Limits applied when serving chain exchange requests.
+    pub chain_exchange: ChainExchangeConfig,
+    /// Limits applied when serving bitswap want lists.
+    pub bitswap: BitswapConfig,
 }
 
 impl Default for Libp2pConfig {
@@ -30,6 +37,142 @@ impl Default for Libp2pConfig {
             mdns: false,
             kademlia: true,
             target_peer_count: 75,
+            chain_exchange: Default::default(),
+            bitswap: Default::default(),
+        }
+    }
+}
+
+/// Limits protecting the node from peers syncing from it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct ChainExchangeConfig {
+    /// Maximum number of tipsets served per request. Longer requests get a
+    /// shorter response.
+    pub max_window: u64,
+    /// Maximum number of requests of a single peer processed at once.
+    pub max_concurrent_requests_per_peer: usize,
+    /// Maximum number of bytes per second served to a single peer. Zero
+    /// disables the limit.
+    pub max_bytes_per_sec_per_peer: u64,
+    /// Number of compacted message bundles of recent tipsets kept in memory.
+    /// Zero disables the cache.
+    pub message_cache_size: usize,
+}
+
+impl Default for ChainExchangeConfig {
+    fn default() -> Self {
+        Self {
+            // Same as the `MaxRequestLength` of Lotus.
+            max_window: 900,
+            max_concurrent_requests_per_peer: 2,
+            max_bytes_per_sec_per_peer: 4 * 1024 * 1024,
+            message_cache_size: 256,
+        }
+    }
+}
+
+impl ChainExchangeConfig {
+    /// Checks that the limits let peers be served.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            self.max_window > 0,
+            "network.chain_exchange.max_window must be greater than zero"
+        );
+        anyhow::ensure!(
+            self.max_concurrent_requests_per_peer > 0,
+            "network.chain_exchange.max_concurrent_requests_per_peer must be greater than zero"
+        );
+        Ok(())
+    }
+}
+
+/// Limits protecting the node from peers fetching blocks from it over
+/// bitswap.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(default)]
+pub struct BitswapConfig {
+    /// Responses are batched into messages of up to this many bytes.
+    pub max_message_size: usize,
+    /// Maximum number of entries kept in the want list of a peer.
+    pub max_want_list_size: usize,
+    /// Maximum number of block bytes per second served to a single peer. Zero
+    /// disables the limit.
+    pub max_bytes_per_sec_per_peer: u64,
+    /// Only serve the headers, messages, receipt roots and state trees of
+    /// tipsets within `recent_state_depth` epochs of the head.
+    pub serve_recent_state_only: bool,
+    /// Depth of the tipsets served when `serve_recent_state_only` is set.
+    pub recent_state_depth: u64,
+}
+
+impl Default for BitswapConfig {
+    fn default() -> Self {
+        Self {
+            max_message_size: 1024 * 1024,
+            max_want_list_size: 1024,
+            max_bytes_per_sec_per_peer: 4 * 1024 * 1024,
+            serve_recent_state_only: false,
+            recent_state_depth: 900,
+        }
+    }
+}
+
+impl BitswapConfig {
+    /// Checks that the limits let peers be served.
+    pub fn validate(&self) -> anyhow::Result<()> {
+        anyhow::ensure!(
+            self.max_message_size > 0,
+            "network.bitswap.max_message_size must be greater than zero"
+        );
+        anyhow::ensure!(
+            self.max_want_list_size > 0,
+            "network.bitswap.max_want_list_size must be greater than zero"
+        );
+        anyhow::ensure!(
+            !self.serve_recent_state_only || self.recent_state_depth > 0,
+            "network.bitswap.recent_state_depth must be greater than zero when serve_recent_state_only is set"
+        );
+        Ok(())
+    }
+
+    pub(crate) fn server_config(&self) -> BitswapServerConfig {
+        BitswapServerConfig {
+            max_message_size: self.max_message_size,
+            max_want_list_size: self.max_want_list_size,
+            max_bytes_per_sec_per_peer: NonZeroU64::new(self.max_bytes_per_sec_per_peer),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn zero_bitswap_rate_is_unlimited() {
+        let config = BitswapConfig {
+            max_bytes_per_sec_per_peer: 0,
+            ..Default::default()
+        };
+        assert!(config.validate().is_ok());
+        assert_eq!(config.server_config().max_bytes_per_sec_per_peer, None);
+    }
+
+    #[test]
+    fn bitswap_config_is_validated() {
+        assert!(BitswapConfig::default().validate().is_ok());
+        assert!(BitswapConfig {
+            max_message_size: 0,
+            ..Default::default()
+        }
+        .validate()
+        .is_err());
+        assert!(BitswapConfig {
+            max_want_list_size: 0,
+            ..Default::default()
         }
+        .validate()
+        .is_err());
     }
 }
@@ -10,11 +10,12 @@ use std::{
 };
 
 use ahash::{HashMap, HashMapExt, HashSet, HashSetExt};
+use futures::channel::oneshot::Sender as OneShotSender;
 use libp2p::{
     core::{connection::ConnectionId, Multiaddr, PeerId, PublicKey},
     kad::{
         handler::KademliaHandlerProto, record::store::MemoryStore, Kademlia, KademliaConfig,
-        KademliaEvent, QueryId,
+        KademliaEvent, QueryId, QueryResult,
     },
     mdns::{tokio::Behaviour as Mdns, Event as MdnsEvent},
     multiaddr::Protocol,
@@ -161,10 +162,19 @@ impl<'a> DiscoveryConfig<'a> {
             peers,
             peer_addresses,
             discovery_max,
+            find_peer_queries: HashMap::new(),
         }
     }
 }
 
+/// Kademlia lookup of a peer's addresses, started by
+/// [`DiscoveryBehaviour::find_peer`].
+struct FindPeerQuery {
+    peer_id: PeerId,
+    addresses: HashSet<Multiaddr>,
+    response_channel: OneShotSender<Vec<Multiaddr>>,
+}
+
 /// Implementation of `NetworkBehaviour` that discovers the nodes on the
 /// network.
 pub struct DiscoveryBehaviour {
@@ -191,6 +201,8 @@ pub struct DiscoveryBehaviour {
     peer_addresses: HashMap<PeerId, Vec<Multiaddr>>,
     /// Number of active connections to pause discovery on.
     discovery_max: u64,
+    /// Pending peer lookups.
+    find_peer_queries: HashMap<QueryId, FindPeerQuery>,
 }
 
 impl DiscoveryBehaviour {
@@ -212,6 +224,67 @@ impl DiscoveryBehaviour {
             Err("Kademlia is not activated".to_string())
         }
     }
+
+    /// Adds known addresses of a peer, e.g. from a previous session, to the
+    /// Kademlia routing table.
+    pub fn add_peer_addresses(&mut self, peer_id: PeerId, addresses: &[Multiaddr]) {
+        if let Some(kad) = self.kademlia.as_mut() {
+            for address in addresses {
+                kad.add_address(&peer_id, address.clone());
+            }
+        }
+    }
+
+    /// Looks up the addresses of a peer. Addresses of connected peers are
+    /// returned right away, otherwise a Kademlia query is started and the
+    /// addresses found are sent once it completes.
+    pub fn find_peer(&mut self, peer_id: PeerId, response_channel: OneShotSender<Vec<Multiaddr>>) {
+        if let Some(addresses) = self.peer_addresses.get(&peer_id) {
+            let _ = response_channel.send(addresses.clone());
+            return;
+        }
+        match self.kademlia.as_mut() {
+            Some(kad) => {
+                let query_id = kad.get_closest_peers(peer_id);
+                self.find_peer_queries.insert(
+                    query_id,
+                    FindPeerQuery {
+                        peer_id,
+                        addresses: HashSet::new(),
+                        response_channel,
+                    },
+                );
+            }
+            None => {
+                let _ = response_channel.send(self.addresses_of_peer(&peer_id));
+            }
+        }
+    }
+
+    /// Records an address discovered for a peer that is being looked up.
+    fn on_peer_address(&mut self, peer_id: &PeerId, address: Multiaddr) {
+        for query in self.find_peer_queries.values_mut() {
+            if &query.peer_id == peer_id {
+                query.addresses.insert(address.clone());
+            }
+        }
+    }
+
+    fn complete_find_peer(&mut self, query_id: &QueryId) {
+        if let Some(FindPeerQuery {
+            peer_id,
+            mut addresses,
+            response_channel,
+        }) = self.find_peer_queries.remove(query_id)
+        {
+            addresses.extend(self.addresses_of_peer(&peer_id));
+            debug!(
+                "Kademlia lookup of {peer_id} completed with {} addresses",
+                addresses.len()
+            );
+            let _ = response_channel.send(addresses.into_iter().collect());
+        }
+    }
 }
 
 impl NetworkBehaviour for DiscoveryBehaviour {
@@ -250,7 +323,11 @@ impl NetworkBehaviour for DiscoveryBehaviour {
             FromSwarm::ConnectionEstablished(e) => {
                 self.num_connections += 1;
                 if e.other_established == 0 {
-                    let multiaddr = self.addresses_of_peer(&e.peer_id);
+                    let mut multiaddr = self.addresses_of_peer(&e.peer_id);
+                    let remote_address = e.endpoint.get_remote_address();
+                    if !multiaddr.contains(remote_address) {
+                        multiaddr.push(remote_address.clone());
+                    }
                     self.peer_addresses.insert(e.peer_id, multiaddr.clone());
                     self.peers.insert(e.peer_id);
                     self.pending_events
@@ -324,10 +401,23 @@ impl NetworkBehaviour for DiscoveryBehaviour {
                 NetworkBehaviourAction::GenerateEvent(ev) => match ev {
                     // Adding to Kademlia buckets is automatic with our config,
                     // no need to do manually.
-                    KademliaEvent::RoutingUpdated { .. } => {}
-                    KademliaEvent::RoutablePeer { .. } => {}
-                    KademliaEvent::PendingRoutablePeer { .. } => {
-                        // Intentionally ignore
+                    KademliaEvent::RoutingUpdated {
+                        peer, addresses, ..
+                    } => {
+                        for address in addresses.iter() {
+                            self.on_peer_address(&peer, address.clone());
+                        }
+                    }
+                    KademliaEvent::RoutablePeer { peer, address }
+                    | KademliaEvent::PendingRoutablePeer { peer, address } => {
+                        self.on_peer_address(&peer, address);
+                    }
+                    KademliaEvent::OutboundQueryProgressed {
+                        id,
+                        result: QueryResult::GetClosestPeers(_),
+                        ..
+                    } if self.find_peer_queries.contains_key(&id) => {
+                        self.complete_find_peer(&id);
                     }
                     other => {
                         debug!("Libp2p => Unhandled Kademlia event: {:?}", other)
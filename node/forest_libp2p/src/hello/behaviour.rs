@@ -1,6 +1,8 @@
 // Copyright 2019-2023 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+use std::sync::Arc;
+
 use ahash::HashMap;
 use libp2p::{
     request_response::{ProtocolSupport, RequestId, RequestResponse, ResponseChannel},
@@ -10,7 +12,7 @@ use libp2p::{
 use log::warn;
 
 use super::*;
-use crate::service::metrics;
+use crate::{bandwidth::BandwidthCounter, service::metrics};
 
 type InnerBehaviour = RequestResponse<HelloCodec>;
 
@@ -66,11 +68,17 @@ impl HelloBehaviour {
     }
 }
 
-impl Default for HelloBehaviour {
-    fn default() -> Self {
+impl HelloBehaviour {
+    /// Creates the behaviour, recording the protocol traffic into the given
+    /// bandwidth counter.
+    pub fn new(bandwidth: Arc<BandwidthCounter>) -> Self {
+        Self::with_codec(HelloCodec::with_bandwidth_counter(bandwidth))
+    }
+
+    fn with_codec(codec: HelloCodec) -> Self {
         Self {
             inner: RequestResponse::new(
-                HelloCodec::default(),
+                codec,
                 [(HelloProtocolName, ProtocolSupport::Full)],
                 Default::default(),
             ),
@@ -79,6 +87,12 @@ impl Default for HelloBehaviour {
     }
 }
 
+impl Default for HelloBehaviour {
+    fn default() -> Self {
+        Self::with_codec(HelloCodec::default())
+    }
+}
+
 impl NetworkBehaviour for HelloBehaviour {
     type ConnectionHandler = <InnerBehaviour as NetworkBehaviour>::ConnectionHandler;
 
@@ -3,7 +3,9 @@
 
 #![recursion_limit = "1024"]
 
+mod bandwidth;
 mod behaviour;
+mod blocklist;
 pub mod chain_exchange;
 mod config;
 mod discovery;
@@ -11,9 +13,14 @@ mod gossip_params;
 pub mod hello;
 mod metrics;
 mod peer_manager;
+mod peer_store;
+mod recent_state;
+mod reputation;
 pub mod rpc;
 mod service;
 
+pub use forest_libp2p_bitswap::BitswapLedger;
+pub use ipnet::IpNet;
 // Re-export some libp2p types
 pub use libp2p::{
     core::PeerId,
@@ -23,4 +30,12 @@ pub use libp2p::{
 pub use multihash::Multihash;
 
 pub(crate) use self::behaviour::*;
-pub use self::{config::*, peer_manager::*, service::*};
+pub use self::{
+    bandwidth::{BandwidthCounter, BandwidthStats},
+    blocklist::{BlockList, NetBlockList},
+    config::*,
+    peer_manager::*,
+    peer_store::{PeerRecord, PeerStore},
+    reputation::PeerScore,
+    service::*,
+};
@@ -2,7 +2,10 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 use lazy_static::lazy_static;
-use prometheus::core::{AtomicU64, GenericCounter, GenericGauge};
+use prometheus::{
+    core::{AtomicU64, GenericCounter, GenericCounterVec, GenericGauge, Opts},
+    Histogram, HistogramOpts,
+};
 
 lazy_static! {
     pub static ref PEER_FAILURE_TOTAL: Box<GenericCounter<AtomicU64>> = {
@@ -46,4 +49,71 @@ lazy_static! {
             .expect("Registering the bad_peers metric with the metrics registry must succeed");
         bad_peers
     };
+    pub static ref CHAIN_EXCHANGE_RESPONSE_BYTES: Box<Histogram> = {
+        let chain_exchange_response_bytes = Box::new(
+            Histogram::with_opts(HistogramOpts {
+                common_opts: Opts::new(
+                    "chain_exchange_response_bytes",
+                    "Size of the chain exchange responses served by the node, in bytes",
+                ),
+                buckets: prometheus::exponential_buckets(1024., 4., 10)
+                    .expect("Defining the chain_exchange_response_bytes buckets must succeed"),
+            })
+            .expect("Defining the chain_exchange_response_bytes metric must succeed"),
+        );
+        prometheus::default_registry()
+            .register(chain_exchange_response_bytes.clone())
+            .expect(
+                "Registering the chain_exchange_response_bytes metric with the metrics registry must succeed",
+            );
+        chain_exchange_response_bytes
+    };
+    pub static ref CHAIN_EXCHANGE_RESPONSES_TOTAL: Box<GenericCounterVec<AtomicU64>> = {
+        let chain_exchange_responses_total = Box::new(
+            GenericCounterVec::<AtomicU64>::new(
+                Opts::new(
+                    "chain_exchange_responses_total",
+                    "Total number of chain exchange responses served by the node, by status",
+                ),
+                &[labels::STATUS],
+            )
+            .expect("Defining the chain_exchange_responses_total metric must succeed"),
+        );
+        prometheus::default_registry()
+            .register(chain_exchange_responses_total.clone())
+            .expect(
+                "Registering the chain_exchange_responses_total metric with the metrics registry must succeed",
+            );
+        chain_exchange_responses_total
+    };
+    pub static ref CHAIN_EXCHANGE_MESSAGE_CACHE: Box<GenericCounterVec<AtomicU64>> = {
+        let chain_exchange_message_cache = Box::new(
+            GenericCounterVec::<AtomicU64>::new(
+                Opts::new(
+                    "chain_exchange_message_cache",
+                    "Lookups in the cache of compacted messages used to serve chain exchange requests",
+                ),
+                &[labels::KIND],
+            )
+            .expect("Defining the chain_exchange_message_cache metric must succeed"),
+        );
+        prometheus::default_registry()
+            .register(chain_exchange_message_cache.clone())
+            .expect(
+                "Registering the chain_exchange_message_cache metric with the metrics registry must succeed",
+            );
+        chain_exchange_message_cache
+    };
+}
+
+pub mod labels {
+    pub const STATUS: &str = "status";
+    pub const KIND: &str = "kind";
+}
+
+pub mod values {
+    /// Chain exchange message cache hit.
+    pub const HIT: &str = "hit";
+    /// Chain exchange message cache miss.
+    pub const MISS: &str = "miss";
 }
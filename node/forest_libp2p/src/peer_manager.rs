@@ -3,18 +3,25 @@
 
 use std::{
     cmp::Ordering,
+    num::NonZeroUsize,
     sync::Arc,
     time::{Duration, Instant},
 };
 
 use ahash::{HashMap, HashSet};
+use cid::Cid;
 use flume::{Receiver, Sender};
 use forest_blocks::Tipset;
 use log::{debug, trace, warn};
+use lru::LruCache;
+use parking_lot::Mutex;
 use rand::seq::SliceRandom;
 use tokio::sync::RwLock;
 
-use crate::*;
+use crate::{
+    reputation::{BAN_SCORE_THRESHOLD, MAX_SCORE, REPUTATION_BAN_DURATION},
+    *,
+};
 
 /// New peer multiplier slightly less than 1 to incentivize choosing new peers.
 const NEW_PEER_MUL: f64 = 0.9;
@@ -27,6 +34,11 @@ const LOCAL_INV_ALPHA: u32 = 5;
 /// Global duration multiplier, affects duration delta change.
 const GLOBAL_INV_ALPHA: u32 = 20;
 
+/// Number of blocks for which the peer that served them is remembered, so that
+/// it can be blamed if they turn out to be invalid.
+const BLOCK_PROVIDERS_CAPACITY: NonZeroUsize =
+    forest_utils::const_option!(NonZeroUsize::new(1 << 14));
+
 #[derive(Debug, Default)]
 /// Contains info about the peer's head [Tipset], as well as the request stats.
 struct PeerInfo {
@@ -51,6 +63,18 @@ impl PeerInfo {
     }
 }
 
+/// Connection details of a peer, gathered from the `identify` and `ping`
+/// protocols.
+#[derive(Debug, Clone, Default)]
+pub struct PeerNetInfo {
+    /// Agent version the peer advertised.
+    pub agent_version: Option<String>,
+    /// Protocols supported by the peer.
+    pub protocols: Vec<String>,
+    /// Latest round trip time measured with the peer.
+    pub latency: Option<Duration>,
+}
+
 /// Peer tracking sets, these are handled together to avoid race conditions or
 /// deadlocks when updating state.
 #[derive(Default)]
@@ -60,6 +84,9 @@ struct PeerSets {
     /// Set of peers to ignore for being incompatible/ failing to accept
     /// connections.
     bad_peers: HashSet<PeerId>,
+    /// Reputation scores of peers. Unlike `full_peers`, these are kept when a
+    /// peer disconnects and persisted across restarts.
+    scores: HashMap<PeerId, PeerScore>,
 }
 
 /// Thread safe peer manager which handles peer management for the
@@ -75,6 +102,12 @@ pub struct PeerManager {
     peer_ops_rx: Receiver<PeerOperation>,
     /// Peer ban list, key is peer id, value is expiration time
     peer_ban_list: RwLock<HashMap<PeerId, Option<Instant>>>,
+    /// Connection details of connected peers.
+    peer_net_info: RwLock<HashMap<PeerId, PeerNetInfo>>,
+    /// Peers which are never banned.
+    protected_peers: RwLock<HashSet<PeerId>>,
+    /// Peers which served recently received block headers.
+    block_providers: Mutex<LruCache<Cid, PeerId>>,
 }
 
 impl Default for PeerManager {
@@ -86,6 +119,9 @@ impl Default for PeerManager {
             peer_ops_tx,
             peer_ops_rx,
             peer_ban_list: Default::default(),
+            peer_net_info: Default::default(),
+            protected_peers: Default::default(),
+            block_providers: Mutex::new(LruCache::new(BLOCK_PROVIDERS_CAPACITY)),
         }
     }
 }
@@ -111,7 +147,7 @@ impl PeerManager {
     }
 
     /// Sort peers based on a score function with the success rate and latency
-    /// of requests.
+    /// of requests, weighted by the reputation of each peer.
     pub(crate) async fn sorted_peers(&self) -> Vec<PeerId> {
         let peer_lk = self.peers.read().await;
         let average_time = self.avg_global_time.read().await;
@@ -119,7 +155,7 @@ impl PeerManager {
             .full_peers
             .iter()
             .map(|(p, info)| {
-                let cost = if (info.successes + info.failures) > 0 {
+                let base_cost = if (info.successes + info.failures) > 0 {
                     // Calculate cost based on fail rate and latency
                     let fail_rate = f64::from(info.failures) / f64::from(info.successes);
                     info.average_time.as_secs_f64() + fail_rate * average_time.as_secs_f64()
@@ -127,6 +163,14 @@ impl PeerManager {
                     // There have been no failures or successes
                     average_time.as_secs_f64() * NEW_PEER_MUL
                 };
+                // Scale the cost by the reputation gathered over time, from 0.5 for
+                // the best peers to 1.5 for the worst ones.
+                let score = peer_lk
+                    .scores
+                    .get(p)
+                    .map(PeerScore::total)
+                    .unwrap_or_default();
+                let cost = base_cost * (1. - score / (2. * MAX_SCORE));
                 (p, cost)
             })
             .collect();
@@ -184,6 +228,7 @@ impl PeerManager {
         let peer_stats = peers.full_peers.entry(peer).or_default();
         peer_stats.successes += 1;
         log_time(peer_stats, dur);
+        peers.scores.entry(peer).or_default().log_success();
     }
 
     /// Logs a failure for the given peer, and updates the average request
@@ -191,6 +236,7 @@ impl PeerManager {
     pub async fn log_failure(&self, peer: PeerId, dur: Duration) {
         debug!("logging failure for {:?}", peer);
         let mut peers = self.peers.write().await;
+        let mut score = None;
         if !peers.bad_peers.contains(&peer) {
             metrics::PEER_FAILURE_TOTAL.inc();
             if !peers.full_peers.contains_key(&peer) {
@@ -199,6 +245,93 @@ impl PeerManager {
             let peer_stats = peers.full_peers.entry(peer).or_default();
             peer_stats.failures += 1;
             log_time(peer_stats, dur);
+            let peer_score = peers.scores.entry(peer).or_default();
+            peer_score.log_failure();
+            score = Some(*peer_score);
+        }
+        drop(peers);
+        if let Some(score) = score {
+            self.ban_if_disreputable(peer, &score).await;
+        }
+    }
+
+    /// Rewards a peer for a block we requested over bitswap.
+    pub async fn log_bitswap_block(&self, peer: PeerId) {
+        let mut peers = self.peers.write().await;
+        peers.scores.entry(peer).or_default().log_bitswap_block();
+    }
+
+    /// Updates the gossipsub scores of peers, as computed by the gossipsub
+    /// behaviour.
+    pub async fn update_gossipsub_scores(&self, scores: impl IntoIterator<Item = (PeerId, f64)>) {
+        let mut updated = vec![];
+        {
+            let mut peers = self.peers.write().await;
+            for (peer, gossipsub_score) in scores {
+                let score = peers.scores.entry(peer).or_default();
+                score.set_gossipsub_score(gossipsub_score);
+                updated.push((peer, *score));
+            }
+        }
+        for (peer, score) in updated {
+            self.ban_if_disreputable(peer, &score).await;
+        }
+    }
+
+    /// Remembers the peer a set of block headers was received from.
+    pub fn record_block_providers(&self, peer: PeerId, blocks: impl IntoIterator<Item = Cid>) {
+        let mut block_providers = self.block_providers.lock();
+        for cid in blocks {
+            block_providers.put(cid, peer);
+        }
+    }
+
+    /// Penalizes the peer which served an invalid block, if it is known.
+    pub async fn blame_block_provider(&self, block: &Cid, reason: &str) {
+        let provider = self.block_providers.lock().pop(block);
+        if let Some(peer) = provider {
+            self.blame_peer(peer, format!("invalid block {block}: {reason}"))
+                .await;
+        }
+    }
+
+    /// Penalizes a peer for serving invalid data.
+    pub async fn blame_peer(&self, peer: PeerId, reason: impl Into<String>) {
+        let reason = reason.into();
+        debug!("blaming {peer} for {reason}");
+        let score = {
+            let mut peers = self.peers.write().await;
+            let score = peers.scores.entry(peer).or_default();
+            score.log_invalid_data();
+            *score
+        };
+        self.ban_if_disreputable(peer, &score).await;
+    }
+
+    /// Bans a peer whose reputation dropped below [`BAN_SCORE_THRESHOLD`],
+    /// unless it is already banned.
+    async fn ban_if_disreputable(&self, peer: PeerId, score: &PeerScore) {
+        let total = score.total();
+        if total > BAN_SCORE_THRESHOLD || self.peer_ban_list.read().await.contains_key(&peer) {
+            return;
+        }
+        self.ban_peer(
+            peer,
+            format!("Reputation score {total:.1} below threshold {BAN_SCORE_THRESHOLD}"),
+            Some(REPUTATION_BAN_DURATION),
+        )
+        .await;
+    }
+
+    /// Gives unbanned peers a fresh start, as their score would otherwise
+    /// still be below [`BAN_SCORE_THRESHOLD`] and get them banned again on
+    /// their first failure.
+    async fn reset_scores(&self, unbanned: &[PeerId]) {
+        let mut peers = self.peers.write().await;
+        for peer in unbanned {
+            if let Some(score) = peers.scores.get_mut(peer) {
+                *score = PeerScore::default();
+            }
         }
     }
 
@@ -231,6 +364,18 @@ impl PeerManager {
         removed
     }
 
+    /// Seeds the reputation scores, typically with the ones persisted in the
+    /// previous session.
+    pub async fn seed_peer_scores(&self, scores: impl IntoIterator<Item = (PeerId, PeerScore)>) {
+        let mut peers = self.peers.write().await;
+        peers.scores.extend(scores);
+    }
+
+    /// Returns the reputation scores of all known peers.
+    pub async fn peer_scores(&self) -> HashMap<PeerId, PeerScore> {
+        self.peers.read().await.scores.clone()
+    }
+
     /// Gets peer operation receiver
     pub fn peer_ops_rx(&self) -> &Receiver<PeerOperation> {
         &self.peer_ops_rx
@@ -243,6 +388,10 @@ impl PeerManager {
         reason: impl Into<String>,
         duration: Option<Duration>,
     ) {
+        if self.is_peer_protected(&peer).await {
+            debug!("Not banning protected peer {peer}");
+            return;
+        }
         let mut locked = self.peer_ban_list.write().await;
         locked.insert(peer, duration.and_then(|d| Instant::now().checked_add(d)));
         if let Err(e) = self
@@ -254,12 +403,81 @@ impl PeerManager {
         }
     }
 
+    /// Updates the agent version and protocols of a peer, as advertised through
+    /// `identify`.
+    pub async fn update_peer_identity(
+        &self,
+        peer_id: PeerId,
+        agent_version: String,
+        protocols: Vec<String>,
+    ) {
+        let mut peer_net_info = self.peer_net_info.write().await;
+        let info = peer_net_info.entry(peer_id).or_default();
+        info.agent_version = Some(agent_version);
+        info.protocols = protocols;
+    }
+
+    /// Updates the round trip time measured with a peer.
+    pub async fn update_peer_latency(&self, peer_id: PeerId, rtt: Duration) {
+        let mut peer_net_info = self.peer_net_info.write().await;
+        peer_net_info.entry(peer_id).or_default().latency = Some(rtt);
+    }
+
+    /// Returns the connection details of a peer, if any were collected.
+    pub async fn peer_net_info(&self, peer_id: &PeerId) -> Option<PeerNetInfo> {
+        self.peer_net_info.read().await.get(peer_id).cloned()
+    }
+
+    /// Drops the connection details of a disconnected peer.
+    pub async fn remove_peer_net_info(&self, peer_id: &PeerId) {
+        self.peer_net_info.write().await.remove(peer_id);
+    }
+
+    /// Protects a peer from being banned. Any ban already in place is lifted.
+    pub async fn protect_peer(&self, peer_id: PeerId) {
+        self.protected_peers.write().await.insert(peer_id);
+        if self.peer_ban_list.write().await.remove(&peer_id).is_some() {
+            self.reset_scores(&[peer_id]).await;
+            if let Err(e) = self
+                .peer_ops_tx
+                .send_async(PeerOperation::Unban(peer_id))
+                .await
+            {
+                warn!("unban_peer err: {e}");
+            }
+        }
+    }
+
+    /// Removes the protection of a peer, returns true if it was protected.
+    pub async fn unprotect_peer(&self, peer_id: &PeerId) -> bool {
+        self.protected_peers.write().await.remove(peer_id)
+    }
+
+    pub async fn is_peer_protected(&self, peer_id: &PeerId) -> bool {
+        self.protected_peers.read().await.contains(peer_id)
+    }
+
+    /// Returns the list of protected peers.
+    pub async fn protected_peers(&self) -> Vec<PeerId> {
+        self.protected_peers.read().await.iter().copied().collect()
+    }
+
     pub async fn peer_operation_event_loop_task(self: Arc<Self>) -> anyhow::Result<()> {
         let mut unban_list = vec![];
+        let mut last_decay = Instant::now();
         loop {
             unban_list.clear();
 
             let now = Instant::now();
+            {
+                let elapsed = now.duration_since(last_decay);
+                let mut peers = self.peers.write().await;
+                for score in peers.scores.values_mut() {
+                    score.decay(elapsed);
+                }
+                last_decay = now;
+            }
+
             for (peer, expiration) in self.peer_ban_list.read().await.iter() {
                 if let Some(expiration) = expiration {
                     if &now > expiration {
@@ -274,6 +492,7 @@ impl PeerManager {
                         locked.remove(peer);
                     }
                 }
+                self.reset_scores(&unban_list).await;
                 for &peer in unban_list.iter() {
                     if let Err(e) = self
                         .peer_ops_tx
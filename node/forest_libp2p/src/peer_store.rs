@@ -0,0 +1,245 @@
+// Copyright 2019-2023 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use std::{
+    cmp::Ordering,
+    str::FromStr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use ahash::HashMap;
+use forest_db::Store;
+use libp2p::{Multiaddr, PeerId};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+
+use crate::PeerScore;
+
+/// Key under which the known peers are persisted.
+const PEER_STORE_KEY: &str = "peer_store";
+
+/// Maximum number of peers kept in the store. Peers with the lowest scores are
+/// evicted first.
+const MAX_STORED_PEERS: usize = 1000;
+
+/// Peers which have not been seen for this long are dropped from the store.
+const PEER_RECORD_TTL: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// What is known about a peer from previous sessions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PeerRecord {
+    /// Addresses the peer was reachable at.
+    pub addresses: Vec<Multiaddr>,
+    /// Last time the peer was connected, in seconds since the Unix epoch.
+    pub last_seen: u64,
+    /// Reputation of the peer.
+    pub score: PeerScore,
+    /// Time the score was last decayed to, in seconds since the Unix epoch.
+    /// Zero for records written before it was persisted, whose scores were
+    /// decayed up to `last_seen`.
+    #[serde(default)]
+    pub score_decayed_at: u64,
+}
+
+/// Known peers, persisted in the database so that the node can reconnect to
+/// good peers right after a restart instead of relying on bootstrap peers
+/// only.
+pub struct PeerStore<DB> {
+    db: DB,
+    records: HashMap<PeerId, PeerRecord>,
+}
+
+impl<DB: Store> PeerStore<DB> {
+    /// Loads the known peers from the database. Scores are decayed by the time
+    /// elapsed since they were last decayed, and stale peers are dropped.
+    pub fn load(db: DB) -> Self {
+        let records = match read_records(&db) {
+            Ok(records) => records,
+            Err(e) => {
+                warn!("Failed to load the peer store: {e}");
+                Default::default()
+            }
+        };
+        let now = unix_now();
+        let records: HashMap<_, _> = records
+            .into_iter()
+            .filter_map(|(peer_id, mut record)| {
+                let age = Duration::from_secs(now.saturating_sub(record.last_seen));
+                if age > PEER_RECORD_TTL {
+                    return None;
+                }
+                let decayed_at = match record.score_decayed_at {
+                    0 => record.last_seen,
+                    decayed_at => decayed_at,
+                };
+                record
+                    .score
+                    .decay(Duration::from_secs(now.saturating_sub(decayed_at)));
+                record.score_decayed_at = now;
+                Some((peer_id, record))
+            })
+            .collect();
+        debug!("Loaded {} peers from the peer store", records.len());
+        Self { db, records }
+    }
+
+    /// Returns up to `n` known peers, best scores first.
+    pub fn best_peers(&self, n: usize) -> Vec<(PeerId, PeerRecord)> {
+        let mut peers: Vec<_> = self
+            .records
+            .iter()
+            .map(|(peer_id, record)| (*peer_id, record.clone()))
+            .collect();
+        peers.sort_unstable_by(|(_, a), (_, b)| {
+            b.score
+                .total()
+                .partial_cmp(&a.score.total())
+                .unwrap_or(Ordering::Equal)
+        });
+        peers.truncate(n);
+        peers
+    }
+
+    /// Records that a peer is connected at the given addresses.
+    pub fn peer_seen(&mut self, peer_id: PeerId, addresses: Vec<Multiaddr>) {
+        let record = self.records.entry(peer_id).or_insert_with(|| PeerRecord {
+            addresses: vec![],
+            last_seen: 0,
+            score: Default::default(),
+            score_decayed_at: 0,
+        });
+        if !addresses.is_empty() {
+            record.addresses = addresses;
+        }
+        record.last_seen = unix_now();
+    }
+
+    /// Updates the scores of known peers, as decayed by the peer manager up to
+    /// now.
+    pub fn update_scores(&mut self, scores: &HashMap<PeerId, PeerScore>) {
+        let now = unix_now();
+        for (peer_id, record) in self.records.iter_mut() {
+            if let Some(score) = scores.get(peer_id) {
+                record.score = *score;
+                record.score_decayed_at = now;
+            }
+        }
+    }
+
+    /// Writes the known peers to the database, evicting the worst ones if the
+    /// store is over capacity.
+    pub fn save(&mut self) -> anyhow::Result<()> {
+        if self.records.len() > MAX_STORED_PEERS {
+            self.records = self.best_peers(MAX_STORED_PEERS).into_iter().collect();
+        }
+        let records: HashMap<String, &PeerRecord> = self
+            .records
+            .iter()
+            .map(|(peer_id, record)| (peer_id.to_string(), record))
+            .collect();
+        self.db
+            .write(PEER_STORE_KEY, serde_json::to_vec(&records)?)?;
+        debug!("Saved {} peers to the peer store", records.len());
+        Ok(())
+    }
+}
+
+fn read_records(db: &impl Store) -> anyhow::Result<HashMap<PeerId, PeerRecord>> {
+    let bytes = match db.read(PEER_STORE_KEY)? {
+        Some(bytes) => bytes,
+        None => return Ok(Default::default()),
+    };
+    let records: HashMap<String, PeerRecord> = serde_json::from_slice(&bytes)?;
+    records
+        .into_iter()
+        .map(|(peer_id, record)| Ok((PeerId::from_str(&peer_id)?, record)))
+        .collect()
+}
+
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("System time before unix epoch")
+        .as_secs()
+}
+
+#[cfg(test)]
+mod tests {
+    use forest_db::MemoryDB;
+
+    use super::*;
+
+    #[test]
+    fn peer_store_roundtrip() {
+        let db = MemoryDB::default();
+        let good_peer = PeerId::random();
+        let bad_peer = PeerId::random();
+        let addr: Multiaddr = "/ip4/127.0.0.1/tcp/1234".parse().unwrap();
+
+        let mut store = PeerStore::load(&db);
+        store.peer_seen(good_peer, vec![addr.clone()]);
+        store.peer_seen(bad_peer, vec![]);
+        let score = |chain_exchange| PeerScore {
+            chain_exchange,
+            ..Default::default()
+        };
+        store.update_scores(&HashMap::from_iter([
+            (good_peer, score(10.)),
+            (bad_peer, score(-10.)),
+        ]));
+        store.save().unwrap();
+
+        let store = PeerStore::load(&db);
+        let peers = store.best_peers(10);
+        assert_eq!(peers.len(), 2);
+        assert_eq!(peers[0].0, good_peer);
+        assert_eq!(peers[0].1.addresses, vec![addr]);
+        assert_eq!(peers[1].0, bad_peer);
+    }
+
+    #[test]
+    fn stale_peers_are_dropped() {
+        let db = MemoryDB::default();
+        let records = HashMap::from_iter([(
+            PeerId::random().to_string(),
+            PeerRecord {
+                addresses: vec![],
+                last_seen: unix_now() - PEER_RECORD_TTL.as_secs() - 1,
+                score: Default::default(),
+                score_decayed_at: 0,
+            },
+        )]);
+        db.write(PEER_STORE_KEY, serde_json::to_vec(&records).unwrap())
+            .unwrap();
+
+        assert!(PeerStore::load(&db).best_peers(10).is_empty());
+    }
+
+    #[test]
+    fn scores_are_decayed_from_last_decay() {
+        let db = MemoryDB::default();
+        let peer = PeerId::random();
+        let now = unix_now();
+        let score = PeerScore {
+            chain_exchange: 10.,
+            ..Default::default()
+        };
+        let records = HashMap::from_iter([(
+            peer.to_string(),
+            PeerRecord {
+                addresses: vec![],
+                last_seen: now - 24 * 60 * 60,
+                score,
+                score_decayed_at: now,
+            },
+        )]);
+        db.write(PEER_STORE_KEY, serde_json::to_vec(&records).unwrap())
+            .unwrap();
+
+        // Decayed up to now when saved, so loading right away barely decays it
+        let mut expected = score;
+        expected.decay(Duration::from_secs(1));
+        let loaded = PeerStore::load(&db).best_peers(1)[0].1.score.total();
+        assert!(loaded >= expected.total());
+    }
+}
@@ -0,0 +1,243 @@
+// Copyright 2019-2023 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use std::sync::Arc;
+
+use ahash::{HashMap, HashSet};
+use cid::Cid;
+use forest_blocks::{Tipset, TipsetKeys};
+use forest_chain::{read_msg_cids, ChainStore};
+use forest_db::Store;
+use forest_ipld::{CidHashSet, Ipld};
+use forest_libp2p_bitswap::ServeFilter;
+use fvm_ipld_blockstore::Blockstore;
+use fvm_ipld_encoding::{from_slice, DAG_CBOR};
+use fvm_shared::clock::ChainEpoch;
+use log::debug;
+use parking_lot::{Mutex, RwLock};
+
+/// Blocks of the tipsets within a given depth of the head: headers, messages,
+/// receipt roots and the nodes of their state trees. Used to restrict what is
+/// served over bitswap.
+pub(crate) struct RecentChainBlocks {
+    depth: u64,
+    allowed: Arc<RwLock<HashSet<Cid>>>,
+    // Nodes of the state trees of the tipsets in the window. They are only
+    // added to as the head moves, and collected again from scratch every
+    // `depth` epochs, so they span at most twice the window.
+    states: Arc<RwLock<CidHashSet>>,
+    state: Mutex<RecentChainState>,
+}
+
+#[derive(Default)]
+struct RecentChainState {
+    head: Option<TipsetKeys>,
+    // Blocks of each tipset in the window, so that only new tipsets are read
+    // when the head moves.
+    by_tipset: HashMap<TipsetKeys, Arc<Vec<Cid>>>,
+    // Epoch of the head when the state tree nodes were last collected from
+    // scratch.
+    states_epoch: Option<ChainEpoch>,
+}
+
+impl RecentChainBlocks {
+    pub fn new(depth: u64) -> Self {
+        Self {
+            depth,
+            allowed: Default::default(),
+            states: Default::default(),
+            state: Default::default(),
+        }
+    }
+
+    /// Filter allowing the recent blocks only.
+    pub fn serve_filter(&self) -> ServeFilter {
+        let allowed = self.allowed.clone();
+        let states = self.states.clone();
+        Arc::new(move |cid| allowed.read().contains(cid) || states.read().contains(cid))
+    }
+
+    /// Follows the head of the chain. Performs database IO, so it should not be
+    /// called from async context.
+    pub fn update<DB>(&self, cs: &ChainStore<DB>)
+    where
+        DB: Blockstore + Store + Send + Sync,
+    {
+        let mut state = self.state.lock();
+        let head = cs.heaviest_tipset();
+        if state.head.as_ref() == Some(head.key()) {
+            return;
+        }
+
+        let mut by_tipset = HashMap::default();
+        let mut state_roots = vec![];
+        let mut tipset = head.clone();
+        loop {
+            state_roots.push(*tipset.parent_state());
+            let cids = state
+                .by_tipset
+                .remove(tipset.key())
+                .unwrap_or_else(|| Arc::new(tipset_blocks(cs.blockstore(), &tipset)));
+            by_tipset.insert(tipset.key().clone(), cids);
+            if tipset.epoch() == 0 || (head.epoch() - tipset.epoch()) as u64 >= self.depth {
+                break;
+            }
+            tipset = match cs.tipset_from_keys(tipset.parents()) {
+                Ok(parent) => parent,
+                Err(e) => {
+                    debug!("Failed to load tipset for the bitswap serve filter: {e}");
+                    break;
+                }
+            };
+        }
+
+        let allowed = by_tipset
+            .values()
+            .flat_map(|cids| cids.iter().copied())
+            .collect();
+        *self.allowed.write() = allowed;
+        state.by_tipset = by_tipset;
+        state.head = Some(head.key().clone());
+
+        let stale = match state.states_epoch {
+            Some(epoch) => (head.epoch() - epoch) as u64 >= self.depth,
+            None => true,
+        };
+        if stale {
+            let mut states = CidHashSet::default();
+            for root in state_roots {
+                walk_state(cs.blockstore(), root, |cid| states.insert(cid));
+            }
+            *self.states.write() = states;
+            state.states_epoch = Some(head.epoch());
+        } else {
+            // Only the nodes that aren't allowed yet are walked, the ones the
+            // new state trees don't share with the previous ones.
+            let mut new = vec![];
+            {
+                let states = self.states.read();
+                let mut walked = CidHashSet::default();
+                for root in state_roots {
+                    walk_state(cs.blockstore(), root, |cid| {
+                        if states.contains(cid) || !walked.insert(cid) {
+                            return false;
+                        }
+                        new.push(*cid);
+                        true
+                    });
+                }
+            }
+            let mut states = self.states.write();
+            for cid in &new {
+                states.insert(cid);
+            }
+        }
+    }
+}
+
+/// Walks the nodes of a state tree that are in the store, and the raw blocks
+/// they link to, down from the ones for which `visit` returns true.
+fn walk_state<DB: Blockstore>(db: &DB, root: Cid, mut visit: impl FnMut(&Cid) -> bool) {
+    let mut stack = vec![root];
+    while let Some(cid) = stack.pop() {
+        if cid.codec() != DAG_CBOR {
+            visit(&cid);
+            continue;
+        }
+        // Nodes missing from the store are visited once they are there.
+        let Ok(Some(data)) = db.get(&cid) else {
+            continue;
+        };
+        if !visit(&cid) {
+            continue;
+        }
+        if let Ok(ipld) = from_slice::<Ipld>(&data) {
+            push_links(&ipld, &mut stack);
+        }
+    }
+}
+
+fn push_links(ipld: &Ipld, links: &mut Vec<Cid>) {
+    match ipld {
+        Ipld::Map(map) => map.values().for_each(|v| push_links(v, links)),
+        Ipld::List(list) => list.iter().for_each(|v| push_links(v, links)),
+        Ipld::Link(cid) => links.push(*cid),
+        _ => (),
+    }
+}
+
+fn tipset_blocks<DB: Blockstore>(db: &DB, tipset: &Tipset) -> Vec<Cid> {
+    let mut cids = vec![];
+    for header in tipset.blocks() {
+        cids.extend([
+            *header.cid(),
+            *header.messages(),
+            *header.message_receipts(),
+        ]);
+        // Messages of blocks that were never validated may be missing
+        if let Ok((bls_cids, secp_cids)) = read_msg_cids(db, header.messages()) {
+            cids.extend(bls_cids);
+            cids.extend(secp_cids);
+        }
+    }
+    cids
+}
+
+#[cfg(test)]
+mod tests {
+    use cid::multihash::Code::Blake2b256;
+    use forest_blocks::BlockHeader;
+    use forest_db::MemoryDB;
+    use forest_networks::ChainConfig;
+    use forest_shim::address::Address;
+    use forest_utils::db::BlockstoreExt;
+
+    use super::*;
+
+    #[test]
+    fn serve_filter_allows_the_state_trees_of_recent_tipsets() {
+        let db = MemoryDB::default();
+        let shared_leaf = db.put_obj(&"shared", Blake2b256).unwrap();
+        let mut state_leaves = vec![];
+        let mut headers: Vec<BlockHeader> = vec![];
+        for epoch in 0..4 {
+            let state_leaf = db.put_obj(&("state", epoch), Blake2b256).unwrap();
+            let state_root = db.put_obj(&(shared_leaf, state_leaf), Blake2b256).unwrap();
+            let parents = match headers.last() {
+                Some(parent) => TipsetKeys::new(vec![*parent.cid()]),
+                None => TipsetKeys::default(),
+            };
+            let header = BlockHeader::builder()
+                .epoch(epoch)
+                .weight(epoch.into())
+                .parents(parents)
+                .state_root(state_root)
+                .miner_address(Address::new_id(0))
+                .build()
+                .unwrap();
+            db.put_obj(&header, Blake2b256).unwrap();
+            state_leaves.push(state_leaf);
+            headers.push(header);
+        }
+        let cs = ChainStore::new(db, Arc::new(ChainConfig::default()), &headers[0]).unwrap();
+        let recent_chain_blocks = RecentChainBlocks::new(1);
+        let serve_filter = recent_chain_blocks.serve_filter();
+
+        cs.set_heaviest_tipset(Arc::new(Tipset::from(&headers[2])))
+            .unwrap();
+        recent_chain_blocks.update(&cs);
+        assert!(serve_filter(&shared_leaf));
+        assert!(serve_filter(&state_leaves[1]));
+        assert!(serve_filter(&state_leaves[2]));
+        assert!(!serve_filter(&state_leaves[3]));
+
+        // The state tree nodes of the tipsets that left the window are dropped
+        // once they are collected again.
+        cs.set_heaviest_tipset(Arc::new(Tipset::from(&headers[3])))
+            .unwrap();
+        recent_chain_blocks.update(&cs);
+        assert!(serve_filter(headers[3].cid()));
+        assert!(serve_filter(&state_leaves[3]));
+        assert!(!serve_filter(&state_leaves[1]));
+    }
+}
@@ -0,0 +1,126 @@
+// Copyright 2019-2023 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+/// Bounds of the total reputation score of a peer.
+pub(crate) const MAX_SCORE: f64 = 100.;
+pub(crate) const MIN_SCORE: f64 = -100.;
+
+/// Peers whose total score drops to this value are banned.
+pub(crate) const BAN_SCORE_THRESHOLD: f64 = -50.;
+/// How long peers with a bad reputation are banned for.
+pub(crate) const REPUTATION_BAN_DURATION: Duration = Duration::from_secs(60 * 60);
+
+/// Score change on a successful chain exchange or hello request.
+const SUCCESS_REWARD: f64 = 1.;
+/// Score change on a failed request. Failures weigh more than successes so
+/// that unreliable peers quickly fall behind.
+const FAILURE_PENALTY: f64 = -5.;
+
+/// Score change for each requested block received over bitswap.
+const BITSWAP_BLOCK_REWARD: f64 = 0.1;
+/// Bitswap usefulness is capped, so that it can not make up for serving
+/// invalid data.
+const MAX_BITSWAP_SCORE: f64 = 20.;
+
+/// Score change for serving a block that turned out to be invalid.
+const INVALID_DATA_PENALTY: f64 = -50.;
+
+/// Gossipsub scores are scaled down by this factor. With the thresholds in
+/// `gossip_params`, a graylisted peer ends up with the minimum score.
+const GOSSIPSUB_SCORE_SCALE: f64 = 25.;
+/// Gossipsub rewards first deliveries generously, cap them so that a fast
+/// gossiper does not outweigh the other components.
+const MAX_GOSSIPSUB_SCORE: f64 = 10.;
+
+/// Time for a score to decay to half its value.
+const SCORE_HALF_LIFE: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Reputation of a peer, made of what we observed from it on each protocol.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PeerScore {
+    /// Reliability when serving chain exchange and hello requests.
+    pub chain_exchange: f64,
+    /// Usefulness as a bitswap provider.
+    pub bitswap: f64,
+    /// Blame for serving blocks that failed validation.
+    pub invalid_data: f64,
+    /// Latest gossipsub score, scaled down.
+    pub gossipsub: f64,
+}
+
+impl PeerScore {
+    /// Combined score of the peer, between [`MIN_SCORE`] and [`MAX_SCORE`].
+    pub fn total(&self) -> f64 {
+        (self.chain_exchange + self.bitswap + self.invalid_data + self.gossipsub)
+            .clamp(MIN_SCORE, MAX_SCORE)
+    }
+
+    pub(crate) fn log_success(&mut self) {
+        self.chain_exchange = (self.chain_exchange + SUCCESS_REWARD).clamp(MIN_SCORE, MAX_SCORE);
+    }
+
+    pub(crate) fn log_failure(&mut self) {
+        self.chain_exchange = (self.chain_exchange + FAILURE_PENALTY).clamp(MIN_SCORE, MAX_SCORE);
+    }
+
+    pub(crate) fn log_bitswap_block(&mut self) {
+        self.bitswap = (self.bitswap + BITSWAP_BLOCK_REWARD).min(MAX_BITSWAP_SCORE);
+    }
+
+    pub(crate) fn log_invalid_data(&mut self) {
+        self.invalid_data = (self.invalid_data + INVALID_DATA_PENALTY).max(MIN_SCORE);
+    }
+
+    pub(crate) fn set_gossipsub_score(&mut self, score: f64) {
+        self.gossipsub = (score / GOSSIPSUB_SCORE_SCALE).clamp(MIN_SCORE, MAX_GOSSIPSUB_SCORE);
+    }
+
+    /// Decays all components exponentially over the elapsed time, so that old
+    /// behavior of a peer matters less than its recent behavior.
+    pub(crate) fn decay(&mut self, elapsed: Duration) {
+        let factor = 0.5f64.powf(elapsed.as_secs_f64() / SCORE_HALF_LIFE.as_secs_f64());
+        self.chain_exchange *= factor;
+        self.bitswap *= factor;
+        self.invalid_data *= factor;
+        self.gossipsub *= factor;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn invalid_data_outweighs_good_behavior() {
+        let mut score = PeerScore::default();
+        for _ in 0..1000 {
+            score.log_bitswap_block();
+        }
+        for _ in 0..10 {
+            score.log_success();
+        }
+        assert!(score.total() > 0.);
+
+        score.log_invalid_data();
+        assert!(score.total() < 0.);
+        score.log_invalid_data();
+        assert!(score.total() <= BAN_SCORE_THRESHOLD);
+    }
+
+    #[test]
+    fn score_decays_by_half() {
+        let mut score = PeerScore {
+            chain_exchange: 10.,
+            invalid_data: -50.,
+            ..Default::default()
+        };
+        score.decay(SCORE_HALF_LIFE);
+        assert!((score.chain_exchange - 5.).abs() < 1e-9);
+        assert!((score.invalid_data + 25.).abs() < 1e-9);
+    }
+}
@@ -2,7 +2,7 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 
 mod decoder;
-use std::{io, marker::PhantomData, time::Duration};
+use std::{io, marker::PhantomData, sync::Arc, time::Duration};
 
 use async_trait::async_trait;
 use decoder::DagCborDecodingReader;
@@ -13,6 +13,8 @@ use libp2p::{
 };
 use serde::{de::DeserializeOwned, Serialize};
 
+use crate::bandwidth::{BandwidthCounter, CountingStream};
+
 /// Generic `Cbor` `RequestResponse` type. This is just needed to satisfy
 /// [`RequestResponseCodec`] for Hello and `ChainExchange` protocols without
 /// duplication.
@@ -21,6 +23,7 @@ pub struct CborRequestResponse<P, RQ, RS> {
     protocol: PhantomData<P>,
     request: PhantomData<RQ>,
     response: PhantomData<RS>,
+    bandwidth: Option<Arc<BandwidthCounter>>,
 }
 
 impl<P, RQ, RS> Default for CborRequestResponse<P, RQ, RS> {
@@ -29,8 +32,32 @@ impl<P, RQ, RS> Default for CborRequestResponse<P, RQ, RS> {
             protocol: PhantomData::<P>::default(),
             request: PhantomData::<RQ>::default(),
             response: PhantomData::<RS>::default(),
+            bandwidth: None,
+        }
+    }
+}
+
+impl<P, RQ, RS> CborRequestResponse<P, RQ, RS>
+where
+    P: ProtocolName,
+{
+    /// Creates a codec recording the payload it reads and writes into the
+    /// given counter.
+    pub fn with_bandwidth_counter(bandwidth: Arc<BandwidthCounter>) -> Self {
+        Self {
+            bandwidth: Some(bandwidth),
+            ..Default::default()
         }
     }
+
+    fn counting<T>(&self, protocol: &P, io: T) -> CountingStream<T> {
+        let meters = self
+            .bandwidth
+            .iter()
+            .map(|bandwidth| bandwidth.protocol_meter(protocol.protocol_name()))
+            .collect();
+        CountingStream::new(io, meters)
+    }
 }
 
 /// Libp2p request response outbound error type. This indicates a failure
@@ -79,21 +106,26 @@ where
     type Request = RQ;
     type Response = RS;
 
-    async fn read_request<T>(&mut self, _: &Self::Protocol, io: &mut T) -> io::Result<Self::Request>
+    async fn read_request<T>(
+        &mut self,
+        protocol: &Self::Protocol,
+        io: &mut T,
+    ) -> io::Result<Self::Request>
     where
         T: AsyncRead + Unpin + Send,
     {
-        read_request_and_decode(io).await
+        read_request_and_decode(&mut self.counting(protocol, io)).await
     }
 
     async fn read_response<T>(
         &mut self,
-        _: &Self::Protocol,
+        protocol: &Self::Protocol,
         io: &mut T,
     ) -> io::Result<Self::Response>
     where
         T: AsyncRead + Unpin + Send,
     {
+        let mut io = self.counting(protocol, io);
         let mut bytes = vec![];
         io.read_to_end(&mut bytes).await?;
         serde_ipld_dagcbor::de::from_reader(bytes.as_slice())
@@ -102,26 +134,26 @@ where
 
     async fn write_request<T>(
         &mut self,
-        _: &Self::Protocol,
+        protocol: &Self::Protocol,
         io: &mut T,
         req: Self::Request,
     ) -> io::Result<()>
     where
         T: AsyncWrite + Unpin + Send,
     {
-        encode_and_write(io, req).await
+        encode_and_write(&mut self.counting(protocol, io), req).await
     }
 
     async fn write_response<T>(
         &mut self,
-        _: &Self::Protocol,
+        protocol: &Self::Protocol,
         io: &mut T,
         res: Self::Response,
     ) -> io::Result<()>
     where
         T: AsyncWrite + Unpin + Send,
     {
-        encode_and_write(io, res).await
+        encode_and_write(&mut self.counting(protocol, io), res).await
     }
 }
 
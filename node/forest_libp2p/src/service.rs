@@ -15,7 +15,8 @@ use forest_blocks::GossipBlock;
 use forest_chain::ChainStore;
 use forest_db::Store;
 use forest_libp2p_bitswap::{
-    request_manager::BitswapRequestManager, BitswapStoreRead, BitswapStoreReadWrite,
+    request_manager::BitswapRequestManager, BitswapBehaviourEvent, BitswapMessage, BitswapResponse,
+    BitswapStoreRead, BitswapStoreReadWrite,
 };
 use forest_message::SignedMessage;
 use forest_utils::io::read_file_to_vec;
@@ -28,13 +29,14 @@ use libp2p::{
     core,
     core::{muxing::StreamMuxerBox, transport::Boxed, Multiaddr},
     gossipsub::GossipsubEvent,
+    identify,
     identity::{ed25519, Keypair},
     metrics::{Metrics, Recorder},
     multiaddr::Protocol,
     multihash::Multihash,
     noise, ping,
     request_response::{RequestId, RequestResponseEvent, RequestResponseMessage, ResponseChannel},
-    swarm::{ConnectionLimits, SwarmBuilder, SwarmEvent},
+    swarm::{dial_opts::DialOpts, ConnectionLimits, SwarmBuilder, SwarmEvent},
     yamux::YamuxConfig,
     PeerId, Swarm, Transport,
 };
@@ -42,15 +44,18 @@ use log::{debug, error, info, trace, warn};
 use tokio_stream::wrappers::IntervalStream;
 
 use super::{
-    chain_exchange::{make_chain_exchange_response, ChainExchangeRequest, ChainExchangeResponse},
+    chain_exchange::{ChainExchangeRequest, ChainExchangeResponder, ChainExchangeResponse},
     ForestBehaviour, ForestBehaviourEvent, Libp2pConfig,
 };
 use crate::{
+    bandwidth::{BandwidthCounter, BandwidthStats, CountingUpgrade},
+    blocklist::{BlockList, NetBlockList},
     chain_exchange::ChainExchangeBehaviour,
     discovery::DiscoveryOut,
     hello::{HelloBehaviour, HelloRequest, HelloResponse},
+    recent_state::RecentChainBlocks,
     rpc::RequestResponseError,
-    PeerManager, PeerOperation,
+    BitswapLedger, PeerManager, PeerNetInfo, PeerOperation, PeerScore, PeerStore,
 };
 
 pub(crate) mod metrics {
@@ -92,6 +97,10 @@ pub const PUBSUB_MSG_STR: &str = "/fil/msgs";
 
 const PUBSUB_TOPICS: [&str; 2] = [PUBSUB_BLOCK_STR, PUBSUB_MSG_STR];
 
+/// `Gossipsub` protocol identifier, used to account for the bandwidth of
+/// pubsub messages.
+const GOSSIPSUB_PROTOCOL_ID: &[u8] = b"/meshsub/1.1.0";
+
 pub const BITSWAP_TIMEOUT: Duration = Duration::from_secs(10);
 
 const BAN_PEER_DURATION: Duration = Duration::from_secs(60 * 60); //1h
@@ -166,11 +175,23 @@ pub enum NetworkMessage {
         cid: Cid,
         response_channel: flume::Sender<bool>,
     },
+    /// Fetches a block over bitswap even if the store has it, and returns its
+    /// data verified against the CID without writing it to the store.
+    BitswapRefetch {
+        cid: Cid,
+        response_channel: flume::Sender<Option<Vec<u8>>>,
+    },
     JSONRPCRequest {
         method: NetRPCMethods,
     },
 }
 
+/// Interval at which the known peers are written to the peer store.
+const PEER_STORE_SAVE_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Interval at which the want list entries of rate limited peers are served.
+const BITSWAP_SERVE_INTERVAL: Duration = Duration::from_secs(1);
+
 /// Network RPC API methods used to gather data from libp2p node.
 #[derive(Debug)]
 pub enum NetRPCMethods {
@@ -178,6 +199,19 @@ pub enum NetRPCMethods {
     NetPeers(OneShotSender<HashMap<PeerId, Vec<Multiaddr>>>),
     NetConnect(OneShotSender<bool>, PeerId, Vec<Multiaddr>),
     NetDisconnect(OneShotSender<()>, PeerId),
+    NetPeerInfo(OneShotSender<Option<(Vec<Multiaddr>, PeerNetInfo)>>, PeerId),
+    NetBandwidthStats(OneShotSender<BandwidthStats>),
+    NetBandwidthStatsByPeer(OneShotSender<HashMap<PeerId, BandwidthStats>>),
+    NetBandwidthStatsByProtocol(OneShotSender<HashMap<String, BandwidthStats>>),
+    NetFindPeer(OneShotSender<Vec<Multiaddr>>, PeerId),
+    NetProtectAdd(OneShotSender<()>, Vec<PeerId>),
+    NetProtectRemove(OneShotSender<()>, Vec<PeerId>),
+    NetProtectList(OneShotSender<Vec<PeerId>>),
+    NetBlockAdd(OneShotSender<()>, NetBlockList),
+    NetBlockRemove(OneShotSender<()>, NetBlockList),
+    NetBlockList(OneShotSender<NetBlockList>),
+    NetPeerScores(OneShotSender<HashMap<PeerId, PeerScore>>),
+    NetBitswapLedger(OneShotSender<HashMap<PeerId, BitswapLedger>>),
 }
 
 /// The `Libp2pService` listens to events from the libp2p swarm.
@@ -186,6 +220,11 @@ pub struct Libp2pService<DB> {
     swarm: Swarm<ForestBehaviour>,
     cs: Arc<ChainStore<DB>>,
     peer_manager: Arc<PeerManager>,
+    bandwidth: Arc<BandwidthCounter>,
+    block_list: Arc<BlockList>,
+    peer_store: PeerStore<DB>,
+    chain_exchange_responder: Arc<ChainExchangeResponder>,
+    recent_chain_blocks: Option<Arc<RecentChainBlocks>>,
     network_receiver_in: flume::Receiver<NetworkMessage>,
     network_sender_in: Sender<NetworkMessage>,
     network_receiver_out: flume::Receiver<NetworkEvent>,
@@ -208,8 +247,10 @@ where
     ) -> Self {
         let peer_id = PeerId::from(net_keypair.public());
 
-        let transport =
-            build_transport(net_keypair.clone()).expect("Failed to build libp2p transport");
+        let bandwidth = Arc::new(BandwidthCounter::default());
+        let block_list = Arc::new(BlockList::default());
+        let transport = build_transport(net_keypair.clone(), bandwidth.clone(), block_list.clone())
+            .expect("Failed to build libp2p transport");
 
         let limits = ConnectionLimits::default()
             .with_max_pending_incoming(Some(10))
@@ -220,7 +261,7 @@ where
 
         let mut swarm = SwarmBuilder::with_tokio_executor(
             transport,
-            ForestBehaviour::new(&net_keypair, &config, network_name),
+            ForestBehaviour::new(&net_keypair, &config, network_name, &bandwidth),
             peer_id,
         )
         .connection_limits(limits)
@@ -237,11 +278,28 @@ where
         let (network_sender_in, network_receiver_in) = flume::unbounded();
         let (network_sender_out, network_receiver_out) = flume::unbounded();
 
+        let peer_store = PeerStore::load(cs.blockstore().clone());
+        let chain_exchange_responder =
+            Arc::new(ChainExchangeResponder::new(config.chain_exchange.clone()));
+        let recent_chain_blocks = config.bitswap.serve_recent_state_only.then(|| {
+            let recent_chain_blocks = RecentChainBlocks::new(config.bitswap.recent_state_depth);
+            swarm
+                .behaviour_mut()
+                .bitswap
+                .set_serve_filter(recent_chain_blocks.serve_filter());
+            Arc::new(recent_chain_blocks)
+        });
+
         Libp2pService {
             config,
             swarm,
             cs,
             peer_manager,
+            bandwidth,
+            block_list,
+            peer_store,
+            chain_exchange_responder,
+            recent_chain_blocks,
             network_receiver_in,
             network_sender_in,
             network_receiver_out,
@@ -266,11 +324,17 @@ where
             warn!("Failed to bootstrap with Kademlia: {e}");
         }
 
+        self.connect_known_peers().await;
+
         let bitswap_request_manager = self.swarm.behaviour().bitswap.request_manager();
         let mut swarm_stream = self.swarm.fuse();
         let mut network_stream = self.network_receiver_in.stream().fuse();
         let mut interval =
             IntervalStream::new(tokio::time::interval(Duration::from_secs(15))).fuse();
+        let mut peer_store_interval =
+            IntervalStream::new(tokio::time::interval(PEER_STORE_SAVE_INTERVAL)).fuse();
+        let mut bitswap_serve_interval =
+            IntervalStream::new(tokio::time::interval(BITSWAP_SERVE_INTERVAL)).fuse();
         let pubsub_block_str = format!("{}/{}", PUBSUB_BLOCK_STR, self.network_name);
         let pubsub_msg_str = format!("{}/{}", PUBSUB_MSG_STR, self.network_name);
 
@@ -296,7 +360,9 @@ where
                             &bitswap_request_manager,
                             &self.peer_manager,
                             event,
+                            &self.bandwidth,
                             &self.cs,
+                            &self.chain_exchange_responder,
                             &self.genesis_cid,
                             &self.network_sender_out,
                             cx_response_tx.clone(),
@@ -313,6 +379,9 @@ where
                             swarm_stream.get_mut(),
                             self.cs.clone(),
                             bitswap_request_manager.clone(),
+                            &self.peer_manager,
+                            &self.bandwidth,
+                            &self.block_list,
                             message,
                             &self.network_sender_out).await;
                     }
@@ -321,6 +390,20 @@ where
                 interval_event = interval.next() => if interval_event.is_some() {
                     // Print peer count on an interval.
                     debug!("Peers connected: {}", swarm_stream.get_mut().behaviour_mut().peers().len());
+                    self.bandwidth.update_rates();
+                    let gossipsub_scores = swarm_stream.get_mut().behaviour().gossipsub_scores();
+                    self.peer_manager.update_gossipsub_scores(gossipsub_scores).await;
+                    if let Some(recent_chain_blocks) = &self.recent_chain_blocks {
+                        let recent_chain_blocks = recent_chain_blocks.clone();
+                        let cs = self.cs.clone();
+                        tokio::task::spawn_blocking(move || recent_chain_blocks.update(&cs));
+                    }
+                },
+                bitswap_serve_event = bitswap_serve_interval.next() => if bitswap_serve_event.is_some() {
+                    swarm_stream.get_mut().behaviour_mut().bitswap.serve_pending(self.cs.blockstore());
+                },
+                peer_store_event = peer_store_interval.next() => if peer_store_event.is_some() {
+                    save_peer_store(&mut self.peer_store, swarm_stream.get_mut(), &self.peer_manager).await;
                 },
                 cs_pair_opt = cx_response_rx_stream.next() => {
                     if let Some((_request_id, channel, cx_response)) = cs_pair_opt {
@@ -338,14 +421,55 @@ where
                 }
                 peer_ops_opt = peer_ops_rx_stream.next() => {
                     if let Some(peer_ops) = peer_ops_opt {
-                        handle_peer_ops(swarm_stream.get_mut(), peer_ops);
+                        handle_peer_ops(swarm_stream.get_mut(), &self.block_list, peer_ops);
                     }
                 },
             };
         }
+        save_peer_store(
+            &mut self.peer_store,
+            swarm_stream.get_mut(),
+            &self.peer_manager,
+        )
+        .await;
         Ok(())
     }
 
+    /// Seeds the peer manager and the discovery with the peers known from
+    /// previous sessions, and dials the best of them so that the node does not
+    /// have to rely on bootstrap peers only.
+    async fn connect_known_peers(&mut self) {
+        let known_peers = self
+            .peer_store
+            .best_peers(self.config.target_peer_count as usize);
+        self.peer_manager
+            .seed_peer_scores(
+                known_peers
+                    .iter()
+                    .map(|(peer_id, record)| (*peer_id, record.score)),
+            )
+            .await;
+        for (peer_id, record) in known_peers {
+            if record.addresses.is_empty() {
+                continue;
+            }
+            self.swarm
+                .behaviour_mut()
+                .add_peer_addresses(peer_id, &record.addresses);
+            // Peers with a bad reputation are kept for discovery, but not dialed
+            // eagerly.
+            if record.score.total() < 0. {
+                continue;
+            }
+            let opts = DialOpts::peer_id(peer_id)
+                .addresses(record.addresses)
+                .build();
+            if let Err(e) = self.swarm.dial(opts) {
+                debug!("Failed to dial known peer {peer_id}: {e}");
+            }
+        }
+    }
+
     /// Returns a sender which allows sending messages to the libp2p service.
     pub fn network_sender(&self) -> Sender<NetworkMessage> {
         self.network_sender_in.clone()
@@ -357,13 +481,35 @@ where
     }
 }
 
-fn handle_peer_ops(swarm: &mut Swarm<ForestBehaviour>, peer_ops: PeerOperation) {
+/// Records the connected peers along with the latest reputation scores in the
+/// peer store, and writes it to the database.
+async fn save_peer_store<DB: Store>(
+    peer_store: &mut PeerStore<DB>,
+    swarm: &mut Swarm<ForestBehaviour>,
+    peer_manager: &PeerManager,
+) {
+    for (peer_id, addresses) in swarm.behaviour_mut().peer_addresses() {
+        peer_store.peer_seen(*peer_id, addresses.clone());
+    }
+    peer_store.update_scores(&peer_manager.peer_scores().await);
+    if let Err(e) = peer_store.save() {
+        warn!("Failed to save the peer store: {e}");
+    }
+}
+
+fn handle_peer_ops(
+    swarm: &mut Swarm<ForestBehaviour>,
+    block_list: &BlockList,
+    peer_ops: PeerOperation,
+) {
     use PeerOperation::*;
     match peer_ops {
         Ban(peer_id, reason) => {
             warn!("Banning {peer_id}, reason: {reason}");
             swarm.ban_peer_id(peer_id);
         }
+        // Blocked peers stay banned until they are removed from the block list.
+        Unban(peer_id) if block_list.is_peer_blocked(&peer_id) => {}
         Unban(peer_id) => {
             info!("Unbanning {peer_id}");
             swarm.unban_peer_id(peer_id);
@@ -371,17 +517,23 @@ fn handle_peer_ops(swarm: &mut Swarm<ForestBehaviour>, peer_ops: PeerOperation)
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 async fn handle_network_message(
     swarm: &mut Swarm<ForestBehaviour>,
     store: Arc<impl BitswapStoreReadWrite>,
     bitswap_request_manager: Arc<BitswapRequestManager>,
+    peer_manager: &Arc<PeerManager>,
+    bandwidth: &BandwidthCounter,
+    block_list: &BlockList,
     message: NetworkMessage,
     network_sender_out: &Sender<NetworkEvent>,
 ) {
     match message {
         NetworkMessage::PubsubMessage { topic, message } => {
-            if let Err(e) = swarm.behaviour_mut().publish(topic, message) {
-                warn!("Failed to send gossipsub message: {:?}", e);
+            let len = message.len();
+            match swarm.behaviour_mut().publish(topic, message) {
+                Ok(_) => bandwidth.record_protocol_out(GOSSIPSUB_PROTOCOL_ID, len),
+                Err(e) => warn!("Failed to send gossipsub message: {:?}", e),
             }
         }
         NetworkMessage::HelloRequest {
@@ -423,6 +575,12 @@ async fn handle_network_message(
         } => {
             bitswap_request_manager.get_block(store, cid, BITSWAP_TIMEOUT, Some(response_channel));
         }
+        NetworkMessage::BitswapRefetch {
+            cid,
+            response_channel,
+        } => {
+            bitswap_request_manager.refetch_block(cid, BITSWAP_TIMEOUT, response_channel);
+        }
         NetworkMessage::JSONRPCRequest { method } => match method {
             NetRPCMethods::NetAddrsListen(response_channel) => {
                 let listeners: Vec<_> = Swarm::listeners(swarm).cloned().collect();
@@ -464,12 +622,125 @@ async fn handle_network_message(
                     warn!("Failed to disconnect from a peer");
                 }
             }
+            NetRPCMethods::NetPeerInfo(response_channel, peer_id) => {
+                let peer_info = match swarm.behaviour_mut().peer_addresses().get(&peer_id) {
+                    Some(addresses) => Some((
+                        addresses.clone(),
+                        peer_manager
+                            .peer_net_info(&peer_id)
+                            .await
+                            .unwrap_or_default(),
+                    )),
+                    None => None,
+                };
+                if response_channel.send(peer_info).is_err() {
+                    warn!("Failed to get peer info");
+                }
+            }
+            NetRPCMethods::NetBandwidthStats(response_channel) => {
+                if response_channel.send(bandwidth.totals()).is_err() {
+                    warn!("Failed to get bandwidth stats");
+                }
+            }
+            NetRPCMethods::NetBandwidthStatsByPeer(response_channel) => {
+                if response_channel.send(bandwidth.by_peer()).is_err() {
+                    warn!("Failed to get bandwidth stats by peer");
+                }
+            }
+            NetRPCMethods::NetBandwidthStatsByProtocol(response_channel) => {
+                if response_channel.send(bandwidth.by_protocol()).is_err() {
+                    warn!("Failed to get bandwidth stats by protocol");
+                }
+            }
+            NetRPCMethods::NetFindPeer(response_channel, peer_id) => {
+                swarm.behaviour_mut().find_peer(peer_id, response_channel);
+            }
+            NetRPCMethods::NetProtectAdd(response_channel, peers) => {
+                for peer_id in peers {
+                    peer_manager.protect_peer(peer_id).await;
+                }
+                if response_channel.send(()).is_err() {
+                    warn!("Failed to protect peers");
+                }
+            }
+            NetRPCMethods::NetProtectRemove(response_channel, peers) => {
+                for peer_id in peers.iter() {
+                    peer_manager.unprotect_peer(peer_id).await;
+                }
+                if response_channel.send(()).is_err() {
+                    warn!("Failed to unprotect peers");
+                }
+            }
+            NetRPCMethods::NetProtectList(response_channel) => {
+                if response_channel
+                    .send(peer_manager.protected_peers().await)
+                    .is_err()
+                {
+                    warn!("Failed to list protected peers");
+                }
+            }
+            NetRPCMethods::NetBlockAdd(response_channel, list) => {
+                block_list.add(&list);
+                for peer_id in list.peers.iter() {
+                    info!("Blocking {peer_id}");
+                    swarm.ban_peer_id(*peer_id);
+                }
+                // Drop the connections to peers behind a newly blocked address.
+                let blocked_peers: Vec<_> = swarm
+                    .behaviour_mut()
+                    .peer_addresses()
+                    .iter()
+                    .filter(|(_, addresses)| {
+                        addresses
+                            .iter()
+                            .any(|addr| block_list.is_addr_blocked(addr))
+                    })
+                    .map(|(peer_id, _)| *peer_id)
+                    .collect();
+                for peer_id in blocked_peers {
+                    info!("Disconnecting from blocked peer {peer_id}");
+                    let _ = Swarm::disconnect_peer_id(swarm, peer_id);
+                }
+                if response_channel.send(()).is_err() {
+                    warn!("Failed to add to the block list");
+                }
+            }
+            NetRPCMethods::NetBlockRemove(response_channel, list) => {
+                block_list.remove(&list);
+                for peer_id in list.peers.iter() {
+                    info!("Unblocking {peer_id}");
+                    swarm.unban_peer_id(*peer_id);
+                }
+                if response_channel.send(()).is_err() {
+                    warn!("Failed to remove from the block list");
+                }
+            }
+            NetRPCMethods::NetBlockList(response_channel) => {
+                if response_channel.send(block_list.list()).is_err() {
+                    warn!("Failed to get the block list");
+                }
+            }
+            NetRPCMethods::NetPeerScores(response_channel) => {
+                if response_channel
+                    .send(peer_manager.peer_scores().await)
+                    .is_err()
+                {
+                    warn!("Failed to get the peer scores");
+                }
+            }
+            NetRPCMethods::NetBitswapLedger(response_channel) => {
+                let ledgers = swarm.behaviour().bitswap.server().ledgers();
+                if response_channel.send(ledgers).is_err() {
+                    warn!("Failed to get the bitswap ledgers");
+                }
+            }
         },
     }
 }
 
 async fn handle_discovery_event(
     discovery_out: DiscoveryOut,
+    peer_manager: &Arc<PeerManager>,
     network_sender_out: &Sender<NetworkEvent>,
 ) {
     match discovery_out {
@@ -479,6 +750,7 @@ async fn handle_discovery_event(
         }
         DiscoveryOut::Disconnected(peer_id, _) => {
             debug!("Peer disconnected, {:?}", peer_id);
+            peer_manager.remove_peer_net_info(&peer_id).await;
             emit_event(network_sender_out, NetworkEvent::PeerDisconnected(peer_id)).await;
         }
     }
@@ -486,6 +758,7 @@ async fn handle_discovery_event(
 
 async fn handle_gossip_event(
     e: GossipsubEvent,
+    bandwidth: &BandwidthCounter,
     network_sender_out: &Sender<NetworkEvent>,
     pubsub_block_str: &str,
     pubsub_msg_str: &str,
@@ -499,6 +772,7 @@ async fn handle_gossip_event(
         let topic = message.topic.as_str();
         let message = message.data;
         trace!("Got a Gossip Message from {:?}", source);
+        bandwidth.record_protocol_in(GOSSIPSUB_PROTOCOL_ID, message.len());
         if topic == pubsub_block_str {
             match fvm_ipld_encoding::from_slice::<GossipBlock>(&message) {
                 Ok(b) => {
@@ -634,6 +908,28 @@ async fn handle_hello_event(
     }
 }
 
+/// Rewards peers for the blocks they sent us over bitswap, in response to our
+/// requests. Unsolicited blocks are not rewarded.
+async fn log_bitswap_blocks(
+    event: &BitswapBehaviourEvent,
+    bitswap_request_manager: &BitswapRequestManager,
+    peer_manager: &PeerManager,
+) {
+    if let BitswapBehaviourEvent::Message {
+        peer,
+        message: RequestResponseMessage::Request { request, .. },
+    } = event
+    {
+        for message in request {
+            if let BitswapMessage::Response(cid, BitswapResponse::Block(_)) = message {
+                if bitswap_request_manager.is_block_requested(cid) {
+                    peer_manager.log_bitswap_block(*peer).await;
+                }
+            }
+        }
+    }
+}
+
 async fn handle_ping_event(ping_event: ping::Event, peer_manager: &Arc<PeerManager>) {
     match ping_event.result {
         Ok(ping::Success::Ping { rtt }) => {
@@ -642,6 +938,7 @@ async fn handle_ping_event(ping_event: ping::Event, peer_manager: &Arc<PeerManag
                 ping_event.peer.to_base58(),
                 rtt.as_millis()
             );
+            peer_manager.update_peer_latency(ping_event.peer, rtt).await;
         }
         Ok(ping::Success::Pong) => {
             trace!("PingSuccess::Pong from {}", ping_event.peer.to_base58());
@@ -670,10 +967,20 @@ async fn handle_ping_event(ping_event: ping::Event, peer_manager: &Arc<PeerManag
     }
 }
 
+async fn handle_identify_event(identify_event: identify::Event, peer_manager: &Arc<PeerManager>) {
+    if let identify::Event::Received { peer_id, info } = identify_event {
+        trace!("Identified {peer_id} as {}", info.agent_version);
+        peer_manager
+            .update_peer_identity(peer_id, info.agent_version, info.protocols)
+            .await;
+    }
+}
+
 async fn handle_chain_exchange_event<DB>(
     chain_exchange: &mut ChainExchangeBehaviour,
     ce_event: RequestResponseEvent<ChainExchangeRequest, ChainExchangeResponse>,
     db: &Arc<ChainStore<DB>>,
+    responder: &Arc<ChainExchangeResponder>,
     network_sender_out: &Sender<NetworkEvent>,
     cx_response_tx: Sender<(
         RequestId,
@@ -684,45 +991,56 @@ async fn handle_chain_exchange_event<DB>(
     DB: Blockstore + Store + Clone + Sync + Send + 'static,
 {
     match ce_event {
-        RequestResponseEvent::Message { peer, message } => {
-            match message {
-                RequestResponseMessage::Request {
-                    request,
-                    channel,
-                    request_id,
-                } => {
-                    trace!("Received chain_exchange request (request_id:{request_id}, peer_id: {peer:?})",);
-                    emit_event(
-                        network_sender_out,
-                        NetworkEvent::ChainExchangeRequestInbound { request_id },
-                    )
-                    .await;
-                    let db = db.clone();
-                    tokio::task::spawn(async move {
-                        if let Err(e) = cx_response_tx.send((
-                            request_id,
-                            channel,
-                            make_chain_exchange_response(db.as_ref(), &request),
-                        )) {
+        RequestResponseEvent::Message { peer, message } => match message {
+            RequestResponseMessage::Request {
+                request,
+                channel,
+                request_id,
+            } => {
+                trace!(
+                    "Received chain_exchange request (request_id:{request_id}, peer_id: {peer:?})",
+                );
+                emit_event(
+                    network_sender_out,
+                    NetworkEvent::ChainExchangeRequestInbound { request_id },
+                )
+                .await;
+                match responder.try_acquire(peer) {
+                    Ok(slot) => {
+                        let db = db.clone();
+                        tokio::task::spawn(async move {
+                            let response = slot.respond(db.as_ref(), &request);
+                            drop(slot);
+                            if let Err(e) = cx_response_tx.send((request_id, channel, response)) {
+                                debug!("Failed to send ChainExchangeResponse: {e:?}");
+                            }
+                        });
+                    }
+                    Err(response) => {
+                        debug!(
+                            "Turning away chain_exchange request of {peer}: {}",
+                            response.message
+                        );
+                        if let Err(e) = cx_response_tx.send((request_id, channel, response)) {
                             debug!("Failed to send ChainExchangeResponse: {e:?}");
                         }
-                    });
+                    }
                 }
-                RequestResponseMessage::Response {
-                    request_id,
-                    response,
-                } => {
-                    emit_event(
-                        network_sender_out,
-                        NetworkEvent::ChainExchangeResponseInbound { request_id },
-                    )
+            }
+            RequestResponseMessage::Response {
+                request_id,
+                response,
+            } => {
+                emit_event(
+                    network_sender_out,
+                    NetworkEvent::ChainExchangeResponseInbound { request_id },
+                )
+                .await;
+                chain_exchange
+                    .handle_inbound_response(&request_id, response)
                     .await;
-                    chain_exchange
-                        .handle_inbound_response(&request_id, response)
-                        .await;
-                }
             }
-        }
+        },
         RequestResponseEvent::OutboundFailure {
             peer: _,
             request_id,
@@ -756,7 +1074,9 @@ async fn handle_forest_behaviour_event<DB>(
     bitswap_request_manager: &Arc<BitswapRequestManager>,
     peer_manager: &Arc<PeerManager>,
     event: ForestBehaviourEvent,
+    bandwidth: &BandwidthCounter,
     db: &Arc<ChainStore<DB>>,
+    chain_exchange_responder: &Arc<ChainExchangeResponder>,
     genesis_cid: &Cid,
     network_sender_out: &Sender<NetworkEvent>,
     cx_response_tx: Sender<(
@@ -771,10 +1091,17 @@ async fn handle_forest_behaviour_event<DB>(
 {
     match event {
         ForestBehaviourEvent::Discovery(discovery_out) => {
-            handle_discovery_event(discovery_out, network_sender_out).await
+            handle_discovery_event(discovery_out, peer_manager, network_sender_out).await
         }
         ForestBehaviourEvent::Gossipsub(e) => {
-            handle_gossip_event(e, network_sender_out, pubsub_block_str, pubsub_msg_str).await
+            handle_gossip_event(
+                e,
+                bandwidth,
+                network_sender_out,
+                pubsub_block_str,
+                pubsub_msg_str,
+            )
+            .await
         }
         ForestBehaviourEvent::Hello(rr_event) => {
             handle_hello_event(
@@ -787,6 +1114,7 @@ async fn handle_forest_behaviour_event<DB>(
             .await
         }
         ForestBehaviourEvent::Bitswap(event) => {
+            log_bitswap_blocks(&event, bitswap_request_manager, peer_manager).await;
             if let Err(e) = bitswap_request_manager.handle_event(
                 &mut swarm.behaviour_mut().bitswap,
                 db.blockstore(),
@@ -796,12 +1124,15 @@ async fn handle_forest_behaviour_event<DB>(
             }
         }
         ForestBehaviourEvent::Ping(ping_event) => handle_ping_event(ping_event, peer_manager).await,
-        ForestBehaviourEvent::Identify(_) => {}
+        ForestBehaviourEvent::Identify(identify_event) => {
+            handle_identify_event(identify_event, peer_manager).await
+        }
         ForestBehaviourEvent::ChainExchange(ce_event) => {
             handle_chain_exchange_event(
                 &mut swarm.behaviour_mut().chain_exchange,
                 ce_event,
                 db,
+                chain_exchange_responder,
                 network_sender_out,
                 cx_response_tx,
             )
@@ -822,11 +1153,31 @@ async fn emit_event(sender: &Sender<NetworkEvent>, event: NetworkEvent) {
 ///
 /// As a reference `lotus` uses the default `go-libp2p` transport builder which
 /// has all above protocols enabled.
-pub fn build_transport(local_key: Keypair) -> anyhow::Result<Boxed<(PeerId, StreamMuxerBox)>> {
+///
+/// Connections from or to addresses in the block list are dropped before any
+/// handshake, and the traffic of each peer is recorded into the bandwidth
+/// counter.
+pub fn build_transport(
+    local_key: Keypair,
+    bandwidth: Arc<BandwidthCounter>,
+    block_list: Arc<BlockList>,
+) -> anyhow::Result<Boxed<(PeerId, StreamMuxerBox)>> {
     let build_tcp = || libp2p::tcp::tokio::Transport::new(libp2p::tcp::Config::new().nodelay(true));
     let build_dns_tcp = || libp2p::dns::TokioDnsConfig::system(build_tcp());
-    let transport =
-        libp2p::websocket::WsConfig::new(build_dns_tcp()?).or_transport(build_dns_tcp()?);
+    let transport = libp2p::websocket::WsConfig::new(build_dns_tcp()?)
+        .or_transport(build_dns_tcp()?)
+        .and_then(move |conn, endpoint: core::ConnectedPoint| {
+            let remote_address = endpoint.get_remote_address();
+            futures::future::ready(if block_list.is_addr_blocked(remote_address) {
+                debug!("Refusing connection with blocked address {remote_address}");
+                Err(std::io::Error::new(
+                    std::io::ErrorKind::ConnectionRefused,
+                    format!("{remote_address} is blocked"),
+                ))
+            } else {
+                Ok(conn)
+            })
+        });
 
     let auth_config = {
         let dh_keys = noise::Keypair::<noise::X25519Spec>::new()
@@ -838,7 +1189,9 @@ pub fn build_transport(local_key: Keypair) -> anyhow::Result<Boxed<(PeerId, Stre
     Ok(transport
         .upgrade(core::upgrade::Version::V1)
         .authenticate(auth_config)
-        .multiplex(YamuxConfig::default())
+        .multiplex_ext(move |peer_id, _| {
+            CountingUpgrade::new(YamuxConfig::default(), *peer_id, &bandwidth)
+        })
         .timeout(Duration::from_secs(20))
         .boxed())
 }
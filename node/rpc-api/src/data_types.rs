@@ -1,28 +1,41 @@
 // Copyright 2019-2023 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use std::sync::Arc;
+use std::{
+    path::PathBuf,
+    sync::{atomic::AtomicU64, Arc},
+    time::Duration,
+};
 
+use ahash::HashMap;
 use cid::Cid;
 use forest_actor_interface::market::{DealProposal, DealState};
 use forest_beacon::{Beacon, BeaconSchedule};
 use forest_blocks::{tipset_keys_json::TipsetKeysJson, Tipset};
-use forest_chain::ChainStore;
+use forest_chain::{ChainStore, CheckReport};
 use forest_chain_sync::{BadBlockCache, SyncState};
 use forest_ipld::json::IpldJson;
 use forest_json::{cid::CidJson, message_receipt::json::ReceiptJson, token_amount::json};
-use forest_key_management::KeyStore;
+use forest_key_management::{KeyStore, Signer};
 pub use forest_libp2p::{Multiaddr, Protocol};
 use forest_libp2p::{Multihash, NetworkMessage};
 use forest_message::signed_message::SignedMessage;
 use forest_message_pool::{MessagePool, MpoolRpcProvider};
-use forest_shim::{econ::TokenAmount, message::Message};
+use forest_shim::{
+    address::Address, econ::TokenAmount, message::Message, sector::RegisteredSealProof,
+};
 use forest_state_manager::StateManager;
+use fvm_ipld_bitfield::BitField;
 use fvm_ipld_blockstore::Blockstore;
+use fvm_shared::{
+    clock::ChainEpoch, deal::DealID, piece::PaddedPieceSize, sector::SectorNumber, ActorID,
+    MethodNum,
+};
 use jsonrpc_v2::{MapRouter as JsonRpcMapRouter, Server as JsonRpcServer};
-use parking_lot::RwLock as SyncRwLock;
+use parking_lot::{Mutex as SyncMutex, RwLock as SyncRwLock};
 use serde::{Deserialize, Serialize};
-use tokio::sync::RwLock;
+use serde_with::{serde_as, DurationNanoSeconds};
+use tokio::sync::{Mutex, RwLock};
 
 /// This is where you store persistent data, or at least access to stateful
 /// data.
@@ -32,6 +45,9 @@ where
     B: Beacon,
 {
     pub keystore: Arc<RwLock<KeyStore>>,
+    /// Signs messages with the keys of the wallet, which may be held outside
+    /// of the `keystore`
+    pub signer: Arc<dyn Signer>,
     pub chain_store: Arc<ChainStore<DB>>,
     pub state_manager: Arc<StateManager<DB>>,
     pub mpool: Arc<MessagePool<MpoolRpcProvider<DB>>>,
@@ -41,6 +57,15 @@ where
     pub network_name: String,
     pub new_mined_block_tx: flume::Sender<Arc<Tipset>>,
     pub beacon: Arc<BeaconSchedule<B>>,
+    /// Locks of the sending addresses, held from the selection of the nonce
+    /// of a message until it is in the message pool, so that concurrent
+    /// pushes from an address never get the same nonce. An address is only
+    /// in there while messages from it are pushed.
+    pub push_locks: SyncMutex<HashMap<Address, Arc<Mutex<()>>>>,
+    /// Serializes the updates of the payment channel store.
+    pub paych_lock: Mutex<()>,
+    /// Last store check started on the node.
+    pub store_check: Arc<SyncMutex<Option<StoreCheck>>>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -65,6 +90,64 @@ pub struct BlockMessages {
     pub cids: Vec<Cid>,
 }
 
+/// Where the blocks found bad by a store check are fetched again from.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub enum RepairSource {
+    /// Peers of the node, over bitswap
+    Bitswap,
+    /// Snapshot file on the host of the node
+    Snapshot(PathBuf),
+}
+
+/// Blocks found bad by a store check, see [`forest_chain::CheckReport`].
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct StoreCheckReport {
+    pub checked: u64,
+    #[serde(with = "forest_json::cid::vec")]
+    pub missing: Vec<Cid>,
+    #[serde(with = "forest_json::cid::vec")]
+    pub corrupted: Vec<Cid>,
+    pub unreadable: Vec<(CidJson, String)>,
+    #[serde(with = "forest_json::cid::vec")]
+    pub repaired: Vec<Cid>,
+    pub aborted: Option<String>,
+}
+
+impl From<CheckReport> for StoreCheckReport {
+    fn from(report: CheckReport) -> Self {
+        Self {
+            checked: report.checked,
+            missing: report.missing,
+            corrupted: report.corrupted,
+            unreadable: report
+                .unreadable
+                .into_iter()
+                .map(|(cid, e)| (CidJson(cid), e))
+                .collect(),
+            repaired: report.repaired,
+            aborted: report.aborted,
+        }
+    }
+}
+
+/// Store check started on the node, with its report once it is done.
+pub struct StoreCheck {
+    pub checked: Arc<AtomicU64>,
+    pub report: Option<StoreCheckReport>,
+}
+
+/// Progress of the store check running in the background, and its report
+/// once it is done.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct StoreCheckStatus {
+    pub running: bool,
+    pub checked: u64,
+    pub report: Option<StoreCheckReport>,
+}
+
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct MessageSendSpec {
@@ -72,14 +155,44 @@ pub struct MessageSendSpec {
     max_fee: TokenAmount,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct MarketDeal {
     pub proposal: DealProposal,
     pub state: DealState,
 }
 
-#[derive(Serialize)]
+/// Criteria of the market deals to list. Unset criteria match all deals.
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct DealsFilter {
+    #[serde(with = "forest_json::address::json::opt", default)]
+    pub client: Option<Address>,
+    #[serde(with = "forest_json::address::json::opt", default)]
+    pub provider: Option<Address>,
+    #[serde(default)]
+    pub verified: Option<bool>,
+    /// Whether the deals have been activated in a proven sector.
+    #[serde(default)]
+    pub active: Option<bool>,
+    /// Only lists the deals of greater IDs, to page through the deals.
+    #[serde(default)]
+    pub after: Option<DealID>,
+    /// Maximum number of deals to list.
+    pub limit: u64,
+}
+
+/// Page of the market deals matching a [`DealsFilter`], in deal ID order.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct DealsPage {
+    pub deals: Vec<(DealID, MarketDeal)>,
+    /// Value of [`DealsFilter::after`] to list the next page, unset on the
+    /// last page.
+    pub next: Option<DealID>,
+}
+
+#[derive(Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
 pub struct MessageLookup {
     pub receipt: ReceiptJson,
@@ -90,6 +203,150 @@ pub struct MessageLookup {
     pub return_dec: IpldJson,
 }
 
+/// Data cap allocation of a verified client to a storage provider.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Allocation {
+    pub client: ActorID,
+    pub provider: ActorID,
+    #[serde(with = "forest_json::cid")]
+    pub data: Cid,
+    pub size: PaddedPieceSize,
+    pub term_min: ChainEpoch,
+    pub term_max: ChainEpoch,
+    pub expiration: ChainEpoch,
+}
+
+/// Data cap allocation claimed by a storage provider.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Claim {
+    pub provider: ActorID,
+    pub client: ActorID,
+    #[serde(with = "forest_json::cid")]
+    pub data: Cid,
+    pub size: PaddedPieceSize,
+    pub term_min: ChainEpoch,
+    pub term_max: ChainEpoch,
+    pub term_start: ChainEpoch,
+    pub sector: SectorNumber,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Deadline {
+    /// Partitions for which a proof has been submitted in the current proving
+    /// period.
+    #[serde(with = "forest_json::bitfield::json")]
+    pub post_submissions: BitField,
+    pub disputable_proof_count: u64,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Partition {
+    #[serde(with = "forest_json::bitfield::json")]
+    pub all_sectors: BitField,
+    #[serde(with = "forest_json::bitfield::json")]
+    pub faulty_sectors: BitField,
+    #[serde(with = "forest_json::bitfield::json")]
+    pub recovering_sectors: BitField,
+    #[serde(with = "forest_json::bitfield::json")]
+    pub live_sectors: BitField,
+    #[serde(with = "forest_json::bitfield::json")]
+    pub active_sectors: BitField,
+}
+
+/// Number of sectors of a miner in each state.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct MinerSectors {
+    pub live: u64,
+    pub active: u64,
+    pub faulty: u64,
+}
+
+/// Funds of a miner vesting at an epoch.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct VestingFund {
+    pub epoch: ChainEpoch,
+    #[serde(with = "json")]
+    pub amount: TokenAmount,
+}
+
+/// Sector a miner is to pre-commit, to estimate its collateral.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct SectorPreCommitInfo {
+    pub seal_proof: RegisteredSealProof,
+    pub sector_number: SectorNumber,
+    #[serde(rename = "SealedCID", with = "forest_json::cid")]
+    pub sealed_cid: Cid,
+    pub seal_rand_epoch: ChainEpoch,
+    /// Deals of the sector, `null` if it has none.
+    #[serde(rename = "DealIDs")]
+    pub deal_ids: Option<Vec<DealID>>,
+    pub expiration: ChainEpoch,
+}
+
+// Multisig API
+/// Transaction proposed to a multisig, awaiting approvals.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct MsigTransaction {
+    #[serde(rename = "ID")]
+    pub id: i64,
+    #[serde(with = "forest_json::address::json")]
+    pub to: Address,
+    #[serde(with = "json")]
+    pub value: TokenAmount,
+    pub method: MethodNum,
+    /// Base64 encoded parameters of the method.
+    pub params: String,
+    #[serde(with = "forest_json::address::json::vec")]
+    pub approved: Vec<Address>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct MsigVesting {
+    #[serde(with = "json")]
+    pub initial_balance: TokenAmount,
+    pub start_epoch: ChainEpoch,
+    pub unlock_duration: ChainEpoch,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct MsigSigners {
+    #[serde(with = "forest_json::address::json::vec")]
+    pub signers: Vec<Address>,
+    /// Number of approvals required to execute a transaction.
+    pub threshold: u64,
+}
+
+// Payment channel API
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct PaychChannelInfo {
+    /// Unset while the creation message of the channel is not executed.
+    #[serde(with = "forest_json::address::json::opt")]
+    pub channel: Option<Address>,
+    /// Message creating or funding the channel, unset if none was sent.
+    pub wait_sentinel: Option<CidJson>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct VoucherCreateResult {
+    /// Encoded voucher, unset if the channel lacks funds for it.
+    pub voucher: Option<String>,
+    /// Funds missing from the channel to cover the voucher.
+    #[serde(with = "json")]
+    pub shortfall: TokenAmount,
+}
+
 // Net API
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
@@ -99,11 +356,59 @@ pub struct AddrInfo {
     pub addrs: Vec<Multiaddr>,
 }
 
+/// Details of a connected peer.
+#[serde_as]
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct ExtendedPeerInfo {
+    #[serde(rename = "ID")]
+    pub id: String,
+    pub agent: String,
+    pub addrs: Vec<Multiaddr>,
+    pub protocols: Vec<String>,
+    /// Latest round trip time measured with the peer.
+    #[serde_as(as = "Option<DurationNanoSeconds<u64>>")]
+    pub latency: Option<Duration>,
+}
+
+/// A key derived from the mnemonic seed of the wallet.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct DerivedAddress {
+    pub address: String,
+    /// `BIP-32` derivation path of the key.
+    pub path: String,
+    pub index: u32,
+}
+
+/// Peers, IP addresses and IP subnets (in CIDR notation) the node refuses to
+/// connect to.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(rename_all = "PascalCase")]
+pub struct NetBlockList {
+    pub peers: Vec<String>,
+    #[serde(rename = "IPAddrs")]
+    pub ip_addrs: Vec<String>,
+    #[serde(rename = "IPSubnets")]
+    pub ip_subnets: Vec<String>,
+}
+
 #[derive(Serialize, Deserialize)]
 pub struct PeerID {
     pub multihash: Multihash,
 }
 
+/// Node a backup was taken from.
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct BackupInfo {
+    pub forest_version: String,
+    pub network: String,
+    /// Backend of the database, the copy of which is in the `db/<backend>`
+    /// directory of the backup.
+    pub db_backend: String,
+}
+
 /// Represents the current version of the API.
 #[derive(Serialize, Deserialize)]
 #[serde(rename_all = "PascalCase")]
@@ -29,6 +29,8 @@ pub static ACCESS_MAP: Lazy<HashMap<&str, Access>> = Lazy::new(|| {
     // Chain API
     access.insert(chain_api::CHAIN_GET_MESSAGE, Access::Read);
     access.insert(chain_api::CHAIN_EXPORT, Access::Read);
+    access.insert(chain_api::CHAIN_CHECK_STORE, Access::Admin);
+    access.insert(chain_api::CHAIN_CHECK_STORE_STATUS, Access::Admin);
     access.insert(chain_api::CHAIN_READ_OBJ, Access::Read);
     access.insert(chain_api::CHAIN_HAS_OBJ, Access::Read);
     access.insert(chain_api::CHAIN_GET_BLOCK_MESSAGES, Access::Read);
@@ -45,6 +47,7 @@ pub static ACCESS_MAP: Lazy<HashMap<&str, Access>> = Lazy::new(|| {
     access.insert(mpool_api::MPOOL_PENDING, Access::Read);
     access.insert(mpool_api::MPOOL_PUSH, Access::Write);
     access.insert(mpool_api::MPOOL_PUSH_MESSAGE, Access::Sign);
+    access.insert(mpool_api::MPOOL_GET_NONCE, Access::Read);
 
     // Sync API
     access.insert(sync_api::SYNC_CHECK_BAD, Access::Read);
@@ -62,16 +65,69 @@ pub static ACCESS_MAP: Lazy<HashMap<&str, Access>> = Lazy::new(|| {
     access.insert(wallet_api::WALLET_SET_DEFAULT, Access::Write);
     access.insert(wallet_api::WALLET_SIGN, Access::Sign);
     access.insert(wallet_api::WALLET_VERIFY, Access::Read);
+    access.insert(wallet_api::WALLET_HD_NEW, Access::Admin);
+    access.insert(wallet_api::WALLET_HD_RESTORE, Access::Admin);
+    access.insert(wallet_api::WALLET_HD_DERIVE, Access::Write);
 
     // State API
     access.insert(state_api::STATE_CALL, Access::Read);
     access.insert(state_api::STATE_REPLAY, Access::Read);
     access.insert(state_api::STATE_MARKET_BALANCE, Access::Read);
     access.insert(state_api::STATE_MARKET_DEALS, Access::Read);
+    access.insert(state_api::STATE_MARKET_DEALS_PAGE, Access::Read);
+    access.insert(state_api::STATE_MARKET_STORAGE_DEAL, Access::Read);
+    access.insert(state_api::STATE_MARKET_PARTICIPANTS, Access::Read);
     access.insert(state_api::STATE_GET_RECEIPT, Access::Read);
     access.insert(state_api::STATE_WAIT_MSG, Access::Read);
     access.insert(state_api::STATE_NETWORK_NAME, Access::Read);
     access.insert(state_api::STATE_NETWORK_VERSION, Access::Read);
+    access.insert(state_api::STATE_ACTOR_CODE_CIDS, Access::Read);
+    access.insert(state_api::STATE_VERIFIED_CLIENT_STATUS, Access::Read);
+    access.insert(state_api::STATE_VERIFIER_STATUS, Access::Read);
+    access.insert(state_api::STATE_VERIFIED_REGISTRY_ROOT_KEY, Access::Read);
+    access.insert(state_api::STATE_GET_ALLOCATIONS, Access::Read);
+    access.insert(state_api::STATE_GET_CLAIMS, Access::Read);
+    access.insert(state_api::STATE_MINER_SECTORS, Access::Read);
+    access.insert(state_api::STATE_MINER_ACTIVE_SECTORS, Access::Read);
+    access.insert(state_api::STATE_MINER_DEADLINES, Access::Read);
+    access.insert(state_api::STATE_MINER_PARTITIONS, Access::Read);
+    access.insert(state_api::STATE_MINER_FAULTS, Access::Read);
+    access.insert(state_api::STATE_MINER_RECOVERIES, Access::Read);
+    access.insert(state_api::STATE_MINER_PROVING_DEADLINE, Access::Read);
+    access.insert(state_api::STATE_SECTOR_GET_INFO, Access::Read);
+    access.insert(state_api::STATE_MINER_SECTOR_COUNT, Access::Read);
+    access.insert(state_api::STATE_MINER_AVAILABLE_BALANCE, Access::Read);
+    access.insert(state_api::STATE_MINER_VESTING_FUNDS, Access::Read);
+    access.insert(
+        state_api::STATE_MINER_INITIAL_PLEDGE_COLLATERAL,
+        Access::Read,
+    );
+    access.insert(
+        state_api::STATE_MINER_PRE_COMMIT_DEPOSIT_FOR_POWER,
+        Access::Read,
+    );
+    access.insert(state_api::STATE_CIRCULATING_SUPPLY, Access::Read);
+    access.insert(
+        state_api::STATE_VM_CIRCULATING_SUPPLY_INTERNAL,
+        Access::Read,
+    );
+
+    // Multisig API
+    access.insert(msig_api::MSIG_GET_AVAILABLE_BALANCE, Access::Read);
+    access.insert(msig_api::MSIG_GET_PENDING, Access::Read);
+    access.insert(msig_api::MSIG_GET_VESTED, Access::Read);
+    access.insert(msig_api::MSIG_GET_VESTING_SCHEDULE, Access::Read);
+    access.insert(msig_api::MSIG_GET_SIGNERS, Access::Read);
+
+    // Payment channel API
+    access.insert(paych_api::PAYCH_GET, Access::Sign);
+    access.insert(paych_api::PAYCH_GET_WAIT_READY, Access::Sign);
+    access.insert(paych_api::PAYCH_VOUCHER_CREATE, Access::Sign);
+    access.insert(paych_api::PAYCH_VOUCHER_CHECK_VALID, Access::Read);
+    access.insert(paych_api::PAYCH_VOUCHER_ADD, Access::Write);
+    access.insert(paych_api::PAYCH_VOUCHER_SUBMIT, Access::Sign);
+    access.insert(paych_api::PAYCH_SETTLE, Access::Sign);
+    access.insert(paych_api::PAYCH_COLLECT, Access::Sign);
 
     // Gas API
     access.insert(gas_api::GAS_ESTIMATE_GAS_LIMIT, Access::Read);
@@ -82,12 +138,26 @@ pub static ACCESS_MAP: Lazy<HashMap<&str, Access>> = Lazy::new(|| {
     // Common API
     access.insert(common_api::VERSION, Access::Read);
     access.insert(common_api::SHUTDOWN, Access::Admin);
+    access.insert(common_api::CREATE_BACKUP, Access::Admin);
 
     // Net API
     access.insert(net_api::NET_ADDRS_LISTEN, Access::Read);
     access.insert(net_api::NET_PEERS, Access::Read);
     access.insert(net_api::NET_CONNECT, Access::Write);
     access.insert(net_api::NET_DISCONNECT, Access::Write);
+    access.insert(net_api::NET_PEER_INFO, Access::Read);
+    access.insert(net_api::NET_BANDWIDTH_STATS, Access::Read);
+    access.insert(net_api::NET_BANDWIDTH_STATS_BY_PEER, Access::Read);
+    access.insert(net_api::NET_BANDWIDTH_STATS_BY_PROTOCOL, Access::Read);
+    access.insert(net_api::NET_FIND_PEER, Access::Read);
+    access.insert(net_api::NET_PROTECT_ADD, Access::Admin);
+    access.insert(net_api::NET_PROTECT_REMOVE, Access::Admin);
+    access.insert(net_api::NET_PROTECT_LIST, Access::Read);
+    access.insert(net_api::NET_BLOCK_ADD, Access::Admin);
+    access.insert(net_api::NET_BLOCK_REMOVE, Access::Admin);
+    access.insert(net_api::NET_BLOCK_LIST, Access::Read);
+    access.insert(net_api::NET_PEER_SCORES, Access::Read);
+    access.insert(net_api::NET_BITSWAP_LEDGER, Access::Read);
 
     access
 });
@@ -151,7 +221,7 @@ pub mod chain_api {
     use fvm_shared::clock::ChainEpoch;
     use serde::{Deserialize, Serialize};
 
-    use crate::data_types::BlockMessages;
+    use crate::data_types::{BlockMessages, RepairSource, StoreCheckStatus};
 
     pub const CHAIN_GET_MESSAGE: &str = "Filecoin.ChainGetMessage";
     pub type ChainGetMessageParams = (CidJson,);
@@ -171,6 +241,21 @@ pub mod chain_api {
 
     pub type ChainExportResult = PathBuf;
 
+    pub const CHAIN_CHECK_STORE: &str = "Filecoin.ChainCheckStore";
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    pub struct ChainCheckStoreParams {
+        pub recent_roots: i64,
+        pub tipset_keys: TipsetKeysJson,
+        pub repair: Option<RepairSource>,
+    }
+
+    pub type ChainCheckStoreResult = ();
+
+    pub const CHAIN_CHECK_STORE_STATUS: &str = "Filecoin.ChainCheckStoreStatus";
+    pub type ChainCheckStoreStatusParams = ();
+    pub type ChainCheckStoreStatusResult = StoreCheckStatus;
+
     pub const CHAIN_READ_OBJ: &str = "Filecoin.ChainReadObj";
     pub type ChainReadObjParams = (CidJson,);
     pub type ChainReadObjResult = String;
@@ -219,6 +304,7 @@ pub mod chain_api {
 /// Message Pool API
 pub mod mpool_api {
     use forest_json::{
+        address::json::AddressJson,
         cid::{vec::CidJsonVec, CidJson},
         message::json::MessageJson,
         signed_message::json::SignedMessageJson,
@@ -238,6 +324,10 @@ pub mod mpool_api {
     pub const MPOOL_PUSH_MESSAGE: &str = "Filecoin.MpoolPushMessage";
     pub type MpoolPushMessageParams = (MessageJson, Option<MessageSendSpec>);
     pub type MpoolPushMessageResult = SignedMessageJson;
+
+    pub const MPOOL_GET_NONCE: &str = "Filecoin.MpoolGetNonce";
+    pub type MpoolGetNonceParams = (AddressJson,);
+    pub type MpoolGetNonceResult = u64;
 }
 
 /// Sync API
@@ -267,6 +357,8 @@ pub mod wallet_api {
     };
     use forest_key_management::json::KeyInfoJson;
 
+    use crate::data_types::DerivedAddress;
+
     pub const WALLET_BALANCE: &str = "Filecoin.WalletBalance";
     pub type WalletBalanceParams = (String,);
     pub type WalletBalanceResult = String;
@@ -306,20 +398,44 @@ pub mod wallet_api {
     pub const WALLET_VERIFY: &str = "Filecoin.WalletVerify";
     pub type WalletVerifyParams = (AddressJson, Vec<u8>, SignatureJson);
     pub type WalletVerifyResult = bool;
+
+    pub const WALLET_HD_NEW: &str = "Filecoin.WalletHdNew";
+    pub type WalletHdNewParams = (SignatureTypeJson,);
+    pub type WalletHdNewResult = DerivedAddress;
+    /// Error code of [`WALLET_HD_NEW`] when the wallet has no mnemonic seed.
+    /// The mnemonic is never sent by the node, clients generate it and pass
+    /// it to [`WALLET_HD_RESTORE`].
+    pub const WALLET_HD_NO_SEED_ERROR_CODE: i64 = 2;
+
+    pub const WALLET_HD_RESTORE: &str = "Filecoin.WalletHdRestore";
+    /// Maximum number of keys derived by [`WALLET_HD_RESTORE`].
+    pub const WALLET_HD_RESTORE_MAX_COUNT: u32 = 100;
+    /// Mnemonic, passphrase, key type and number of keys to derive.
+    pub type WalletHdRestoreParams = (String, String, SignatureTypeJson, u32);
+    pub type WalletHdRestoreResult = Vec<DerivedAddress>;
+
+    pub const WALLET_HD_DERIVE: &str = "Filecoin.WalletHdDerive";
+    pub type WalletHdDeriveParams = (SignatureTypeJson, u32);
+    pub type WalletHdDeriveResult = DerivedAddress;
 }
 
 /// State API
 pub mod state_api {
     use ahash::HashMap;
+    use forest_actor_interface::miner::{DeadlineInfo, SectorOnChainInfo};
     use forest_blocks::tipset_keys_json::TipsetKeysJson;
     use forest_json::{
-        address::json::AddressJson, cid::CidJson, message::json::MessageJson,
-        message_receipt::json::ReceiptJson,
+        address::json::AddressJson, bitfield::json::BitFieldJson, cid::CidJson,
+        message::json::MessageJson, message_receipt::json::ReceiptJson,
     };
     use forest_shim::version::NetworkVersion;
-    use forest_state_manager::{InvocResult, MarketBalance};
+    use forest_state_manager::{CirculatingSupply, InvocResult, MarketBalance};
+    use fvm_shared::{deal::DealID, sector::SectorNumber};
 
-    use crate::data_types::{MarketDeal, MessageLookup};
+    use crate::data_types::{
+        Allocation, Claim, Deadline, DealsFilter, DealsPage, MarketDeal, MessageLookup,
+        MinerSectors, Partition, SectorPreCommitInfo, VestingFund,
+    };
 
     pub const STATE_CALL: &str = "Filecoin.StateCall";
     pub type StateCallParams = (MessageJson, TipsetKeysJson);
@@ -345,6 +461,21 @@ pub mod state_api {
     pub type StateMarketDealsParams = (TipsetKeysJson,);
     pub type StateMarketDealsResult = HashMap<String, MarketDeal>;
 
+    /// Page of the market deals matching the filter, for markets too large to
+    /// list at once.
+    pub const STATE_MARKET_DEALS_PAGE: &str = "Filecoin.StateMarketDealsPage";
+    pub type StateMarketDealsPageParams = (DealsFilter, TipsetKeysJson);
+    pub type StateMarketDealsPageResult = DealsPage;
+
+    pub const STATE_MARKET_STORAGE_DEAL: &str = "Filecoin.StateMarketStorageDeal";
+    pub type StateMarketStorageDealParams = (DealID, TipsetKeysJson);
+    pub type StateMarketStorageDealResult = MarketDeal;
+
+    /// Escrowed and locked balances of the market participants, by address.
+    pub const STATE_MARKET_PARTICIPANTS: &str = "Filecoin.StateMarketParticipants";
+    pub type StateMarketParticipantsParams = (TipsetKeysJson,);
+    pub type StateMarketParticipantsResult = HashMap<String, MarketBalance>;
+
     pub const STATE_GET_RECEIPT: &str = "Filecoin.StateGetReceipt";
     pub type StateGetReceiptParams = (CidJson, TipsetKeysJson);
     pub type StateGetReceiptResult = ReceiptJson;
@@ -352,6 +483,191 @@ pub mod state_api {
     pub const STATE_WAIT_MSG: &str = "Filecoin.StateWaitMsg";
     pub type StateWaitMsgParams = (CidJson, i64);
     pub type StateWaitMsgResult = MessageLookup;
+
+    pub const STATE_ACTOR_CODE_CIDS: &str = "Filecoin.StateActorCodeCIDs";
+    pub type StateActorCodeCidsParams = (NetworkVersion,);
+    pub type StateActorCodeCidsResult = HashMap<String, CidJson>;
+
+    /// Data cap in bytes of a verified client, `null` if it isn't one.
+    pub const STATE_VERIFIED_CLIENT_STATUS: &str = "Filecoin.StateVerifiedClientStatus";
+    pub type StateVerifiedClientStatusParams = (AddressJson, TipsetKeysJson);
+    pub type StateVerifiedClientStatusResult = Option<String>;
+
+    /// Data cap in bytes of a verifier, `null` if it isn't one.
+    pub const STATE_VERIFIER_STATUS: &str = "Filecoin.StateVerifierStatus";
+    pub type StateVerifierStatusParams = (AddressJson, TipsetKeysJson);
+    pub type StateVerifierStatusResult = Option<String>;
+
+    pub const STATE_VERIFIED_REGISTRY_ROOT_KEY: &str = "Filecoin.StateVerifiedRegistryRootKey";
+    pub type StateVerifiedRegistryRootKeyParams = (TipsetKeysJson,);
+    pub type StateVerifiedRegistryRootKeyResult = AddressJson;
+
+    /// Data cap allocations of a client, by allocation ID.
+    pub const STATE_GET_ALLOCATIONS: &str = "Filecoin.StateGetAllocations";
+    pub type StateGetAllocationsParams = (AddressJson, TipsetKeysJson);
+    pub type StateGetAllocationsResult = HashMap<String, Allocation>;
+
+    /// Claims of a storage provider, by claim ID.
+    pub const STATE_GET_CLAIMS: &str = "Filecoin.StateGetClaims";
+    pub type StateGetClaimsParams = (AddressJson, TipsetKeysJson);
+    pub type StateGetClaimsResult = HashMap<String, Claim>;
+
+    /// Sectors of a miner, restricted to the given sector numbers if any.
+    pub const STATE_MINER_SECTORS: &str = "Filecoin.StateMinerSectors";
+    pub type StateMinerSectorsParams = (AddressJson, Option<BitFieldJson>, TipsetKeysJson);
+    pub type StateMinerSectorsResult = Vec<SectorOnChainInfo>;
+
+    /// Sectors of a miner that are proven and not faulty.
+    pub const STATE_MINER_ACTIVE_SECTORS: &str = "Filecoin.StateMinerActiveSectors";
+    pub type StateMinerActiveSectorsParams = (AddressJson, TipsetKeysJson);
+    pub type StateMinerActiveSectorsResult = Vec<SectorOnChainInfo>;
+
+    pub const STATE_MINER_DEADLINES: &str = "Filecoin.StateMinerDeadlines";
+    pub type StateMinerDeadlinesParams = (AddressJson, TipsetKeysJson);
+    pub type StateMinerDeadlinesResult = Vec<Deadline>;
+
+    /// Partitions of the deadline of the given index of a miner.
+    pub const STATE_MINER_PARTITIONS: &str = "Filecoin.StateMinerPartitions";
+    pub type StateMinerPartitionsParams = (AddressJson, u64, TipsetKeysJson);
+    pub type StateMinerPartitionsResult = Vec<Partition>;
+
+    pub const STATE_MINER_FAULTS: &str = "Filecoin.StateMinerFaults";
+    pub type StateMinerFaultsParams = (AddressJson, TipsetKeysJson);
+    pub type StateMinerFaultsResult = BitFieldJson;
+
+    pub const STATE_MINER_RECOVERIES: &str = "Filecoin.StateMinerRecoveries";
+    pub type StateMinerRecoveriesParams = (AddressJson, TipsetKeysJson);
+    pub type StateMinerRecoveriesResult = BitFieldJson;
+
+    /// Current deadline of a miner, or the next one if the current one has
+    /// closed.
+    pub const STATE_MINER_PROVING_DEADLINE: &str = "Filecoin.StateMinerProvingDeadline";
+    pub type StateMinerProvingDeadlineParams = (AddressJson, TipsetKeysJson);
+    pub type StateMinerProvingDeadlineResult = DeadlineInfo;
+
+    /// Sector of a miner, `null` if the miner doesn't have it.
+    pub const STATE_SECTOR_GET_INFO: &str = "Filecoin.StateSectorGetInfo";
+    pub type StateSectorGetInfoParams = (AddressJson, SectorNumber, TipsetKeysJson);
+    pub type StateSectorGetInfoResult = Option<SectorOnChainInfo>;
+
+    pub const STATE_MINER_SECTOR_COUNT: &str = "Filecoin.StateMinerSectorCount";
+    pub type StateMinerSectorCountParams = (AddressJson, TipsetKeysJson);
+    pub type StateMinerSectorCountResult = MinerSectors;
+
+    /// Balance of a miner that can be withdrawn, in attoFIL.
+    pub const STATE_MINER_AVAILABLE_BALANCE: &str = "Filecoin.StateMinerAvailableBalance";
+    pub type StateMinerAvailableBalanceParams = (AddressJson, TipsetKeysJson);
+    pub type StateMinerAvailableBalanceResult = String;
+
+    /// Vesting schedule of the locked funds of a miner.
+    pub const STATE_MINER_VESTING_FUNDS: &str = "Filecoin.StateMinerVestingFunds";
+    pub type StateMinerVestingFundsParams = (AddressJson, TipsetKeysJson);
+    pub type StateMinerVestingFundsResult = Vec<VestingFund>;
+
+    /// Estimated initial pledge of a sector, in attoFIL.
+    pub const STATE_MINER_INITIAL_PLEDGE_COLLATERAL: &str =
+        "Filecoin.StateMinerInitialPledgeCollateral";
+    pub type StateMinerInitialPledgeCollateralParams =
+        (AddressJson, SectorPreCommitInfo, TipsetKeysJson);
+    pub type StateMinerInitialPledgeCollateralResult = String;
+
+    /// Estimated pre-commit deposit of a sector, in attoFIL.
+    pub const STATE_MINER_PRE_COMMIT_DEPOSIT_FOR_POWER: &str =
+        "Filecoin.StateMinerPreCommitDepositForPower";
+    pub type StateMinerPreCommitDepositForPowerParams =
+        (AddressJson, SectorPreCommitInfo, TipsetKeysJson);
+    pub type StateMinerPreCommitDepositForPowerResult = String;
+
+    /// Circulating supply at the tipset, in attoFIL.
+    pub const STATE_CIRCULATING_SUPPLY: &str = "Filecoin.StateCirculatingSupply";
+    pub type StateCirculatingSupplyParams = (TipsetKeysJson,);
+    pub type StateCirculatingSupplyResult = String;
+
+    /// Circulating supply the VM sees at the tipset, along with the amounts it
+    /// is computed from.
+    pub const STATE_VM_CIRCULATING_SUPPLY_INTERNAL: &str =
+        "Filecoin.StateVMCirculatingSupplyInternal";
+    pub type StateVMCirculatingSupplyInternalParams = (TipsetKeysJson,);
+    pub type StateVMCirculatingSupplyInternalResult = CirculatingSupply;
+}
+
+/// Multisig API
+pub mod msig_api {
+    use forest_blocks::tipset_keys_json::TipsetKeysJson;
+    use forest_json::address::json::AddressJson;
+
+    use crate::data_types::{MsigSigners, MsigTransaction, MsigVesting};
+
+    pub const MSIG_GET_AVAILABLE_BALANCE: &str = "Filecoin.MsigGetAvailableBalance";
+    pub type MsigGetAvailableBalanceParams = (AddressJson, TipsetKeysJson);
+    pub type MsigGetAvailableBalanceResult = String;
+
+    pub const MSIG_GET_PENDING: &str = "Filecoin.MsigGetPending";
+    pub type MsigGetPendingParams = (AddressJson, TipsetKeysJson);
+    pub type MsigGetPendingResult = Vec<MsigTransaction>;
+
+    /// Amount vested between the two tipsets.
+    pub const MSIG_GET_VESTED: &str = "Filecoin.MsigGetVested";
+    pub type MsigGetVestedParams = (AddressJson, TipsetKeysJson, TipsetKeysJson);
+    pub type MsigGetVestedResult = String;
+
+    pub const MSIG_GET_VESTING_SCHEDULE: &str = "Filecoin.MsigGetVestingSchedule";
+    pub type MsigGetVestingScheduleParams = (AddressJson, TipsetKeysJson);
+    pub type MsigGetVestingScheduleResult = MsigVesting;
+
+    pub const MSIG_GET_SIGNERS: &str = "Filecoin.MsigGetSigners";
+    pub type MsigGetSignersParams = (AddressJson, TipsetKeysJson);
+    pub type MsigGetSignersResult = MsigSigners;
+}
+
+/// Payment channel API
+pub mod paych_api {
+    use forest_json::{address::json::AddressJson, cid::CidJson};
+
+    use crate::data_types::{PaychChannelInfo, VoucherCreateResult};
+
+    /// Returns the outbound channel from the first address to the second one,
+    /// adding the amount in attoFIL to it. The channel is created if there is
+    /// none yet, in which case its address is unset until the creation message
+    /// returned as wait sentinel is executed.
+    pub const PAYCH_GET: &str = "Filecoin.PaychGet";
+    pub type PaychGetParams = (AddressJson, AddressJson, String);
+    pub type PaychGetResult = PaychChannelInfo;
+
+    /// Waits for the wait sentinel returned by `Filecoin.PaychGet` to be
+    /// executed, and returns the address of the channel.
+    pub const PAYCH_GET_WAIT_READY: &str = "Filecoin.PaychGetWaitReady";
+    pub type PaychGetWaitReadyParams = (CidJson,);
+    pub type PaychGetWaitReadyResult = AddressJson;
+
+    /// Creates a voucher for the lane of an outbound channel, for an amount in
+    /// attoFIL on top of the previous vouchers of the lane.
+    pub const PAYCH_VOUCHER_CREATE: &str = "Filecoin.PaychVoucherCreate";
+    pub type PaychVoucherCreateParams = (AddressJson, String, u64);
+    pub type PaychVoucherCreateResult = VoucherCreateResult;
+
+    pub const PAYCH_VOUCHER_CHECK_VALID: &str = "Filecoin.PaychVoucherCheckValid";
+    pub type PaychVoucherCheckValidParams = (AddressJson, String);
+    pub type PaychVoucherCheckValidResult = ();
+
+    /// Stores a voucher received through a channel, if it is worth at least the
+    /// minimum delta in attoFIL over the previous voucher of the lane. Returns
+    /// the delta.
+    pub const PAYCH_VOUCHER_ADD: &str = "Filecoin.PaychVoucherAdd";
+    pub type PaychVoucherAddParams = (AddressJson, String, String);
+    pub type PaychVoucherAddResult = String;
+
+    pub const PAYCH_VOUCHER_SUBMIT: &str = "Filecoin.PaychVoucherSubmit";
+    pub type PaychVoucherSubmitParams = (AddressJson, String, Vec<u8>);
+    pub type PaychVoucherSubmitResult = CidJson;
+
+    pub const PAYCH_SETTLE: &str = "Filecoin.PaychSettle";
+    pub type PaychSettleParams = (AddressJson,);
+    pub type PaychSettleResult = CidJson;
+
+    pub const PAYCH_COLLECT: &str = "Filecoin.PaychCollect";
+    pub type PaychCollectParams = (AddressJson,);
+    pub type PaychCollectResult = CidJson;
 }
 
 /// Gas API
@@ -380,7 +696,9 @@ pub mod gas_api {
 
 /// Common API
 pub mod common_api {
-    use super::data_types::APIVersion;
+    use std::path::PathBuf;
+
+    use super::data_types::{APIVersion, BackupInfo};
 
     pub const VERSION: &str = "Filecoin.Version";
     pub type VersionParams = ();
@@ -389,11 +707,18 @@ pub mod common_api {
     pub const SHUTDOWN: &str = "Filecoin.Shutdown";
     pub type ShutdownParams = ();
     pub type ShutdownResult = ();
+
+    pub const CREATE_BACKUP: &str = "Filecoin.CreateBackup";
+    pub type CreateBackupParams = (PathBuf,);
+    pub type CreateBackupResult = BackupInfo;
 }
 
 /// Net API
 pub mod net_api {
-    use crate::data_types::AddrInfo;
+    use ahash::HashMap;
+    use forest_libp2p::{BandwidthStats, BitswapLedger, PeerScore};
+
+    use crate::data_types::{AddrInfo, ExtendedPeerInfo, NetBlockList};
 
     pub const NET_ADDRS_LISTEN: &str = "Filecoin.NetAddrsListen";
     pub type NetAddrsListenParams = ();
@@ -410,4 +735,56 @@ pub mod net_api {
     pub const NET_DISCONNECT: &str = "Filecoin.NetDisconnect";
     pub type NetDisconnectParams = (String,);
     pub type NetDisconnectResult = ();
+
+    pub const NET_PEER_INFO: &str = "Filecoin.NetPeerInfo";
+    pub type NetPeerInfoParams = (String,);
+    pub type NetPeerInfoResult = ExtendedPeerInfo;
+
+    pub const NET_BANDWIDTH_STATS: &str = "Filecoin.NetBandwidthStats";
+    pub type NetBandwidthStatsParams = ();
+    pub type NetBandwidthStatsResult = BandwidthStats;
+
+    pub const NET_BANDWIDTH_STATS_BY_PEER: &str = "Filecoin.NetBandwidthStatsByPeer";
+    pub type NetBandwidthStatsByPeerParams = ();
+    pub type NetBandwidthStatsByPeerResult = HashMap<String, BandwidthStats>;
+
+    pub const NET_BANDWIDTH_STATS_BY_PROTOCOL: &str = "Filecoin.NetBandwidthStatsByProtocol";
+    pub type NetBandwidthStatsByProtocolParams = ();
+    pub type NetBandwidthStatsByProtocolResult = HashMap<String, BandwidthStats>;
+
+    pub const NET_FIND_PEER: &str = "Filecoin.NetFindPeer";
+    pub type NetFindPeerParams = (String,);
+    pub type NetFindPeerResult = AddrInfo;
+
+    pub const NET_PROTECT_ADD: &str = "Filecoin.NetProtectAdd";
+    pub type NetProtectAddParams = (Vec<String>,);
+    pub type NetProtectAddResult = ();
+
+    pub const NET_PROTECT_REMOVE: &str = "Filecoin.NetProtectRemove";
+    pub type NetProtectRemoveParams = (Vec<String>,);
+    pub type NetProtectRemoveResult = ();
+
+    pub const NET_PROTECT_LIST: &str = "Filecoin.NetProtectList";
+    pub type NetProtectListParams = ();
+    pub type NetProtectListResult = Vec<String>;
+
+    pub const NET_BLOCK_ADD: &str = "Filecoin.NetBlockAdd";
+    pub type NetBlockAddParams = (NetBlockList,);
+    pub type NetBlockAddResult = ();
+
+    pub const NET_BLOCK_REMOVE: &str = "Filecoin.NetBlockRemove";
+    pub type NetBlockRemoveParams = (NetBlockList,);
+    pub type NetBlockRemoveResult = ();
+
+    pub const NET_BLOCK_LIST: &str = "Filecoin.NetBlockList";
+    pub type NetBlockListParams = ();
+    pub type NetBlockListResult = NetBlockList;
+
+    pub const NET_PEER_SCORES: &str = "Filecoin.NetPeerScores";
+    pub type NetPeerScoresParams = ();
+    pub type NetPeerScoresResult = HashMap<String, PeerScore>;
+
+    pub const NET_BITSWAP_LEDGER: &str = "Filecoin.NetBitswapLedger";
+    pub type NetBitswapLedgerParams = ();
+    pub type NetBitswapLedgerResult = HashMap<String, BitswapLedger>;
 }
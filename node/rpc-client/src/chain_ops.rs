@@ -20,6 +20,19 @@ pub async fn chain_export(
     call(CHAIN_EXPORT, params, auth_token).await
 }
 
+pub async fn chain_check_store(
+    params: ChainCheckStoreParams,
+    auth_token: &Option<String>,
+) -> Result<ChainCheckStoreResult, Error> {
+    call(CHAIN_CHECK_STORE, params, auth_token).await
+}
+
+pub async fn chain_check_store_status(
+    auth_token: &Option<String>,
+) -> Result<ChainCheckStoreStatusResult, Error> {
+    call(CHAIN_CHECK_STORE_STATUS, (), auth_token).await
+}
+
 pub async fn chain_get_genesis(
     auth_token: &Option<String>,
 ) -> Result<ChainGetGenesisResult, Error> {
@@ -51,6 +64,13 @@ pub async fn chain_get_tipset(
     call(CHAIN_GET_TIPSET, keys, auth_token).await
 }
 
+pub async fn chain_get_tipset_by_height(
+    params: ChainGetTipsetByHeightParams,
+    auth_token: &Option<String>,
+) -> Result<ChainGetTipsetByHeightResult, Error> {
+    call(CHAIN_GET_TIPSET_BY_HEIGHT, params, auth_token).await
+}
+
 pub async fn chain_get_tipset_hash(
     keys: ChainGetTipSetHashParams,
     auth_token: &Option<String>,
@@ -19,3 +19,10 @@ pub async fn shutdown(
 ) -> Result<ShutdownResult, Error> {
     call(SHUTDOWN, params, auth_token).await
 }
+
+pub async fn create_backup(
+    params: CreateBackupParams,
+    auth_token: &Option<String>,
+) -> Result<CreateBackupResult, Error> {
+    call(CREATE_BACKUP, params, auth_token).await
+}
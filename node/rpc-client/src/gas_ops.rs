@@ -0,0 +1,14 @@
+// Copyright 2019-2023 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use forest_rpc_api::gas_api::*;
+use jsonrpc_v2::Error;
+
+use crate::call;
+
+pub async fn gas_estimate_message_gas(
+    params: GasEstimateMessageGasParams,
+    auth_token: &Option<String>,
+) -> Result<GasEstimateMessageGasResult, Error> {
+    call(GAS_ESTIMATE_MESSAGE_GAS, params, auth_token).await
+}
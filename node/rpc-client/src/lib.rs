@@ -5,13 +5,16 @@
 pub mod auth_ops;
 pub mod chain_ops;
 pub mod common_ops;
+pub mod gas_ops;
 pub mod mpool_ops;
+pub mod msig_ops;
 pub mod net_ops;
+pub mod paych_ops;
 pub mod state_ops;
 pub mod sync_ops;
 pub mod wallet_ops;
 
-use std::env;
+use std::{env, path::PathBuf};
 
 use forest_libp2p::{Multiaddr, Protocol};
 use forest_utils::net::{https_client, hyper, hyper::http::HeaderValue, HyperBodyExt};
@@ -30,8 +33,8 @@ pub const DEFAULT_URL: &str = "http://127.0.0.1:1234/rpc/v0";
 pub const RPC_ENDPOINT: &str = "rpc/v0";
 
 pub use self::{
-    auth_ops::*, chain_ops::*, common_ops::*, mpool_ops::*, net_ops::*, state_ops::*, sync_ops::*,
-    wallet_ops::*,
+    auth_ops::*, chain_ops::*, common_ops::*, gas_ops::*, mpool_ops::*, msig_ops::*, net_ops::*,
+    paych_ops::*, state_ops::*, sync_ops::*, wallet_ops::*,
 };
 
 pub struct ApiInfo {
@@ -138,6 +141,32 @@ fn multiaddress_to_url(multiaddr: Multiaddr) -> String {
     url
 }
 
+/// Extracts the socket path from a multi-address of the form `/unix/<path>`.
+/// The path is always absolute, as the leading slash is consumed by the
+/// multi-address parser.
+fn multiaddress_to_unix_path(multiaddr: &Multiaddr) -> Option<PathBuf> {
+    multiaddr.iter().find_map(|protocol| match protocol {
+        Protocol::Unix(path) => Some(PathBuf::from("/").join(path.as_ref())),
+        _ => None,
+    })
+}
+
+/// Sends a request over the Unix socket at the given path. The socket is
+/// trusted by the daemon, so no token is required.
+async fn unix_request(
+    path: PathBuf,
+    request: hyper::Request<hyper::Body>,
+) -> Result<hyper::Response<hyper::Body>, Error> {
+    let stream = tokio::net::UnixStream::connect(&path).await?;
+    let (mut sender, connection) = hyper::client::conn::handshake(stream).await?;
+    tokio::spawn(async move {
+        if let Err(e) = connection.await {
+            debug!("Unix socket connection closed with error: {e}");
+        }
+    });
+    Ok(sender.send_request(request).await?)
+}
+
 /// Utility method for sending RPC requests over HTTP
 async fn call<P, R>(method_name: &str, params: P, token: &Option<String>) -> Result<R, Error>
 where
@@ -149,11 +178,15 @@ where
         .with_params(serde_json::to_value(params)?)
         .finish();
 
-    let api_url = multiaddress_to_url(API_INFO.multiaddr.to_owned());
+    let unix_path = multiaddress_to_unix_path(&API_INFO.multiaddr);
+    let api_url = match &unix_path {
+        // Requests over a Unix socket are routed by path only
+        Some(_) => format!("/{RPC_ENDPOINT}"),
+        None => multiaddress_to_url(API_INFO.multiaddr.to_owned()),
+    };
 
     debug!("Using JSON-RPC v2 HTTP URL: {}", api_url);
 
-    let client = https_client();
     // Split the JWT off if present, format multiaddress as URL, then post RPC
     // request to URL
     let mut request =
@@ -170,7 +203,15 @@ where
             }
         }
     }
-    let response = client.request(request).await?;
+    let response = match unix_path {
+        Some(path) => {
+            request
+                .headers_mut()
+                .insert("host", HeaderValue::from_static("localhost"));
+            unix_request(path, request).await?
+        }
+        None => https_client().request(request).await?,
+    };
     let code = response.status();
     if !code.is_success() {
         return Err(Error::Full {
@@ -201,3 +242,20 @@ where
         }),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unix_multiaddress_to_path() {
+        let addr: Multiaddr = "/unix/tmp/forest.sock".parse().unwrap();
+        assert_eq!(
+            multiaddress_to_unix_path(&addr),
+            Some(PathBuf::from("/tmp/forest.sock"))
+        );
+
+        let addr: Multiaddr = DEFAULT_MULTIADDRESS.parse().unwrap();
+        assert_eq!(multiaddress_to_unix_path(&addr), None);
+    }
+}
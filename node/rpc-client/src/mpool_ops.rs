@@ -19,3 +19,17 @@ pub async fn mpool_push_message(
 ) -> Result<MpoolPushMessageResult, Error> {
     call(MPOOL_PUSH_MESSAGE, params, auth_token).await
 }
+
+pub async fn mpool_push(
+    params: MpoolPushParams,
+    auth_token: &Option<String>,
+) -> Result<MpoolPushResult, Error> {
+    call(MPOOL_PUSH, params, auth_token).await
+}
+
+pub async fn mpool_get_nonce(
+    params: MpoolGetNonceParams,
+    auth_token: &Option<String>,
+) -> Result<MpoolGetNonceResult, Error> {
+    call(MPOOL_GET_NONCE, params, auth_token).await
+}
@@ -0,0 +1,42 @@
+// Copyright 2019-2023 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use forest_rpc_api::msig_api::*;
+use jsonrpc_v2::Error;
+
+use crate::call;
+
+pub async fn msig_get_available_balance(
+    params: MsigGetAvailableBalanceParams,
+    auth_token: &Option<String>,
+) -> Result<MsigGetAvailableBalanceResult, Error> {
+    call(MSIG_GET_AVAILABLE_BALANCE, params, auth_token).await
+}
+
+pub async fn msig_get_pending(
+    params: MsigGetPendingParams,
+    auth_token: &Option<String>,
+) -> Result<MsigGetPendingResult, Error> {
+    call(MSIG_GET_PENDING, params, auth_token).await
+}
+
+pub async fn msig_get_vested(
+    params: MsigGetVestedParams,
+    auth_token: &Option<String>,
+) -> Result<MsigGetVestedResult, Error> {
+    call(MSIG_GET_VESTED, params, auth_token).await
+}
+
+pub async fn msig_get_vesting_schedule(
+    params: MsigGetVestingScheduleParams,
+    auth_token: &Option<String>,
+) -> Result<MsigGetVestingScheduleResult, Error> {
+    call(MSIG_GET_VESTING_SCHEDULE, params, auth_token).await
+}
+
+pub async fn msig_get_signers(
+    params: MsigGetSignersParams,
+    auth_token: &Option<String>,
+) -> Result<MsigGetSignersResult, Error> {
+    call(MSIG_GET_SIGNERS, params, auth_token).await
+}
@@ -33,3 +33,94 @@ pub async fn net_disconnect(
 ) -> Result<NetDisconnectResult, Error> {
     call(NET_DISCONNECT, params, auth_token).await
 }
+
+pub async fn net_peer_info(
+    params: NetPeerInfoParams,
+    auth_token: &Option<String>,
+) -> Result<NetPeerInfoResult, Error> {
+    call(NET_PEER_INFO, params, auth_token).await
+}
+
+pub async fn net_bandwidth_stats(
+    params: NetBandwidthStatsParams,
+    auth_token: &Option<String>,
+) -> Result<NetBandwidthStatsResult, Error> {
+    call(NET_BANDWIDTH_STATS, params, auth_token).await
+}
+
+pub async fn net_bandwidth_stats_by_peer(
+    params: NetBandwidthStatsByPeerParams,
+    auth_token: &Option<String>,
+) -> Result<NetBandwidthStatsByPeerResult, Error> {
+    call(NET_BANDWIDTH_STATS_BY_PEER, params, auth_token).await
+}
+
+pub async fn net_bandwidth_stats_by_protocol(
+    params: NetBandwidthStatsByProtocolParams,
+    auth_token: &Option<String>,
+) -> Result<NetBandwidthStatsByProtocolResult, Error> {
+    call(NET_BANDWIDTH_STATS_BY_PROTOCOL, params, auth_token).await
+}
+
+pub async fn net_find_peer(
+    params: NetFindPeerParams,
+    auth_token: &Option<String>,
+) -> Result<NetFindPeerResult, Error> {
+    call(NET_FIND_PEER, params, auth_token).await
+}
+
+pub async fn net_protect_add(
+    params: NetProtectAddParams,
+    auth_token: &Option<String>,
+) -> Result<NetProtectAddResult, Error> {
+    call(NET_PROTECT_ADD, params, auth_token).await
+}
+
+pub async fn net_protect_remove(
+    params: NetProtectRemoveParams,
+    auth_token: &Option<String>,
+) -> Result<NetProtectRemoveResult, Error> {
+    call(NET_PROTECT_REMOVE, params, auth_token).await
+}
+
+pub async fn net_protect_list(
+    params: NetProtectListParams,
+    auth_token: &Option<String>,
+) -> Result<NetProtectListResult, Error> {
+    call(NET_PROTECT_LIST, params, auth_token).await
+}
+
+pub async fn net_block_add(
+    params: NetBlockAddParams,
+    auth_token: &Option<String>,
+) -> Result<NetBlockAddResult, Error> {
+    call(NET_BLOCK_ADD, params, auth_token).await
+}
+
+pub async fn net_block_remove(
+    params: NetBlockRemoveParams,
+    auth_token: &Option<String>,
+) -> Result<NetBlockRemoveResult, Error> {
+    call(NET_BLOCK_REMOVE, params, auth_token).await
+}
+
+pub async fn net_block_list(
+    params: NetBlockListParams,
+    auth_token: &Option<String>,
+) -> Result<NetBlockListResult, Error> {
+    call(NET_BLOCK_LIST, params, auth_token).await
+}
+
+pub async fn net_peer_scores(
+    params: NetPeerScoresParams,
+    auth_token: &Option<String>,
+) -> Result<NetPeerScoresResult, Error> {
+    call(NET_PEER_SCORES, params, auth_token).await
+}
+
+pub async fn net_bitswap_ledger(
+    params: NetBitswapLedgerParams,
+    auth_token: &Option<String>,
+) -> Result<NetBitswapLedgerResult, Error> {
+    call(NET_BITSWAP_LEDGER, params, auth_token).await
+}
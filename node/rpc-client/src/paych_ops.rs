@@ -0,0 +1,63 @@
+// Copyright 2019-2023 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use forest_rpc_api::paych_api::*;
+use jsonrpc_v2::Error;
+
+use crate::call;
+
+pub async fn paych_get(
+    params: PaychGetParams,
+    auth_token: &Option<String>,
+) -> Result<PaychGetResult, Error> {
+    call(PAYCH_GET, params, auth_token).await
+}
+
+pub async fn paych_get_wait_ready(
+    params: PaychGetWaitReadyParams,
+    auth_token: &Option<String>,
+) -> Result<PaychGetWaitReadyResult, Error> {
+    call(PAYCH_GET_WAIT_READY, params, auth_token).await
+}
+
+pub async fn paych_voucher_create(
+    params: PaychVoucherCreateParams,
+    auth_token: &Option<String>,
+) -> Result<PaychVoucherCreateResult, Error> {
+    call(PAYCH_VOUCHER_CREATE, params, auth_token).await
+}
+
+pub async fn paych_voucher_check_valid(
+    params: PaychVoucherCheckValidParams,
+    auth_token: &Option<String>,
+) -> Result<PaychVoucherCheckValidResult, Error> {
+    call(PAYCH_VOUCHER_CHECK_VALID, params, auth_token).await
+}
+
+pub async fn paych_voucher_add(
+    params: PaychVoucherAddParams,
+    auth_token: &Option<String>,
+) -> Result<PaychVoucherAddResult, Error> {
+    call(PAYCH_VOUCHER_ADD, params, auth_token).await
+}
+
+pub async fn paych_voucher_submit(
+    params: PaychVoucherSubmitParams,
+    auth_token: &Option<String>,
+) -> Result<PaychVoucherSubmitResult, Error> {
+    call(PAYCH_VOUCHER_SUBMIT, params, auth_token).await
+}
+
+pub async fn paych_settle(
+    params: PaychSettleParams,
+    auth_token: &Option<String>,
+) -> Result<PaychSettleResult, Error> {
+    call(PAYCH_SETTLE, params, auth_token).await
+}
+
+pub async fn paych_collect(
+    params: PaychCollectParams,
+    auth_token: &Option<String>,
+) -> Result<PaychCollectResult, Error> {
+    call(PAYCH_COLLECT, params, auth_token).await
+}
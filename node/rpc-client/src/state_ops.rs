@@ -1,2 +1,126 @@
 // Copyright 2019-2023 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
+
+use forest_rpc_api::state_api::*;
+use jsonrpc_v2::Error;
+
+use crate::call;
+
+pub async fn state_network_version(
+    params: StateNetworkVersionParams,
+    auth_token: &Option<String>,
+) -> Result<StateNetworkVersionResult, Error> {
+    call(STATE_NETWORK_VERSION, params, auth_token).await
+}
+
+pub async fn state_wait_msg(
+    params: StateWaitMsgParams,
+    auth_token: &Option<String>,
+) -> Result<StateWaitMsgResult, Error> {
+    call(STATE_WAIT_MSG, params, auth_token).await
+}
+
+pub async fn state_actor_code_cids(
+    params: StateActorCodeCidsParams,
+    auth_token: &Option<String>,
+) -> Result<StateActorCodeCidsResult, Error> {
+    call(STATE_ACTOR_CODE_CIDS, params, auth_token).await
+}
+
+pub async fn state_verified_client_status(
+    params: StateVerifiedClientStatusParams,
+    auth_token: &Option<String>,
+) -> Result<StateVerifiedClientStatusResult, Error> {
+    call(STATE_VERIFIED_CLIENT_STATUS, params, auth_token).await
+}
+
+pub async fn state_verifier_status(
+    params: StateVerifierStatusParams,
+    auth_token: &Option<String>,
+) -> Result<StateVerifierStatusResult, Error> {
+    call(STATE_VERIFIER_STATUS, params, auth_token).await
+}
+
+pub async fn state_verified_registry_root_key(
+    params: StateVerifiedRegistryRootKeyParams,
+    auth_token: &Option<String>,
+) -> Result<StateVerifiedRegistryRootKeyResult, Error> {
+    call(STATE_VERIFIED_REGISTRY_ROOT_KEY, params, auth_token).await
+}
+
+pub async fn state_get_allocations(
+    params: StateGetAllocationsParams,
+    auth_token: &Option<String>,
+) -> Result<StateGetAllocationsResult, Error> {
+    call(STATE_GET_ALLOCATIONS, params, auth_token).await
+}
+
+pub async fn state_get_claims(
+    params: StateGetClaimsParams,
+    auth_token: &Option<String>,
+) -> Result<StateGetClaimsResult, Error> {
+    call(STATE_GET_CLAIMS, params, auth_token).await
+}
+
+pub async fn state_miner_available_balance(
+    params: StateMinerAvailableBalanceParams,
+    auth_token: &Option<String>,
+) -> Result<StateMinerAvailableBalanceResult, Error> {
+    call(STATE_MINER_AVAILABLE_BALANCE, params, auth_token).await
+}
+
+pub async fn state_miner_vesting_funds(
+    params: StateMinerVestingFundsParams,
+    auth_token: &Option<String>,
+) -> Result<StateMinerVestingFundsResult, Error> {
+    call(STATE_MINER_VESTING_FUNDS, params, auth_token).await
+}
+
+pub async fn state_miner_initial_pledge_collateral(
+    params: StateMinerInitialPledgeCollateralParams,
+    auth_token: &Option<String>,
+) -> Result<StateMinerInitialPledgeCollateralResult, Error> {
+    call(STATE_MINER_INITIAL_PLEDGE_COLLATERAL, params, auth_token).await
+}
+
+pub async fn state_miner_pre_commit_deposit_for_power(
+    params: StateMinerPreCommitDepositForPowerParams,
+    auth_token: &Option<String>,
+) -> Result<StateMinerPreCommitDepositForPowerResult, Error> {
+    call(STATE_MINER_PRE_COMMIT_DEPOSIT_FOR_POWER, params, auth_token).await
+}
+
+pub async fn state_market_deals_page(
+    params: StateMarketDealsPageParams,
+    auth_token: &Option<String>,
+) -> Result<StateMarketDealsPageResult, Error> {
+    call(STATE_MARKET_DEALS_PAGE, params, auth_token).await
+}
+
+pub async fn state_market_storage_deal(
+    params: StateMarketStorageDealParams,
+    auth_token: &Option<String>,
+) -> Result<StateMarketStorageDealResult, Error> {
+    call(STATE_MARKET_STORAGE_DEAL, params, auth_token).await
+}
+
+pub async fn state_market_participants(
+    params: StateMarketParticipantsParams,
+    auth_token: &Option<String>,
+) -> Result<StateMarketParticipantsResult, Error> {
+    call(STATE_MARKET_PARTICIPANTS, params, auth_token).await
+}
+
+pub async fn state_circulating_supply(
+    params: StateCirculatingSupplyParams,
+    auth_token: &Option<String>,
+) -> Result<StateCirculatingSupplyResult, Error> {
+    call(STATE_CIRCULATING_SUPPLY, params, auth_token).await
+}
+
+pub async fn state_vm_circulating_supply_internal(
+    params: StateVMCirculatingSupplyInternalParams,
+    auth_token: &Option<String>,
+) -> Result<StateVMCirculatingSupplyInternalResult, Error> {
+    call(STATE_VM_CIRCULATING_SUPPLY_INTERNAL, params, auth_token).await
+}
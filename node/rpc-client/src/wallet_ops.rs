@@ -75,3 +75,24 @@ pub async fn wallet_verify(
 ) -> Result<WalletVerifyResult, Error> {
     call(WALLET_VERIFY, message, auth_token).await
 }
+
+pub async fn wallet_hd_new(
+    params: WalletHdNewParams,
+    auth_token: &Option<String>,
+) -> Result<WalletHdNewResult, Error> {
+    call(WALLET_HD_NEW, params, auth_token).await
+}
+
+pub async fn wallet_hd_restore(
+    params: WalletHdRestoreParams,
+    auth_token: &Option<String>,
+) -> Result<WalletHdRestoreResult, Error> {
+    call(WALLET_HD_RESTORE, params, auth_token).await
+}
+
+pub async fn wallet_hd_derive(
+    params: WalletHdDeriveParams,
+    auth_token: &Option<String>,
+) -> Result<WalletHdDeriveResult, Error> {
+    call(WALLET_HD_DERIVE, params, auth_token).await
+}
@@ -4,7 +4,10 @@
 
 use std::{
     path::{Path, PathBuf},
-    sync::Arc,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc,
+    },
 };
 
 use anyhow::Result;
@@ -13,16 +16,21 @@ use forest_blocks::{
     header::json::BlockHeaderJson, tipset_json::TipsetJson, tipset_keys_json::TipsetKeysJson,
     BlockHeader, Tipset,
 };
+use forest_chain::repair_from_car;
 use forest_db::Store;
 use forest_json::{cid::CidJson, message::json::MessageJson};
+use forest_libp2p::NetworkMessage;
 use forest_rpc_api::{
     chain_api::*,
-    data_types::{BlockMessages, RPCState},
+    data_types::{
+        BlockMessages, RPCState, RepairSource, StoreCheck, StoreCheckReport, StoreCheckStatus,
+    },
 };
 use forest_shim::message::Message;
 use forest_utils::{
     db::BlockstoreExt,
     io::{AsyncWriterWithChecksum, VoidAsyncWriterWithNoChecksum},
+    net::FetchProgress,
 };
 use fvm_ipld_blockstore::Blockstore;
 use hex::ToHex;
@@ -134,6 +142,111 @@ where
     Ok(output_path)
 }
 
+pub(crate) async fn chain_check_store<DB, B>(
+    data: Data<RPCState<DB, B>>,
+    Params(ChainCheckStoreParams {
+        recent_roots,
+        tipset_keys: TipsetKeysJson(tsk),
+        repair,
+    }): Params<ChainCheckStoreParams>,
+) -> Result<ChainCheckStoreResult, JsonRpcError>
+where
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+{
+    let head = data.chain_store.tipset_from_keys(&tsk)?;
+    let checked = Arc::new(AtomicU64::default());
+    {
+        let mut store_check = data.store_check.lock();
+        if matches!(&*store_check, Some(StoreCheck { report: None, .. })) {
+            return Err(JsonRpcError::Provided {
+                code: http::StatusCode::SERVICE_UNAVAILABLE.as_u16() as _,
+                message: "Another store check is still in progress",
+            });
+        }
+        *store_check = Some(StoreCheck {
+            checked: checked.clone(),
+            report: None,
+        });
+    }
+
+    let chain_store = data.chain_store.clone();
+    let network_send = data.network_send.clone();
+    let check = tokio::spawn(async move {
+        let bitswap = matches!(repair, Some(RepairSource::Bitswap));
+        let mut report = chain_store
+            .check(&head, recent_roots, &checked, |cid| {
+                let network_send = network_send.clone();
+                async move {
+                    if !bitswap {
+                        return None;
+                    }
+                    // The store only overwrites the block once the data hashes to its CID
+                    let (tx, rx) = flume::bounded(1);
+                    network_send
+                        .send_async(NetworkMessage::BitswapRefetch {
+                            cid,
+                            response_channel: tx,
+                        })
+                        .await
+                        .ok()?;
+                    rx.recv_async().await.ok().flatten()
+                }
+            })
+            .await;
+
+        if let Some(RepairSource::Snapshot(path)) = repair {
+            let repaired = async {
+                let reader = FetchProgress::fetch_from_file(File::open(&path).await?).await?;
+                repair_from_car(chain_store.blockstore(), reader, &report.unrepaired()).await
+            }
+            .await;
+            match repaired {
+                Ok(repaired) => report.repaired.extend(repaired),
+                Err(e) => report.aborted = Some(format!("repairing from {}: {e}", path.display())),
+            }
+        }
+
+        StoreCheckReport::from(report)
+    });
+    let store_check = data.store_check.clone();
+    tokio::spawn(async move {
+        let result = check.await;
+        if let Some(store_check) = store_check.lock().as_mut() {
+            // A check that panicked is reported as aborted, so that it isn't
+            // reported running and another one can be started
+            store_check.report = Some(result.unwrap_or_else(|e| StoreCheckReport {
+                checked: store_check.checked.load(Ordering::Relaxed),
+                missing: vec![],
+                corrupted: vec![],
+                unreadable: vec![],
+                repaired: vec![],
+                aborted: Some(format!("the check failed: {e}")),
+            }));
+        }
+    });
+
+    Ok(())
+}
+
+pub(crate) async fn chain_check_store_status<DB, B>(
+    data: Data<RPCState<DB, B>>,
+) -> Result<ChainCheckStoreStatusResult, JsonRpcError>
+where
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+{
+    let store_check = data.store_check.lock();
+    let store_check = store_check
+        .as_ref()
+        .ok_or("No store check was started on this node")?;
+    Ok(StoreCheckStatus {
+        running: store_check.report.is_none(),
+        checked: store_check.checked.load(Ordering::Relaxed),
+        report: store_check.report.clone(),
+    })
+}
+
 /// Prints hex-encoded representation of SHA-256 checksum and saves it to a file
 /// with the same name but with a `.sha256sum` extension.
 async fn save_checksum(source: &Path, hash: Output<Sha256>) -> Result<()> {
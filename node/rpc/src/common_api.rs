@@ -2,11 +2,16 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 #![allow(clippy::unused_async)]
 
+use std::sync::Arc;
+
+use forest_beacon::Beacon;
+use forest_db::{DbCheckpoint, Store};
 use forest_rpc_api::{
     common_api::*,
-    data_types::{APIVersion, Version},
+    data_types::{APIVersion, BackupInfo, RPCState, Version},
 };
-use jsonrpc_v2::Error as JsonRpcError;
+use fvm_ipld_blockstore::Blockstore;
+use jsonrpc_v2::{Data, Error as JsonRpcError, Params};
 use semver::Version as SemVer;
 use tokio::sync::mpsc::Sender;
 
@@ -29,3 +34,41 @@ pub(crate) async fn shutdown(shutdown_send: Sender<()>) -> Result<ShutdownResult
     }
     Ok(())
 }
+
+/// Writes a consistent copy of the database of the running node to the `db`
+/// directory of the backup, along with its keystore and its configuration
+/// without secrets, on the host of the node.
+pub(crate) async fn create_backup<DB, B>(
+    data: Data<RPCState<DB, B>>,
+    Params((dir,)): Params<CreateBackupParams>,
+    forest_version: &'static str,
+    config: Arc<str>,
+) -> Result<CreateBackupResult, JsonRpcError>
+where
+    DB: Blockstore + Store + DbCheckpoint + Clone + Send + Sync + 'static,
+    B: Beacon,
+{
+    if dir.exists() {
+        return Err(format!("Backup destination {} already exists", dir.display()).into());
+    }
+    let db = data.chain_store.blockstore().clone();
+    let db_backend = db.backend_name();
+    std::fs::create_dir_all(dir.join("db"))?;
+    let db_dir = dir.join("db").join(db_backend);
+    tokio::task::spawn_blocking(move || db.checkpoint(&db_dir)).await??;
+
+    // The keystore file is only replaced under the write lock
+    let keystore = data.keystore.read().await;
+    if let Some(path) = keystore.file_path().filter(|path| path.is_file()) {
+        let name = path.file_name().ok_or("Invalid keystore path")?;
+        std::fs::copy(path, dir.join(name))?;
+    }
+    drop(keystore);
+    std::fs::write(dir.join("config.toml"), config.as_bytes())?;
+
+    Ok(BackupInfo {
+        forest_version: forest_version.to_owned(),
+        network: data.state_manager.chain_config().name.clone(),
+        db_backend: db_backend.to_owned(),
+    })
+}
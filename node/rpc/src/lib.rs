@@ -6,46 +6,61 @@ mod beacon_api;
 mod chain_api;
 mod common_api;
 mod gas_api;
+mod metrics;
 mod mpool_api;
+mod msig_api;
 mod net_api;
+mod paych_api;
+mod paych_store;
 mod rpc_http_handler;
+mod rpc_transport;
 mod rpc_util;
 mod rpc_ws_handler;
 mod state_api;
 mod sync_api;
 mod wallet_api;
 
-use std::{net::TcpListener, sync::Arc};
+use std::{sync::Arc, time::Duration};
 
-use axum::routing::{get, post};
+use axum::{
+    routing::{get, post},
+    Extension,
+};
 use forest_beacon::Beacon;
 use forest_chain::Scale;
-use forest_db::Store;
+use forest_db::{DbCheckpoint, Store};
 use forest_rpc_api::{
     auth_api::*, beacon_api::*, chain_api::*, common_api::*, data_types::RPCState, gas_api::*,
-    mpool_api::*, net_api::*, state_api::*, sync_api::*, wallet_api::*,
+    mpool_api::*, msig_api::*, net_api::*, paych_api::*, state_api::*, sync_api::*, wallet_api::*,
 };
 use fvm_ipld_blockstore::Blockstore;
-use jsonrpc_v2::{Data, Error as JSONRPCError, Server};
+use jsonrpc_v2::{Data, Error as JSONRPCError, Params, Server};
 use log::info;
 use tokio::sync::mpsc::Sender;
 
+pub use crate::rpc_transport::{
+    bind_unix_socket, load_tls_config, RpcEndpoints, RpcTransport, TlsEndpoint,
+};
 use crate::{
     beacon_api::beacon_get_entry,
-    common_api::{shutdown, version},
+    common_api::{create_backup, shutdown, version},
     rpc_http_handler::rpc_http_handler,
+    rpc_transport::{tls_incoming, unix_incoming},
+    rpc_util::SlowCallThreshold,
     rpc_ws_handler::rpc_ws_handler,
     state_api::*,
 };
 
 pub async fn start_rpc<DB, B, S>(
     state: Arc<RPCState<DB, B>>,
-    rpc_endpoint: TcpListener,
+    rpc_endpoints: RpcEndpoints,
+    slow_call_threshold: Option<Duration>,
     forest_version: &'static str,
+    backup_config: String,
     shutdown_send: Sender<()>,
 ) -> Result<(), JSONRPCError>
 where
-    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    DB: Blockstore + Store + DbCheckpoint + Clone + Send + Sync + 'static,
     B: Beacon,
     S: Scale + 'static,
 {
@@ -57,6 +72,7 @@ where
     use wallet_api::*;
 
     let block_delay = state.state_manager.chain_config().block_delay_secs;
+    let backup_config: Arc<str> = backup_config.into();
     let rpc_server = Arc::new(
         Server::new()
             .with_data(Data(state))
@@ -68,6 +84,11 @@ where
             // Chain API
             .with_method(CHAIN_GET_MESSAGE, chain_api::chain_get_message::<DB, B>)
             .with_method(CHAIN_EXPORT, chain_api::chain_export::<DB, B>)
+            .with_method(CHAIN_CHECK_STORE, chain_api::chain_check_store::<DB, B>)
+            .with_method(
+                CHAIN_CHECK_STORE_STATUS,
+                chain_api::chain_check_store_status::<DB, B>,
+            )
             .with_method(CHAIN_READ_OBJ, chain_read_obj::<DB, B>)
             .with_method(CHAIN_HAS_OBJ, chain_has_obj::<DB, B>)
             .with_method(CHAIN_GET_BLOCK_MESSAGES, chain_get_block_messages::<DB, B>)
@@ -89,6 +110,7 @@ where
             .with_method(MPOOL_PENDING, mpool_pending::<DB, B>)
             .with_method(MPOOL_PUSH, mpool_push::<DB, B>)
             .with_method(MPOOL_PUSH_MESSAGE, mpool_push_message::<DB, B>)
+            .with_method(MPOOL_GET_NONCE, mpool_get_nonce::<DB, B>)
             // Sync API
             .with_method(SYNC_CHECK_BAD, sync_check_bad::<DB, B>)
             .with_method(SYNC_MARK_BAD, sync_mark_bad::<DB, B>)
@@ -104,6 +126,9 @@ where
             .with_method(WALLET_SET_DEFAULT, wallet_set_default::<DB, B>)
             .with_method(WALLET_SIGN, wallet_sign::<DB, B>)
             .with_method(WALLET_VERIFY, wallet_verify::<DB, B>)
+            .with_method(WALLET_HD_NEW, wallet_hd_new::<DB, B>)
+            .with_method(WALLET_HD_RESTORE, wallet_hd_restore::<DB, B>)
+            .with_method(WALLET_HD_DERIVE, wallet_hd_derive::<DB, B>)
             // State API
             .with_method(STATE_CALL, state_call::<DB, B>)
             .with_method(STATE_REPLAY, state_replay::<DB, B>)
@@ -112,8 +137,98 @@ where
             .with_method(STATE_REPLAY, state_replay::<DB, B>)
             .with_method(STATE_MARKET_BALANCE, state_market_balance::<DB, B>)
             .with_method(STATE_MARKET_DEALS, state_market_deals::<DB, B>)
+            .with_method(STATE_MARKET_DEALS_PAGE, state_market_deals_page::<DB, B>)
+            .with_method(
+                STATE_MARKET_STORAGE_DEAL,
+                state_market_storage_deal::<DB, B>,
+            )
+            .with_method(
+                STATE_MARKET_PARTICIPANTS,
+                state_market_participants::<DB, B>,
+            )
             .with_method(STATE_GET_RECEIPT, state_get_receipt::<DB, B>)
             .with_method(STATE_WAIT_MSG, state_wait_msg::<DB, B>)
+            .with_method(STATE_ACTOR_CODE_CIDS, state_actor_code_cids::<DB, B>)
+            .with_method(
+                STATE_VERIFIED_CLIENT_STATUS,
+                state_verified_client_status::<DB, B>,
+            )
+            .with_method(STATE_VERIFIER_STATUS, state_verifier_status::<DB, B>)
+            .with_method(
+                STATE_VERIFIED_REGISTRY_ROOT_KEY,
+                state_verified_registry_root_key::<DB, B>,
+            )
+            .with_method(STATE_GET_ALLOCATIONS, state_get_allocations::<DB, B>)
+            .with_method(STATE_GET_CLAIMS, state_get_claims::<DB, B>)
+            .with_method(STATE_MINER_SECTORS, state_miner_sectors::<DB, B>)
+            .with_method(
+                STATE_MINER_ACTIVE_SECTORS,
+                state_miner_active_sectors::<DB, B>,
+            )
+            .with_method(STATE_MINER_DEADLINES, state_miner_deadlines::<DB, B>)
+            .with_method(STATE_MINER_PARTITIONS, state_miner_partitions::<DB, B>)
+            .with_method(STATE_MINER_FAULTS, state_miner_faults::<DB, B>)
+            .with_method(STATE_MINER_RECOVERIES, state_miner_recoveries::<DB, B>)
+            .with_method(
+                STATE_MINER_PROVING_DEADLINE,
+                state_miner_proving_deadline::<DB, B>,
+            )
+            .with_method(STATE_SECTOR_GET_INFO, state_sector_get_info::<DB, B>)
+            .with_method(STATE_MINER_SECTOR_COUNT, state_miner_sector_count::<DB, B>)
+            .with_method(
+                STATE_MINER_AVAILABLE_BALANCE,
+                state_miner_available_balance::<DB, B>,
+            )
+            .with_method(
+                STATE_MINER_VESTING_FUNDS,
+                state_miner_vesting_funds::<DB, B>,
+            )
+            .with_method(
+                STATE_MINER_INITIAL_PLEDGE_COLLATERAL,
+                state_miner_initial_pledge_collateral::<DB, B>,
+            )
+            .with_method(
+                STATE_MINER_PRE_COMMIT_DEPOSIT_FOR_POWER,
+                state_miner_pre_commit_deposit_for_power::<DB, B>,
+            )
+            .with_method(STATE_CIRCULATING_SUPPLY, state_circulating_supply::<DB, B>)
+            .with_method(
+                STATE_VM_CIRCULATING_SUPPLY_INTERNAL,
+                state_vm_circulating_supply_internal::<DB, B>,
+            )
+            // Multisig API
+            .with_method(
+                MSIG_GET_AVAILABLE_BALANCE,
+                msig_api::msig_get_available_balance::<DB, B>,
+            )
+            .with_method(MSIG_GET_PENDING, msig_api::msig_get_pending::<DB, B>)
+            .with_method(MSIG_GET_VESTED, msig_api::msig_get_vested::<DB, B>)
+            .with_method(
+                MSIG_GET_VESTING_SCHEDULE,
+                msig_api::msig_get_vesting_schedule::<DB, B>,
+            )
+            .with_method(MSIG_GET_SIGNERS, msig_api::msig_get_signers::<DB, B>)
+            // Payment channel API
+            .with_method(PAYCH_GET, paych_api::paych_get::<DB, B>)
+            .with_method(
+                PAYCH_GET_WAIT_READY,
+                paych_api::paych_get_wait_ready::<DB, B>,
+            )
+            .with_method(
+                PAYCH_VOUCHER_CREATE,
+                paych_api::paych_voucher_create::<DB, B>,
+            )
+            .with_method(
+                PAYCH_VOUCHER_CHECK_VALID,
+                paych_api::paych_voucher_check_valid::<DB, B>,
+            )
+            .with_method(PAYCH_VOUCHER_ADD, paych_api::paych_voucher_add::<DB, B>)
+            .with_method(
+                PAYCH_VOUCHER_SUBMIT,
+                paych_api::paych_voucher_submit::<DB, B>,
+            )
+            .with_method(PAYCH_SETTLE, paych_api::paych_settle::<DB, B>)
+            .with_method(PAYCH_COLLECT, paych_api::paych_collect::<DB, B>)
             // Gas API
             .with_method(GAS_ESTIMATE_FEE_CAP, gas_estimate_fee_cap::<DB, B>)
             .with_method(GAS_ESTIMATE_GAS_LIMIT, gas_estimate_gas_limit::<DB, B>)
@@ -122,22 +237,77 @@ where
             // Common API
             .with_method(VERSION, move || version(block_delay, forest_version))
             .with_method(SHUTDOWN, move || shutdown(shutdown_send.clone()))
+            .with_method(
+                CREATE_BACKUP,
+                move |data: Data<RPCState<DB, B>>, params: Params<CreateBackupParams>| {
+                    create_backup(data, params, forest_version, backup_config.clone())
+                },
+            )
             // Net API
             .with_method(NET_ADDRS_LISTEN, net_api::net_addrs_listen::<DB, B>)
             .with_method(NET_PEERS, net_api::net_peers::<DB, B>)
             .with_method(NET_CONNECT, net_api::net_connect::<DB, B>)
             .with_method(NET_DISCONNECT, net_api::net_disconnect::<DB, B>)
+            .with_method(NET_PEER_INFO, net_api::net_peer_info::<DB, B>)
+            .with_method(NET_BANDWIDTH_STATS, net_api::net_bandwidth_stats::<DB, B>)
+            .with_method(
+                NET_BANDWIDTH_STATS_BY_PEER,
+                net_api::net_bandwidth_stats_by_peer::<DB, B>,
+            )
+            .with_method(
+                NET_BANDWIDTH_STATS_BY_PROTOCOL,
+                net_api::net_bandwidth_stats_by_protocol::<DB, B>,
+            )
+            .with_method(NET_FIND_PEER, net_api::net_find_peer::<DB, B>)
+            .with_method(NET_PROTECT_ADD, net_api::net_protect_add::<DB, B>)
+            .with_method(NET_PROTECT_REMOVE, net_api::net_protect_remove::<DB, B>)
+            .with_method(NET_PROTECT_LIST, net_api::net_protect_list::<DB, B>)
+            .with_method(NET_BLOCK_ADD, net_api::net_block_add::<DB, B>)
+            .with_method(NET_BLOCK_REMOVE, net_api::net_block_remove::<DB, B>)
+            .with_method(NET_BLOCK_LIST, net_api::net_block_list::<DB, B>)
+            .with_method(NET_PEER_SCORES, net_api::net_peer_scores::<DB, B>)
+            .with_method(NET_BITSWAP_LEDGER, net_api::net_bitswap_ledger::<DB, B>)
             .finish_unwrapped(),
     );
 
-    let app = axum::Router::new()
-        .route("/rpc/v0", get(rpc_ws_handler::<DB, B>))
-        .route("/rpc/v0", post(rpc_http_handler::<DB, B>))
-        .with_state(rpc_server);
+    let app = |transport: RpcTransport| {
+        axum::Router::new()
+            .route("/rpc/v0", get(rpc_ws_handler::<DB, B>))
+            .route("/rpc/v0", post(rpc_http_handler::<DB, B>))
+            .layer(Extension(transport))
+            .layer(Extension(SlowCallThreshold(slow_call_threshold)))
+            .with_state(rpc_server.clone())
+    };
+
+    let RpcEndpoints { http, unix, tls } = rpc_endpoints;
+
+    let http_server = async {
+        axum::Server::from_tcp(http)?
+            .serve(app(RpcTransport::Http).into_make_service())
+            .await?;
+        Ok::<_, anyhow::Error>(())
+    };
+
+    let unix_server = async {
+        if let Some(listener) = unix {
+            axum::Server::builder(hyper::server::accept::from_stream(unix_incoming(listener)?))
+                .serve(app(RpcTransport::Unix).into_make_service())
+                .await?;
+        }
+        Ok::<_, anyhow::Error>(())
+    };
+
+    let tls_server = async {
+        if let Some(endpoint) = tls {
+            axum::Server::builder(hyper::server::accept::from_stream(tls_incoming(endpoint)?))
+                .serve(app(RpcTransport::Tls).into_make_service())
+                .await?;
+        }
+        Ok::<_, anyhow::Error>(())
+    };
 
     info!("Ready for RPC connections");
-    let server = axum::Server::from_tcp(rpc_endpoint)?.serve(app.into_make_service());
-    server.await?;
+    tokio::try_join!(http_server, unix_server, tls_server)?;
 
     info!("Stopped accepting RPC connections");
 
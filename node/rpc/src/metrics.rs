@@ -0,0 +1,111 @@
+// Copyright 2019-2023 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use lazy_static::lazy_static;
+use prometheus::{
+    core::{AtomicI64, AtomicU64, GenericCounterVec, GenericGaugeVec, Opts},
+    HistogramOpts, HistogramVec,
+};
+
+lazy_static! {
+    pub static ref RPC_METHOD_CALLS_TOTAL: Box<GenericCounterVec<AtomicU64>> = {
+        let rpc_method_calls_total = Box::new(
+            GenericCounterVec::<AtomicU64>::new(
+                Opts::new(
+                    "rpc_method_calls_total",
+                    "Total number of RPC calls by method",
+                ),
+                &[labels::METHOD],
+            )
+            .expect("Defining the rpc_method_calls_total metric must succeed"),
+        );
+        prometheus::default_registry()
+            .register(rpc_method_calls_total.clone())
+            .expect(
+                "Registering the rpc_method_calls_total metric with the metrics registry must succeed",
+            );
+        rpc_method_calls_total
+    };
+    pub static ref RPC_METHOD_ERRORS_TOTAL: Box<GenericCounterVec<AtomicU64>> = {
+        let rpc_method_errors_total = Box::new(
+            GenericCounterVec::<AtomicU64>::new(
+                Opts::new(
+                    "rpc_method_errors_total",
+                    "Total number of RPC calls that returned an error by method",
+                ),
+                &[labels::METHOD],
+            )
+            .expect("Defining the rpc_method_errors_total metric must succeed"),
+        );
+        prometheus::default_registry()
+            .register(rpc_method_errors_total.clone())
+            .expect(
+                "Registering the rpc_method_errors_total metric with the metrics registry must succeed",
+            );
+        rpc_method_errors_total
+    };
+    pub static ref RPC_METHOD_LATENCY: Box<HistogramVec> = {
+        let rpc_method_latency = Box::new(
+            HistogramVec::new(
+                HistogramOpts {
+                    common_opts: Opts::new(
+                        "rpc_method_latency",
+                        "Duration of RPC calls in seconds by method",
+                    ),
+                    buckets: vec![],
+                },
+                &[labels::METHOD],
+            )
+            .expect("Defining the rpc_method_latency metric must succeed"),
+        );
+        prometheus::default_registry()
+            .register(rpc_method_latency.clone())
+            .expect(
+                "Registering the rpc_method_latency metric with the metrics registry must succeed",
+            );
+        rpc_method_latency
+    };
+    pub static ref RPC_IN_FLIGHT_REQUESTS: Box<GenericGaugeVec<AtomicI64>> = {
+        let rpc_in_flight_requests = Box::new(
+            GenericGaugeVec::<AtomicI64>::new(
+                Opts::new(
+                    "rpc_in_flight_requests",
+                    "Number of RPC calls currently being processed by method",
+                ),
+                &[labels::METHOD],
+            )
+            .expect("Defining the rpc_in_flight_requests metric must succeed"),
+        );
+        prometheus::default_registry()
+            .register(rpc_in_flight_requests.clone())
+            .expect(
+                "Registering the rpc_in_flight_requests metric with the metrics registry must succeed",
+            );
+        rpc_in_flight_requests
+    };
+}
+
+pub mod labels {
+    pub const METHOD: &str = "method";
+}
+
+/// Tracks an in-flight RPC call. The gauge is decremented on drop, so calls
+/// whose connection goes away mid-request are accounted for as well.
+pub(crate) struct InFlightGuard {
+    method: &'static str,
+}
+
+impl InFlightGuard {
+    pub(crate) fn new(method: &'static str) -> Self {
+        RPC_IN_FLIGHT_REQUESTS.with_label_values(&[method]).inc();
+        Self { method }
+    }
+}
+
+impl Drop for InFlightGuard {
+    fn drop(&mut self) {
+        RPC_IN_FLIGHT_REQUESTS
+            .with_label_values(&[self.method])
+            .dec();
+    }
+}
@@ -2,26 +2,66 @@
 // SPDX-License-Identifier: Apache-2.0, MIT
 #![allow(clippy::unused_async)]
 
-use std::convert::TryFrom;
+use std::{convert::TryFrom, sync::Arc};
 
-use ahash::{HashSet, HashSetExt};
+use ahash::{HashMap, HashSet, HashSetExt};
 use forest_beacon::Beacon;
 use forest_blocks::TipsetKeys;
 use forest_db::Store;
 use forest_json::{
+    address::json::AddressJson,
     cid::{vec::CidJsonVec, CidJson},
     message::json::MessageJson,
     signed_message::json::SignedMessageJson,
 };
+use forest_key_management::ensure_message_signable;
 use forest_message::SignedMessage;
-use forest_rpc_api::{data_types::RPCState, mpool_api::*};
-use forest_shim::address::Protocol;
+use forest_rpc_api::{
+    data_types::{MessageSendSpec, RPCState},
+    mpool_api::*,
+};
+use forest_shim::{
+    address::{Address, Protocol},
+    message::Message,
+};
 use fvm_ipld_blockstore::Blockstore;
 use fvm_ipld_encoding::Cbor;
 use jsonrpc_v2::{Data, Error as JsonRpcError, Params};
+use parking_lot::Mutex as SyncMutex;
+use tokio::sync::Mutex;
 
 use super::gas_api::estimate_message_gas;
 
+/// Lock of a sending address in [`RPCState::push_locks`], removed from them
+/// when dropped unless another push holds it.
+struct PushLock<'a> {
+    locks: &'a SyncMutex<HashMap<Address, Arc<Mutex<()>>>>,
+    address: Address,
+    lock: Arc<Mutex<()>>,
+}
+
+impl<'a> PushLock<'a> {
+    fn new(locks: &'a SyncMutex<HashMap<Address, Arc<Mutex<()>>>>, address: Address) -> Self {
+        let lock = locks.lock().entry(address).or_default().clone();
+        Self {
+            locks,
+            address,
+            lock,
+        }
+    }
+}
+
+impl Drop for PushLock<'_> {
+    fn drop(&mut self) {
+        let mut locks = self.locks.lock();
+        // Held by the locks and by this push only, new pushes clone it under
+        // the locks.
+        if Arc::strong_count(&self.lock) == 2 {
+            locks.remove(&self.address);
+        }
+    }
+}
+
 /// Return `Vec` of pending messages in `mpool`
 pub(crate) async fn mpool_pending<DB, B>(
     data: Data<RPCState<DB, B>>,
@@ -98,6 +138,20 @@ where
     Ok(CidJson(cid))
 }
 
+/// Return the next nonce of an account, accounting for its messages in `mpool`
+pub(crate) async fn mpool_get_nonce<DB, B>(
+    data: Data<RPCState<DB, B>>,
+    Params(params): Params<MpoolGetNonceParams>,
+) -> Result<MpoolGetNonceResult, JsonRpcError>
+where
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+{
+    let (AddressJson(address),) = params;
+
+    Ok(data.mpool.get_sequence(&address)?)
+}
+
 /// Sign given `UnsignedMessage` and add it to `mpool`, return `SignedMessage`
 pub(crate) async fn mpool_push_message<DB, B>(
     data: Data<RPCState<DB, B>>,
@@ -108,22 +162,37 @@ where
     B: Beacon,
 {
     let (MessageJson(umsg), spec) = params;
+    Ok(SignedMessageJson(push_message(&data, umsg, spec).await?))
+}
 
+/// Estimates the gas of the message, signs it with the wallet of the node and
+/// adds it to the message pool. The nonce is filled in by the node.
+pub(crate) async fn push_message<DB, B>(
+    data: &Data<RPCState<DB, B>>,
+    umsg: Message,
+    spec: Option<MessageSendSpec>,
+) -> Result<SignedMessage, JsonRpcError>
+where
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+{
     let from = umsg.from;
 
-    let mut keystore = data.keystore.as_ref().write().await;
     let heaviest_tipset = data.state_manager.chain_store().heaviest_tipset();
     let key_addr = data
         .state_manager
         .resolve_to_key_addr(&from.into(), &heaviest_tipset)
         .await?;
+    ensure_message_signable(&key_addr)?;
 
     if umsg.sequence != 0 {
         return Err(
             "Expected nonce for MpoolPushMessage is 0, and will be calculated for you.".into(),
         );
     }
-    let mut umsg = estimate_message_gas::<DB, B>(&data, umsg, spec, Default::default()).await?;
+    let push_lock = PushLock::new(&data.push_locks, key_addr.into());
+    let _locked = push_lock.lock.lock().await;
+    let mut umsg = estimate_message_gas::<DB, B>(data, umsg, spec, Default::default()).await?;
     if umsg.gas_premium > umsg.gas_fee_cap {
         return Err("After estimation, gas premium is greater than gas fee cap".into());
     }
@@ -133,19 +202,33 @@ where
     }
     let nonce = data.mpool.get_sequence(&from.into())?;
     umsg.sequence = nonce;
-    let key = forest_key_management::Key::try_from(forest_key_management::try_find(
-        &key_addr,
-        &mut keystore,
-    )?)?;
-    let sig = forest_key_management::sign(
-        *key.key_info.key_type(),
-        key.key_info.private_key(),
-        umsg.cid().unwrap().to_bytes().as_slice(),
-    )?;
+    let sig = data
+        .signer
+        .sign(&key_addr, umsg.cid().unwrap().to_bytes().as_slice())
+        .await?;
 
     let smsg = SignedMessage::new_from_parts(umsg, sig)?;
 
     data.mpool.as_ref().push(smsg.clone()).await?;
 
-    Ok(SignedMessageJson(smsg))
+    Ok(smsg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_locks_are_removed_once_released() {
+        let locks = SyncMutex::default();
+        let address = Address::new_id(1);
+        let first = PushLock::new(&locks, address);
+        let second = PushLock::new(&locks, address);
+        assert!(Arc::ptr_eq(&first.lock, &second.lock));
+
+        drop(first);
+        assert!(locks.lock().contains_key(&address));
+        drop(second);
+        assert!(locks.lock().is_empty());
+    }
 }
@@ -0,0 +1,133 @@
+// Copyright 2019-2023 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+#![allow(clippy::unused_async)]
+
+use base64::{prelude::BASE64_STANDARD, Engine};
+use forest_actor_interface::multisig;
+use forest_beacon::Beacon;
+use forest_blocks::{tipset_keys_json::TipsetKeysJson, Tipset};
+use forest_db::Store;
+use forest_json::address::json::AddressJson;
+use forest_rpc_api::{
+    data_types::{MsigSigners, MsigTransaction, MsigVesting, RPCState},
+    msig_api::*,
+};
+use forest_shim::{address::Address, econ::TokenAmount, state_tree::ActorState};
+use fvm_ipld_blockstore::Blockstore;
+use jsonrpc_v2::{Data, Error as JsonRpcError, Params};
+
+/// Loads the multisig actor of the address in the parent state of the tipset.
+fn load_multisig<DB, B>(
+    data: &RPCState<DB, B>,
+    address: &Address,
+    tipset: &Tipset,
+) -> anyhow::Result<(ActorState, multisig::State)>
+where
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+{
+    let actor = data
+        .state_manager
+        .get_actor(address, *tipset.parent_state())?
+        .ok_or_else(|| anyhow::anyhow!("Actor not found for address {address}"))?;
+    let state = multisig::State::load(data.state_manager.blockstore(), &actor)?;
+    Ok((actor, state))
+}
+
+/// Returns the balance of the multisig that can be spent, that is its balance
+/// minus the amount still locked by its vesting schedule.
+pub(crate) async fn msig_get_available_balance<
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+>(
+    data: Data<RPCState<DB, B>>,
+    Params(params): Params<MsigGetAvailableBalanceParams>,
+) -> Result<MsigGetAvailableBalanceResult, JsonRpcError> {
+    let (AddressJson(address), TipsetKeysJson(tsk)) = params;
+    let ts = data.chain_store.tipset_from_keys(&tsk)?;
+    let (actor, state) = load_multisig(&data, &address, &ts)?;
+    let balance = TokenAmount::from(&actor.balance);
+    let locked = state.locked_balance(ts.epoch());
+    let available = if balance > locked {
+        balance - &locked
+    } else {
+        TokenAmount::default()
+    };
+    Ok(available.atto().to_string())
+}
+
+pub(crate) async fn msig_get_pending<
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+>(
+    data: Data<RPCState<DB, B>>,
+    Params(params): Params<MsigGetPendingParams>,
+) -> Result<MsigGetPendingResult, JsonRpcError> {
+    let (AddressJson(address), TipsetKeysJson(tsk)) = params;
+    let ts = data.chain_store.tipset_from_keys(&tsk)?;
+    let (_, state) = load_multisig(&data, &address, &ts)?;
+    let pending = state.pending_transactions(data.state_manager.blockstore())?;
+    Ok(pending
+        .into_iter()
+        .map(|txn| MsigTransaction {
+            id: txn.id,
+            to: txn.to,
+            value: txn.value,
+            method: txn.method,
+            params: BASE64_STANDARD.encode(txn.params),
+            approved: txn.approved,
+        })
+        .collect())
+}
+
+pub(crate) async fn msig_get_vested<
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+>(
+    data: Data<RPCState<DB, B>>,
+    Params(params): Params<MsigGetVestedParams>,
+) -> Result<MsigGetVestedResult, JsonRpcError> {
+    let (AddressJson(address), TipsetKeysJson(start_tsk), TipsetKeysJson(end_tsk)) = params;
+    let start_ts = data.chain_store.tipset_from_keys(&start_tsk)?;
+    let end_ts = data.chain_store.tipset_from_keys(&end_tsk)?;
+    if start_ts.epoch() > end_ts.epoch() {
+        return Err("Start tipset is after the end tipset".into());
+    }
+    let (_, state) = load_multisig(&data, &address, &end_ts)?;
+    let vested = state.locked_balance(start_ts.epoch()) - &state.locked_balance(end_ts.epoch());
+    Ok(vested.atto().to_string())
+}
+
+pub(crate) async fn msig_get_vesting_schedule<
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+>(
+    data: Data<RPCState<DB, B>>,
+    Params(params): Params<MsigGetVestingScheduleParams>,
+) -> Result<MsigGetVestingScheduleResult, JsonRpcError> {
+    let (AddressJson(address), TipsetKeysJson(tsk)) = params;
+    let ts = data.chain_store.tipset_from_keys(&tsk)?;
+    let (_, state) = load_multisig(&data, &address, &ts)?;
+    let schedule = state.vesting_schedule();
+    Ok(MsigVesting {
+        initial_balance: schedule.initial_balance,
+        start_epoch: schedule.start_epoch,
+        unlock_duration: schedule.unlock_duration,
+    })
+}
+
+pub(crate) async fn msig_get_signers<
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+>(
+    data: Data<RPCState<DB, B>>,
+    Params(params): Params<MsigGetSignersParams>,
+) -> Result<MsigGetSignersResult, JsonRpcError> {
+    let (AddressJson(address), TipsetKeysJson(tsk)) = params;
+    let ts = data.chain_store.tipset_from_keys(&tsk)?;
+    let (_, state) = load_multisig(&data, &address, &ts)?;
+    Ok(MsigSigners {
+        signers: state.signers(),
+        threshold: state.threshold(),
+    })
+}
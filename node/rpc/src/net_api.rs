@@ -1,13 +1,13 @@
 // Copyright 2019-2023 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use std::str::FromStr;
+use std::{net::IpAddr, str::FromStr};
 
 use forest_beacon::Beacon;
 use forest_db::Store;
-use forest_libp2p::{NetRPCMethods, NetworkMessage, PeerId};
+use forest_libp2p::{IpNet, NetRPCMethods, NetworkMessage, PeerId};
 use forest_rpc_api::{
-    data_types::{AddrInfo, RPCState},
+    data_types::{AddrInfo, ExtendedPeerInfo, NetBlockList, RPCState},
     net_api::*,
 };
 use futures::channel::oneshot;
@@ -104,3 +104,291 @@ pub(crate) async fn net_disconnect<
 
     Ok(())
 }
+
+pub(crate) async fn net_peer_info<
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+>(
+    data: Data<RPCState<DB, B>>,
+    Params(params): Params<NetPeerInfoParams>,
+) -> Result<NetPeerInfoResult, JsonRpcError> {
+    let (id,) = params;
+    let peer_id = PeerId::from_str(&id)?;
+
+    let (tx, rx) = oneshot::channel();
+    let req = NetworkMessage::JSONRPCRequest {
+        method: NetRPCMethods::NetPeerInfo(tx, peer_id),
+    };
+
+    data.network_send.send_async(req).await?;
+    let (addrs, info) = rx
+        .await?
+        .ok_or_else(|| format!("peer {peer_id} is not connected"))?;
+
+    Ok(ExtendedPeerInfo {
+        id,
+        agent: info.agent_version.unwrap_or_default(),
+        addrs,
+        protocols: info.protocols,
+        latency: info.latency,
+    })
+}
+
+pub(crate) async fn net_bandwidth_stats<
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+>(
+    data: Data<RPCState<DB, B>>,
+) -> Result<NetBandwidthStatsResult, JsonRpcError> {
+    let (tx, rx) = oneshot::channel();
+    let req = NetworkMessage::JSONRPCRequest {
+        method: NetRPCMethods::NetBandwidthStats(tx),
+    };
+
+    data.network_send.send_async(req).await?;
+    Ok(rx.await?)
+}
+
+pub(crate) async fn net_bandwidth_stats_by_peer<
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+>(
+    data: Data<RPCState<DB, B>>,
+) -> Result<NetBandwidthStatsByPeerResult, JsonRpcError> {
+    let (tx, rx) = oneshot::channel();
+    let req = NetworkMessage::JSONRPCRequest {
+        method: NetRPCMethods::NetBandwidthStatsByPeer(tx),
+    };
+
+    data.network_send.send_async(req).await?;
+    let stats = rx.await?;
+
+    Ok(stats
+        .into_iter()
+        .map(|(peer_id, stats)| (peer_id.to_string(), stats))
+        .collect())
+}
+
+pub(crate) async fn net_bandwidth_stats_by_protocol<
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+>(
+    data: Data<RPCState<DB, B>>,
+) -> Result<NetBandwidthStatsByProtocolResult, JsonRpcError> {
+    let (tx, rx) = oneshot::channel();
+    let req = NetworkMessage::JSONRPCRequest {
+        method: NetRPCMethods::NetBandwidthStatsByProtocol(tx),
+    };
+
+    data.network_send.send_async(req).await?;
+    Ok(rx.await?)
+}
+
+pub(crate) async fn net_find_peer<
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+>(
+    data: Data<RPCState<DB, B>>,
+    Params(params): Params<NetFindPeerParams>,
+) -> Result<NetFindPeerResult, JsonRpcError> {
+    let (id,) = params;
+    let peer_id = PeerId::from_str(&id)?;
+
+    let (tx, rx) = oneshot::channel();
+    let req = NetworkMessage::JSONRPCRequest {
+        method: NetRPCMethods::NetFindPeer(tx, peer_id),
+    };
+
+    data.network_send.send_async(req).await?;
+    let addrs = rx.await?;
+    if addrs.is_empty() {
+        return Err(format!("peer {peer_id} could not be found").into());
+    }
+
+    Ok(AddrInfo { id, addrs })
+}
+
+pub(crate) async fn net_protect_add<
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+>(
+    data: Data<RPCState<DB, B>>,
+    Params(params): Params<NetProtectAddParams>,
+) -> Result<NetProtectAddResult, JsonRpcError> {
+    let (ids,) = params;
+    let peers = parse_peer_ids(&ids)?;
+
+    let (tx, rx) = oneshot::channel();
+    let req = NetworkMessage::JSONRPCRequest {
+        method: NetRPCMethods::NetProtectAdd(tx, peers),
+    };
+
+    data.network_send.send_async(req).await?;
+    rx.await?;
+
+    Ok(())
+}
+
+pub(crate) async fn net_protect_remove<
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+>(
+    data: Data<RPCState<DB, B>>,
+    Params(params): Params<NetProtectRemoveParams>,
+) -> Result<NetProtectRemoveResult, JsonRpcError> {
+    let (ids,) = params;
+    let peers = parse_peer_ids(&ids)?;
+
+    let (tx, rx) = oneshot::channel();
+    let req = NetworkMessage::JSONRPCRequest {
+        method: NetRPCMethods::NetProtectRemove(tx, peers),
+    };
+
+    data.network_send.send_async(req).await?;
+    rx.await?;
+
+    Ok(())
+}
+
+pub(crate) async fn net_protect_list<
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+>(
+    data: Data<RPCState<DB, B>>,
+) -> Result<NetProtectListResult, JsonRpcError> {
+    let (tx, rx) = oneshot::channel();
+    let req = NetworkMessage::JSONRPCRequest {
+        method: NetRPCMethods::NetProtectList(tx),
+    };
+
+    data.network_send.send_async(req).await?;
+    let peers = rx.await?;
+
+    Ok(peers.iter().map(PeerId::to_string).collect())
+}
+
+pub(crate) async fn net_block_add<
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+>(
+    data: Data<RPCState<DB, B>>,
+    Params(params): Params<NetBlockAddParams>,
+) -> Result<NetBlockAddResult, JsonRpcError> {
+    let (list,) = params;
+    let list = parse_block_list(list)?;
+
+    let (tx, rx) = oneshot::channel();
+    let req = NetworkMessage::JSONRPCRequest {
+        method: NetRPCMethods::NetBlockAdd(tx, list),
+    };
+
+    data.network_send.send_async(req).await?;
+    rx.await?;
+
+    Ok(())
+}
+
+pub(crate) async fn net_block_remove<
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+>(
+    data: Data<RPCState<DB, B>>,
+    Params(params): Params<NetBlockRemoveParams>,
+) -> Result<NetBlockRemoveResult, JsonRpcError> {
+    let (list,) = params;
+    let list = parse_block_list(list)?;
+
+    let (tx, rx) = oneshot::channel();
+    let req = NetworkMessage::JSONRPCRequest {
+        method: NetRPCMethods::NetBlockRemove(tx, list),
+    };
+
+    data.network_send.send_async(req).await?;
+    rx.await?;
+
+    Ok(())
+}
+
+pub(crate) async fn net_block_list<
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+>(
+    data: Data<RPCState<DB, B>>,
+) -> Result<NetBlockListResult, JsonRpcError> {
+    let (tx, rx) = oneshot::channel();
+    let req = NetworkMessage::JSONRPCRequest {
+        method: NetRPCMethods::NetBlockList(tx),
+    };
+
+    data.network_send.send_async(req).await?;
+    let list = rx.await?;
+
+    Ok(NetBlockList {
+        peers: list.peers.iter().map(PeerId::to_string).collect(),
+        ip_addrs: list.ip_addrs.iter().map(IpAddr::to_string).collect(),
+        ip_subnets: list.ip_subnets.iter().map(IpNet::to_string).collect(),
+    })
+}
+
+fn parse_peer_ids(ids: &[String]) -> Result<Vec<PeerId>, JsonRpcError> {
+    Ok(ids
+        .iter()
+        .map(|id| PeerId::from_str(id))
+        .collect::<Result<_, _>>()?)
+}
+
+fn parse_block_list(list: NetBlockList) -> Result<forest_libp2p::NetBlockList, JsonRpcError> {
+    Ok(forest_libp2p::NetBlockList {
+        peers: parse_peer_ids(&list.peers)?,
+        ip_addrs: list
+            .ip_addrs
+            .iter()
+            .map(|ip| IpAddr::from_str(ip))
+            .collect::<Result<_, _>>()?,
+        ip_subnets: list
+            .ip_subnets
+            .iter()
+            .map(|subnet| IpNet::from_str(subnet))
+            .collect::<Result<_, _>>()?,
+    })
+}
+
+pub(crate) async fn net_peer_scores<
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+>(
+    data: Data<RPCState<DB, B>>,
+) -> Result<NetPeerScoresResult, JsonRpcError> {
+    let (tx, rx) = oneshot::channel();
+    let req = NetworkMessage::JSONRPCRequest {
+        method: NetRPCMethods::NetPeerScores(tx),
+    };
+
+    data.network_send.send_async(req).await?;
+    let scores = rx.await?;
+
+    Ok(scores
+        .into_iter()
+        .map(|(peer_id, score)| (peer_id.to_string(), score))
+        .collect())
+}
+
+pub(crate) async fn net_bitswap_ledger<
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+>(
+    data: Data<RPCState<DB, B>>,
+) -> Result<NetBitswapLedgerResult, JsonRpcError> {
+    let (tx, rx) = oneshot::channel();
+    let req = NetworkMessage::JSONRPCRequest {
+        method: NetRPCMethods::NetBitswapLedger(tx),
+    };
+
+    data.network_send.send_async(req).await?;
+    let ledgers = rx.await?;
+
+    Ok(ledgers
+        .into_iter()
+        .map(|(peer_id, ledger)| (peer_id.to_string(), ledger))
+        .collect())
+}
@@ -0,0 +1,594 @@
+// Copyright 2019-2023 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+#![allow(clippy::unused_async)]
+
+use std::{collections::BTreeMap, str::FromStr};
+
+use anyhow::Context;
+use cid::Cid;
+use forest_actor_interface::{init, paych};
+use forest_beacon::Beacon;
+use forest_chain::get_chain_message;
+use forest_db::Store;
+use forest_json::{address::json::AddressJson, cid::CidJson};
+use forest_message::Message as _;
+use forest_rpc_api::{
+    data_types::{PaychChannelInfo, RPCState, VoucherCreateResult},
+    paych_api::*,
+};
+use forest_shim::{address::Address, econ::TokenAmount, executor::Receipt, state_tree::ActorState};
+use fvm_ipld_blockstore::Blockstore;
+use fvm_ipld_encoding::{Cbor, RawBytes};
+use fvm_shared::{message::Message, MethodNum, METHOD_SEND};
+use jsonrpc_v2::{Data, Error as JsonRpcError, Params};
+use num::BigInt;
+use num_traits::Zero;
+
+use crate::{
+    mpool_api::push_message,
+    paych_store::{
+        decode_voucher, encode_voucher, PaychChannel, PaychStore, PendingChannel, VoucherInfo,
+    },
+    state_api::builtin_actor_codes,
+};
+
+/// Number of epochs after which the creation of a channel is considered final.
+const MESSAGE_CONFIDENCE: i64 = 5;
+
+fn parse_amount(amount: &str) -> anyhow::Result<TokenAmount> {
+    Ok(TokenAmount::from_atto(
+        BigInt::from_str(amount).with_context(|| format!("Invalid amount {amount}"))?,
+    ))
+}
+
+/// Signs the message with the wallet of the node and adds it to the message
+/// pool.
+async fn push<DB, B>(data: &Data<RPCState<DB, B>>, message: Message) -> anyhow::Result<Cid>
+where
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+{
+    let smsg = push_message(data, message.into(), None)
+        .await
+        .map_err(|e| anyhow::anyhow!("Failed to push message: {e:?}"))?;
+    Ok(smsg.cid()?)
+}
+
+/// Loads the payment channel actor of the address at the chain head.
+fn load_channel<DB, B>(
+    data: &RPCState<DB, B>,
+    channel: &Address,
+) -> anyhow::Result<(ActorState, paych::State)>
+where
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+{
+    let head = data.state_manager.chain_store().heaviest_tipset();
+    let actor = data
+        .state_manager
+        .get_actor(channel, *head.parent_state())?
+        .with_context(|| format!("Payment channel {channel} not found"))?;
+    let state = paych::State::load(data.state_manager.blockstore(), &actor)?;
+    Ok((actor, state))
+}
+
+/// Returns the states of the lanes of the channel, including the vouchers of
+/// the store not redeemed on chain yet.
+fn lane_states<BS: Blockstore>(
+    store: &BS,
+    state: &paych::State,
+    tracked: Option<&PaychChannel>,
+) -> anyhow::Result<BTreeMap<u64, paych::LaneState>> {
+    let mut lanes: BTreeMap<_, _> = state.lane_states(store)?.into_iter().collect();
+    for info in tracked.iter().flat_map(|channel| &channel.vouchers) {
+        let lane = lanes.entry(info.voucher.lane).or_default();
+        if info.voucher.nonce > lane.nonce {
+            lane.nonce = info.voucher.nonce;
+            lane.redeemed = info.voucher.amount.clone();
+        }
+    }
+    Ok(lanes)
+}
+
+/// Sum of the amounts of the lanes, with the given amount for the given lane.
+fn total_amount(
+    lanes: &BTreeMap<u64, paych::LaneState>,
+    lane: u64,
+    amount: &TokenAmount,
+) -> TokenAmount {
+    lanes
+        .iter()
+        .filter(|(id, _)| **id != lane)
+        .fold(amount.clone(), |total, (_, state)| total + &state.redeemed)
+}
+
+/// Checks that the voucher is signed by the owner of the channel and can be
+/// redeemed on top of the previous vouchers of its lane. Returns the amount of
+/// the lane before the voucher.
+async fn check_voucher<DB, B>(
+    data: &RPCState<DB, B>,
+    channel: &Address,
+    voucher: &paych::SignedVoucher,
+    tracked: Option<&PaychChannel>,
+) -> anyhow::Result<TokenAmount>
+where
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+{
+    anyhow::ensure!(
+        voucher.channel_addr == *channel,
+        "Voucher is for channel {}, not {channel}",
+        voucher.channel_addr
+    );
+    anyhow::ensure!(voucher.merges.is_empty(), "Lane merges are not supported");
+
+    let (actor, state) = load_channel(data, channel)?;
+    let signature = voucher
+        .signature
+        .as_ref()
+        .context("Voucher is not signed")?;
+    let head = data.state_manager.chain_store().heaviest_tipset();
+    let from = data
+        .state_manager
+        .resolve_to_key_addr(&state.from, &head)
+        .await?;
+    signature
+        .verify(&voucher.signing_bytes()?, &from.into())
+        .map_err(|e| anyhow::anyhow!("Invalid voucher signature: {e}"))?;
+
+    let lanes = lane_states(data.state_manager.blockstore(), &state, tracked)?;
+    let lane = lanes.get(&voucher.lane).cloned().unwrap_or_default();
+    anyhow::ensure!(
+        voucher.nonce > lane.nonce,
+        "Voucher nonce {} is not above the nonce {} of lane {}",
+        voucher.nonce,
+        lane.nonce,
+        voucher.lane
+    );
+    anyhow::ensure!(
+        voucher.amount > lane.redeemed,
+        "Voucher amount {} is not above the amount {} of lane {}",
+        voucher.amount,
+        lane.redeemed,
+        voucher.lane
+    );
+    let balance = TokenAmount::from(&actor.balance);
+    anyhow::ensure!(
+        total_amount(&lanes, voucher.lane, &voucher.amount) <= balance,
+        "Vouchers of the channel exceed its balance of {balance}"
+    );
+    Ok(lane.redeemed)
+}
+
+/// Returns the tracked channel, failing if the node doesn't know it.
+fn tracked_channel<DB: Store>(db: &DB, channel: &Address) -> anyhow::Result<PaychChannel> {
+    PaychStore::new(db)
+        .get(channel)?
+        .with_context(|| format!("Payment channel {channel} is not tracked by the node"))
+}
+
+pub(crate) async fn paych_get<DB, B>(
+    data: Data<RPCState<DB, B>>,
+    Params(params): Params<PaychGetParams>,
+) -> Result<PaychGetResult, JsonRpcError>
+where
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+{
+    let (AddressJson(from), AddressJson(to), amount) = params;
+    let amount = parse_amount(&amount)?;
+    let _locked = data.paych_lock.lock().await;
+    let store = PaychStore::new(data.state_manager.blockstore());
+
+    // The creation of a pending channel may have been executed without
+    // `PaychGetWaitReady` being called
+    if let Some(pending) = store.get_pending_outbound(&from, &to)? {
+        if let Some((tipset, receipt)) = data.state_manager.search_for_message(pending.message)? {
+            let head = data.state_manager.chain_store().heaviest_tipset();
+            if head.epoch() - tipset.epoch() >= MESSAGE_CONFIDENCE {
+                // A failed creation is forgotten, and another channel created
+                track_created_channel(&data, &store, pending.message, &receipt)?;
+            }
+        }
+    }
+
+    if let Some(channel) = store.get_outbound(&from, &to)? {
+        if amount.is_zero() {
+            return Ok(PaychChannelInfo {
+                channel: Some(channel.channel),
+                wait_sentinel: None,
+            });
+        }
+        let cid = push(
+            &data,
+            Message {
+                from: from.into(),
+                to: channel.channel.into(),
+                value: amount.into(),
+                method_num: METHOD_SEND,
+                ..Default::default()
+            },
+        )
+        .await?;
+        return Ok(PaychChannelInfo {
+            channel: Some(channel.channel),
+            wait_sentinel: Some(CidJson(cid)),
+        });
+    }
+
+    if let Some(pending) = store.get_pending_outbound(&from, &to)? {
+        if !amount.is_zero() {
+            return Err(format!(
+                "The channel from {from} to {to} is being created by message {}, wait for it before adding funds",
+                pending.message
+            )
+            .into());
+        }
+        return Ok(PaychChannelInfo {
+            channel: None,
+            wait_sentinel: Some(CidJson(pending.message)),
+        });
+    }
+
+    let head = data.state_manager.chain_store().heaviest_tipset();
+    let code_cid = builtin_actor_codes(&data, &head)?
+        .into_iter()
+        .find_map(|(name, code)| (name == paych::ACTOR_NAME).then_some(code))
+        .ok_or("No payment channel actor in the current network")?;
+    let params = init::ExecParams {
+        code_cid,
+        constructor_params: RawBytes::serialize(paych::ConstructorParams { from, to })?,
+    };
+    let cid = push(
+        &data,
+        Message {
+            from: from.into(),
+            to: init::ADDRESS,
+            value: amount.into(),
+            method_num: init::Method::Exec as MethodNum,
+            params: RawBytes::serialize(params)?,
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    // The address of the channel is only known once the creation message is
+    // executed, see `paych_get_wait_ready`.
+    store.put_pending(&PendingChannel {
+        message: cid,
+        control: from,
+        target: to,
+    })?;
+    Ok(PaychChannelInfo {
+        channel: None,
+        wait_sentinel: Some(CidJson(cid)),
+    })
+}
+
+pub(crate) async fn paych_get_wait_ready<DB, B>(
+    data: Data<RPCState<DB, B>>,
+    Params((CidJson(cid),)): Params<PaychGetWaitReadyParams>,
+) -> Result<PaychGetWaitReadyResult, JsonRpcError>
+where
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+{
+    let (_, receipt) = data
+        .state_manager
+        .wait_for_message(cid, MESSAGE_CONFIDENCE)
+        .await?;
+    let receipt = receipt.ok_or("wait for msg returned empty receipt")?;
+
+    let _locked = data.paych_lock.lock().await;
+    let store = PaychStore::new(data.state_manager.blockstore());
+    let channel = track_created_channel(&data, &store, cid, &receipt)?.ok_or_else(|| {
+        format!(
+            "Message {cid} of the payment channel failed with exit code {}",
+            receipt.exit_code()
+        )
+    })?;
+    tracked_channel(data.state_manager.blockstore(), &channel)?;
+    Ok(AddressJson(channel))
+}
+
+/// Tracks the channel created by the executed message, if it is the creation
+/// of a pending channel, and returns the address of the channel the message
+/// created or funded. Returns `None` if the message failed, forgetting the
+/// pending channel so that another one can be created in its place.
+fn track_created_channel<DB, B>(
+    data: &RPCState<DB, B>,
+    store: &PaychStore<DB>,
+    cid: Cid,
+    receipt: &Receipt,
+) -> anyhow::Result<Option<Address>>
+where
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+{
+    let message = get_chain_message(data.state_manager.blockstore(), &cid)?;
+    let pending = store.get_pending(&cid)?;
+    if !receipt.exit_code().is_success() {
+        if let Some(pending) = &pending {
+            store.remove_pending(pending)?;
+        }
+        return Ok(None);
+    }
+
+    let channel = if message.to() == Address::from(init::ADDRESS) {
+        let ret: init::ExecReturn = fvm_ipld_encoding::from_slice(receipt.return_data().bytes())?;
+        Address::from(ret.robust_address)
+    } else {
+        message.to()
+    };
+    if let Some(pending) = pending {
+        store.put(&PaychChannel {
+            channel,
+            control: pending.control,
+            target: pending.target,
+            outbound: true,
+            vouchers: vec![],
+        })?;
+        store.remove_pending(&pending)?;
+    }
+    Ok(Some(channel))
+}
+
+pub(crate) async fn paych_voucher_create<DB, B>(
+    data: Data<RPCState<DB, B>>,
+    Params(params): Params<PaychVoucherCreateParams>,
+) -> Result<PaychVoucherCreateResult, JsonRpcError>
+where
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+{
+    let (AddressJson(channel), amount, lane) = params;
+    let amount = parse_amount(&amount)?;
+    let _locked = data.paych_lock.lock().await;
+    let store = PaychStore::new(data.state_manager.blockstore());
+    let mut tracked = store
+        .get(&channel)?
+        .filter(|tracked| tracked.outbound)
+        .ok_or_else(|| format!("No outbound payment channel {channel}"))?;
+
+    let (actor, state) = load_channel(&data, &channel)?;
+    let lanes = lane_states(data.state_manager.blockstore(), &state, Some(&tracked))?;
+    let previous = lanes.get(&lane).cloned().unwrap_or_default();
+    let mut voucher = paych::SignedVoucher {
+        channel_addr: channel,
+        time_lock_min: 0,
+        time_lock_max: 0,
+        secret_pre_image: vec![],
+        extra: None,
+        lane,
+        nonce: previous.nonce + 1,
+        amount: previous.redeemed + &amount,
+        min_settle_height: 0,
+        merges: vec![],
+        signature: None,
+    };
+
+    let balance = TokenAmount::from(&actor.balance);
+    let total = total_amount(&lanes, lane, &voucher.amount);
+    if total > balance {
+        return Ok(VoucherCreateResult {
+            voucher: None,
+            shortfall: total - &balance,
+        });
+    }
+
+    let head = data.state_manager.chain_store().heaviest_tipset();
+    let from = data
+        .state_manager
+        .resolve_to_key_addr(&state.from, &head)
+        .await?;
+    voucher.signature = Some(data.signer.sign(&from, &voucher.signing_bytes()?).await?);
+    let encoded = encode_voucher(&voucher)?;
+    tracked.vouchers.push(VoucherInfo {
+        voucher,
+        submitted: false,
+    });
+    store.put(&tracked)?;
+
+    Ok(VoucherCreateResult {
+        voucher: Some(encoded),
+        shortfall: TokenAmount::default(),
+    })
+}
+
+pub(crate) async fn paych_voucher_check_valid<DB, B>(
+    data: Data<RPCState<DB, B>>,
+    Params(params): Params<PaychVoucherCheckValidParams>,
+) -> Result<PaychVoucherCheckValidResult, JsonRpcError>
+where
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+{
+    let (AddressJson(channel), voucher) = params;
+    let voucher = decode_voucher(&voucher)?;
+    let tracked = PaychStore::new(data.state_manager.blockstore()).get(&channel)?;
+    check_voucher(&data, &channel, &voucher, tracked.as_ref()).await?;
+    Ok(())
+}
+
+pub(crate) async fn paych_voucher_add<DB, B>(
+    data: Data<RPCState<DB, B>>,
+    Params(params): Params<PaychVoucherAddParams>,
+) -> Result<PaychVoucherAddResult, JsonRpcError>
+where
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+{
+    let (AddressJson(channel), voucher, min_delta) = params;
+    let voucher = decode_voucher(&voucher)?;
+    let min_delta = parse_amount(&min_delta)?;
+    let _locked = data.paych_lock.lock().await;
+    let store = PaychStore::new(data.state_manager.blockstore());
+
+    let mut tracked = match store.get(&channel)? {
+        Some(tracked) => tracked,
+        None => {
+            let (_, state) = load_channel(&data, &channel)?;
+            PaychChannel {
+                channel,
+                control: state.to,
+                target: state.from,
+                outbound: false,
+                vouchers: vec![],
+            }
+        }
+    };
+    if tracked.vouchers.iter().any(|info| info.voucher == voucher) {
+        return Ok("0".into());
+    }
+
+    let previous = check_voucher(&data, &channel, &voucher, Some(&tracked)).await?;
+    let delta = voucher.amount.clone() - &previous;
+    if delta < min_delta {
+        return Err(format!("Voucher adds {delta} to the channel, less than {min_delta}").into());
+    }
+    tracked.vouchers.push(VoucherInfo {
+        voucher,
+        submitted: false,
+    });
+    store.put(&tracked)?;
+    Ok(delta.atto().to_string())
+}
+
+pub(crate) async fn paych_voucher_submit<DB, B>(
+    data: Data<RPCState<DB, B>>,
+    Params(params): Params<PaychVoucherSubmitParams>,
+) -> Result<PaychVoucherSubmitResult, JsonRpcError>
+where
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+{
+    let (AddressJson(channel), voucher, secret) = params;
+    let voucher = decode_voucher(&voucher)?;
+    let _locked = data.paych_lock.lock().await;
+    let store = PaychStore::new(data.state_manager.blockstore());
+    let mut tracked = tracked_channel(data.state_manager.blockstore(), &channel)?;
+
+    let params = paych::UpdateChannelStateParams {
+        sv: voucher.clone(),
+        secret,
+    };
+    let cid = push(
+        &data,
+        Message {
+            from: tracked.control.into(),
+            to: channel.into(),
+            method_num: paych::Method::UpdateChannelState as MethodNum,
+            params: RawBytes::serialize(params)?,
+            ..Default::default()
+        },
+    )
+    .await?;
+
+    match tracked
+        .vouchers
+        .iter_mut()
+        .find(|info| info.voucher == voucher)
+    {
+        Some(info) => info.submitted = true,
+        None => tracked.vouchers.push(VoucherInfo {
+            voucher,
+            submitted: true,
+        }),
+    }
+    store.put(&tracked)?;
+    Ok(CidJson(cid))
+}
+
+pub(crate) async fn paych_settle<DB, B>(
+    data: Data<RPCState<DB, B>>,
+    Params(params): Params<PaychSettleParams>,
+) -> Result<PaychSettleResult, JsonRpcError>
+where
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+{
+    let (AddressJson(channel),) = params;
+    let tracked = tracked_channel(data.state_manager.blockstore(), &channel)?;
+    let cid = push(
+        &data,
+        Message {
+            from: tracked.control.into(),
+            to: channel.into(),
+            method_num: paych::Method::Settle as MethodNum,
+            ..Default::default()
+        },
+    )
+    .await?;
+    Ok(CidJson(cid))
+}
+
+pub(crate) async fn paych_collect<DB, B>(
+    data: Data<RPCState<DB, B>>,
+    Params(params): Params<PaychCollectParams>,
+) -> Result<PaychCollectResult, JsonRpcError>
+where
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+{
+    let (AddressJson(channel),) = params;
+    let tracked = tracked_channel(data.state_manager.blockstore(), &channel)?;
+    let cid = push(
+        &data,
+        Message {
+            from: tracked.control.into(),
+            to: channel.into(),
+            method_num: paych::Method::Collect as MethodNum,
+            ..Default::default()
+        },
+    )
+    .await?;
+    Ok(CidJson(cid))
+}
+
+#[cfg(test)]
+mod tests {
+    use cid::multihash::Code::Blake2b256;
+    use forest_utils::db::BlockstoreExt;
+
+    use super::*;
+    use crate::sync_api::tests::state_setup;
+
+    #[tokio::test]
+    async fn get_pending_channel() {
+        let (state, _) = state_setup();
+        let message = state
+            .state_manager
+            .blockstore()
+            .put_obj(&Message::default(), Blake2b256)
+            .unwrap();
+        let pending = PendingChannel {
+            message,
+            control: Address::new_id(100),
+            target: Address::new_id(200),
+        };
+        PaychStore::new(state.state_manager.blockstore())
+            .put_pending(&pending)
+            .unwrap();
+        let params = |amount: &str| {
+            Params((
+                AddressJson(pending.control),
+                AddressJson(pending.target),
+                amount.to_owned(),
+            ))
+        };
+
+        let info = paych_get(Data(state.clone()), params("0")).await.unwrap();
+        assert_eq!(info.channel, None);
+        assert_eq!(info.wait_sentinel.map(|cid| cid.0), Some(pending.message));
+
+        // Funds are only added once the channel is created
+        assert!(paych_get(Data(state), params("10")).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn get_wait_ready_unknown_message() {
+        let (state, _) = state_setup();
+        let params = Params((CidJson(Cid::default()),));
+        assert!(paych_get_wait_ready(Data(state), params).await.is_err());
+    }
+}
@@ -0,0 +1,257 @@
+// Copyright 2019-2023 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Payment channels of the wallet of the node, along with the vouchers sent or
+//! received through them, persisted in the node database.
+
+use base64::{prelude::BASE64_URL_SAFE_NO_PAD, Engine};
+use cid::Cid;
+use forest_actor_interface::paych::SignedVoucher;
+use forest_db::Store;
+use forest_shim::address::Address;
+use fvm_ipld_encoding::tuple::{Deserialize_tuple, Serialize_tuple};
+
+const CHANNEL_KEY_PREFIX: &str = "/paych/channel/";
+const OUTBOUND_KEY_PREFIX: &str = "/paych/outbound/";
+const PENDING_KEY_PREFIX: &str = "/paych/pending/";
+const PENDING_OUTBOUND_KEY_PREFIX: &str = "/paych/pending-outbound/";
+
+/// Payment channel tracked by the node.
+#[derive(Serialize_tuple, Deserialize_tuple, Clone, Debug, PartialEq, Eq)]
+pub(crate) struct PaychChannel {
+    pub channel: Address,
+    /// Wallet address of the node in the channel: the owner of outbound
+    /// channels, the recipient of inbound ones.
+    pub control: Address,
+    /// Other party of the channel.
+    pub target: Address,
+    pub outbound: bool,
+    pub vouchers: Vec<VoucherInfo>,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple, Clone, Debug, PartialEq, Eq)]
+pub(crate) struct VoucherInfo {
+    pub voucher: SignedVoucher,
+    /// Whether the voucher has been submitted to the channel.
+    pub submitted: bool,
+}
+
+/// Outbound channel of which the creation message is not executed yet.
+#[derive(Serialize_tuple, Deserialize_tuple, Clone, Debug, PartialEq, Eq)]
+pub(crate) struct PendingChannel {
+    /// Message creating the channel.
+    pub message: Cid,
+    pub control: Address,
+    pub target: Address,
+}
+
+pub(crate) struct PaychStore<'a, DB> {
+    db: &'a DB,
+}
+
+impl<'a, DB: Store> PaychStore<'a, DB> {
+    pub fn new(db: &'a DB) -> Self {
+        Self { db }
+    }
+
+    pub fn get(&self, channel: &Address) -> anyhow::Result<Option<PaychChannel>> {
+        self.db
+            .read(format!("{CHANNEL_KEY_PREFIX}{channel}"))?
+            .map(|bytes| Ok(fvm_ipld_encoding::from_slice(&bytes)?))
+            .transpose()
+    }
+
+    /// Returns the outbound channel of the wallet address to the target, if
+    /// any.
+    pub fn get_outbound(
+        &self,
+        control: &Address,
+        target: &Address,
+    ) -> anyhow::Result<Option<PaychChannel>> {
+        match self
+            .db
+            .read(format!("{OUTBOUND_KEY_PREFIX}{control}/{target}"))?
+        {
+            Some(channel) => self.get(&Address::from_bytes(&channel)?),
+            None => Ok(None),
+        }
+    }
+
+    pub fn put(&self, channel: &PaychChannel) -> anyhow::Result<()> {
+        self.db.write(
+            format!("{CHANNEL_KEY_PREFIX}{}", channel.channel),
+            fvm_ipld_encoding::to_vec(channel)?,
+        )?;
+        if channel.outbound {
+            self.db.write(
+                format!(
+                    "{OUTBOUND_KEY_PREFIX}{}/{}",
+                    channel.control, channel.target
+                ),
+                channel.channel.to_bytes(),
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Returns the pending channel created by the message, if any.
+    pub fn get_pending(&self, message: &Cid) -> anyhow::Result<Option<PendingChannel>> {
+        self.db
+            .read(format!("{PENDING_KEY_PREFIX}{message}"))?
+            .map(|bytes| Ok(fvm_ipld_encoding::from_slice(&bytes)?))
+            .transpose()
+    }
+
+    /// Returns the pending outbound channel of the wallet address to the
+    /// target, if any.
+    pub fn get_pending_outbound(
+        &self,
+        control: &Address,
+        target: &Address,
+    ) -> anyhow::Result<Option<PendingChannel>> {
+        match self
+            .db
+            .read(format!("{PENDING_OUTBOUND_KEY_PREFIX}{control}/{target}"))?
+        {
+            Some(message) => self.get_pending(&Cid::try_from(message)?),
+            None => Ok(None),
+        }
+    }
+
+    pub fn put_pending(&self, pending: &PendingChannel) -> anyhow::Result<()> {
+        self.db.write(
+            format!("{PENDING_KEY_PREFIX}{}", pending.message),
+            fvm_ipld_encoding::to_vec(pending)?,
+        )?;
+        self.db.write(
+            format!(
+                "{PENDING_OUTBOUND_KEY_PREFIX}{}/{}",
+                pending.control, pending.target
+            ),
+            pending.message.to_bytes(),
+        )?;
+        Ok(())
+    }
+
+    pub fn remove_pending(&self, pending: &PendingChannel) -> anyhow::Result<()> {
+        self.db.delete(format!(
+            "{PENDING_OUTBOUND_KEY_PREFIX}{}/{}",
+            pending.control, pending.target
+        ))?;
+        self.db
+            .delete(format!("{PENDING_KEY_PREFIX}{}", pending.message))?;
+        Ok(())
+    }
+}
+
+/// Encodes a voucher as base64url encoded `CBOR`, the encoding of the Lotus
+/// command line.
+pub(crate) fn encode_voucher(voucher: &SignedVoucher) -> anyhow::Result<String> {
+    Ok(BASE64_URL_SAFE_NO_PAD.encode(fvm_ipld_encoding::to_vec(voucher)?))
+}
+
+pub(crate) fn decode_voucher(encoded: &str) -> anyhow::Result<SignedVoucher> {
+    Ok(fvm_ipld_encoding::from_slice(
+        &BASE64_URL_SAFE_NO_PAD.decode(encoded)?,
+    )?)
+}
+
+#[cfg(test)]
+mod tests {
+    use forest_db::MemoryDB;
+    use forest_shim::econ::TokenAmount;
+
+    use super::*;
+
+    fn voucher(channel: Address, nonce: u64) -> SignedVoucher {
+        SignedVoucher {
+            channel_addr: channel,
+            time_lock_min: 0,
+            time_lock_max: 0,
+            secret_pre_image: vec![],
+            extra: None,
+            lane: 0,
+            nonce,
+            amount: TokenAmount::from_atto(100 * nonce),
+            min_settle_height: 0,
+            merges: vec![],
+            signature: None,
+        }
+    }
+
+    #[test]
+    fn store_channels() {
+        let db = MemoryDB::default();
+        let store = PaychStore::new(&db);
+        let channel = PaychChannel {
+            channel: Address::new_id(1000),
+            control: Address::new_id(100),
+            target: Address::new_id(200),
+            outbound: true,
+            vouchers: vec![VoucherInfo {
+                voucher: voucher(Address::new_id(1000), 1),
+                submitted: false,
+            }],
+        };
+        store.put(&channel).unwrap();
+
+        assert_eq!(store.get(&channel.channel).unwrap(), Some(channel.clone()));
+        assert_eq!(
+            store
+                .get_outbound(&channel.control, &channel.target)
+                .unwrap(),
+            Some(channel.clone())
+        );
+        assert_eq!(
+            store
+                .get_outbound(&channel.target, &channel.control)
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn store_pending_channels() {
+        let db = MemoryDB::default();
+        let store = PaychStore::new(&db);
+        let pending = PendingChannel {
+            message: Cid::default(),
+            control: Address::new_id(100),
+            target: Address::new_id(200),
+        };
+        store.put_pending(&pending).unwrap();
+
+        assert_eq!(
+            store.get_pending(&pending.message).unwrap(),
+            Some(pending.clone())
+        );
+        assert_eq!(
+            store
+                .get_pending_outbound(&pending.control, &pending.target)
+                .unwrap(),
+            Some(pending.clone())
+        );
+        assert_eq!(
+            store
+                .get_pending_outbound(&pending.target, &pending.control)
+                .unwrap(),
+            None
+        );
+
+        store.remove_pending(&pending).unwrap();
+        assert_eq!(store.get_pending(&pending.message).unwrap(), None);
+        assert_eq!(
+            store
+                .get_pending_outbound(&pending.control, &pending.target)
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn voucher_encoding_roundtrip() {
+        let voucher = voucher(Address::new_id(1000), 3);
+        let encoded = encode_voucher(&voucher).unwrap();
+        assert_eq!(decode_voucher(&encoded).unwrap(), voucher);
+    }
+}
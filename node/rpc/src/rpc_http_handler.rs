@@ -8,22 +8,41 @@ use fvm_ipld_blockstore::Blockstore;
 use http::{HeaderMap, StatusCode};
 use jsonrpc_v2::RequestObject as JsonRpcRequestObject;
 
-use crate::rpc_util::{call_rpc_str, check_permissions, get_auth_header, is_streaming_method};
+use crate::{
+    rpc_transport::RpcTransport,
+    rpc_util::{
+        call_rpc_str, check_permissions, get_auth_header, is_streaming_method, SlowCallThreshold,
+    },
+};
 
 pub async fn rpc_http_handler<DB, B>(
     headers: HeaderMap,
+    axum::Extension(transport): axum::Extension<RpcTransport>,
+    axum::Extension(SlowCallThreshold(slow_call_threshold)): axum::Extension<SlowCallThreshold>,
     axum::extract::State(rpc_server): axum::extract::State<JsonRpcServerState>,
-    axum::Json(rpc_call): axum::Json<JsonRpcRequestObject>,
+    request_text: String,
 ) -> impl IntoResponse
 where
     DB: Blockstore + Send + Sync + 'static,
     B: Beacon,
 {
     let response_headers = [("content-type", "application/json-rpc;charset=utf-8")];
+    // Kept as text to be logged as received if the call turns out to be slow
+    let rpc_call: JsonRpcRequestObject = match serde_json::from_str(&request_text) {
+        Ok(rpc_call) => rpc_call,
+        Err(e) => {
+            return (
+                StatusCode::BAD_REQUEST,
+                response_headers,
+                format!("Failed to deserialize the request: {e}"),
+            )
+        }
+    };
     if let Err((code, msg)) = check_permissions::<DB, B>(
         rpc_server.clone(),
         rpc_call.method_ref(),
         get_auth_header(headers),
+        transport,
     )
     .await
     {
@@ -38,7 +57,14 @@ where
         );
     }
 
-    match call_rpc_str(rpc_server.clone(), rpc_call).await {
+    match call_rpc_str(
+        rpc_server.clone(),
+        rpc_call,
+        &request_text,
+        slow_call_threshold,
+    )
+    .await
+    {
         Ok(result) => (StatusCode::OK, response_headers, result),
         Err(err) => (
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -0,0 +1,185 @@
+// Copyright 2019-2023 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+use std::{
+    fs::File, io::BufReader, net::TcpListener, os::unix::net::UnixListener, path::Path, sync::Arc,
+    time::Duration,
+};
+
+use anyhow::Context;
+use futures::{Stream, StreamExt};
+use log::warn;
+use tokio_rustls::{
+    rustls::{Certificate, PrivateKey, ServerConfig},
+    server::TlsStream,
+    TlsAcceptor,
+};
+use tokio_stream::wrappers::{TcpListenerStream, UnixListenerStream};
+
+/// Maximum number of TLS handshakes that are processed concurrently.
+const MAX_CONCURRENT_TLS_HANDSHAKES: usize = 64;
+
+/// Time a client is given to complete the TLS handshake.
+const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Transport over which an RPC request has been received. Requests coming
+/// through the Unix socket are trusted: access to the socket is controlled by
+/// file-system permissions instead of JWT tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RpcTransport {
+    Http,
+    Tls,
+    Unix,
+}
+
+impl RpcTransport {
+    pub fn is_local(&self) -> bool {
+        matches!(self, RpcTransport::Unix)
+    }
+}
+
+/// TLS listener along with its `rustls` configuration.
+pub struct TlsEndpoint {
+    pub listener: TcpListener,
+    pub config: Arc<ServerConfig>,
+}
+
+/// Set of listeners the RPC server accepts connections on. The plain HTTP
+/// endpoint is always present, the Unix socket and TLS endpoints are
+/// optional and served concurrently with it.
+pub struct RpcEndpoints {
+    pub http: TcpListener,
+    pub unix: Option<UnixListener>,
+    pub tls: Option<TlsEndpoint>,
+}
+
+impl RpcEndpoints {
+    pub fn new(http: TcpListener) -> Self {
+        Self {
+            http,
+            unix: None,
+            tls: None,
+        }
+    }
+}
+
+/// Binds a Unix socket at the given path, removing a stale socket left behind
+/// by a previous run. The socket is only accessible by the current user: it
+/// is bound in a private directory and moved into place once its permissions
+/// are restricted.
+pub fn bind_unix_socket(path: &Path) -> anyhow::Result<UnixListener> {
+    use std::os::unix::fs::{DirBuilderExt, FileTypeExt, PermissionsExt};
+
+    match std::fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_socket() => std::fs::remove_file(path)
+            .with_context(|| format!("could not remove stale socket {}", path.display()))?,
+        Ok(_) => anyhow::bail!("{} exists and is not a socket", path.display()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e.into()),
+    }
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    std::fs::create_dir_all(parent)?;
+
+    let private_dir = parent.join(format!(".rpc-socket-{}", std::process::id()));
+    std::fs::DirBuilder::new()
+        .mode(0o700)
+        .create(&private_dir)
+        .with_context(|| format!("could not create {}", private_dir.display()))?;
+    let bind = || {
+        let private_path = private_dir.join("rpc.sock");
+        let listener = UnixListener::bind(&private_path)
+            .with_context(|| format!("could not bind to rpc socket {}", path.display()))?;
+        std::fs::set_permissions(&private_path, std::fs::Permissions::from_mode(0o600))?;
+        std::fs::rename(&private_path, path)?;
+        anyhow::Ok(listener)
+    };
+    let listener = bind();
+    if let Err(e) = std::fs::remove_dir_all(&private_dir) {
+        warn!("Failed to remove {}: {e}", private_dir.display());
+    }
+    listener
+}
+
+/// Loads a PEM encoded certificate chain and private key into a `rustls`
+/// server configuration.
+pub fn load_tls_config(cert_path: &Path, key_path: &Path) -> anyhow::Result<Arc<ServerConfig>> {
+    let certs = rustls_pemfile::certs(&mut BufReader::new(
+        File::open(cert_path)
+            .with_context(|| format!("could not open certificate {}", cert_path.display()))?,
+    ))?
+    .into_iter()
+    .map(Certificate)
+    .collect::<Vec<_>>();
+    anyhow::ensure!(
+        !certs.is_empty(),
+        "no certificate found in {}",
+        cert_path.display()
+    );
+
+    let key = read_private_key(key_path)?;
+
+    let config = ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)?;
+    Ok(Arc::new(config))
+}
+
+fn read_private_key(key_path: &Path) -> anyhow::Result<PrivateKey> {
+    let mut reader = BufReader::new(
+        File::open(key_path)
+            .with_context(|| format!("could not open private key {}", key_path.display()))?,
+    );
+    while let Some(item) = rustls_pemfile::read_one(&mut reader)? {
+        match item {
+            rustls_pemfile::Item::PKCS8Key(key)
+            | rustls_pemfile::Item::RSAKey(key)
+            | rustls_pemfile::Item::ECKey(key) => return Ok(PrivateKey(key)),
+            _ => continue,
+        }
+    }
+    anyhow::bail!("no private key found in {}", key_path.display())
+}
+
+/// Stream of connections accepted on a Unix socket.
+pub(crate) fn unix_incoming(listener: UnixListener) -> anyhow::Result<UnixListenerStream> {
+    listener.set_nonblocking(true)?;
+    Ok(UnixListenerStream::new(tokio::net::UnixListener::from_std(
+        listener,
+    )?))
+}
+
+/// Stream of TLS connections. Handshakes run concurrently and time out so that
+/// slow clients can't stall the accept loop, and failed handshakes are dropped
+/// instead of shutting the server down.
+pub(crate) fn tls_incoming(
+    endpoint: TlsEndpoint,
+) -> anyhow::Result<impl Stream<Item = std::io::Result<TlsStream<tokio::net::TcpStream>>>> {
+    endpoint.listener.set_nonblocking(true)?;
+    let acceptor = TlsAcceptor::from(endpoint.config);
+    let listener = tokio::net::TcpListener::from_std(endpoint.listener)?;
+    Ok(TcpListenerStream::new(listener)
+        .map(move |stream| {
+            let acceptor = acceptor.clone();
+            async move {
+                tokio::time::timeout(TLS_HANDSHAKE_TIMEOUT, acceptor.accept(stream?))
+                    .await
+                    .map_err(|_| {
+                        std::io::Error::new(std::io::ErrorKind::TimedOut, "TLS handshake timed out")
+                    })?
+            }
+        })
+        .buffer_unordered(MAX_CONCURRENT_TLS_HANDSHAKES)
+        .filter_map(|res| async move {
+            match res {
+                Ok(stream) => Some(Ok(stream)),
+                Err(e) => {
+                    warn!("Failed to accept RPC TLS connection: {e}");
+                    None
+                }
+            }
+        }))
+}
@@ -1,12 +1,23 @@
 // Copyright 2019-2023 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
+use std::time::Duration;
+
+use forest_auth::ADMIN;
 use forest_beacon::Beacon;
-use forest_rpc_api::{auth_api::*, check_access, data_types::JsonRpcServerState, ACCESS_MAP};
+use forest_rpc_api::{
+    auth_api::*, check_access, data_types::JsonRpcServerState, Access, ACCESS_MAP,
+};
 use fvm_ipld_blockstore::Blockstore;
 use http::{HeaderMap, HeaderValue, StatusCode};
-use log::{debug, error};
+use log::{debug, error, warn};
 use serde::de::DeserializeOwned;
+use tracing::{info_span, Instrument};
+
+use crate::{
+    metrics::{InFlightGuard, RPC_METHOD_CALLS_TOTAL, RPC_METHOD_ERRORS_TOTAL, RPC_METHOD_LATENCY},
+    rpc_transport::RpcTransport,
+};
 
 pub fn get_error_obj(code: i64, message: String) -> jsonrpc_v2::Error {
     debug!(
@@ -45,6 +56,7 @@ pub async fn check_permissions<DB, B>(
     rpc_server: JsonRpcServerState,
     method: &str,
     authorization_header: Option<HeaderValue>,
+    transport: RpcTransport,
 ) -> Result<(), (StatusCode, String)>
 where
     DB: Blockstore,
@@ -70,6 +82,9 @@ where
 
             claims
         }
+        // Access to the Unix socket is restricted by file-system permissions, so
+        // local callers without a token are granted admin access
+        None if transport.is_local() => ADMIN.clone(),
         // If no token is passed, assume read behavior
         None => vec!["read".to_owned()],
     };
@@ -90,15 +105,66 @@ pub fn get_auth_header(headers: HeaderMap) -> Option<HeaderValue> {
     headers.get("Authorization").cloned()
 }
 
-// Calls an RPC method and returns the full response as a string.
+/// Duration above which RPC calls are logged along with their parameters.
+/// Slow-call logging is disabled when unset.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SlowCallThreshold(pub Option<Duration>);
+
+/// Label used in metrics for methods that are not part of the API, so that
+/// arbitrary method names can't inflate metrics cardinality.
+const UNKNOWN_METHOD: &str = "unknown";
+
+// Calls an RPC method and returns the full response as a string. Records
+// per-method call, error and latency metrics, and logs calls slower than
+// `slow_call_threshold` along with `request_text`, the request as received.
 pub async fn call_rpc_str(
     rpc_server: JsonRpcServerState,
     rpc_request: jsonrpc_v2::RequestObject,
+    request_text: &str,
+    slow_call_threshold: Option<Duration>,
 ) -> anyhow::Result<String> {
-    let rpc_subscription_response = rpc_server.handle(rpc_request).await;
+    let (method, access) = ACCESS_MAP
+        .get_key_value(rpc_request.method_ref())
+        .map(|(method, access)| (*method, Some(access)))
+        .unwrap_or((UNKNOWN_METHOD, None));
+    let span = info_span!("rpc_call", method, id = ?rpc_request.id_ref());
+
+    RPC_METHOD_CALLS_TOTAL.with_label_values(&[method]).inc();
+    let _in_flight = InFlightGuard::new(method);
+    let timer = RPC_METHOD_LATENCY
+        .with_label_values(&[method])
+        .start_timer();
+
+    let rpc_subscription_response = rpc_server.handle(rpc_request).instrument(span).await;
+
+    let elapsed = Duration::from_secs_f64(timer.stop_and_record());
+    if is_error_response(&rpc_subscription_response) {
+        RPC_METHOD_ERRORS_TOTAL.with_label_values(&[method]).inc();
+    }
+    if matches!(slow_call_threshold, Some(threshold) if elapsed >= threshold) {
+        // Parameters of privileged methods may contain private keys, never log them
+        let request = match access {
+            Some(Access::Read | Access::Write) => request_text,
+            _ => "<redacted>",
+        };
+        warn!("Slow RPC call {method} took {elapsed:?}, request: {request}");
+    }
+
     Ok(serde_json::to_string(&rpc_subscription_response)?)
 }
 
+fn is_error_response(response: &jsonrpc_v2::ResponseObjects) -> bool {
+    match response {
+        jsonrpc_v2::ResponseObjects::One(response) => {
+            matches!(response, jsonrpc_v2::ResponseObject::Error { .. })
+        }
+        jsonrpc_v2::ResponseObjects::Many(responses) => responses
+            .iter()
+            .any(|response| matches!(response, jsonrpc_v2::ResponseObject::Error { .. })),
+        jsonrpc_v2::ResponseObjects::Empty => false,
+    }
+}
+
 // Returns both the RPC response string and the result value in a tuple.
 pub async fn call_rpc<T>(
     rpc_server: JsonRpcServerState,
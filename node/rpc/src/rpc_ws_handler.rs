@@ -19,11 +19,19 @@ use http::{HeaderMap, HeaderValue};
 use log::{debug, error, info, warn};
 use tokio::sync::RwLock;
 
-use crate::rpc_util::{call_rpc_str, check_permissions, get_auth_header, get_error_str};
+use crate::{
+    rpc_transport::RpcTransport,
+    rpc_util::{
+        call_rpc_str, check_permissions, get_auth_header, get_error_str, SlowCallThreshold,
+    },
+};
 
 async fn rpc_ws_task<DB, B>(
     authorization_header: Option<HeaderValue>,
+    transport: RpcTransport,
+    slow_call_threshold: SlowCallThreshold,
     rpc_call: jsonrpc_v2::RequestObject,
+    request_text: String,
     rpc_server: JsonRpcServerState,
     _is_socket_active: Arc<AtomicCell<bool>>,
     ws_sender: Arc<RwLock<SplitSink<WebSocket, Message>>>,
@@ -35,12 +43,23 @@ where
     let call_method = rpc_call.method_ref();
     let _call_id = rpc_call.id_ref();
 
-    check_permissions::<DB, B>(rpc_server.clone(), call_method, authorization_header)
-        .await
-        .map_err(|(_, e)| anyhow::Error::msg(e))?;
+    check_permissions::<DB, B>(
+        rpc_server.clone(),
+        call_method,
+        authorization_header,
+        transport,
+    )
+    .await
+    .map_err(|(_, e)| anyhow::Error::msg(e))?;
 
-    info!("RPC WS called method: {}", call_method);
-    let response = call_rpc_str(rpc_server.clone(), rpc_call).await?;
+    debug!("RPC WS called method: {}", call_method);
+    let response = call_rpc_str(
+        rpc_server.clone(),
+        rpc_call,
+        &request_text,
+        slow_call_threshold.0,
+    )
+    .await?;
     ws_sender
         .write()
         .await
@@ -52,6 +71,8 @@ where
 
 pub async fn rpc_ws_handler<DB, B>(
     headers: HeaderMap,
+    axum::Extension(transport): axum::Extension<RpcTransport>,
+    axum::Extension(slow_call_threshold): axum::Extension<SlowCallThreshold>,
     axum::extract::State(rpc_server): axum::extract::State<JsonRpcServerState>,
     ws: WebSocketUpgrade,
 ) -> impl IntoResponse
@@ -61,13 +82,22 @@ where
 {
     let authorization_header = get_auth_header(headers);
     ws.on_upgrade(move |socket| async {
-        rpc_ws_handler_inner::<DB, B>(socket, authorization_header, rpc_server).await
+        rpc_ws_handler_inner::<DB, B>(
+            socket,
+            authorization_header,
+            transport,
+            slow_call_threshold,
+            rpc_server,
+        )
+        .await
     })
 }
 
 async fn rpc_ws_handler_inner<DB, B>(
     socket: WebSocket,
     authorization_header: Option<HeaderValue>,
+    transport: RpcTransport,
+    slow_call_threshold: SlowCallThreshold,
     rpc_server: JsonRpcServerState,
 ) where
     DB: Blockstore,
@@ -82,7 +112,7 @@ async fn rpc_ws_handler_inner<DB, B>(
         if let Message::Text(request_text) = message {
             debug!("WS RPC Request: {}", request_text);
             if !request_text.is_empty() {
-                info!("RPC Request Received: {:?}", &request_text);
+                debug!("RPC Request Received: {:?}", &request_text);
                 let authorization_header = authorization_header.clone();
                 let task_rpc_server = rpc_server.clone();
                 let task_socket_active = socket_active.clone();
@@ -94,7 +124,10 @@ async fn rpc_ws_handler_inner<DB, B>(
                         tokio::task::spawn(async move {
                             match rpc_ws_task::<DB, B>(
                                 authorization_header,
+                                transport,
+                                slow_call_threshold,
                                 rpc_call,
+                                request_text,
                                 task_rpc_server,
                                 task_socket_active,
                                 task_ws_sender.clone(),
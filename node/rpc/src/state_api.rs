@@ -3,19 +3,30 @@
 #![allow(clippy::unused_async)]
 
 use ahash::{HashMap, HashMapExt};
+use anyhow::Context;
 use cid::Cid;
-use forest_actor_interface::market;
+use forest_actor_interface::{
+    datacap,
+    market::{self, DealState},
+    miner, power, reward, system, verifreg,
+};
 use forest_beacon::Beacon;
-use forest_blocks::tipset_keys_json::TipsetKeysJson;
+use forest_blocks::{tipset_keys_json::TipsetKeysJson, Tipset};
 use forest_db::Store;
 use forest_ipld::json::IpldJson;
-use forest_json::cid::CidJson;
+use forest_json::{address::json::AddressJson, bitfield::json::BitFieldJson, cid::CidJson};
 use forest_rpc_api::{
-    data_types::{MarketDeal, MessageLookup, RPCState},
+    data_types::{
+        Allocation, Claim, Deadline, DealsPage, MarketDeal, MessageLookup, MinerSectors, Partition,
+        RPCState, SectorPreCommitInfo, VestingFund,
+    },
     state_api::*,
 };
-use forest_state_manager::InvocResult;
+use forest_shim::{address::Address, econ::TokenAmount, sector::SectorSize};
+use forest_state_manager::{InvocResult, MarketBalance};
+use fvm_ipld_bitfield::BitField;
 use fvm_ipld_blockstore::Blockstore;
+use fvm_shared::sector::StoragePower;
 use jsonrpc_v2::{Data, Error as JsonRpcError, Params};
 use libipld_core::ipld::Ipld;
 
@@ -112,6 +123,18 @@ pub(crate) async fn state_market_balance<
         .map_err(|e| e.into())
 }
 
+fn load_market<DB, B>(data: &RPCState<DB, B>, tipset: &Tipset) -> anyhow::Result<market::State>
+where
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+{
+    let actor = data
+        .state_manager
+        .get_actor(&market::ADDRESS, *tipset.parent_state())?
+        .context("Market actor address could not be resolved")?;
+    market::State::load(data.state_manager.blockstore(), &actor)
+}
+
 pub(crate) async fn state_market_deals<
     DB: Blockstore + Store + Clone + Send + Sync + 'static,
     B: Beacon,
@@ -121,22 +144,14 @@ pub(crate) async fn state_market_deals<
 ) -> Result<StateMarketDealsResult, JsonRpcError> {
     let (TipsetKeysJson(tsk),) = params;
     let ts = data.chain_store.tipset_from_keys(&tsk)?;
-    let actor = data
-        .state_manager
-        .get_actor(&market::ADDRESS, *ts.parent_state())?
-        .ok_or("Market actor address could not be resolved")?;
-    let market_state = market::State::load(data.state_manager.blockstore(), &actor)?;
+    let market_state = load_market(&data, &ts)?;
 
     let da = market_state.proposals(data.state_manager.blockstore())?;
     let sa = market_state.states(data.state_manager.blockstore())?;
 
     let mut out = HashMap::new();
     da.for_each(|deal_id, d| {
-        let s = sa.get(deal_id)?.unwrap_or(market::DealState {
-            sector_start_epoch: -1,
-            last_updated_epoch: -1,
-            slash_epoch: -1,
-        });
+        let s = sa.get(deal_id)?.unwrap_or_else(DealState::unactivated);
         out.insert(
             deal_id.to_string(),
             MarketDeal {
@@ -149,6 +164,122 @@ pub(crate) async fn state_market_deals<
     Ok(out)
 }
 
+/// Returns the deals matching the filter, a page at a time.
+pub(crate) async fn state_market_deals_page<
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+>(
+    data: Data<RPCState<DB, B>>,
+    Params(params): Params<StateMarketDealsPageParams>,
+) -> Result<StateMarketDealsPageResult, JsonRpcError> {
+    let (filter, TipsetKeysJson(tsk)) = params;
+    if filter.limit == 0 {
+        return Err("Limit has to be positive".into());
+    }
+    let ts = data.chain_store.tipset_from_keys(&tsk)?;
+    // Deal proposals refer to the market participants by ID address.
+    let client = filter
+        .client
+        .map(|client| lookup_id(&data, &client, &ts))
+        .transpose()?;
+    let provider = filter
+        .provider
+        .map(|provider| lookup_id(&data, &provider, &ts))
+        .transpose()?;
+    let store = data.state_manager.blockstore();
+    let market_state = load_market(&data, &ts)?;
+    let proposals = market_state.proposals(store)?;
+    let states = market_state.states(store)?;
+
+    let mut page = DealsPage {
+        deals: vec![],
+        next: None,
+    };
+    let start = filter.after.map_or(0, |after| after + 1);
+    proposals.for_each_while_from(start, |deal_id, proposal| {
+        if client.map_or(false, |client| proposal.client != client)
+            || provider.map_or(false, |provider| proposal.provider != provider)
+            || filter
+                .verified
+                .map_or(false, |verified| proposal.verified_deal != verified)
+        {
+            return Ok(true);
+        }
+        let state = states.get(deal_id)?;
+        if filter
+            .active
+            .map_or(false, |active| state.is_some() != active)
+        {
+            return Ok(true);
+        }
+        // There is another matching deal after a full page.
+        if page.deals.len() as u64 == filter.limit {
+            page.next = page.deals.last().map(|(deal_id, _)| *deal_id);
+            return Ok(false);
+        }
+        page.deals.push((
+            deal_id,
+            MarketDeal {
+                proposal,
+                state: state.unwrap_or_else(DealState::unactivated),
+            },
+        ));
+        Ok(true)
+    })?;
+    Ok(page)
+}
+
+pub(crate) async fn state_market_storage_deal<
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+>(
+    data: Data<RPCState<DB, B>>,
+    Params(params): Params<StateMarketStorageDealParams>,
+) -> Result<StateMarketStorageDealResult, JsonRpcError> {
+    let (deal_id, TipsetKeysJson(tsk)) = params;
+    let ts = data.chain_store.tipset_from_keys(&tsk)?;
+    let store = data.state_manager.blockstore();
+    let market_state = load_market(&data, &ts)?;
+    let proposal = market_state
+        .proposals(store)?
+        .get(deal_id)?
+        .with_context(|| format!("Deal {deal_id} not found"))?;
+    let state = market_state
+        .states(store)?
+        .get(deal_id)?
+        .unwrap_or_else(DealState::unactivated);
+    Ok(MarketDeal { proposal, state })
+}
+
+pub(crate) async fn state_market_participants<
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+>(
+    data: Data<RPCState<DB, B>>,
+    Params(params): Params<StateMarketParticipantsParams>,
+) -> Result<StateMarketParticipantsResult, JsonRpcError> {
+    let (TipsetKeysJson(tsk),) = params;
+    let ts = data.chain_store.tipset_from_keys(&tsk)?;
+    let store = data.state_manager.blockstore();
+    let market_state = load_market(&data, &ts)?;
+    let locked_table = market_state.locked_table(store)?;
+
+    let mut out = HashMap::new();
+    market_state
+        .escrow_table(store)?
+        .for_each(|address, escrow| {
+            out.insert(
+                address.to_string(),
+                MarketBalance {
+                    escrow: escrow.clone(),
+                    locked: locked_table.get(&address)?,
+                },
+            );
+            Ok(())
+        })?;
+    Ok(out)
+}
+
 /// returns the message receipt for the given message
 pub(crate) async fn state_get_receipt<
     DB: Blockstore + Store + Clone + Send + Sync + 'static,
@@ -197,3 +328,571 @@ pub(crate) async fn state_wait_msg<
         return_dec: IpldJson(ipld),
     })
 }
+
+/// Returns the code CIDs of the built-in actors, by actor name. Only the
+/// actors of the network version of the chain head are known.
+pub(crate) async fn state_actor_code_cids<
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+>(
+    data: Data<RPCState<DB, B>>,
+    Params(params): Params<StateActorCodeCidsParams>,
+) -> Result<StateActorCodeCidsResult, JsonRpcError> {
+    let (network_version,) = params;
+    let head = data.state_manager.chain_store().heaviest_tipset();
+    let head_version = data.state_manager.get_network_version(head.epoch());
+    if network_version != head_version {
+        return Err(format!(
+            "Actor code CIDs are only known for the network version of the chain head, {head_version:?}"
+        )
+        .into());
+    }
+
+    Ok(builtin_actor_codes(&data, &head)?
+        .into_iter()
+        .map(|(name, code)| (name, CidJson(code)))
+        .collect())
+}
+
+/// Returns the code CIDs of the built-in actors in the parent state of the
+/// tipset, by actor name.
+pub(crate) fn builtin_actor_codes<DB, B>(
+    data: &RPCState<DB, B>,
+    tipset: &Tipset,
+) -> anyhow::Result<Vec<(String, Cid)>>
+where
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+{
+    let actor = data
+        .state_manager
+        .get_actor(&system::ADDRESS, *tipset.parent_state())?
+        .context("System actor address could not be resolved")?;
+    let system_state = system::State::load(data.state_manager.blockstore(), &actor)?;
+    system_state.builtin_actors(data.state_manager.blockstore())
+}
+
+/// Resolves the address to its ID address in the parent state of the tipset.
+fn lookup_id<DB, B>(
+    data: &RPCState<DB, B>,
+    address: &Address,
+    tipset: &Tipset,
+) -> anyhow::Result<Address>
+where
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+{
+    data.state_manager
+        .lookup_id(address, tipset)?
+        .with_context(|| format!("Actor {address} not found"))
+}
+
+fn load_verifreg<DB, B>(data: &RPCState<DB, B>, tipset: &Tipset) -> anyhow::Result<verifreg::State>
+where
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+{
+    let actor = data
+        .state_manager
+        .get_actor(&verifreg::ADDRESS, *tipset.parent_state())?
+        .context("Verified registry actor address could not be resolved")?;
+    verifreg::State::load(data.state_manager.blockstore(), &actor)
+}
+
+/// Returns the data cap of a verified client, held by the verified registry
+/// up to actors v8 and by the `DataCap` actor since.
+pub(crate) async fn state_verified_client_status<
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+>(
+    data: Data<RPCState<DB, B>>,
+    Params(params): Params<StateVerifiedClientStatusParams>,
+) -> Result<StateVerifiedClientStatusResult, JsonRpcError> {
+    let (AddressJson(address), TipsetKeysJson(tsk)) = params;
+    let ts = data.chain_store.tipset_from_keys(&tsk)?;
+    let id = lookup_id(&data, &address, &ts)?;
+    let store = data.state_manager.blockstore();
+    let verifreg_state = load_verifreg(&data, &ts)?;
+    let data_cap = match verifreg_state {
+        verifreg::State::V8(_) => verifreg_state.verified_client_data_cap(store, &id)?,
+        verifreg::State::V9(_) => {
+            let actor = data
+                .state_manager
+                .get_actor(&datacap::ADDRESS, *ts.parent_state())?
+                .ok_or("DataCap actor address could not be resolved")?;
+            datacap::State::load(store, &actor)?.balance(store, id.id()?)?
+        }
+    };
+    Ok(data_cap.map(|data_cap| data_cap.to_string()))
+}
+
+pub(crate) async fn state_verifier_status<
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+>(
+    data: Data<RPCState<DB, B>>,
+    Params(params): Params<StateVerifierStatusParams>,
+) -> Result<StateVerifierStatusResult, JsonRpcError> {
+    let (AddressJson(address), TipsetKeysJson(tsk)) = params;
+    let ts = data.chain_store.tipset_from_keys(&tsk)?;
+    let id = lookup_id(&data, &address, &ts)?;
+    let data_cap =
+        load_verifreg(&data, &ts)?.verifier_data_cap(data.state_manager.blockstore(), &id)?;
+    Ok(data_cap.map(|data_cap| data_cap.to_string()))
+}
+
+pub(crate) async fn state_verified_registry_root_key<
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+>(
+    data: Data<RPCState<DB, B>>,
+    Params(params): Params<StateVerifiedRegistryRootKeyParams>,
+) -> Result<StateVerifiedRegistryRootKeyResult, JsonRpcError> {
+    let (TipsetKeysJson(tsk),) = params;
+    let ts = data.chain_store.tipset_from_keys(&tsk)?;
+    Ok(AddressJson(load_verifreg(&data, &ts)?.root_key()))
+}
+
+pub(crate) async fn state_get_allocations<
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+>(
+    data: Data<RPCState<DB, B>>,
+    Params(params): Params<StateGetAllocationsParams>,
+) -> Result<StateGetAllocationsResult, JsonRpcError> {
+    let (AddressJson(client), TipsetKeysJson(tsk)) = params;
+    let ts = data.chain_store.tipset_from_keys(&tsk)?;
+    let client = lookup_id(&data, &client, &ts)?.id()?;
+    let allocations =
+        load_verifreg(&data, &ts)?.allocations(data.state_manager.blockstore(), client)?;
+    Ok(allocations
+        .into_iter()
+        .map(|(id, allocation)| {
+            (
+                id.to_string(),
+                Allocation {
+                    client: allocation.client,
+                    provider: allocation.provider,
+                    data: allocation.data,
+                    size: allocation.size,
+                    term_min: allocation.term_min,
+                    term_max: allocation.term_max,
+                    expiration: allocation.expiration,
+                },
+            )
+        })
+        .collect())
+}
+
+pub(crate) async fn state_get_claims<
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+>(
+    data: Data<RPCState<DB, B>>,
+    Params(params): Params<StateGetClaimsParams>,
+) -> Result<StateGetClaimsResult, JsonRpcError> {
+    let (AddressJson(provider), TipsetKeysJson(tsk)) = params;
+    let ts = data.chain_store.tipset_from_keys(&tsk)?;
+    let provider = lookup_id(&data, &provider, &ts)?.id()?;
+    let claims = load_verifreg(&data, &ts)?.claims(data.state_manager.blockstore(), provider)?;
+    Ok(claims
+        .into_iter()
+        .map(|(id, claim)| {
+            (
+                id.to_string(),
+                Claim {
+                    provider: claim.provider,
+                    client: claim.client,
+                    data: claim.data,
+                    size: claim.size,
+                    term_min: claim.term_min,
+                    term_max: claim.term_max,
+                    term_start: claim.term_start,
+                    sector: claim.sector,
+                },
+            )
+        })
+        .collect())
+}
+
+fn load_miner<DB, B>(
+    data: &RPCState<DB, B>,
+    address: &Address,
+    tipset: &Tipset,
+) -> anyhow::Result<miner::State>
+where
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+{
+    let actor = data
+        .state_manager
+        .get_actor(address, *tipset.parent_state())?
+        .with_context(|| format!("Miner actor {address} not found"))?;
+    miner::State::load(data.state_manager.blockstore(), &actor)
+}
+
+/// Calls the closure on each partition of each deadline of the miner.
+fn for_each_partition<DB, B>(
+    data: &RPCState<DB, B>,
+    state: &miner::State,
+    mut f: impl FnMut(&miner::Partition),
+) -> anyhow::Result<()>
+where
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+{
+    let store = data.state_manager.blockstore();
+    state.for_each_deadline(
+        &data.state_manager.chain_config().policy,
+        store,
+        |_, deadline| {
+            deadline.for_each(store, |_, partition| {
+                f(&partition);
+                Ok(())
+            })
+        },
+    )
+}
+
+pub(crate) async fn state_miner_sectors<
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+>(
+    data: Data<RPCState<DB, B>>,
+    Params(params): Params<StateMinerSectorsParams>,
+) -> Result<StateMinerSectorsResult, JsonRpcError> {
+    let (AddressJson(address), filter, TipsetKeysJson(tsk)) = params;
+    let ts = data.chain_store.tipset_from_keys(&tsk)?;
+    let state = load_miner(&data, &address, &ts)?;
+    let filter = filter.map(BitField::from);
+    Ok(state.load_sectors(data.state_manager.blockstore(), filter.as_ref())?)
+}
+
+pub(crate) async fn state_miner_active_sectors<
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+>(
+    data: Data<RPCState<DB, B>>,
+    Params(params): Params<StateMinerActiveSectorsParams>,
+) -> Result<StateMinerActiveSectorsResult, JsonRpcError> {
+    let (AddressJson(address), TipsetKeysJson(tsk)) = params;
+    let ts = data.chain_store.tipset_from_keys(&tsk)?;
+    let state = load_miner(&data, &address, &ts)?;
+    let mut active = BitField::new();
+    for_each_partition(&data, &state, |partition| {
+        active |= &partition.active_sectors();
+    })?;
+    Ok(state.load_sectors(data.state_manager.blockstore(), Some(&active))?)
+}
+
+pub(crate) async fn state_miner_deadlines<
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+>(
+    data: Data<RPCState<DB, B>>,
+    Params(params): Params<StateMinerDeadlinesParams>,
+) -> Result<StateMinerDeadlinesResult, JsonRpcError> {
+    let (AddressJson(address), TipsetKeysJson(tsk)) = params;
+    let ts = data.chain_store.tipset_from_keys(&tsk)?;
+    let state = load_miner(&data, &address, &ts)?;
+    let store = data.state_manager.blockstore();
+    let mut deadlines = vec![];
+    state.for_each_deadline(
+        &data.state_manager.chain_config().policy,
+        store,
+        |_, deadline| {
+            deadlines.push(Deadline {
+                post_submissions: deadline.partitions_posted().clone(),
+                disputable_proof_count: deadline.disputable_proof_count(store)?,
+            });
+            Ok(())
+        },
+    )?;
+    Ok(deadlines)
+}
+
+pub(crate) async fn state_miner_partitions<
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+>(
+    data: Data<RPCState<DB, B>>,
+    Params(params): Params<StateMinerPartitionsParams>,
+) -> Result<StateMinerPartitionsResult, JsonRpcError> {
+    let (AddressJson(address), deadline_index, TipsetKeysJson(tsk)) = params;
+    let ts = data.chain_store.tipset_from_keys(&tsk)?;
+    let state = load_miner(&data, &address, &ts)?;
+    let store = data.state_manager.blockstore();
+    let deadline = state.load_deadline(
+        &data.state_manager.chain_config().policy,
+        store,
+        deadline_index,
+    )?;
+    let mut partitions = vec![];
+    deadline.for_each(store, |_, partition| {
+        partitions.push(Partition {
+            all_sectors: partition.all_sectors().clone(),
+            faulty_sectors: partition.faulty_sectors().clone(),
+            recovering_sectors: partition.recovering_sectors().clone(),
+            live_sectors: partition.live_sectors(),
+            active_sectors: partition.active_sectors(),
+        });
+        Ok(())
+    })?;
+    Ok(partitions)
+}
+
+pub(crate) async fn state_miner_faults<
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+>(
+    data: Data<RPCState<DB, B>>,
+    Params(params): Params<StateMinerFaultsParams>,
+) -> Result<StateMinerFaultsResult, JsonRpcError> {
+    let (AddressJson(address), TipsetKeysJson(tsk)) = params;
+    let ts = data.chain_store.tipset_from_keys(&tsk)?;
+    let state = load_miner(&data, &address, &ts)?;
+    let mut faults = BitField::new();
+    for_each_partition(&data, &state, |partition| {
+        faults |= partition.faulty_sectors();
+    })?;
+    Ok(BitFieldJson(faults))
+}
+
+pub(crate) async fn state_miner_recoveries<
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+>(
+    data: Data<RPCState<DB, B>>,
+    Params(params): Params<StateMinerRecoveriesParams>,
+) -> Result<StateMinerRecoveriesResult, JsonRpcError> {
+    let (AddressJson(address), TipsetKeysJson(tsk)) = params;
+    let ts = data.chain_store.tipset_from_keys(&tsk)?;
+    let state = load_miner(&data, &address, &ts)?;
+    let mut recoveries = BitField::new();
+    for_each_partition(&data, &state, |partition| {
+        recoveries |= partition.recovering_sectors();
+    })?;
+    Ok(BitFieldJson(recoveries))
+}
+
+pub(crate) async fn state_miner_proving_deadline<
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+>(
+    data: Data<RPCState<DB, B>>,
+    Params(params): Params<StateMinerProvingDeadlineParams>,
+) -> Result<StateMinerProvingDeadlineResult, JsonRpcError> {
+    let (AddressJson(address), TipsetKeysJson(tsk)) = params;
+    let ts = data.chain_store.tipset_from_keys(&tsk)?;
+    let state = load_miner(&data, &address, &ts)?;
+    let policy = &data.state_manager.chain_config().policy;
+    Ok(state
+        .deadline_info(policy, ts.epoch())
+        .next_not_elapsed(policy))
+}
+
+pub(crate) async fn state_sector_get_info<
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+>(
+    data: Data<RPCState<DB, B>>,
+    Params(params): Params<StateSectorGetInfoParams>,
+) -> Result<StateSectorGetInfoResult, JsonRpcError> {
+    let (AddressJson(address), sector_number, TipsetKeysJson(tsk)) = params;
+    let ts = data.chain_store.tipset_from_keys(&tsk)?;
+    let state = load_miner(&data, &address, &ts)?;
+    Ok(state.get_sector(data.state_manager.blockstore(), sector_number)?)
+}
+
+pub(crate) async fn state_miner_sector_count<
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+>(
+    data: Data<RPCState<DB, B>>,
+    Params(params): Params<StateMinerSectorCountParams>,
+) -> Result<StateMinerSectorCountResult, JsonRpcError> {
+    let (AddressJson(address), TipsetKeysJson(tsk)) = params;
+    let ts = data.chain_store.tipset_from_keys(&tsk)?;
+    let state = load_miner(&data, &address, &ts)?;
+    let mut count = MinerSectors {
+        live: 0,
+        active: 0,
+        faulty: 0,
+    };
+    for_each_partition(&data, &state, |partition| {
+        count.live += partition.live_sectors().len();
+        count.active += partition.active_sectors().len();
+        count.faulty += partition.faulty_sectors().len();
+    })?;
+    Ok(count)
+}
+
+/// Returns the balance of a miner that can be withdrawn, which includes the
+/// funds that have vested but are yet to be unlocked by the miner actor.
+pub(crate) async fn state_miner_available_balance<
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+>(
+    data: Data<RPCState<DB, B>>,
+    Params(params): Params<StateMinerAvailableBalanceParams>,
+) -> Result<StateMinerAvailableBalanceResult, JsonRpcError> {
+    let (AddressJson(address), TipsetKeysJson(tsk)) = params;
+    let ts = data.chain_store.tipset_from_keys(&tsk)?;
+    let actor = data
+        .state_manager
+        .get_actor(&address, *ts.parent_state())?
+        .with_context(|| format!("Miner actor {address} not found"))?;
+    let store = data.state_manager.blockstore();
+    let state = miner::State::load(store, &actor)?;
+    let available = state.available_balance(&TokenAmount::from(&actor.balance))
+        + state.vested_funds(store, ts.epoch())?;
+    Ok(available.atto().to_string())
+}
+
+pub(crate) async fn state_miner_vesting_funds<
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+>(
+    data: Data<RPCState<DB, B>>,
+    Params(params): Params<StateMinerVestingFundsParams>,
+) -> Result<StateMinerVestingFundsResult, JsonRpcError> {
+    let (AddressJson(address), TipsetKeysJson(tsk)) = params;
+    let ts = data.chain_store.tipset_from_keys(&tsk)?;
+    let state = load_miner(&data, &address, &ts)?;
+    Ok(state
+        .vesting_funds(data.state_manager.blockstore())?
+        .into_iter()
+        .map(|(epoch, amount)| VestingFund { epoch, amount })
+        .collect())
+}
+
+/// The collateral estimates are padded by 10%, like Lotus does, for the
+/// messages of the miner not to fail if the network power or reward changes
+/// in the meantime.
+const COLLATERAL_ESTIMATE_NUM: u32 = 110;
+const COLLATERAL_ESTIMATE_DEN: u32 = 100;
+
+/// Returns the quality adjusted power of the sector to pre-commit, from the
+/// weights of its deals.
+fn sector_qa_power<DB, B>(
+    data: &RPCState<DB, B>,
+    miner: &Address,
+    info: &SectorPreCommitInfo,
+    tipset: &Tipset,
+) -> anyhow::Result<StoragePower>
+where
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+{
+    let sector_size = SectorSize::from(
+        info.seal_proof
+            .sector_size()
+            .map_err(|e| anyhow::anyhow!("Invalid seal proof: {e}"))?,
+    );
+    let store = data.state_manager.blockstore();
+    let market_state = load_market(data, tipset)?;
+    let (deal_weight, verified_weight) = market_state.verify_deals_for_activation(
+        store,
+        info.deal_ids.as_deref().unwrap_or_default(),
+        &lookup_id(data, miner, tipset)?,
+        info.expiration,
+        tipset.epoch(),
+    )?;
+    Ok(load_miner(data, miner, tipset)?.qa_power_for_weight(
+        sector_size,
+        info.expiration - tipset.epoch(),
+        &deal_weight,
+        &verified_weight,
+    ))
+}
+
+fn load_power<DB, B>(data: &RPCState<DB, B>, tipset: &Tipset) -> anyhow::Result<power::State>
+where
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+{
+    let actor = data
+        .state_manager
+        .get_actor(&power::ADDRESS, *tipset.parent_state())?
+        .context("Power actor address could not be resolved")?;
+    power::State::load(data.state_manager.blockstore(), &actor)
+}
+
+fn load_reward<DB, B>(data: &RPCState<DB, B>, tipset: &Tipset) -> anyhow::Result<reward::State>
+where
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+{
+    let actor = data
+        .state_manager
+        .get_actor(&reward::ADDRESS, *tipset.parent_state())?
+        .context("Reward actor address could not be resolved")?;
+    reward::State::load(data.state_manager.blockstore(), &actor)
+}
+
+/// Returns the estimated initial pledge collateral of a sector, once proven.
+pub(crate) async fn state_miner_initial_pledge_collateral<
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+>(
+    data: Data<RPCState<DB, B>>,
+    Params(params): Params<StateMinerInitialPledgeCollateralParams>,
+) -> Result<StateMinerInitialPledgeCollateralResult, JsonRpcError> {
+    let (AddressJson(address), info, TipsetKeysJson(tsk)) = params;
+    let ts = data.chain_store.tipset_from_keys(&tsk)?;
+    let qa_power = sector_qa_power(&data, &address, &info, &ts)?;
+    let circulating_supply = data
+        .state_manager
+        .get_circulating_supply(ts.epoch(), ts.parent_state())?;
+    let initial_pledge = load_reward(&data, &ts)?.initial_pledge_for_power(
+        &qa_power,
+        &load_power(&data, &ts)?.total_power_smoothed(),
+        &circulating_supply,
+    );
+    Ok((initial_pledge.atto() * COLLATERAL_ESTIMATE_NUM / COLLATERAL_ESTIMATE_DEN).to_string())
+}
+
+/// Returns the estimated deposit to pre-commit a sector.
+pub(crate) async fn state_miner_pre_commit_deposit_for_power<
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+>(
+    data: Data<RPCState<DB, B>>,
+    Params(params): Params<StateMinerPreCommitDepositForPowerParams>,
+) -> Result<StateMinerPreCommitDepositForPowerResult, JsonRpcError> {
+    let (AddressJson(address), info, TipsetKeysJson(tsk)) = params;
+    let ts = data.chain_store.tipset_from_keys(&tsk)?;
+    let qa_power = sector_qa_power(&data, &address, &info, &ts)?;
+    let deposit = load_reward(&data, &ts)?
+        .pre_commit_deposit_for_power(&load_power(&data, &ts)?.total_power_smoothed(), &qa_power);
+    Ok((deposit.atto() * COLLATERAL_ESTIMATE_NUM / COLLATERAL_ESTIMATE_DEN).to_string())
+}
+
+pub(crate) async fn state_circulating_supply<
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+>(
+    data: Data<RPCState<DB, B>>,
+    Params(params): Params<StateCirculatingSupplyParams>,
+) -> Result<StateCirculatingSupplyResult, JsonRpcError> {
+    let (TipsetKeysJson(tsk),) = params;
+    let ts = data.chain_store.tipset_from_keys(&tsk)?;
+    let supply = data
+        .state_manager
+        .get_state_circulating_supply(ts.epoch(), ts.parent_state())?;
+    Ok(supply.atto().to_string())
+}
+
+pub(crate) async fn state_vm_circulating_supply_internal<
+    DB: Blockstore + Store + Clone + Send + Sync + 'static,
+    B: Beacon,
+>(
+    data: Data<RPCState<DB, B>>,
+    Params(params): Params<StateVMCirculatingSupplyInternalParams>,
+) -> Result<StateVMCirculatingSupplyInternalResult, JsonRpcError> {
+    let (TipsetKeysJson(tsk),) = params;
+    let ts = data.chain_store.tipset_from_keys(&tsk)?;
+    Ok(data
+        .state_manager
+        .get_circulating_supply_detail(ts.epoch(), ts.parent_state())?)
+}
@@ -58,7 +58,7 @@ where
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use std::{sync::Arc, time::Duration};
 
     use forest_beacon::{BeaconPoint, BeaconSchedule, MockBeacon};
@@ -66,7 +66,7 @@ mod tests {
     use forest_chain::ChainStore;
     use forest_chain_sync::SyncStage;
     use forest_db::{MemoryDB, Store};
-    use forest_key_management::{KeyStore, KeyStoreConfig};
+    use forest_key_management::{KeyStore, KeyStoreConfig, KeyStoreSigner};
     use forest_libp2p::NetworkMessage;
     use forest_message_pool::{MessagePool, MpoolRpcProvider};
     use forest_networks::ChainConfig;
@@ -80,7 +80,7 @@ mod tests {
 
     const TEST_NET_NAME: &str = "test";
 
-    fn state_setup() -> (
+    pub(crate) fn state_setup() -> (
         Arc<RPCState<MemoryDB, MockBeacon>>,
         flume::Receiver<NetworkMessage>,
     ) {
@@ -141,9 +141,11 @@ mod tests {
             .unwrap()
         };
         let (new_mined_block_tx, _) = flume::bounded(5);
+        let keystore = Arc::new(RwLock::new(KeyStore::new(KeyStoreConfig::Memory).unwrap()));
         let state = Arc::new(RPCState {
             state_manager,
-            keystore: Arc::new(RwLock::new(KeyStore::new(KeyStoreConfig::Memory).unwrap())),
+            signer: Arc::new(KeyStoreSigner::new(keystore.clone())),
+            keystore,
             mpool: Arc::new(pool),
             bad_blocks: Default::default(),
             sync_state: Arc::new(parking_lot::RwLock::new(Default::default())),
@@ -152,6 +154,9 @@ mod tests {
             chain_store: cs_for_chain,
             beacon,
             new_mined_block_tx,
+            push_locks: Default::default(),
+            paych_lock: Default::default(),
+            store_check: Default::default(),
         });
         (state, network_rx)
     }
@@ -7,8 +7,11 @@ use base64::{prelude::BASE64_STANDARD, Engine};
 use forest_beacon::Beacon;
 use forest_db::Store;
 use forest_json::{address::json::AddressJson, signature::json::SignatureJson};
-use forest_key_management::{json::KeyInfoJson, Error, Key};
-use forest_rpc_api::{data_types::RPCState, wallet_api::*};
+use forest_key_management::{json::KeyInfoJson, DerivedKey, Error, Key, KeyStore};
+use forest_rpc_api::{
+    data_types::{DerivedAddress, RPCState},
+    wallet_api::*,
+};
 use forest_shim::{address::Address, econ::TokenAmount, state_tree::StateTree};
 use fvm_ipld_blockstore::Blockstore;
 use jsonrpc_v2::{Data, Error as JsonRpcError, Params};
@@ -87,10 +90,7 @@ where
     let (addr_str,) = params;
     let addr = Address::from_str(&addr_str)?;
 
-    let keystore = data.keystore.read().await;
-
-    let key = forest_key_management::find_key(&addr, &keystore).is_ok();
-    Ok(key)
+    Ok(data.signer.has(&addr).await?)
 }
 
 /// Import `KeyInfo` to the Wallet, return the Address that corresponds to it
@@ -134,8 +134,10 @@ where
     DB: Blockstore,
     B: Beacon,
 {
-    let keystore = data.keystore.read().await;
-    Ok(forest_key_management::list_addrs(&keystore)?
+    Ok(data
+        .signer
+        .list()
+        .await?
         .into_iter()
         .map(AddressJson::from)
         .collect())
@@ -199,20 +201,10 @@ where
     let key_addr = state_manager
         .resolve_to_key_addr(&address, &heaviest_tipset)
         .await?;
-    let keystore = &mut *data.keystore.write().await;
-    let key = match forest_key_management::find_key(&key_addr, keystore) {
-        Ok(key) => key,
-        Err(_) => {
-            let key_info = forest_key_management::try_find(&key_addr, keystore)?;
-            Key::try_from(key_info)?
-        }
-    };
-
-    let sig = forest_key_management::sign(
-        *key.key_info.key_type(),
-        key.key_info.private_key(),
-        &BASE64_STANDARD.decode(msg_string)?,
-    )?;
+    let sig = data
+        .signer
+        .sign(&key_addr, &BASE64_STANDARD.decode(msg_string)?)
+        .await?;
 
     Ok(SignatureJson(sig))
 }
@@ -232,3 +224,99 @@ where
     let ret = sig.verify(&msg, &address.into()).is_ok();
     Ok(ret)
 }
+
+/// Derive a new Address from the mnemonic seed of the Wallet. The seed is never
+/// created here, as its mnemonic would have to be returned
+pub(crate) async fn wallet_hd_new<DB, B>(
+    data: Data<RPCState<DB, B>>,
+    Params(params): Params<WalletHdNewParams>,
+) -> Result<WalletHdNewResult, JsonRpcError>
+where
+    DB: Blockstore,
+    B: Beacon,
+{
+    let (sig_raw,) = params;
+    let mut keystore = data.keystore.write().await;
+
+    if !forest_key_management::has_hd_seed(&keystore) {
+        return Err(JsonRpcError::Provided {
+            code: WALLET_HD_NO_SEED_ERROR_CODE,
+            message: "Wallet has no mnemonic seed",
+        });
+    }
+    let key = forest_key_management::derive_next_key(&mut keystore, sig_raw.0)?;
+    set_default_if_missing(&mut keystore, &key)?;
+
+    Ok(derived_address(key))
+}
+
+/// Restore the mnemonic seed of the Wallet, deriving the first Addresses of the
+/// given type
+pub(crate) async fn wallet_hd_restore<DB, B>(
+    data: Data<RPCState<DB, B>>,
+    Params(params): Params<WalletHdRestoreParams>,
+) -> Result<WalletHdRestoreResult, JsonRpcError>
+where
+    DB: Blockstore,
+    B: Beacon,
+{
+    let (phrase, passphrase, sig_raw, count) = params;
+    if count > WALLET_HD_RESTORE_MAX_COUNT {
+        return Err(JsonRpcError::Provided {
+            code: 1,
+            message: "Too many keys to derive",
+        });
+    }
+    let mnemonic = forest_key_management::parse_mnemonic(&phrase)?;
+    let mut keystore = data.keystore.write().await;
+
+    if let Err(error) = forest_key_management::init_hd_seed(&mut keystore, &mnemonic, &passphrase) {
+        return match error {
+            Error::KeyExists => Err(JsonRpcError::Provided {
+                code: 1,
+                message: "Wallet already has a mnemonic seed",
+            }),
+            _ => Err(error.into()),
+        };
+    }
+
+    let mut keys = Vec::with_capacity(count as usize);
+    for index in 0..count {
+        let key = forest_key_management::derive_key(&mut keystore, sig_raw.0, index)?;
+        set_default_if_missing(&mut keystore, &key)?;
+        keys.push(derived_address(key));
+    }
+    Ok(keys)
+}
+
+/// Derive the Address at the given index from the mnemonic seed of the Wallet
+pub(crate) async fn wallet_hd_derive<DB, B>(
+    data: Data<RPCState<DB, B>>,
+    Params(params): Params<WalletHdDeriveParams>,
+) -> Result<WalletHdDeriveResult, JsonRpcError>
+where
+    DB: Blockstore,
+    B: Beacon,
+{
+    let (sig_raw, index) = params;
+    let mut keystore = data.keystore.write().await;
+
+    let key = forest_key_management::derive_key(&mut keystore, sig_raw.0, index)?;
+    set_default_if_missing(&mut keystore, &key)?;
+    Ok(derived_address(key))
+}
+
+fn set_default_if_missing(keystore: &mut KeyStore, key: &DerivedKey) -> Result<(), Error> {
+    if keystore.get("default").is_err() {
+        keystore.put("default".to_string(), key.key.key_info.clone())?;
+    }
+    Ok(())
+}
+
+fn derived_address(key: DerivedKey) -> DerivedAddress {
+    DerivedAddress {
+        address: key.key.address.to_string(),
+        path: key.path,
+        index: key.index,
+    }
+}
@@ -15,6 +15,16 @@ pub use fvm_shared3::address::{
 };
 use serde::{Deserialize, Serialize};
 
+/// Namespace of the Ethereum Address Manager actor. Delegated addresses in this
+/// namespace hold an Ethereum address as sub-address.
+pub const EAM_NAMESPACE: u64 = 10;
+
+/// Length of Ethereum addresses, in bytes
+pub const ETH_ADDRESS_LEN: usize = 20;
+
+/// Leading byte of Ethereum addresses wrapping an actor ID
+const ETH_MASKED_ID_PREFIX: u8 = 0xff;
+
 #[derive(Copy, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(transparent)]
 pub struct Address(Address_v3);
@@ -36,6 +46,10 @@ impl Address {
         Address_v3::new_secp256k1(pubkey).map(Address::from)
     }
 
+    pub fn new_delegated(namespace: u64, subaddress: &[u8]) -> Result<Self, Error> {
+        Address_v3::new_delegated(namespace, subaddress).map(Address::from)
+    }
+
     pub fn protocol(&self) -> Protocol {
         self.0.protocol()
     }
@@ -128,3 +142,107 @@ impl From<&Address> for Address_v3 {
         other.0
     }
 }
+
+/// Ethereum address, either wrapping an actor ID or held by a delegated
+/// address in the [`EAM_NAMESPACE`].
+#[derive(Copy, Clone, Debug, Hash, PartialEq, Eq)]
+pub struct EthAddress(pub [u8; ETH_ADDRESS_LEN]);
+
+impl EthAddress {
+    /// Ethereum address wrapping the actor ID: `0xff`, 11 zero bytes, and the
+    /// big-endian ID.
+    pub fn from_id(id: u64) -> Self {
+        let mut bytes = [0; ETH_ADDRESS_LEN];
+        bytes[0] = ETH_MASKED_ID_PREFIX;
+        bytes[ETH_ADDRESS_LEN - 8..].copy_from_slice(&id.to_be_bytes());
+        EthAddress(bytes)
+    }
+
+    /// Returns the actor ID if this address wraps one.
+    pub fn as_id(&self) -> Option<u64> {
+        let (prefix, id) = self.0.split_at(ETH_ADDRESS_LEN - 8);
+        if prefix[0] == ETH_MASKED_ID_PREFIX && prefix[1..].iter().all(|b| *b == 0) {
+            Some(u64::from_be_bytes(id.try_into().expect("Infallible")))
+        } else {
+            None
+        }
+    }
+
+    /// Converts an ID address, or a delegated address of the
+    /// [`EAM_NAMESPACE`], to an Ethereum address.
+    pub fn from_filecoin_address(addr: &Address) -> anyhow::Result<Self> {
+        match addr.payload() {
+            Payload::ID(id) => Ok(Self::from_id(*id)),
+            Payload::Delegated(delegated) if delegated.namespace() == EAM_NAMESPACE => {
+                let bytes = delegated
+                    .subaddress()
+                    .try_into()
+                    .map_err(|_| anyhow::anyhow!("Invalid Ethereum address length in {addr}"))?;
+                Ok(EthAddress(bytes))
+            }
+            _ => anyhow::bail!("{addr} has no Ethereum equivalent"),
+        }
+    }
+
+    /// Converts to the Filecoin equivalent: an ID address for addresses
+    /// wrapping an actor ID, an `f410` delegated address otherwise.
+    pub fn to_filecoin_address(&self) -> Address {
+        match self.as_id() {
+            Some(id) => Address::new_id(id),
+            None => Address::new_delegated(EAM_NAMESPACE, &self.0)
+                .expect("Ethereum addresses are valid sub-addresses"),
+        }
+    }
+}
+
+impl FromStr for EthAddress {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let hex = s
+            .strip_prefix("0x")
+            .ok_or_else(|| anyhow::anyhow!("Ethereum address must start with 0x"))?;
+        let bytes = hex::decode(hex)?
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("Ethereum address must be {ETH_ADDRESS_LEN} bytes"))?;
+        Ok(EthAddress(bytes))
+    }
+}
+
+impl Display for EthAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "0x{}", hex::encode(self.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn eth_address_conversions() {
+        let eth: EthAddress = "0xd4c5fb16488aa48081296299d54b0c648c9333da"
+            .parse()
+            .unwrap();
+        let addr = eth.to_filecoin_address();
+        assert_eq!(addr.protocol(), Protocol::Delegated);
+        assert_eq!(EthAddress::from_filecoin_address(&addr).unwrap(), eth);
+        assert_eq!(
+            eth.to_string(),
+            "0xd4c5fb16488aa48081296299d54b0c648c9333da"
+        );
+
+        let id: EthAddress = "0xff00000000000000000000000000000000000401"
+            .parse()
+            .unwrap();
+        assert_eq!(id.as_id(), Some(1025));
+        assert_eq!(id.to_filecoin_address(), Address::new_id(1025));
+        assert_eq!(
+            EthAddress::from_filecoin_address(&Address::new_id(1025)).unwrap(),
+            id
+        );
+
+        assert!("0x1234".parse::<EthAddress>().is_err());
+        assert!(EthAddress::from_filecoin_address(&Address::new_actor(b"actor")).is_err());
+    }
+}
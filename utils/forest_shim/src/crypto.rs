@@ -77,14 +77,14 @@ impl From<Signature> for Signature_v3 {
     }
 }
 
-impl From<&Signature> for Signature_v2 {
-    fn from(other: &Signature) -> Signature_v2 {
-        let sig_type: SignatureType = other.signature_type();
-        let sig: Signature_v2 = Signature_v2 {
-            sig_type: sig_type.into(),
+impl TryFrom<&Signature> for Signature_v2 {
+    type Error = anyhow::Error;
+
+    fn try_from(other: &Signature) -> anyhow::Result<Signature_v2> {
+        Ok(Signature_v2 {
+            sig_type: other.signature_type().try_into()?,
             bytes: other.bytes().into(),
-        };
-        sig
+        })
     }
 }
 
@@ -135,12 +135,38 @@ impl From<SignatureType> for SignatureType_v3 {
     }
 }
 
-impl From<SignatureType> for SignatureType_v2 {
-    fn from(other: SignatureType) -> SignatureType_v2 {
+/// Delegated signatures only exist from FVM v3 on.
+impl TryFrom<SignatureType> for SignatureType_v2 {
+    type Error = anyhow::Error;
+
+    fn try_from(other: SignatureType) -> anyhow::Result<SignatureType_v2> {
         match other.0 {
-            SignatureType_v3::Secp256k1 => SignatureType_v2::Secp256k1,
-            SignatureType_v3::BLS => SignatureType_v2::BLS,
-            SignatureType_v3::Delegated => panic!("Delegated signature type not possible in fvm2"),
+            SignatureType_v3::Secp256k1 => Ok(SignatureType_v2::Secp256k1),
+            SignatureType_v3::BLS => Ok(SignatureType_v2::BLS),
+            SignatureType_v3::Delegated => {
+                anyhow::bail!("Delegated signatures are not supported before FVM v3")
+            }
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signature_type_v2_conversion() {
+        assert_eq!(
+            SignatureType_v2::try_from(SignatureType::BLS).unwrap(),
+            SignatureType_v2::BLS
+        );
+        assert_eq!(
+            SignatureType_v2::try_from(SignatureType::Secp256k1).unwrap(),
+            SignatureType_v2::Secp256k1
+        );
+        assert!(SignatureType_v2::try_from(SignatureType::Delegated).is_err());
+        assert!(
+            Signature_v2::try_from(&Signature::new(SignatureType::Delegated, vec![0; 65])).is_err()
+        );
+    }
+}
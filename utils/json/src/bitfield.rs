@@ -0,0 +1,73 @@
+// Copyright 2019-2023 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+pub mod json {
+    use fvm_ipld_bitfield::{iter::Ranges, BitField};
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    /// Wrapper for serializing and de-serializing a `BitField` from JSON. As in
+    /// Lotus, the bit field is encoded as the lengths of its alternating runs
+    /// of unset and set bits, starting with a run of unset bits.
+    #[derive(Deserialize, Serialize, Clone, Debug)]
+    #[serde(transparent)]
+    pub struct BitFieldJson(#[serde(with = "self")] pub BitField);
+
+    impl From<BitFieldJson> for BitField {
+        fn from(wrapper: BitFieldJson) -> Self {
+            wrapper.0
+        }
+    }
+
+    pub fn serialize<S>(m: &BitField, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut runs = vec![];
+        let mut position = 0;
+        for range in m.ranges() {
+            runs.push(range.start - position);
+            runs.push(range.end - range.start);
+            position = range.end;
+        }
+        runs.serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<BitField, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let runs: Option<Vec<u64>> = Deserialize::deserialize(deserializer)?;
+        let mut ranges = vec![];
+        let mut position = 0;
+        for (i, run) in runs.unwrap_or_default().into_iter().enumerate() {
+            if i % 2 == 1 && run > 0 {
+                ranges.push(position..position + run);
+            }
+            position += run;
+        }
+        Ok(BitField::from_ranges(Ranges::new(ranges)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fvm_ipld_bitfield::BitField;
+
+    use super::json::BitFieldJson;
+
+    #[test]
+    fn lotus_run_lengths() {
+        let bitfield = BitField::try_from_bits([0, 1, 2, 5, 8, 9]).unwrap();
+        let json = serde_json::to_string(&BitFieldJson(bitfield.clone())).unwrap();
+        assert_eq!(json, "[0,3,2,1,2,2]");
+        let BitFieldJson(decoded) = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, bitfield);
+
+        let BitFieldJson(empty) = serde_json::from_str("null").unwrap();
+        assert!(empty.is_empty());
+        assert_eq!(
+            serde_json::to_string(&BitFieldJson(BitField::new())).unwrap(),
+            "[]"
+        );
+    }
+}
@@ -4,6 +4,7 @@
 pub mod actor_state;
 pub mod address;
 pub mod bigint;
+pub mod bitfield;
 pub mod cid;
 pub mod message;
 pub mod message_receipt;
@@ -1,22 +1,51 @@
 // Copyright 2019-2023 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use std::path::PathBuf;
+use std::{
+    path::PathBuf,
+    sync::atomic::{self, AtomicU64},
+};
 
+use forest_db::DBStatistics;
 use log::error;
 use prometheus::{
     core::{Collector, Desc},
-    proto, Gauge, Opts,
+    proto, Gauge, GaugeVec, IntCounter, Opts,
 };
 
-pub struct DBCollector {
+pub struct DBCollector<DB> {
     db_directory: PathBuf,
+    db: DB,
     descs: Vec<Desc>,
     db_size: Gauge,
+    column_keys: GaugeVec,
+    column_bytes: GaugeVec,
+    files: GaugeVec,
+    file_bytes: GaugeVec,
+    cache_hits: DeltaCounter,
+    cache_misses: DeltaCounter,
+    compactions: DeltaCounter,
+    write_stall_micros: DeltaCounter,
+}
+
+/// Counter following a cumulative statistic of the database, increased by
+/// the change of the statistic since it was last collected.
+struct DeltaCounter {
+    counter: IntCounter,
+    last: AtomicU64,
 }
 
-impl DBCollector {
-    pub fn new(db_directory: PathBuf) -> Self {
+impl DeltaCounter {
+    fn update(&self, value: u64) {
+        let last = self.last.swap(value, atomic::Ordering::Relaxed);
+        // The statistic starts over from zero when the database is reopened.
+        self.counter
+            .inc_by(value.checked_sub(last).unwrap_or(value));
+    }
+}
+
+impl<DB> DBCollector<DB> {
+    pub fn new(db_directory: PathBuf, db: DB) -> Self {
         let mut descs: Vec<Desc> = vec![];
         let db_size = Gauge::with_opts(Opts::new(
             "forest_db_size",
@@ -24,15 +53,89 @@ impl DBCollector {
         ))
         .expect("Creating forest_db_size gauge must succeed");
         descs.extend(db_size.desc().into_iter().cloned());
+
+        let gauge_vec = |descs: &mut Vec<Desc>, name: &str, help: &str, label: &str| {
+            let gauge = GaugeVec::new(Opts::new(name, help), &[label])
+                .unwrap_or_else(|e| panic!("Creating {name} gauge must succeed: {e}"));
+            descs.extend(gauge.desc().into_iter().cloned());
+            gauge
+        };
+        let column_keys = gauge_vec(
+            &mut descs,
+            "forest_db_column_keys",
+            "Number of keys of each database column",
+            "column",
+        );
+        let column_bytes = gauge_vec(
+            &mut descs,
+            "forest_db_column_bytes",
+            "Size of the values of each database column in bytes",
+            "column",
+        );
+        let files = gauge_vec(
+            &mut descs,
+            "forest_db_files",
+            "Number of data files of the database, by level",
+            "level",
+        );
+        let file_bytes = gauge_vec(
+            &mut descs,
+            "forest_db_file_bytes",
+            "Size of the data files of the database in bytes, by level",
+            "level",
+        );
+
+        let counter = |descs: &mut Vec<Desc>, name: &str, help: &str| {
+            let counter = IntCounter::with_opts(Opts::new(name, help))
+                .unwrap_or_else(|e| panic!("Creating {name} counter must succeed: {e}"));
+            descs.extend(counter.desc().into_iter().cloned());
+            DeltaCounter {
+                counter,
+                last: AtomicU64::new(0),
+            }
+        };
+        let cache_hits = counter(
+            &mut descs,
+            "forest_db_cache_hits_total",
+            "Reads served from the database block cache",
+        );
+        let cache_misses = counter(
+            &mut descs,
+            "forest_db_cache_misses_total",
+            "Reads missing the database block cache",
+        );
+        let compactions = counter(
+            &mut descs,
+            "forest_db_compactions_total",
+            "Compactions run by the database",
+        );
+        let write_stall_micros = counter(
+            &mut descs,
+            "forest_db_write_stall_micros_total",
+            "Time writes to the database were stalled for in microseconds",
+        );
+
         Self {
             db_directory,
+            db,
             descs,
             db_size,
+            column_keys,
+            column_bytes,
+            files,
+            file_bytes,
+            cache_hits,
+            cache_misses,
+            compactions,
+            write_stall_micros,
         }
     }
 }
 
-impl Collector for DBCollector {
+impl<DB> Collector for DBCollector<DB>
+where
+    DB: DBStatistics + Sync + Send,
+{
     fn desc(&self) -> Vec<&Desc> {
         self.descs.iter().collect()
     }
@@ -50,6 +153,41 @@ impl Collector for DBCollector {
 
         let mut metric_families = vec![];
         metric_families.extend(self.db_size.collect());
+
+        if let Some(stats) = self.db.get_statistics() {
+            for column in &stats.columns {
+                self.column_keys
+                    .with_label_values(&[&column.name])
+                    .set(column.keys as f64);
+                self.column_bytes
+                    .with_label_values(&[&column.name])
+                    .set(column.bytes as f64);
+            }
+            for file in &stats.files {
+                self.files
+                    .with_label_values(&[&file.name])
+                    .set(file.files as f64);
+                self.file_bytes
+                    .with_label_values(&[&file.name])
+                    .set(file.bytes as f64);
+            }
+            metric_families.extend(self.column_keys.collect());
+            metric_families.extend(self.column_bytes.collect());
+            metric_families.extend(self.files.collect());
+            metric_families.extend(self.file_bytes.collect());
+
+            for (counter, value) in [
+                (&self.cache_hits, stats.cache_hits),
+                (&self.cache_misses, stats.cache_misses),
+                (&self.compactions, stats.compactions),
+                (&self.write_stall_micros, stats.write_stall_micros),
+            ] {
+                if let Some(value) = value {
+                    counter.update(value);
+                    metric_families.extend(counter.counter.collect());
+                }
+            }
+        }
         metric_families
     }
 }
@@ -32,7 +32,7 @@ where
     let registry = prometheus::default_registry();
 
     // Add the DBCollector to the registry
-    let db_collector = crate::db::DBCollector::new(db_directory);
+    let db_collector = crate::db::DBCollector::new(db_directory, db.clone());
     registry.register(Box::new(db_collector))?;
 
     // Create an configure HTTP server
@@ -77,7 +77,7 @@ where
     DB: DBStatistics + Sync + Send + Clone + 'static,
 {
     let mut metrics = "# DB statistics:\n".to_owned();
-    if let Some(db_stats) = db.get_statistics() {
+    if let Some(db_stats) = db.get_statistics().and_then(|stats| stats.raw) {
         metrics.push_str(&db_stats);
     } else {
         metrics.push_str("Not enabled. Set enable_statistics to true in config and restart daemon");
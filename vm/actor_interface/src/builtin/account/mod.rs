@@ -9,6 +9,9 @@ use fvm_ipld_blockstore::Blockstore;
 use fvm_shared::address::Address;
 use serde::Serialize;
 
+/// Name of the account actor in the built-in actors manifest.
+pub const ACTOR_NAME: &str = "account";
+
 /// Account actor method.
 pub type Method = fil_actor_account_v8::Method;
 
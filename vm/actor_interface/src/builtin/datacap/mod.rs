@@ -0,0 +1,71 @@
+// Copyright 2019-2023 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! `DataCap` actor types. The `DataCap` actor, introduced in actors v9, holds
+//! the data cap of verified clients as `FRC-46` tokens. It isn't among the
+//! actor crates of the workspace, so its state is mirrored here.
+
+use anyhow::Context;
+use cid::Cid;
+use fil_actors_runtime_v9::{make_map_with_root_and_bitwidth, u64_key};
+use forest_shim::{address::Address, econ::TokenAmount, state_tree::ActorState};
+use forest_utils::db::BlockstoreExt;
+use fvm_ipld_blockstore::Blockstore;
+use fvm_ipld_encoding::tuple::{Deserialize_tuple, Serialize_tuple};
+use fvm_shared::ActorID;
+use num::{BigInt, Zero};
+
+/// `DataCap` actor address.
+pub const ADDRESS: Address = Address::new_id(7);
+
+/// Number of token units per byte of data cap.
+pub const TOKEN_PRECISION: u64 = 1_000_000_000_000_000_000;
+
+/// `DataCap` actor state. Its schema is the same in all the supported actors
+/// versions.
+#[derive(Serialize_tuple, Deserialize_tuple, Clone, Debug)]
+pub struct State {
+    /// Address allowed to mint and burn tokens, the verified registry.
+    pub governor: Address,
+    pub token: TokenState,
+}
+
+/// State of an `FRC-46` token.
+#[derive(Serialize_tuple, Deserialize_tuple, Clone, Debug)]
+pub struct TokenState {
+    pub supply: TokenAmount,
+    /// `HAMT` of the balances, by actor ID.
+    pub balances: Cid,
+    pub allowances: Cid,
+    pub hamt_bit_width: u32,
+}
+
+impl State {
+    pub fn load<BS>(store: &BS, actor: &ActorState) -> anyhow::Result<State>
+    where
+        BS: Blockstore,
+    {
+        store
+            .get_obj(&actor.state)
+            .with_context(|| format!("Actor of code {} is not a DataCap actor", actor.code))?
+            .context("Actor state doesn't exist in store")
+    }
+
+    /// Data cap in bytes of the verified client of the given actor ID, if it
+    /// is a verified client.
+    pub fn balance<BS: Blockstore>(
+        &self,
+        store: &BS,
+        client: ActorID,
+    ) -> anyhow::Result<Option<BigInt>> {
+        let balances = make_map_with_root_and_bitwidth::<_, TokenAmount>(
+            &self.token.balances,
+            store,
+            self.token.hamt_bit_width,
+        )?;
+        Ok(balances
+            .get(&u64_key(client))?
+            .filter(|balance| !balance.is_zero())
+            .map(|balance| balance.atto() / TOKEN_PRECISION))
+    }
+}
@@ -3,6 +3,7 @@
 
 use anyhow::Context;
 use cid::Cid;
+pub use fil_actor_init_v8::{ExecParams, ExecReturn};
 use forest_shim::state_tree::ActorState;
 use forest_utils::db::BlockstoreExt;
 use fvm_ipld_blockstore::Blockstore;
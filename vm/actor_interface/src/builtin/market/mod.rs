@@ -1,16 +1,15 @@
 // Copyright 2019-2023 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use std::marker::PhantomData;
-
 use anyhow::Context;
 use cid::Cid;
+use fil_actors_runtime_v9::{make_map_with_root_and_bitwidth, Array, Map};
 use forest_shim::{address::Address, econ::TokenAmount, state_tree::ActorState};
 use forest_utils::db::BlockstoreExt;
 use fvm_ipld_blockstore::Blockstore;
-use fvm_shared::{clock::ChainEpoch, piece::PaddedPieceSize};
-use num::BigInt;
-use serde::Serialize;
+use fvm_shared::{clock::ChainEpoch, deal::DealID, piece::PaddedPieceSize};
+use num::{BigInt, Zero};
+use serde::{Deserialize, Serialize};
 
 /// Market actor address.
 pub const ADDRESS: Address = Address::new_id(5);
@@ -86,35 +85,56 @@ impl State {
     }
 
     /// Loads escrow table
-    pub fn escrow_table<'bs, BS>(&self, _store: &'bs BS) -> anyhow::Result<BalanceTable<'bs, BS>>
+    pub fn escrow_table<'bs, BS>(&self, store: &'bs BS) -> anyhow::Result<BalanceTable<'bs, BS>>
     where
         BS: Blockstore,
     {
-        unimplemented!()
+        let root = match self {
+            State::V8(st) => &st.escrow_table,
+            State::V9(st) => &st.escrow_table,
+            State::V10(st) => &st.escrow_table,
+        };
+        BalanceTable::load(root, store)
     }
 
     /// Loads locked funds table
-    pub fn locked_table<'bs, BS>(&self, _store: &'bs BS) -> anyhow::Result<BalanceTable<'bs, BS>>
+    pub fn locked_table<'bs, BS>(&self, store: &'bs BS) -> anyhow::Result<BalanceTable<'bs, BS>>
     where
         BS: Blockstore,
     {
-        unimplemented!()
+        let root = match self {
+            State::V8(st) => &st.locked_table,
+            State::V9(st) => &st.locked_table,
+            State::V10(st) => &st.locked_table,
+        };
+        BalanceTable::load(root, store)
     }
 
     /// Deal proposals
-    pub fn proposals<'bs, BS>(&self, _store: &'bs BS) -> anyhow::Result<DealProposals<'bs, BS>>
+    pub fn proposals<'bs, BS>(&self, store: &'bs BS) -> anyhow::Result<DealProposals<'bs, BS>>
     where
         BS: Blockstore,
     {
-        unimplemented!()
+        let root = match self {
+            State::V8(st) => &st.proposals,
+            State::V9(st) => &st.proposals,
+            State::V10(st) => &st.proposals,
+        };
+        // The deal proposal schema is the same in all the supported versions.
+        Ok(DealProposals(Array::load(root, store)?))
     }
 
     /// Deal proposal meta data.
-    pub fn states<'bs, BS>(&self, _store: &'bs BS) -> anyhow::Result<DealStates<'bs, BS>>
+    pub fn states<'bs, BS>(&self, store: &'bs BS) -> anyhow::Result<DealStates<'bs, BS>>
     where
         BS: Blockstore,
     {
-        unimplemented!()
+        // Deal states have a verified claim since v9.
+        Ok(match self {
+            State::V8(st) => DealStates::V8(Array::load(&st.states, store)?),
+            State::V9(st) => DealStates::V9(Array::load(&st.states, store)?),
+            State::V10(st) => DealStates::V9(Array::load(&st.states, store)?),
+        })
     }
 
     /// Consume state to return just total funds locked
@@ -129,42 +149,123 @@ impl State {
     /// Validates a collection of deal `dealProposals` for activation, and
     /// returns their combined weight, split into regular deal weight and
     /// verified deal weight.
+    /// The miner address has to be an ID address.
     pub fn verify_deals_for_activation<BS>(
         &self,
-        _store: &BS,
-        _deal_ids: &[u64],
-        _miner_addr: &Address,
-        _sector_expiry: ChainEpoch,
-        _curr_epoch: ChainEpoch,
+        store: &BS,
+        deal_ids: &[u64],
+        miner_addr: &Address,
+        sector_expiry: ChainEpoch,
+        curr_epoch: ChainEpoch,
     ) -> anyhow::Result<(BigInt, BigInt)>
     where
         BS: Blockstore,
     {
-        unimplemented!()
+        let proposals = self.proposals(store)?;
+
+        let mut deal_weight = BigInt::zero();
+        let mut verified_weight = BigInt::zero();
+        for deal_id in deal_ids {
+            let proposal = proposals
+                .get(*deal_id)?
+                .with_context(|| format!("Deal {deal_id} not found"))?;
+            anyhow::ensure!(
+                proposal.provider == *miner_addr,
+                "Deal {deal_id} has provider {}, not {miner_addr}",
+                proposal.provider
+            );
+            anyhow::ensure!(
+                curr_epoch <= proposal.start_epoch,
+                "Deal {deal_id} should have started at epoch {}",
+                proposal.start_epoch
+            );
+            anyhow::ensure!(
+                proposal.end_epoch <= sector_expiry,
+                "Deal {deal_id} ends at epoch {}, after the sector expiration at {sector_expiry}",
+                proposal.end_epoch
+            );
+
+            let weight =
+                BigInt::from(proposal.piece_size.0) * (proposal.end_epoch - proposal.start_epoch);
+            if proposal.verified_deal {
+                verified_weight += weight;
+            } else {
+                deal_weight += weight;
+            }
+        }
+        Ok((deal_weight, verified_weight))
     }
 }
 
-pub enum BalanceTable<'a, BS> {
-    UnusedBalanceTable(PhantomData<&'a BS>),
-}
+/// Number of bits of the keys of the balance tables `HAMT`s consumed at each
+/// level.
+const BALANCE_TABLE_BITWIDTH: u32 = 6;
 
-pub enum DealProposals<'a, BS> {
-    UnusedDealProposal(PhantomData<&'a BS>),
+/// Balances of the market participants, by ID address.
+pub struct BalanceTable<'a, BS>(Map<'a, BS, TokenAmount>);
+
+impl<'a, BS> BalanceTable<'a, BS>
+where
+    BS: Blockstore,
+{
+    fn load(root: &Cid, store: &'a BS) -> anyhow::Result<Self> {
+        Ok(Self(make_map_with_root_and_bitwidth(
+            root,
+            store,
+            BALANCE_TABLE_BITWIDTH,
+        )?))
+    }
+
+    /// Balance of the participant, zero if it isn't one.
+    pub fn get(&self, key: &Address) -> anyhow::Result<TokenAmount> {
+        Ok(self.0.get(&key.to_bytes())?.cloned().unwrap_or_default())
+    }
+
+    pub fn for_each(
+        &self,
+        mut f: impl FnMut(Address, &TokenAmount) -> anyhow::Result<()>,
+    ) -> anyhow::Result<()> {
+        self.0
+            .for_each(|key, balance| f(Address::from_bytes(key)?, balance))?;
+        Ok(())
+    }
 }
 
-impl<BS> DealProposals<'_, BS> {
+pub struct DealProposals<'a, BS>(Array<'a, fil_actor_market_v9::DealProposal, BS>);
+
+impl<BS> DealProposals<'_, BS>
+where
+    BS: Blockstore,
+{
+    pub fn get(&self, deal_id: DealID) -> anyhow::Result<Option<DealProposal>> {
+        Ok(self.0.get(deal_id)?.cloned().map(From::from))
+    }
+
     pub fn for_each(
         &self,
-        _f: impl FnMut(u64, DealProposal) -> anyhow::Result<(), anyhow::Error>,
-    ) -> anyhow::Result<()>
-    where
-        BS: Blockstore,
-    {
-        unimplemented!()
+        mut f: impl FnMut(u64, DealProposal) -> anyhow::Result<(), anyhow::Error>,
+    ) -> anyhow::Result<()> {
+        self.0
+            .for_each(|deal_id, proposal| f(deal_id, proposal.clone().into()))?;
+        Ok(())
+    }
+
+    /// Calls `f` on the proposals in deal ID order from `start`, until it
+    /// returns `false`.
+    pub fn for_each_while_from(
+        &self,
+        start: DealID,
+        mut f: impl FnMut(u64, DealProposal) -> anyhow::Result<bool>,
+    ) -> anyhow::Result<()> {
+        self.0
+            .for_each_while_ranged(Some(start), None, |deal_id, proposal| {
+                f(deal_id, proposal.clone().into())
+            })?;
+        Ok(())
     }
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(rename_all = "PascalCase")]
 pub struct DealProposal {
     #[serde(with = "forest_json::cid", rename = "PieceCID")]
@@ -175,29 +276,69 @@ pub struct DealProposal {
     pub client: Address,
     #[serde(with = "forest_json::address::json")]
     pub provider: Address,
-    // ! This is the field that requires unsafe unchecked utf8 deserialization
     pub label: String,
     pub start_epoch: ChainEpoch,
     pub end_epoch: ChainEpoch,
+    #[serde(with = "forest_json::token_amount::json")]
     pub storage_price_per_epoch: TokenAmount,
+    #[serde(with = "forest_json::token_amount::json")]
     pub provider_collateral: TokenAmount,
+    #[serde(with = "forest_json::token_amount::json")]
     pub client_collateral: TokenAmount,
 }
 
+impl From<fil_actor_market_v9::DealProposal> for DealProposal {
+    fn from(proposal: fil_actor_market_v9::DealProposal) -> Self {
+        Self {
+            piece_cid: proposal.piece_cid,
+            piece_size: proposal.piece_size,
+            verified_deal: proposal.verified_deal,
+            client: proposal.client.into(),
+            provider: proposal.provider.into(),
+            // Labels are meant to be UTF-8 strings, the few that aren't are
+            // shown lossily.
+            label: match proposal.label {
+                fil_actor_market_v9::Label::String(label) => label,
+                fil_actor_market_v9::Label::Bytes(label) => {
+                    String::from_utf8_lossy(&label).into_owned()
+                }
+            },
+            start_epoch: proposal.start_epoch,
+            end_epoch: proposal.end_epoch,
+            storage_price_per_epoch: proposal.storage_price_per_epoch.into(),
+            provider_collateral: proposal.provider_collateral.into(),
+            client_collateral: proposal.client_collateral.into(),
+        }
+    }
+}
+
 pub enum DealStates<'a, BS> {
-    DealStates(PhantomData<&'a BS>),
+    V8(Array<'a, fil_actor_market_v8::DealState, BS>),
+    V9(Array<'a, fil_actor_market_v9::DealState, BS>),
 }
 
 impl<BS> DealStates<'_, BS>
 where
     BS: Blockstore,
 {
-    pub fn get(&self, _key: u64) -> anyhow::Result<Option<DealState>> {
-        unimplemented!()
+    /// State of the deal, if it has been activated.
+    pub fn get(&self, key: u64) -> anyhow::Result<Option<DealState>> {
+        Ok(match self {
+            DealStates::V8(states) => states.get(key)?.map(|state| DealState {
+                sector_start_epoch: state.sector_start_epoch,
+                last_updated_epoch: state.last_updated_epoch,
+                slash_epoch: state.slash_epoch,
+            }),
+            DealStates::V9(states) => states.get(key)?.map(|state| DealState {
+                sector_start_epoch: state.sector_start_epoch,
+                last_updated_epoch: state.last_updated_epoch,
+                slash_epoch: state.slash_epoch,
+            }),
+        })
     }
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(rename_all = "PascalCase")]
 pub struct DealState {
     pub sector_start_epoch: ChainEpoch, // -1 if not yet included in proven sector
@@ -205,11 +346,13 @@ pub struct DealState {
     pub slash_epoch: ChainEpoch,        // -1 if deal never slashed
 }
 
-impl<BS> BalanceTable<'_, BS>
-where
-    BS: Blockstore,
-{
-    pub fn get(&self, _key: &Address) -> anyhow::Result<TokenAmount> {
-        unimplemented!()
+impl DealState {
+    /// State of a deal that is yet to be activated.
+    pub fn unactivated() -> Self {
+        Self {
+            sector_start_epoch: -1,
+            last_updated_epoch: -1,
+            slash_epoch: -1,
+        }
     }
 }
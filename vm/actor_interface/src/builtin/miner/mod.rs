@@ -17,12 +17,19 @@ use forest_utils::db::BlockstoreExt;
 use fvm_ipld_bitfield::BitField;
 use fvm_ipld_blockstore::Blockstore;
 use fvm_ipld_encoding::BytesDe;
-use fvm_shared::{clock::ChainEpoch, deal::DealID, sector::SectorNumber};
+use fvm_shared::{
+    clock::ChainEpoch,
+    deal::DealID,
+    sector::{SectorNumber, StoragePower},
+};
 use libp2p::PeerId;
 use num::BigInt;
 use serde::{Deserialize, Serialize};
 
 use crate::power::Claim;
+/// Name of the miner actor in the built-in actors manifest.
+pub const ACTOR_NAME: &str = "storageminer";
+
 /// Miner actor method.
 pub type Method = fil_actor_miner_v8::Method;
 
@@ -215,6 +222,269 @@ impl State {
             State::V10(st) => st.fee_debt.clone().into(),
         }
     }
+
+    /// Loads the sector of the given number, if the miner has it.
+    pub fn get_sector<BS: Blockstore>(
+        &self,
+        store: &BS,
+        sector_number: SectorNumber,
+    ) -> anyhow::Result<Option<SectorOnChainInfo>> {
+        match self {
+            State::V8(st) => Ok(st.get_sector(store, sector_number)?.map(From::from)),
+            State::V9(st) => Ok(st.get_sector(store, sector_number)?.map(From::from)),
+            State::V10(st) => Ok(st.get_sector(store, sector_number)?.map(From::from)),
+        }
+    }
+
+    /// Returns the deadline of the proving period in progress at the epoch.
+    pub fn deadline_info(&self, policy: &Policy, current_epoch: ChainEpoch) -> DeadlineInfo {
+        match self {
+            State::V8(st) => st.deadline_info(policy, current_epoch).into(),
+            State::V9(st) => st.deadline_info(policy, current_epoch).into(),
+            State::V10(st) => st.deadline_info(policy, current_epoch).into(),
+        }
+    }
+
+    /// Funds of the miner locked for vesting.
+    pub fn locked_funds(&self) -> TokenAmount {
+        match self {
+            State::V8(st) => st.locked_funds.clone().into(),
+            State::V9(st) => st.locked_funds.clone().into(),
+            State::V10(st) => st.locked_funds.clone().into(),
+        }
+    }
+
+    /// Returns the part of the actor balance that isn't locked for vesting,
+    /// pre-commit deposits or initial pledges, nor owed as fee debt.
+    pub fn available_balance(&self, balance: &TokenAmount) -> TokenAmount {
+        let (pre_commit_deposits, initial_pledge): (TokenAmount, TokenAmount) = match self {
+            State::V8(st) => (
+                st.pre_commit_deposits.clone().into(),
+                st.initial_pledge.clone().into(),
+            ),
+            State::V9(st) => (
+                st.pre_commit_deposits.clone().into(),
+                st.initial_pledge.clone().into(),
+            ),
+            State::V10(st) => (
+                st.pre_commit_deposits.clone().into(),
+                st.initial_pledge.clone().into(),
+            ),
+        };
+        balance.clone()
+            - &self.locked_funds()
+            - &pre_commit_deposits
+            - &initial_pledge
+            - &self.fee_debt()
+    }
+
+    /// Loads the vesting schedule of the miner, as the amounts vesting at each
+    /// epoch.
+    pub fn vesting_funds<BS: Blockstore>(
+        &self,
+        store: &BS,
+    ) -> anyhow::Result<Vec<(ChainEpoch, TokenAmount)>> {
+        Ok(match self {
+            State::V8(st) => st
+                .load_vesting_funds(store)?
+                .funds
+                .into_iter()
+                .map(|fund| (fund.epoch, fund.amount.into()))
+                .collect(),
+            State::V9(st) => st
+                .load_vesting_funds(store)?
+                .funds
+                .into_iter()
+                .map(|fund| (fund.epoch, fund.amount.into()))
+                .collect(),
+            State::V10(st) => st
+                .load_vesting_funds(store)?
+                .funds
+                .into_iter()
+                .map(|fund| (fund.epoch, fund.amount.into()))
+                .collect(),
+        })
+    }
+
+    /// Returns the funds of the vesting schedule that have vested by the
+    /// epoch, but are yet to be unlocked by the miner actor.
+    pub fn vested_funds<BS: Blockstore>(
+        &self,
+        store: &BS,
+        epoch: ChainEpoch,
+    ) -> anyhow::Result<TokenAmount> {
+        let mut vested = TokenAmount::default();
+        for (vest_epoch, amount) in self.vesting_funds(store)? {
+            if vest_epoch < epoch {
+                vested += amount;
+            }
+        }
+        Ok(vested)
+    }
+
+    /// Quality adjusted power of a sector of the given size and duration,
+    /// holding deals of the given weights, as computed by the miner actor of
+    /// the same version.
+    pub fn qa_power_for_weight(
+        &self,
+        size: SectorSize,
+        duration: ChainEpoch,
+        deal_weight: &BigInt,
+        verified_weight: &BigInt,
+    ) -> StoragePower {
+        match self {
+            State::V8(_) => fil_actor_miner_v8::qa_power_for_weight(
+                size.into(),
+                duration,
+                deal_weight,
+                verified_weight,
+            ),
+            State::V9(_) => fil_actor_miner_v9::qa_power_for_weight(
+                size.into(),
+                duration,
+                deal_weight,
+                verified_weight,
+            ),
+            State::V10(_) => fil_actor_miner_v10::qa_power_for_weight(
+                *size,
+                duration,
+                deal_weight,
+                verified_weight,
+            ),
+        }
+    }
+}
+
+/// Timing of a deadline of a proving period of a miner.
+#[derive(Debug, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct DeadlineInfo {
+    /// Epoch at which the info was calculated
+    pub current_epoch: ChainEpoch,
+    /// First epoch of the proving period
+    pub period_start: ChainEpoch,
+    /// Index of the deadline in the proving period
+    pub index: u64,
+    /// First epoch from which a proof may be submitted
+    pub open: ChainEpoch,
+    /// First epoch from which a proof may no longer be submitted
+    pub close: ChainEpoch,
+    /// Epoch at which to sample the chain for challenge
+    pub challenge: ChainEpoch,
+    /// First epoch at which a fault declaration is rejected
+    pub fault_cutoff: ChainEpoch,
+    #[serde(rename = "WPoStPeriodDeadlines")]
+    pub wpost_period_deadlines: u64,
+    #[serde(rename = "WPoStProvingPeriod")]
+    pub wpost_proving_period: ChainEpoch,
+    #[serde(rename = "WPoStChallengeWindow")]
+    pub wpost_challenge_window: ChainEpoch,
+    #[serde(rename = "WPoStChallengeLookback")]
+    pub wpost_challenge_lookback: ChainEpoch,
+    pub fault_declaration_cutoff: ChainEpoch,
+}
+
+impl DeadlineInfo {
+    pub fn new(
+        policy: &Policy,
+        period_start: ChainEpoch,
+        index: u64,
+        current_epoch: ChainEpoch,
+    ) -> Self {
+        let open = period_start + index as ChainEpoch * policy.wpost_challenge_window;
+        Self {
+            current_epoch,
+            period_start,
+            index,
+            open,
+            close: open + policy.wpost_challenge_window,
+            challenge: open - policy.wpost_challenge_lookback,
+            fault_cutoff: open - policy.fault_declaration_cutoff,
+            wpost_period_deadlines: policy.wpost_period_deadlines,
+            wpost_proving_period: policy.wpost_proving_period,
+            wpost_challenge_window: policy.wpost_challenge_window,
+            wpost_challenge_lookback: policy.wpost_challenge_lookback,
+            fault_declaration_cutoff: policy.fault_declaration_cutoff,
+        }
+    }
+
+    /// Whether the deadline is closed at the current epoch.
+    pub fn has_elapsed(&self) -> bool {
+        self.current_epoch >= self.close
+    }
+
+    /// Returns the next instance of the deadline that hasn't closed yet, which
+    /// is the deadline itself if it is still open.
+    pub fn next_not_elapsed(self, policy: &Policy) -> Self {
+        if !self.has_elapsed() {
+            return self;
+        }
+        let gap = self.current_epoch - self.close;
+        let delta = (gap / self.wpost_proving_period + 1) * self.wpost_proving_period;
+        Self::new(
+            policy,
+            self.period_start + delta,
+            self.index,
+            self.current_epoch,
+        )
+    }
+}
+
+impl From<fil_actor_miner_v8::DeadlineInfo> for DeadlineInfo {
+    fn from(info: fil_actor_miner_v8::DeadlineInfo) -> Self {
+        Self {
+            current_epoch: info.current_epoch,
+            period_start: info.period_start,
+            index: info.index,
+            open: info.open,
+            close: info.close,
+            challenge: info.challenge,
+            fault_cutoff: info.fault_cutoff,
+            wpost_period_deadlines: info.w_post_period_deadlines,
+            wpost_proving_period: info.w_post_proving_period,
+            wpost_challenge_window: info.w_post_challenge_window,
+            wpost_challenge_lookback: info.w_post_challenge_lookback,
+            fault_declaration_cutoff: info.fault_declaration_cutoff,
+        }
+    }
+}
+
+impl From<fil_actor_miner_v9::DeadlineInfo> for DeadlineInfo {
+    fn from(info: fil_actor_miner_v9::DeadlineInfo) -> Self {
+        Self {
+            current_epoch: info.current_epoch,
+            period_start: info.period_start,
+            index: info.index,
+            open: info.open,
+            close: info.close,
+            challenge: info.challenge,
+            fault_cutoff: info.fault_cutoff,
+            wpost_period_deadlines: info.w_post_period_deadlines,
+            wpost_proving_period: info.w_post_proving_period,
+            wpost_challenge_window: info.w_post_challenge_window,
+            wpost_challenge_lookback: info.w_post_challenge_lookback,
+            fault_declaration_cutoff: info.fault_declaration_cutoff,
+        }
+    }
+}
+
+impl From<fil_actor_miner_v10::DeadlineInfo> for DeadlineInfo {
+    fn from(info: fil_actor_miner_v10::DeadlineInfo) -> Self {
+        Self {
+            current_epoch: info.current_epoch,
+            period_start: info.period_start,
+            index: info.index,
+            open: info.open,
+            close: info.close,
+            challenge: info.challenge,
+            fault_cutoff: info.fault_cutoff,
+            wpost_period_deadlines: info.w_post_period_deadlines,
+            wpost_proving_period: info.w_post_proving_period,
+            wpost_challenge_window: info.w_post_challenge_window,
+            wpost_challenge_lookback: info.w_post_challenge_lookback,
+            fault_declaration_cutoff: info.fault_declaration_cutoff,
+        }
+    }
 }
 
 /// Static information about miner
@@ -357,6 +627,34 @@ pub enum Deadline {
 }
 
 impl Deadline {
+    /// Partitions for which a proof has been submitted in the current proving
+    /// period.
+    pub fn partitions_posted(&self) -> &BitField {
+        match self {
+            Deadline::V8(dl) => &dl.partitions_posted,
+            Deadline::V9(dl) => &dl.partitions_posted,
+            Deadline::V10(dl) => &dl.partitions_posted,
+        }
+    }
+
+    /// Number of proofs submitted in the previous proving period that can
+    /// still be disputed.
+    pub fn disputable_proof_count<BS: Blockstore>(&self, store: &BS) -> anyhow::Result<u64> {
+        let snapshot = match self {
+            Deadline::V8(dl) => &dl.optimistic_post_submissions_snapshot,
+            Deadline::V9(dl) => &dl.optimistic_post_submissions_snapshot,
+            Deadline::V10(dl) => &dl.optimistic_post_submissions_snapshot,
+        };
+        // The schema of the submitted proofs is the same in all the supported
+        // actors versions.
+        Ok(
+            fil_actors_runtime_v9::Array::<fil_actor_miner_v9::WindowedPoSt, _>::load(
+                snapshot, store,
+            )?
+            .count(),
+        )
+    }
+
     /// For each partition of the deadline
     pub fn for_each<BS: Blockstore>(
         &self,
@@ -400,6 +698,13 @@ impl Partition<'_> {
             Partition::V10(dl) => &dl.faults,
         }
     }
+    pub fn recovering_sectors(&self) -> &BitField {
+        match self {
+            Partition::V8(dl) => &dl.recoveries,
+            Partition::V9(dl) => &dl.recoveries,
+            Partition::V10(dl) => &dl.recoveries,
+        }
+    }
     pub fn live_sectors(&self) -> BitField {
         match self {
             Partition::V8(dl) => dl.live_sectors(),
@@ -436,8 +741,9 @@ pub struct SectorOnChainInfo {
     /// The seal proof type implies the PoSt proofs
     pub seal_proof: RegisteredSealProof,
     /// `CommR`
-    #[serde(with = "forest_json::cid")]
+    #[serde(with = "forest_json::cid", rename = "SealedCID")]
     pub sealed_cid: Cid,
+    #[serde(rename = "DealIDs")]
     pub deal_ids: Vec<DealID>,
     /// Epoch during which the sector proof was accepted
     pub activation: ChainEpoch,
@@ -515,3 +821,30 @@ impl From<fil_actor_miner_v10::SectorOnChainInfo> for SectorOnChainInfo {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deadline_info_next_not_elapsed() {
+        let policy = Policy::mainnet();
+        let window = policy.wpost_challenge_window;
+
+        let info = DeadlineInfo::new(&policy, 100, 2, 100 + 2 * window);
+        assert_eq!(info.open, 100 + 2 * window);
+        assert_eq!(info.close, 100 + 3 * window);
+        assert!(!info.has_elapsed());
+        assert_eq!(
+            DeadlineInfo::new(&policy, 100, 2, 100 + 2 * window).next_not_elapsed(&policy),
+            info
+        );
+
+        let elapsed = DeadlineInfo::new(&policy, 100, 2, 100 + 3 * window);
+        assert!(elapsed.has_elapsed());
+        let next = elapsed.next_not_elapsed(&policy);
+        assert_eq!(next.period_start, 100 + policy.wpost_proving_period);
+        assert_eq!(next.index, 2);
+        assert!(!next.has_elapsed());
+    }
+}
@@ -3,13 +3,16 @@
 
 pub mod account;
 pub mod cron;
+pub mod datacap;
 pub mod init;
 pub mod market;
 pub mod miner;
 pub mod multisig;
+pub mod paych;
 pub mod power;
 pub mod reward;
 pub mod system;
+pub mod verifreg;
 
 use cid::Cid;
 pub use fil_actor_reward_v8::AwardBlockRewardParams;
@@ -1,2 +1,188 @@
 // Copyright 2019-2023 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
+
+use anyhow::Context;
+use cid::Cid;
+pub use fil_actor_multisig_v9::{
+    AddSignerParams, ApproveReturn, ConstructorParams, ProposeParams, ProposeReturn,
+    SwapSignerParams, TxnID, TxnIDParams,
+};
+use forest_shim::{address::Address, econ::TokenAmount, state_tree::ActorState};
+use forest_utils::db::BlockstoreExt;
+use fvm_ipld_blockstore::Blockstore;
+use fvm_shared::{clock::ChainEpoch, MethodNum};
+use serde::Serialize;
+
+/// Name of the multisig actor in the built-in actors manifest.
+pub const ACTOR_NAME: &str = "multisig";
+
+/// Multisig actor method.
+pub type Method = fil_actor_multisig_v9::Method;
+
+/// Multisig actor state. Its schema is the same in all the supported actors
+/// versions, so it is decoded as a v9 state once the actor code is checked.
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum State {
+    V9(fil_actor_multisig_v9::State),
+}
+
+/// Transaction proposed to a multisig, awaiting approvals.
+pub struct Transaction {
+    pub id: i64,
+    pub to: Address,
+    pub value: TokenAmount,
+    pub method: MethodNum,
+    pub params: Vec<u8>,
+    pub approved: Vec<Address>,
+}
+
+/// Linear vesting of the initial balance of a multisig.
+pub struct VestingSchedule {
+    pub initial_balance: TokenAmount,
+    pub start_epoch: ChainEpoch,
+    pub unlock_duration: ChainEpoch,
+}
+
+pub fn is_v8_multisig_cid(cid: &Cid) -> bool {
+    let known_cids = vec![
+        // calibnet v8
+        Cid::try_from("bafk2bzacec66wmb4kohuzvuxsulhcgiwju7sqkldwfpmmgw7dbbwgm5l2574q").unwrap(),
+        // mainnet
+        Cid::try_from("bafk2bzacebhldfjuy4o5v7amrhp5p2gzv2qo5275jut4adnbyp56fxkwy5fag").unwrap(),
+        // devnet
+        Cid::try_from("bafk2bzaced4gcxjwy6garxwfw6y5a2k4jewj4t5nzopjy4qwnimhjtnsgo3ss").unwrap(),
+    ];
+    known_cids.contains(cid)
+}
+
+pub fn is_v9_multisig_cid(cid: &Cid) -> bool {
+    let known_cids = vec![
+        // calibnet v9
+        Cid::try_from("bafk2bzacec6gmi7ucukr3bk67akaxwngohw3lsg3obvdazhmfhdzflkszk3tg").unwrap(),
+        // mainnet v9
+        Cid::try_from("bafk2bzacec4va3nmugyqjqrs3lqyr2ij67jhjia5frvx7omnh2isha6abxzya").unwrap(),
+    ];
+    known_cids.contains(cid)
+}
+
+pub fn is_v10_multisig_cid(cid: &Cid) -> bool {
+    let known_cids = vec![
+        // calibnet v10
+        Cid::try_from("bafk2bzacebv5gdlte2pyovmz6s37me6x2rixaa6a33w6lgqdohmycl23snvwm").unwrap(),
+        // mainnet v10
+        Cid::try_from("bafk2bzaceduf3hayh63jnl4z2knxv7cnrdenoubni22fxersc4octlwpxpmy4").unwrap(),
+    ];
+    known_cids.contains(cid)
+}
+
+impl State {
+    pub fn load<BS>(store: &BS, actor: &ActorState) -> anyhow::Result<State>
+    where
+        BS: Blockstore,
+    {
+        if is_v8_multisig_cid(&actor.code)
+            || is_v9_multisig_cid(&actor.code)
+            || is_v10_multisig_cid(&actor.code)
+        {
+            return store
+                .get_obj(&actor.state)?
+                .map(State::V9)
+                .context("Actor state doesn't exist in store");
+        }
+        Err(anyhow::anyhow!(
+            "Unknown multisig actor code {}",
+            actor.code
+        ))
+    }
+
+    /// Addresses allowed to propose and approve transactions.
+    pub fn signers(&self) -> Vec<Address> {
+        match self {
+            State::V9(st) => st.signers.iter().copied().map(Address::from).collect(),
+        }
+    }
+
+    /// Number of approvals required to execute a transaction.
+    pub fn threshold(&self) -> u64 {
+        match self {
+            State::V9(st) => st.num_approvals_threshold,
+        }
+    }
+
+    pub fn vesting_schedule(&self) -> VestingSchedule {
+        match self {
+            State::V9(st) => VestingSchedule {
+                initial_balance: st.initial_balance.clone().into(),
+                start_epoch: st.start_epoch,
+                unlock_duration: st.unlock_duration,
+            },
+        }
+    }
+
+    /// Part of the initial balance still locked at the given epoch.
+    pub fn locked_balance(&self, epoch: ChainEpoch) -> TokenAmount {
+        match self {
+            State::V9(st) => st.amount_locked(epoch - st.start_epoch).into(),
+        }
+    }
+
+    /// Transactions proposed and not executed nor cancelled yet, by ID.
+    pub fn pending_transactions<BS: Blockstore>(
+        &self,
+        store: &BS,
+    ) -> anyhow::Result<Vec<Transaction>> {
+        match self {
+            State::V9(st) => {
+                let pending = fil_actors_runtime_v9::make_map_with_root::<
+                    _,
+                    fil_actor_multisig_v9::Transaction,
+                >(&st.pending_txs, store)?;
+                let mut transactions = vec![];
+                pending.for_each(|key, txn| {
+                    transactions.push(Transaction {
+                        id: parse_txn_id(key)?,
+                        to: txn.to.into(),
+                        value: txn.value.clone().into(),
+                        method: txn.method,
+                        params: txn.params.to_vec(),
+                        approved: txn.approved.iter().copied().map(Address::from).collect(),
+                    });
+                    Ok(())
+                })?;
+                transactions.sort_by_key(|txn| txn.id);
+                Ok(transactions)
+            }
+        }
+    }
+}
+
+/// Transaction IDs are keyed by their signed varint encoding in the pending
+/// transactions map.
+fn parse_txn_id(key: &[u8]) -> anyhow::Result<i64> {
+    let mut value = 0u64;
+    for (i, byte) in key.iter().enumerate() {
+        anyhow::ensure!(i < 10, "Transaction ID key overflows");
+        value |= u64::from(byte & 0x7f) << (7 * i);
+        if byte & 0x80 == 0 {
+            anyhow::ensure!(i + 1 == key.len(), "Trailing bytes in transaction ID key");
+            return Ok((value >> 1) as i64 ^ -((value & 1) as i64));
+        }
+    }
+    anyhow::bail!("Truncated transaction ID key")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_txn_ids() {
+        assert_eq!(parse_txn_id(&[0x00]).unwrap(), 0);
+        assert_eq!(parse_txn_id(&[0x01]).unwrap(), -1);
+        assert_eq!(parse_txn_id(&[0x02]).unwrap(), 1);
+        assert_eq!(parse_txn_id(&[0xac, 0x02]).unwrap(), 150);
+        assert!(parse_txn_id(&[0x80]).is_err());
+        assert!(parse_txn_id(&[0x02, 0x00]).is_err());
+    }
+}
@@ -0,0 +1,176 @@
+// Copyright 2019-2023 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Payment channel actor types. The payment channel actor isn't among the
+//! actor crates of the workspace, so its state and parameters are mirrored
+//! here. Their schema is the same in all the supported actors versions.
+
+use anyhow::Context;
+use cid::Cid;
+use forest_shim::{address::Address, crypto::Signature, econ::TokenAmount, state_tree::ActorState};
+use forest_utils::db::BlockstoreExt;
+use fvm_ipld_blockstore::Blockstore;
+use fvm_ipld_encoding::{
+    strict_bytes,
+    tuple::{Deserialize_tuple, Serialize_tuple},
+    RawBytes,
+};
+use fvm_shared::{clock::ChainEpoch, MethodNum};
+
+/// Name of the payment channel actor in the built-in actors manifest.
+pub const ACTOR_NAME: &str = "paymentchannel";
+
+/// Payment channel actor method.
+#[repr(u64)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Method {
+    Constructor = 1,
+    UpdateChannelState = 2,
+    Settle = 3,
+    Collect = 4,
+}
+
+/// Payment channel actor state.
+#[derive(Serialize_tuple, Deserialize_tuple, Clone, Debug)]
+pub struct State {
+    /// Channel owner, who has funded the channel.
+    pub from: Address,
+    /// Recipient of payouts from the channel.
+    pub to: Address,
+    /// Amount successfully redeemed through the channel, paid out on collect.
+    pub to_send: TokenAmount,
+    /// Height at which the channel can be collected, 0 if not settling.
+    pub settling_at: ChainEpoch,
+    /// Height before which the channel cannot be collected.
+    pub min_settle_height: ChainEpoch,
+    /// `AMT` of the [`LaneState`]s, by lane ID.
+    pub lane_states: Cid,
+}
+
+/// State of a lane of a payment channel, updated by redeeming vouchers.
+#[derive(Serialize_tuple, Deserialize_tuple, Clone, Debug, Default, PartialEq, Eq)]
+pub struct LaneState {
+    pub redeemed: TokenAmount,
+    pub nonce: u64,
+}
+
+/// Specifies an actor method to call to validate a voucher.
+#[derive(Serialize_tuple, Deserialize_tuple, Clone, Debug, PartialEq, Eq)]
+pub struct ModVerifyParams {
+    pub actor: Address,
+    pub method: MethodNum,
+    pub data: RawBytes,
+}
+
+/// Lane and nonce of another lane merged into the lane of a voucher.
+#[derive(Serialize_tuple, Deserialize_tuple, Clone, Debug, PartialEq, Eq)]
+pub struct Merge {
+    pub lane: u64,
+    pub nonce: u64,
+}
+
+/// Voucher for an amount of the channel, signed by the channel owner.
+#[derive(Serialize_tuple, Deserialize_tuple, Clone, Debug, PartialEq, Eq)]
+pub struct SignedVoucher {
+    pub channel_addr: Address,
+    pub time_lock_min: ChainEpoch,
+    /// Max epoch at which the voucher can be redeemed, 0 for no limit.
+    pub time_lock_max: ChainEpoch,
+    #[serde(with = "strict_bytes")]
+    pub secret_pre_image: Vec<u8>,
+    pub extra: Option<ModVerifyParams>,
+    pub lane: u64,
+    pub nonce: u64,
+    /// Total amount redeemable by the lane, including by previous vouchers.
+    pub amount: TokenAmount,
+    pub min_settle_height: ChainEpoch,
+    pub merges: Vec<Merge>,
+    pub signature: Option<Signature>,
+}
+
+impl SignedVoucher {
+    /// Bytes signed by the channel owner: the voucher without its signature.
+    pub fn signing_bytes(&self) -> anyhow::Result<Vec<u8>> {
+        let unsigned = SignedVoucher {
+            signature: None,
+            ..self.clone()
+        };
+        Ok(fvm_ipld_encoding::to_vec(&unsigned)?)
+    }
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple, Clone, Debug)]
+pub struct ConstructorParams {
+    pub from: Address,
+    pub to: Address,
+}
+
+#[derive(Serialize_tuple, Deserialize_tuple, Clone, Debug)]
+pub struct UpdateChannelStateParams {
+    pub sv: SignedVoucher,
+    #[serde(with = "strict_bytes")]
+    pub secret: Vec<u8>,
+}
+
+pub fn is_v8_paych_cid(cid: &Cid) -> bool {
+    let known_cids = vec![
+        // calibnet v8
+        Cid::try_from("bafk2bzaceblot4pemhfgwb3lceellwrpgxaqkpselzbpqu32maffpopdunlha").unwrap(),
+        // mainnet
+        Cid::try_from("bafk2bzacebalad3f72wyk7qyilvfjijcwubdspytnyzlrhvn73254gqis44rq").unwrap(),
+        // devnet
+        Cid::try_from("bafk2bzaceb3isfguytt6cs4xecyoonbhhekmngfbap2msggbwyde7zch3a6w4").unwrap(),
+    ];
+    known_cids.contains(cid)
+}
+
+pub fn is_v9_paych_cid(cid: &Cid) -> bool {
+    let known_cids = vec![
+        // calibnet v9
+        Cid::try_from("bafk2bzacec4kg3bfjtssvv2b4wizlbdk3pdtrg5aknzgeb3a6rmksgurpynca").unwrap(),
+        // mainnet v9
+        Cid::try_from("bafk2bzacebhdvjbjcgupklddfavzef4e4gnkt3xk3rbmgfmk7xhecszhfxeds").unwrap(),
+    ];
+    known_cids.contains(cid)
+}
+
+pub fn is_v10_paych_cid(cid: &Cid) -> bool {
+    let known_cids = vec![
+        // calibnet v10
+        Cid::try_from("bafk2bzacea7ngq44gedftjlar3j3ql3dmd7e7xkkb6squgxinfncybfmppmlc").unwrap(),
+        // mainnet v10
+        Cid::try_from("bafk2bzaceartlg4mrbwgzcwric6mtvyawpbgx2xclo2vj27nna57nxynf3pgc").unwrap(),
+    ];
+    known_cids.contains(cid)
+}
+
+impl State {
+    pub fn load<BS>(store: &BS, actor: &ActorState) -> anyhow::Result<State>
+    where
+        BS: Blockstore,
+    {
+        if is_v8_paych_cid(&actor.code)
+            || is_v9_paych_cid(&actor.code)
+            || is_v10_paych_cid(&actor.code)
+        {
+            return store
+                .get_obj(&actor.state)?
+                .context("Actor state doesn't exist in store");
+        }
+        Err(anyhow::anyhow!(
+            "Unknown payment channel actor code {}",
+            actor.code
+        ))
+    }
+
+    /// Loads the states of the lanes of the channel, by lane ID.
+    pub fn lane_states<BS: Blockstore>(&self, store: &BS) -> anyhow::Result<Vec<(u64, LaneState)>> {
+        let lanes = fil_actors_runtime_v9::Array::<LaneState, _>::load(&self.lane_states, store)?;
+        let mut states = vec![];
+        lanes.for_each(|lane, state| {
+            states.push((lane, state.clone()));
+            Ok(())
+        })?;
+        Ok(states)
+    }
+}
@@ -6,8 +6,11 @@ use cid::Cid;
 use forest_shim::{address::Address, econ::TokenAmount, state_tree::ActorState};
 use forest_utils::db::BlockstoreExt;
 use fvm_ipld_blockstore::Blockstore;
+use fvm_shared::sector::StoragePower;
 use serde::Serialize;
 
+use crate::FilterEstimate;
+
 /// Reward actor address.
 pub const ADDRESS: Address = Address::new_id(2);
 
@@ -89,4 +92,86 @@ impl State {
             State::V10(st) => st.into_total_storage_power_reward().into(),
         }
     }
+    /// Returns `this_epoch_baseline_power` from the state.
+    pub fn this_epoch_baseline_power(&self) -> &StoragePower {
+        match self {
+            State::V8(st) => &st.this_epoch_baseline_power,
+            State::V9(st) => &st.this_epoch_baseline_power,
+            State::V10(st) => &st.this_epoch_baseline_power,
+        }
+    }
+
+    /// Returns `this_epoch_reward_smoothed` from the state.
+    pub fn this_epoch_reward_smoothed(&self) -> &FilterEstimate {
+        match self {
+            State::V8(st) => &st.this_epoch_reward_smoothed,
+            State::V9(st) => &st.this_epoch_reward_smoothed,
+            State::V10(st) => &st.this_epoch_reward_smoothed,
+        }
+    }
+
+    /// Initial pledge collateral required for a sector of the given quality
+    /// adjusted power, as computed by the miner actor of the same version.
+    pub fn initial_pledge_for_power(
+        &self,
+        qa_power: &StoragePower,
+        network_qa_power: &FilterEstimate,
+        circulating_supply: &TokenAmount,
+    ) -> TokenAmount {
+        match self {
+            State::V8(st) => fil_actor_miner_v8::initial_pledge_for_power(
+                qa_power,
+                &st.this_epoch_baseline_power,
+                &st.this_epoch_reward_smoothed,
+                network_qa_power,
+                &circulating_supply.into(),
+            )
+            .into(),
+            State::V9(st) => fil_actor_miner_v9::initial_pledge_for_power(
+                qa_power,
+                &st.this_epoch_baseline_power,
+                &st.this_epoch_reward_smoothed,
+                network_qa_power,
+                &circulating_supply.into(),
+            )
+            .into(),
+            State::V10(st) => fil_actor_miner_v10::initial_pledge_for_power(
+                qa_power,
+                &st.this_epoch_baseline_power,
+                &st.this_epoch_reward_smoothed,
+                network_qa_power,
+                &circulating_supply.clone().into(),
+            )
+            .into(),
+        }
+    }
+
+    /// Deposit required to pre-commit a sector of the given quality adjusted
+    /// power, as computed by the miner actor of the same version.
+    pub fn pre_commit_deposit_for_power(
+        &self,
+        network_qa_power: &FilterEstimate,
+        qa_power: &StoragePower,
+    ) -> TokenAmount {
+        match self {
+            State::V8(st) => fil_actor_miner_v8::pre_commit_deposit_for_power(
+                &st.this_epoch_reward_smoothed,
+                network_qa_power,
+                qa_power,
+            )
+            .into(),
+            State::V9(st) => fil_actor_miner_v9::pre_commit_deposit_for_power(
+                &st.this_epoch_reward_smoothed,
+                network_qa_power,
+                qa_power,
+            )
+            .into(),
+            State::V10(st) => fil_actor_miner_v10::pre_commit_deposit_for_power(
+                &st.this_epoch_reward_smoothed,
+                network_qa_power,
+                qa_power,
+            )
+            .into(),
+        }
+    }
 }
@@ -1,7 +1,53 @@
 // Copyright 2019-2023 ChainSafe Systems
 // SPDX-License-Identifier: Apache-2.0, MIT
 
-use forest_shim::address::Address;
+use anyhow::Context;
+use cid::Cid;
+use forest_shim::{address::Address, state_tree::ActorState};
+use forest_utils::db::BlockstoreExt;
+use fvm_ipld_blockstore::Blockstore;
+use serde::Serialize;
 
 /// System actor address.
 pub const ADDRESS: Address = Address::new_id(0);
+
+/// Name of the system actor in the built-in actors manifest.
+pub const ACTOR_NAME: &str = "system";
+
+/// System actor state. Its schema is the same in all the supported actors
+/// versions, so it is decoded as a v9 state once the actor code is checked
+/// against the manifest the state points to.
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum State {
+    V9(fil_actor_system_v9::State),
+}
+
+impl State {
+    pub fn load<BS>(store: &BS, actor: &ActorState) -> anyhow::Result<State>
+    where
+        BS: Blockstore,
+    {
+        let state = store
+            .get_obj(&actor.state)
+            .with_context(|| format!("Unknown system actor code {}", actor.code))?
+            .map(State::V9)
+            .context("Actor state doesn't exist in store")?;
+        let is_system = state
+            .builtin_actors(store)?
+            .iter()
+            .any(|(name, code)| name == ACTOR_NAME && *code == actor.code);
+        anyhow::ensure!(is_system, "Unknown system actor code {}", actor.code);
+        Ok(state)
+    }
+
+    /// Names and code CIDs of the built-in actors of the network, as listed
+    /// in the manifest of the actors bundle.
+    pub fn builtin_actors<BS: Blockstore>(&self, store: &BS) -> anyhow::Result<Vec<(String, Cid)>> {
+        match self {
+            State::V9(st) => store
+                .get_obj(&st.builtin_actors)?
+                .context("Built-in actors manifest doesn't exist in store"),
+        }
+    }
+}
@@ -0,0 +1,242 @@
+// Copyright 2019-2023 ChainSafe Systems
+// SPDX-License-Identifier: Apache-2.0, MIT
+
+//! Verified registry actor types. The verified registry actor isn't among the
+//! actor crates of the workspace, so its state is mirrored here.
+
+use anyhow::Context;
+use cid::Cid;
+use fil_actors_runtime_v9::{make_map_with_root, parse_uint_key, u64_key};
+use forest_shim::{address::Address, state_tree::ActorState};
+use forest_utils::db::BlockstoreExt;
+use fvm_ipld_blockstore::Blockstore;
+use fvm_ipld_encoding::tuple::{Deserialize_tuple, Serialize_tuple};
+use fvm_shared::{
+    bigint::bigint_ser, clock::ChainEpoch, piece::PaddedPieceSize, sector::SectorNumber, ActorID,
+};
+use num::BigInt;
+use serde::{Deserialize, Serialize};
+
+/// Verified registry actor address.
+pub const ADDRESS: Address = Address::new_id(6);
+
+pub type AllocationID = u64;
+pub type ClaimID = u64;
+
+/// Verified registry state of actors v8, where the registry holds the data cap
+/// of the verified clients.
+#[derive(Serialize_tuple, Deserialize_tuple, Clone, Debug)]
+pub struct StateV8 {
+    pub root_key: Address,
+    /// `HAMT` of the data cap of the verifiers, by ID address.
+    pub verifiers: Cid,
+    /// `HAMT` of the data cap of the verified clients, by ID address.
+    pub verified_clients: Cid,
+    pub remove_data_cap_proposal_ids: Cid,
+}
+
+/// Verified registry state of actors v9 and later, where the data cap of the
+/// verified clients is held by the `DataCap` actor.
+#[derive(Serialize_tuple, Deserialize_tuple, Clone, Debug)]
+pub struct StateV9 {
+    pub root_key: Address,
+    /// `HAMT` of the data cap of the verifiers, by ID address.
+    pub verifiers: Cid,
+    pub remove_data_cap_proposal_ids: Cid,
+    /// `HAMT` of the [`Allocation`]s of each client, by client and allocation
+    /// ID.
+    pub allocations: Cid,
+    pub next_allocation_id: AllocationID,
+    /// `HAMT` of the [`Claim`]s of each provider, by provider and claim ID.
+    pub claims: Cid,
+}
+
+/// Verified registry actor state.
+#[derive(Serialize)]
+#[serde(untagged)]
+pub enum State {
+    V8(StateV8),
+    V9(StateV9),
+}
+
+/// Data cap of a client allocated to a provider, for the provider to claim by
+/// sealing the data in a sector.
+#[derive(Serialize_tuple, Deserialize_tuple, Clone, Debug, PartialEq, Eq)]
+pub struct Allocation {
+    pub client: ActorID,
+    pub provider: ActorID,
+    pub data: Cid,
+    pub size: PaddedPieceSize,
+    pub term_min: ChainEpoch,
+    pub term_max: ChainEpoch,
+    /// Epoch by which the allocation must be claimed.
+    pub expiration: ChainEpoch,
+}
+
+/// Allocation claimed by a provider, which must keep the data sealed for its
+/// term.
+#[derive(Serialize_tuple, Deserialize_tuple, Clone, Debug, PartialEq, Eq)]
+pub struct Claim {
+    pub provider: ActorID,
+    pub client: ActorID,
+    pub data: Cid,
+    pub size: PaddedPieceSize,
+    pub term_min: ChainEpoch,
+    pub term_max: ChainEpoch,
+    pub term_start: ChainEpoch,
+    pub sector: SectorNumber,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(transparent)]
+struct DataCap(#[serde(with = "bigint_ser")] BigInt);
+
+pub fn is_v8_verifreg_cid(cid: &Cid) -> bool {
+    let known_cids = vec![
+        // calibnet v8
+        Cid::try_from("bafk2bzaceaihibfu625lbtzdp3tcftscshrmbgghgrc7kzqhxn4455pycpdkm").unwrap(),
+        // mainnet
+        Cid::try_from("bafk2bzaceb3zbkjz3auizmoln2unmxep7dyfcmsre64vnqfhdyh7rkqfoxlw4").unwrap(),
+        // devnet
+        Cid::try_from("bafk2bzaceaajgtglewgitshgdi2nzrvq7eihjtyqj5yiamesqun2hujl3xev2").unwrap(),
+    ];
+    known_cids.contains(cid)
+}
+
+pub fn is_v9_verifreg_cid(cid: &Cid) -> bool {
+    let known_cids = vec![
+        // calibnet v9
+        Cid::try_from("bafk2bzacebh7dj6j7yi5vadh7lgqjtq42qi2uq4n6zy2g5vjeathacwn2tscu").unwrap(),
+        // mainnet v9
+        Cid::try_from("bafk2bzacecf3yodlyudzukumehbuabgqljyhjt5ifiv4vetcfohnvsxzynwga").unwrap(),
+    ];
+    known_cids.contains(cid)
+}
+
+pub fn is_v10_verifreg_cid(cid: &Cid) -> bool {
+    let known_cids = vec![
+        // calibnet v10
+        Cid::try_from("bafk2bzacec67wuchq64k7kgrujguukjvdlsl24pgighqdx5vgjhyk6bycrwnc").unwrap(),
+        // mainnet v10
+        Cid::try_from("bafk2bzacedfel6edzqpe5oujno7fog4i526go4dtcs6vwrdtbpy2xq6htvcg6").unwrap(),
+    ];
+    known_cids.contains(cid)
+}
+
+impl State {
+    /// Loads the state. It is unchanged in v10, so v10 states are decoded as
+    /// v9 ones.
+    pub fn load<BS>(store: &BS, actor: &ActorState) -> anyhow::Result<State>
+    where
+        BS: Blockstore,
+    {
+        if is_v8_verifreg_cid(&actor.code) {
+            return store
+                .get_obj(&actor.state)?
+                .map(State::V8)
+                .context("Actor state doesn't exist in store");
+        }
+        if is_v9_verifreg_cid(&actor.code) || is_v10_verifreg_cid(&actor.code) {
+            return store
+                .get_obj(&actor.state)?
+                .map(State::V9)
+                .context("Actor state doesn't exist in store");
+        }
+        Err(anyhow::anyhow!(
+            "Unknown verified registry actor code {}",
+            actor.code
+        ))
+    }
+
+    /// Address allowed to add and remove verifiers.
+    pub fn root_key(&self) -> Address {
+        match self {
+            State::V8(st) => st.root_key,
+            State::V9(st) => st.root_key,
+        }
+    }
+
+    /// Data cap of the verifier of the given ID address, if it is a verifier.
+    pub fn verifier_data_cap<BS: Blockstore>(
+        &self,
+        store: &BS,
+        verifier: &Address,
+    ) -> anyhow::Result<Option<BigInt>> {
+        let verifiers = match self {
+            State::V8(st) => &st.verifiers,
+            State::V9(st) => &st.verifiers,
+        };
+        get_data_cap(store, verifiers, verifier)
+    }
+
+    /// Data cap of the verified client of the given ID address, if it is a
+    /// verified client. Only known to the v8 registry, see
+    /// [`crate::datacap::State::balance`] for later versions.
+    pub fn verified_client_data_cap<BS: Blockstore>(
+        &self,
+        store: &BS,
+        client: &Address,
+    ) -> anyhow::Result<Option<BigInt>> {
+        match self {
+            State::V8(st) => get_data_cap(store, &st.verified_clients, client),
+            State::V9(_) => {
+                anyhow::bail!("Data cap of verified clients is held by the DataCap actor")
+            }
+        }
+    }
+
+    /// Allocations of the client of the given actor ID, by allocation ID.
+    pub fn allocations<BS: Blockstore>(
+        &self,
+        store: &BS,
+        client: ActorID,
+    ) -> anyhow::Result<Vec<(AllocationID, Allocation)>> {
+        match self {
+            State::V8(_) => Ok(vec![]),
+            State::V9(st) => load_inner_map(store, &st.allocations, client),
+        }
+    }
+
+    /// Claims of the provider of the given actor ID, by claim ID.
+    pub fn claims<BS: Blockstore>(
+        &self,
+        store: &BS,
+        provider: ActorID,
+    ) -> anyhow::Result<Vec<(ClaimID, Claim)>> {
+        match self {
+            State::V8(_) => Ok(vec![]),
+            State::V9(st) => load_inner_map(store, &st.claims, provider),
+        }
+    }
+}
+
+fn get_data_cap<BS: Blockstore>(
+    store: &BS,
+    root: &Cid,
+    address: &Address,
+) -> anyhow::Result<Option<BigInt>> {
+    let map = make_map_with_root::<_, DataCap>(root, store)?;
+    Ok(map
+        .get(&address.to_bytes())?
+        .map(|DataCap(data_cap)| data_cap.clone()))
+}
+
+/// Loads the values keyed by actor ID then by ID, of the given actor.
+fn load_inner_map<BS, V>(store: &BS, root: &Cid, actor: ActorID) -> anyhow::Result<Vec<(u64, V)>>
+where
+    BS: Blockstore,
+    V: Serialize + serde::de::DeserializeOwned + Clone,
+{
+    let outer = make_map_with_root::<_, Cid>(root, store)?;
+    let inner_root = match outer.get(&u64_key(actor))? {
+        Some(inner_root) => *inner_root,
+        None => return Ok(vec![]),
+    };
+    let inner = make_map_with_root::<_, V>(&inner_root, store)?;
+    let mut values = vec![];
+    inner.for_each(|key, value| {
+        values.push((parse_uint_key(key)?, value.clone()));
+        Ok(())
+    })?;
+    Ok(values)
+}